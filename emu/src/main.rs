@@ -6,6 +6,7 @@ use bevy::prelude::*;
 use rfd::FileDialog;
 
 use nes::prelude::*;
+use prosystem::prelude::*;
 use vcs::prelude::*;
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
@@ -14,6 +15,7 @@ enum EmuAppState {
     Menu,
     NesGame,
     VcsGame,
+    ProsystemGame,
 }
 
 #[derive(Component)]
@@ -24,10 +26,49 @@ pub struct NesPlugin<S: States> {
 
 #[derive(Component)]
 struct VcsButton;
+
+#[derive(Component)]
+struct ProsystemButton;
+
+#[derive(Component)]
+struct MenuMessage;
+
+#[derive(Component)]
+struct VcsTvButton;
+
+#[derive(Component)]
+struct VcsTvText;
+
+// TV type chosen in the menu, None lets the emulator detect it
+#[derive(Resource, Default)]
+pub struct VcsTvType(pub Option<ConsoleType>);
+
+impl VcsTvType {
+
+    fn next(&self) -> Option<ConsoleType> {
+        match self.0 {
+            None => Some(ConsoleType::NTSC),
+            Some(ConsoleType::NTSC) => Some(ConsoleType::PAL),
+            Some(ConsoleType::PAL) => Some(ConsoleType::SECAM),
+            Some(ConsoleType::SECAM) => None,
+        }
+    }
+
+    fn get_label(&self) -> String {
+        match self.0 {
+            None => String::from("TV: Auto"),
+            Some(console_type) => format!("TV: {:?}", console_type),
+        }
+    }
+}
 pub struct VcsPlugin<S: States> {
     pub state: S,
 }
 
+pub struct ProsystemPlugin<S: States> {
+    pub state: S,
+}
+
 impl<S: States> Plugin for NesPlugin<S> {
     fn build(&self, app: &mut App) {
         app
@@ -44,7 +85,19 @@ impl<S: States> Plugin for VcsPlugin<S> {
         .insert_resource(Time::<Fixed>::from_duration(Duration::from_millis(17)))
         .add_systems(OnEnter(EmuAppState::VcsGame), VcsBevy::setup.run_if(in_state(EmuAppState::VcsGame)))
         .add_systems(FixedUpdate, VcsBevy::frame.run_if(in_state(EmuAppState::VcsGame)))
-        .add_systems(Update, VcsBevy::gamepad_system.run_if(in_state(EmuAppState::VcsGame)));
+        .add_systems(Update, VcsBevy::gamepad_system.run_if(in_state(EmuAppState::VcsGame)))
+        .add_systems(Update, VcsBevy::keyboard_system.run_if(in_state(EmuAppState::VcsGame)));
+    }
+}
+
+impl<S: States> Plugin for ProsystemPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app
+        .insert_resource(Time::<Fixed>::from_duration(Duration::from_millis(17)))
+        .add_systems(OnEnter(EmuAppState::ProsystemGame), ProsystemBevy::setup.run_if(in_state(EmuAppState::ProsystemGame)))
+        .add_systems(FixedUpdate, ProsystemBevy::frame.run_if(in_state(EmuAppState::ProsystemGame)))
+        .add_systems(Update, ProsystemBevy::gamepad_system.run_if(in_state(EmuAppState::ProsystemGame)))
+        .add_systems(Update, ProsystemBevy::keyboard_system.run_if(in_state(EmuAppState::ProsystemGame)));
     }
 }
 
@@ -60,11 +113,15 @@ fn main() {
         }))
         .add_plugins(NesPlugin {state: EmuAppState::NesGame})
         .add_plugins(VcsPlugin {state: EmuAppState::VcsGame})
+        .add_plugins(ProsystemPlugin {state: EmuAppState::ProsystemGame})
         .init_state::<EmuAppState>()
+        .init_resource::<VcsTvType>()
         .add_systems(Startup, setup)
         .add_systems(OnEnter(EmuAppState::Menu), setup_menu)
         .add_systems(Update, nes_menu.run_if(in_state(EmuAppState::Menu)))
         .add_systems(Update, vcs_menu.run_if(in_state(EmuAppState::Menu)))
+        .add_systems(Update, vcs_tv_menu.run_if(in_state(EmuAppState::Menu)))
+        .add_systems(Update, prosystem_menu.run_if(in_state(EmuAppState::Menu)))
         .add_systems(OnExit(EmuAppState::Menu), cleanup_menu)
         .run();
 }
@@ -107,6 +164,8 @@ fn vcs_menu(
         (&Interaction, &mut BackgroundColor),
         (Changed<Interaction>, With<VcsButton>)
         >,
+    mut message_query: Query<&mut Text, With<MenuMessage>>,
+    tv_type: Res<VcsTvType>,
 ) {
     for (interaction, mut color) in &mut interaction_query {
         match *interaction {
@@ -114,9 +173,95 @@ fn vcs_menu(
                 let files = FileDialog::new()
                     .add_filter("BIN", &["bin"])
                     .pick_file();
-                if let Some(file) = files {                    
-                    commands.insert_resource(VcsRomFile::new(file.into_os_string().into_string().unwrap()));
-                    next_state.set(EmuAppState::VcsGame);
+                if let Some(file) = files {
+                    match VcsRomFile::new(file.into_os_string().into_string().unwrap(), tv_type.0) {
+                        Ok(rom_file) => {
+                            commands.insert_resource(rom_file);
+                            next_state.set(EmuAppState::VcsGame);
+                        }
+                        Err(e) => {
+                            for mut text in &mut message_query {
+                                **text = e.to_string();
+                            }
+                        }
+                    }
+                }
+                *color = PRESSED_BUTTON.into();
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+fn prosystem_menu(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<EmuAppState>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ProsystemButton>)
+        >,
+    mut message_query: Query<&mut Text, With<MenuMessage>>,
+    tv_type: Res<VcsTvType>,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                let files = FileDialog::new()
+                    .add_filter("7800", &["a78", "bin"])
+                    .pick_file();
+                if let Some(file) = files {
+                    let rom_file = file.into_os_string().into_string().unwrap();
+
+                    // Like the BIOS, fall back to 2600 mode for 2600 cartridges
+                    let result = if ProsystemRomFile::is_vcs_rom(&rom_file) {
+                        VcsRomFile::new(rom_file, tv_type.0).map(|rom_file| {
+                            commands.insert_resource(rom_file);
+                            next_state.set(EmuAppState::VcsGame);
+                        }).map_err(|e| e.to_string())
+                    } else {
+                        ProsystemRomFile::new(rom_file).map(|rom_file| {
+                            commands.insert_resource(rom_file);
+                            next_state.set(EmuAppState::ProsystemGame);
+                        }).map_err(|e| e.to_string())
+                    };
+
+                    if let Err(e) = result {
+                        for mut text in &mut message_query {
+                            **text = e.clone();
+                        }
+                    }
+                }
+                *color = PRESSED_BUTTON.into();
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+fn vcs_tv_menu(
+    mut tv_type: ResMut<VcsTvType>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<VcsTvButton>)
+        >,
+    mut text_query: Query<&mut Text, With<VcsTvText>>,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                tv_type.0 = tv_type.next();
+                for mut text in &mut text_query {
+                    **text = tv_type.get_label();
                 }
                 *color = PRESSED_BUTTON.into();
             }
@@ -148,7 +293,7 @@ pub fn setup(mut commands: Commands) {
     
 }
 
-pub fn setup_menu(mut commands: Commands) {
+pub fn setup_menu(mut commands: Commands, tv_type: Res<VcsTvType>) {
     let button_entity = 
     commands.spawn((
                         Node {
@@ -195,6 +340,54 @@ pub fn setup_menu(mut commands: Commands) {
                     Text::new("VCS"),
                     TextColor(Color::srgb(0.9, 0.9, 0.9)),
                 )]
+            ),
+            (
+                Button,
+                VcsTvButton,
+                Node {
+                    width: px(150),
+                    height: px(65),
+                    // horizontally center child text
+                    justify_content: JustifyContent::Center,
+                    // vertically center child text
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                children![(
+                    VcsTvText,
+                    Text::new(tv_type.get_label()),
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                )]
+            ),
+            (
+                Button,
+                ProsystemButton,
+                Node {
+                    width: px(150),
+                    height: px(65),
+                    // horizontally center child text
+                    justify_content: JustifyContent::Center,
+                    // vertically center child text
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                children![(
+                    Text::new("7800"),
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                )]
+            ),
+            (
+                // Errors loading a ROM are shown here
+                MenuMessage,
+                Text::new(""),
+                TextColor(Color::srgb(0.9, 0.3, 0.3)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: px(20),
+                    ..default()
+                },
             )],
         ))
         .id();
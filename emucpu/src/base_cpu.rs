@@ -1,10 +1,22 @@
-
 pub mod emu_cpu
 {
+    // The CPU side of a bus. A cycle is started on one tick and its data is
+    // picked up on the next, so the devices can answer in between.
+    pub trait CpuBus {
+
+        // Data from the read started on the previous tick
+        fn get_data(&self) -> u8;
+
+        fn read(&mut self, address: u16);
+
+        fn write(&mut self, address: u16, byte: u8);
+    }
+
+    // execute_tick runs one bus cycle
     pub trait BaseCpu {
 
-        fn execute_tick(&mut self);
+        fn execute_tick<B: CpuBus>(&mut self, bus: &mut B);
 
         fn reset(&mut self);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,114 @@
+// Disassembles a .bin or .nes file:
+//   disassemble <rom> [--origin <hex>] [--symbols <file>] [--variant nmos|2a03|65c02|rockwell]
+// .nes files are split into their 16K PRG banks, the last one at $C000 and the
+// others at $8000, --origin puts all of the PRG ROM there. Other files are
+// split into 4K banks at $F000, or smaller ones at the top of memory, unless
+// --origin gives where the whole file goes.
+
+use std::env;
+use std::fs;
+use std::process;
+
+use emucpu::m6502::emu_cpu::M6502Variant;
+use emucpu::m6502_disassembler::mdisassembler::{M6502Disassembler, M6502Symbols};
+
+const INES_HEADER: usize = 16;
+const INES_TRAINER: usize = 512;
+const NES_BANK_SIZE: usize = 0x4000;
+const BANK_SIZE: usize = 0x1000;
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+// The PRG ROM of an iNES file, after the header and any trainer
+fn get_nes_prg(rom: &[u8]) -> &[u8] {
+    if rom.len() < INES_HEADER || &rom[0..4] != b"NES\x1a" {
+        exit_with("Not an iNES file");
+    }
+    let start = INES_HEADER + if rom[6] & 0x04 != 0 { INES_TRAINER } else { 0 };
+    let end = start + rom[4] as usize * NES_BANK_SIZE;
+    if end > rom.len() {
+        exit_with("The iNES file is shorter than its PRG ROM");
+    }
+    &rom[start..end]
+}
+
+// The PRG ROM banks of an iNES file with where each one is mapped
+fn get_nes_banks(rom: &[u8]) -> Vec<(u16, &[u8])> {
+    let banks: Vec<&[u8]> = get_nes_prg(rom).chunks(NES_BANK_SIZE).collect();
+    let last = banks.len().saturating_sub(1);
+
+    banks.into_iter().enumerate()
+        .map(|(bank, data)| (if bank == last { 0xC000 } else { 0x8000 }, data))
+        .collect()
+}
+
+fn get_banks(rom: &[u8]) -> Vec<(u16, &[u8])> {
+    rom.chunks(BANK_SIZE)
+        .map(|data| ((0x10000 - data.len()) as u16, data))
+        .collect()
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut file = None;
+    let mut origin = None;
+    let mut symbols_file = None;
+    let mut variant = None;
+
+    let mut index = 0;
+    while index < args.len() {
+        let value = args.get(index + 1);
+        match (args[index].as_str(), value) {
+            ("--origin", Some(value)) => {
+                origin = Some(u16::from_str_radix(value.trim_start_matches('$').trim_start_matches("0x"), 16)
+                    .unwrap_or_else(|_| exit_with("Bad origin")));
+                index += 1;
+            },
+            ("--symbols", Some(value)) => {
+                symbols_file = Some(value.clone());
+                index += 1;
+            },
+            ("--variant", Some(value)) => {
+                variant = Some(match value.to_ascii_lowercase().as_str() {
+                    "nmos" => M6502Variant::Nmos,
+                    "2a03" => M6502Variant::Ricoh2A03,
+                    "65c02" => M6502Variant::Wdc65C02,
+                    "rockwell" => M6502Variant::Rockwell65C02,
+                    _ => exit_with("Unknown variant"),
+                });
+                index += 1;
+            },
+            (name, _) if !name.starts_with("--") && file.is_none() => file = Some(name.to_string()),
+            _ => exit_with("Usage: disassemble <rom> [--origin <hex>] [--symbols <file>] [--variant nmos|2a03|65c02|rockwell]"),
+        }
+        index += 1;
+    }
+
+    let file = file.unwrap_or_else(|| exit_with("No ROM file given"));
+    let rom = fs::read(&file).unwrap_or_else(|e| exit_with(&format!("Couldn't read {}: {}", file, e)));
+    let is_nes = file.to_ascii_lowercase().ends_with(".nes");
+
+    let banks = match origin {
+        Some(origin) => vec![(origin, if is_nes { get_nes_prg(&rom) } else { &rom[..] })],
+        None if is_nes => get_nes_banks(&rom),
+        None => get_banks(&rom),
+    };
+
+    let default_variant = if is_nes { M6502Variant::Ricoh2A03 } else { M6502Variant::Nmos };
+    let mut disassembler = M6502Disassembler::new(variant.unwrap_or(default_variant));
+    if let Some(symbols_file) = symbols_file {
+        disassembler.set_symbols(M6502Symbols::load(&symbols_file).unwrap_or_else(|e| exit_with(&e.to_string())));
+    }
+
+    for (bank, (origin, data)) in banks.iter().enumerate() {
+        if banks.len() > 1 {
+            println!("; Bank {}", bank);
+        }
+        for instruction in disassembler.disassemble_all(data, *origin) {
+            println!("{}", disassembler.format_line(&instruction));
+        }
+    }
+}
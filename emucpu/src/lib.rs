@@ -1,7 +1,10 @@
 
 pub mod base_cpu;
-pub mod nm6502;
 pub mod m6502;
 pub mod m6502_address;
+pub mod m6502_assembler;
+pub mod m6502_debugger;
+pub mod m6502_disassembler;
 pub mod m6502_opcodes;
+pub mod m6502_tracer;
 pub mod prelude;
\ No newline at end of file
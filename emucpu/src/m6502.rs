@@ -1,9 +1,11 @@
 
-
 pub mod emu_cpu{
 
-    use crate::m6502_address::maddress::{*};
-    use crate::m6502_opcodes::mopcodes::{*};
+    use crate::base_cpu::emu_cpu::{BaseCpu, CpuBus};
+    use crate::m6502_address::maddress::M6502AddressMode;
+    use crate::m6502_opcodes::mopcodes::{M6502OpCode, M6502Operation, M6502OperationType, OP_CODES,
+        ROCKWELL_OP_CODES, WDC_OP_CODES};
+    use crate::m6502_tracer::mtracer::M6502Tracer;
 
     pub const CARRY_FLAG: u8     = 1;
     pub const ZERO_FLAG: u8      = 2;
@@ -14,139 +16,202 @@ pub mod emu_cpu{
     pub const OVERFLOW_FLAG: u8  = 64;
     pub const NEGATIVE_FLAG: u8  = 128;
 
-    #[derive(Default)]
+    const STACK_PAGE: u16 = 0x100;
+
+    const NMI_VECTOR: u16 =   0xFFFA;
+    const RESET_VECTOR: u16 = 0xFFFC;
+    const IRQ_VECTOR: u16 =   0xFFFE;
+
+    // The consoles' shared bus, each device answers the cycle the CPU left
+    // on it and clears write once it has taken a write
+    #[derive(Default, Debug, Clone, Copy)]
     pub struct AddressBus {
         pub address: u16,
         pub write: bool,
         pub byte: u8,
-        pub is_accumulator: bool,
-        pub is_abs_y: bool,
     }
 
     impl AddressBus {
-        fn new(address: u16, write: bool, byte: u8) -> AddressBus {
-            Self {
-                address,
-                write,
-                byte,
-                is_accumulator: false,
-                is_abs_y: false,
-            }
+        pub fn new() -> AddressBus {
+            AddressBus::default()
+        }
+    }
+
+    impl CpuBus for AddressBus {
+
+        fn get_data(&self) -> u8 {
+            self.byte
+        }
+
+        fn read(&mut self, address: u16) {
+            self.address = address;
+            self.write = false;
+        }
+
+        fn write(&mut self, address: u16, byte: u8) {
+            self.address = address;
+            self.byte = byte;
+            self.write = true;
         }
     }
 
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
     pub struct M6502 {
         pub program_counter: u16,
-        pub stack_pointer_page: u16,
-        pub stack_pointer: u16,
+        pub stack_pointer: u8,
         pub accumulator: u8,
         pub register_x: u8,
         pub register_y: u8,
         pub status_register: u8,
-        pub lookup_address: AddressBus,
-        pub in_interrupt: bool,
-        pub byte: u8,
-        _debug: u8,
     }
 
-    impl M6502 {
-        fn new () -> M6502 {
-            Self {
-                program_counter: 0,
-                stack_pointer_page: 0x100,
-                stack_pointer: 0,
-                accumulator: 0,
-                register_x: 0,
-                register_y: 0,
-                status_register: 0,
-                lookup_address: AddressBus::new(0, false, 0),
-                in_interrupt: false,
-                byte: 0,
-                _debug: 0,
+    #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+    pub enum M6502Version {
+        AtariVcs,
+        Atari7800,
+        Nes
+    }
+
+    impl M6502Version {
+        pub fn get_variant(&self) -> M6502Variant {
+            match self {
+                M6502Version::AtariVcs | M6502Version::Atari7800 => M6502Variant::Nmos,
+                M6502Version::Nes => M6502Variant::Ricoh2A03,
             }
         }
     }
 
-    #[derive(PartialEq)]
-    pub enum M6502Version {
-        AtariVcs,
-        Nes
+    // The chip itself, which op codes it has and how it times them
+    #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+    pub enum M6502Variant {
+        Nmos,
+        // NMOS without decimal mode
+        Ricoh2A03,
+        Wdc65C02,
+        // The 65C02 with the bit operations but without WAI and STP
+        Rockwell65C02,
     }
 
-    #[derive(PartialEq)]
-    enum M6502RunnerStep {
-        ReadPc,
-        AddressStep,
-        AddressStepLoadByte,
-        OpCodeStep,
-        OpCodeWrite,
+    impl M6502Variant {
+        pub fn has_decimal(&self) -> bool {
+            *self != M6502Variant::Ricoh2A03
+        }
+
+        pub fn is_cmos(&self) -> bool {
+            matches!(self, M6502Variant::Wdc65C02 | M6502Variant::Rockwell65C02)
+        }
+
+        pub fn get_op_codes(&self) -> &'static [M6502OpCode; 0x100] {
+            match self {
+                M6502Variant::Nmos | M6502Variant::Ricoh2A03 => &OP_CODES,
+                M6502Variant::Wdc65C02 => &WDC_OP_CODES,
+                M6502Variant::Rockwell65C02 => &ROCKWELL_OP_CODES,
+            }
+        }
+    }
+
+    // A bus cycle the CPU issued
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct M6502Cycle {
+        pub address: u16,
+        pub write: bool,
+    }
+
+    // Keeps the cycle the CPU left on the bus, so RDY can hold it
+    struct M6502CycleBus<'a, B: CpuBus> {
+        bus: &'a mut B,
+        cycle: M6502Cycle,
     }
 
+    impl<B: CpuBus> CpuBus for M6502CycleBus<'_, B> {
+
+        fn get_data(&self) -> u8 {
+            self.bus.get_data()
+        }
+
+        fn read(&mut self, address: u16) {
+            self.cycle = M6502Cycle { address, write: false };
+            self.bus.read(address);
+        }
+
+        fn write(&mut self, address: u16, byte: u8) {
+            self.cycle = M6502Cycle { address, write: true };
+            self.bus.write(address, byte);
+        }
+    }
+
+    #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+    enum M6502Interrupt {
+        Reset,
+        Nmi,
+        Irq,
+    }
+
+    // Cycle stepped 6502. Every tick is one bus cycle, the variant's op code
+    // table gives the operation and addressing mode and the cycles are worked
+    // out from those.
     pub struct M6502Runner {
         cpu: M6502,
-        op_code: u16,
-        op_step: u8,
-        address_step: u8,
-        runner_step: M6502RunnerStep,
+        variant: M6502Variant,
+        op_code: u8,
+        // Bus cycle of the instruction issued on the next tick, 0 fetches the op code
+        cycle: u8,
+        interrupt: Option<M6502Interrupt>,
+        // The last read of the instruction is finished when its data comes in
+        read_pending: bool,
+        address: u16,
+        base_high: u8,
+        pointer: u8,
+        byte: u8,
         is_reset_set: bool,
-        op_code_lookup: Vec<Box<dyn CpuOperation>>,
-        op_code_ticks: Vec<u8>,
-        address_method_lookup: Vec<Box<dyn AddressMethod>>,
-        tick_count: u8,
         is_nmi_set: bool,
-        nmi_buffered: bool,
         is_irq_set: bool,
-        irq_buffered: bool,
+        // Interrupt lines sampled at the start of this tick and the one before,
+        // the op code fetch goes by the instruction's last cycle
+        interrupt_sampled: bool,
+        interrupt_polled: bool,
+        cycle_count: u64,
+        // The last tick fetched an op code that runs, not one dropped for an interrupt
+        op_code_fetched: bool,
+        jammed: bool,
+        // WAI, the CPU idles until an interrupt line is set
+        waiting: bool,
+        rdy: bool,
+        last_cycle: M6502Cycle,
+        tracer: Option<M6502Tracer>,
         pub can_halt: bool,
-        _debug: u8,
     }
 
     impl M6502Runner {
         pub fn new (version: M6502Version) -> M6502Runner {
+            Self::new_variant(version.get_variant())
+        }
 
-            let mut cpu = M6502::new();
-            let mut op_code_lookup = OpCodesUtils::get_opcodes();
-
-            if version == M6502Version::AtariVcs {
-                cpu.stack_pointer_page = 0x00;
-            } else if version == M6502Version::Nes {
-                op_code_lookup[0x61] = Box::new(CpuOpAdcNoDecimal {});
-                op_code_lookup[0x65] = Box::new(CpuOpAdcNoDecimal {});
-                op_code_lookup[0x69] = Box::new(CpuOpAdcNoDecimal {});
-                op_code_lookup[0x6d] = Box::new(CpuOpAdcNoDecimal {});
-                op_code_lookup[0x71] = Box::new(CpuOpAdcNoDecimal {});
-                op_code_lookup[0x75] = Box::new(CpuOpAdcNoDecimal {});
-                op_code_lookup[0x79] = Box::new(CpuOpAdcNoDecimal {});
-                op_code_lookup[0x7d] = Box::new(CpuOpAdcNoDecimal {});
-
-                op_code_lookup[0xe1] = Box::new(CpuOpSbcNoDecimal {});
-                op_code_lookup[0xe5] = Box::new(CpuOpSbcNoDecimal {});
-                op_code_lookup[0xe9] = Box::new(CpuOpSbcNoDecimal {});
-                op_code_lookup[0xeb] = Box::new(CpuOpSbcNoDecimal {});
-                op_code_lookup[0xed] = Box::new(CpuOpSbcNoDecimal {});
-                op_code_lookup[0xf1] = Box::new(CpuOpSbcNoDecimal {});
-                op_code_lookup[0xf5] = Box::new(CpuOpSbcNoDecimal {});
-                op_code_lookup[0xf9] = Box::new(CpuOpSbcNoDecimal {});
-                op_code_lookup[0xfd] = Box::new(CpuOpSbcNoDecimal {});
-            }
-
+        pub fn new_variant(variant: M6502Variant) -> M6502Runner {
             Self {
-                cpu,
-                op_code: 0x100,
-                op_step: 0,
-                address_step: 0,
-                runner_step: M6502RunnerStep::ReadPc,
+                cpu: M6502::default(),
+                variant,
+                op_code: 0,
+                cycle: 0,
+                interrupt: None,
+                read_pending: false,
+                address: 0,
+                base_high: 0,
+                pointer: 0,
+                byte: 0,
                 is_reset_set: true,
-                op_code_lookup,
-                op_code_ticks: OpCodesUtils::get_ticks(),
-                address_method_lookup: AddressOpCodes::get_address_methods(),
-                tick_count: 0,
                 is_nmi_set: false,
-                nmi_buffered: false,
                 is_irq_set: false,
-                irq_buffered: false,
+                interrupt_sampled: false,
+                interrupt_polled: false,
+                cycle_count: 0,
+                op_code_fetched: false,
+                jammed: false,
+                waiting: false,
+                rdy: true,
+                last_cycle: M6502Cycle::default(),
+                tracer: None,
                 can_halt: false,
-                _debug: 0,
             }
         }
 
@@ -158,92 +223,732 @@ pub mod emu_cpu{
             self.is_irq_set = true;
         }
 
-        pub fn execute_tick(&mut self, addr: &mut AddressBus) {
-            
-            if self.runner_step == M6502RunnerStep::AddressStepLoadByte {
-                self.cpu.lookup_address.byte = addr.byte;
-                self.cpu.lookup_address.address = addr.address;
-                self.cpu.lookup_address.is_accumulator = addr.is_accumulator;
-                self.cpu.lookup_address.is_abs_y = addr.is_abs_y;
-                self.runner_step = M6502RunnerStep::OpCodeStep;
-            }
-
-            // Geting new op code
-            if self.runner_step == M6502RunnerStep::ReadPc {
-                self.can_halt = false;
-                addr.is_accumulator = false;
-                self.cpu.lookup_address.is_abs_y = false;
-                if self.is_reset_set {
-                    self.op_code = 0x100;
-                    self.runner_step = M6502RunnerStep::OpCodeStep;
-                    self.is_reset_set = false;
-                } else if self.nmi_buffered {
-                    self.op_code = 0x101;
-                    self.runner_step = M6502RunnerStep::OpCodeStep;
-                    self.nmi_buffered = false;
-                } else if self.irq_buffered {
-                    self.op_code = 0x102;
-                    self.runner_step = M6502RunnerStep::OpCodeStep;
-                    self.irq_buffered = false;
+        // Devices pull RDY low to hold the CPU on its next read, used for DMA and WSYNC
+        pub fn set_rdy(&mut self, ready: bool) {
+            self.rdy = ready;
+        }
+
+        // RDY is low and the cycle on the bus is a read, which the next tick repeats
+        pub fn is_halted(&self) -> bool {
+            !self.rdy && !self.last_cycle.write
+        }
+
+        // For level triggered sources that drop the line before it's taken
+        pub fn clear_irq(&mut self) {
+            self.is_irq_set = false;
+        }
+
+        pub fn get_cpu(&self) -> &M6502 {
+            &self.cpu
+        }
+
+        pub fn get_cpu_mut(&mut self) -> &mut M6502 {
+            &mut self.cpu
+        }
+
+        // The next tick fetches an op code with these registers, any pending reset is dropped
+        pub fn set_cpu(&mut self, cpu: M6502) {
+            self.cpu = cpu;
+            self.cycle = 0;
+            self.read_pending = false;
+            self.interrupt = None;
+            self.is_reset_set = false;
+            self.interrupt_sampled = false;
+            self.interrupt_polled = false;
+            self.jammed = false;
+            self.waiting = false;
+        }
+
+        pub fn get_variant(&self) -> M6502Variant {
+            self.variant
+        }
+
+        // A JAM or STP op code has stopped the CPU, only a reset starts it again
+        pub fn is_jammed(&self) -> bool {
+            self.jammed
+        }
+
+        pub fn is_waiting(&self) -> bool {
+            self.waiting
+        }
+
+        // The last tick fetched an op code, the program counter is its address
+        pub fn is_op_code_fetch(&self) -> bool {
+            self.op_code_fetched
+        }
+
+        // The cycle the last tick put on the bus, held ones included
+        pub fn get_last_cycle(&self) -> M6502Cycle {
+            self.last_cycle
+        }
+
+        // Ticks since power on
+        pub fn get_cycle_count(&self) -> u64 {
+            self.cycle_count
+        }
+
+        // A cycle a DMA took without the CPU ticking, it still counts
+        pub fn skip_cycle(&mut self) {
+            self.cycle_count += 1;
+            self.op_code_fetched = false;
+            self.can_halt = false;
+        }
+
+        pub fn set_tracer(&mut self, tracer: Option<M6502Tracer>) {
+            self.tracer = tracer;
+        }
+
+        pub fn get_tracer_mut(&mut self) -> Option<&mut M6502Tracer> {
+            self.tracer.as_mut()
+        }
+
+        // Consoles call this after every tick, the tracer gets a line when the
+        // tick fetched an op code. read mustn't have side effects.
+        pub fn trace<F: Fn(u16) -> u8>(&mut self, video: Option<(i32, i32)>, read: F) {
+            if let Some(tracer) = self.tracer.as_mut() && self.op_code_fetched {
+                tracer.trace(&self.cpu, self.cycle_count - 1, video, read);
+            }
+        }
+
+        fn push<B: CpuBus>(&mut self, bus: &mut B, byte: u8) {
+            bus.write(STACK_PAGE | self.cpu.stack_pointer as u16, byte);
+            self.cpu.stack_pointer = self.cpu.stack_pointer.wrapping_sub(1);
+        }
+
+        fn read_pc<B: CpuBus>(&mut self, bus: &mut B) {
+            bus.read(self.cpu.program_counter);
+            self.cpu.program_counter = self.cpu.program_counter.wrapping_add(1);
+        }
+
+        fn finish(&mut self) {
+            self.cycle = 0;
+        }
+
+        fn finish_read(&mut self) {
+            self.read_pending = true;
+            self.cycle = 0;
+        }
+
+        // Jumps and branches fetch the next op code on their last cycle
+        fn fetch_next<B: CpuBus>(&mut self, bus: &mut B, data: u8) {
+            self.cycle = 0;
+            self.fetch(bus, data);
+        }
+
+        fn get_op_code(&self) -> M6502OpCode {
+            self.variant.get_op_codes()[self.op_code as usize]
+        }
+
+        // The 65C02 takes an extra cycle for ADC and SBC in decimal mode
+        fn is_decimal_cycle(&self, operation: M6502Operation) -> bool {
+            self.variant.is_cmos() && matches!(operation, M6502Operation::Adc | M6502Operation::Sbc) &&
+                self.cpu.get_status_flag(DECIMAL_MODE)
+        }
+
+        fn execute_read(&mut self, op_code: M6502OpCode, data: u8) {
+            if op_code.operation == M6502Operation::Bit && op_code.mode == M6502AddressMode::Immediate {
+                self.cpu.bit_immediate(data);
+            } else {
+                self.cpu.execute_read(op_code.operation, data, self.variant);
+            }
+        }
+
+        fn fetch<B: CpuBus>(&mut self, bus: &mut B, data: u8) {
+            if self.read_pending {
+                self.read_pending = false;
+                self.execute_read(self.get_op_code(), data);
+            }
+
+            // Interrupts are taken between instructions, the vector is picked later
+            self.interrupt = if self.is_reset_set {
+                self.is_reset_set = false;
+                Some(M6502Interrupt::Reset)
+            } else if self.interrupt_polled {
+                if self.is_nmi_set { Some(M6502Interrupt::Nmi) } else { Some(M6502Interrupt::Irq) }
+            } else {
+                None
+            };
+
+            bus.read(self.cpu.program_counter);
+            self.op_code_fetched = self.interrupt.is_none();
+            // The 7800's MARIA halts the CPU between instructions, after the op code read
+            self.can_halt = true;
+            self.cycle = 1;
+        }
+
+        fn step<B: CpuBus>(&mut self, bus: &mut B, data: u8) {
+            if self.cycle == 1 {
+                if self.interrupt.is_some() {
+                    // The fetched op code is dropped for a BRK
+                    self.op_code = 0x00;
                 } else {
-                    self.runner_step = M6502RunnerStep::AddressStep;
-                    if self.op_code_ticks[self.op_code as usize] != self.tick_count 
-                     && self.op_code != 0xd0 && self.op_code != 0x10 
-                     && self.op_code != 0xb0 && self.op_code != 0x30
-                     && self.op_code != 0xf0 && self.op_code != 0x90 
-                     && self.op_code != 0x50 {
-                        //println!("PC: {:x}, opcode: {:x} tickcount: {} expected: {}", self.cpu.program_counter, self.op_code, self.tick_count, self.op_code_ticks[self.op_code as usize]);
-                    }
-                    self.op_code = addr.byte as u16;
- //                    CSWasteOnly1
-                    //println!("PC: {:x}, opcode: {:x} ", self.cpu.program_counter, self.op_code);
+                    self.op_code = data;
                     self.cpu.program_counter = self.cpu.program_counter.wrapping_add(1);
-                    self.tick_count = 0;
-                }
-                if self.is_irq_set && OpCodesUtils::get_status_flag(&mut self.cpu, INTERRUPT_FLAG) == 0 {
-                    self.irq_buffered = true;
-                    self.is_irq_set = false;
-                }
-                if self.is_nmi_set {
-                    self.nmi_buffered = true;
-                    self.is_nmi_set = false;
                 }
             }
 
-            self.tick_count += 1;
+            let op_code = self.get_op_code();
+            let cycle = self.cycle;
+            self.cycle += 1;
 
-            // Calling address/op tick
-            if self.runner_step == M6502RunnerStep::AddressStep {
-                if self.address_method_lookup[self.op_code as usize].execute(&mut self.cpu, addr, self.address_step) {
-                    self.address_step = 0;
-                    self.runner_step = M6502RunnerStep::AddressStepLoadByte;
-                } else {
-                    self.address_step += 1;
-                }
-            } 
+            match op_code.operation {
+                M6502Operation::Brk => self.step_brk(bus, data, cycle),
+                M6502Operation::Jsr => self.step_jsr(bus, data, cycle),
+                M6502Operation::Rts => self.step_rts(bus, data, cycle),
+                M6502Operation::Rti => self.step_rti(bus, data, cycle),
+                M6502Operation::Jmp => self.step_jmp(bus, data, cycle, op_code.mode),
+                M6502Operation::Jam => self.step_jam(bus, cycle),
+                M6502Operation::Stp => self.step_stp(bus, cycle),
+                M6502Operation::Wai => {
+                    bus.read(self.cpu.program_counter);
+                    if cycle == 2 {
+                        self.waiting = true;
+                        self.finish();
+                    }
+                },
+                M6502Operation::Nop1 => self.fetch_next(bus, data),
+                M6502Operation::Pha | M6502Operation::Php | M6502Operation::Phx | M6502Operation::Phy => {
+                    if cycle == 1 {
+                        bus.read(self.cpu.program_counter);
+                    } else {
+                        let byte = match op_code.operation {
+                            M6502Operation::Pha => self.cpu.accumulator,
+                            M6502Operation::Phx => self.cpu.register_x,
+                            M6502Operation::Phy => self.cpu.register_y,
+                            _ => self.cpu.get_pushed_status(true),
+                        };
+                        self.push(bus, byte);
+                        self.finish();
+                    }
+                },
+                M6502Operation::Pla | M6502Operation::Plp | M6502Operation::Plx | M6502Operation::Ply => {
+                    match cycle {
+                        1 => bus.read(self.cpu.program_counter),
+                        2 => bus.read(STACK_PAGE | self.cpu.stack_pointer as u16),
+                        _ => {
+                            self.cpu.stack_pointer = self.cpu.stack_pointer.wrapping_add(1);
+                            bus.read(STACK_PAGE | self.cpu.stack_pointer as u16);
+                            self.finish_read();
+                        },
+                    }
+                },
+                M6502Operation::Bbr(_) | M6502Operation::Bbs(_) => self.step_bit_branch(bus, data, cycle, op_code.operation),
+                _ if op_code.mode == M6502AddressMode::Relative => self.step_branch(bus, data, cycle, op_code.operation),
+                _ => self.step_mode(bus, data, cycle, op_code.operation, op_code.mode),
+            }
+        }
+
+        // BRK and the interrupt sequences, reset reads the stack instead of writing it
+        fn step_brk<B: CpuBus>(&mut self, bus: &mut B, data: u8, cycle: u8) {
+            let reset = self.interrupt == Some(M6502Interrupt::Reset);
+
+            match cycle {
+                1 => {
+                    if self.interrupt.is_some() {
+                        bus.read(self.cpu.program_counter);
+                    } else {
+                        self.read_pc(bus);
+                    }
+                },
+                2..=4 => {
+                    let byte = match cycle {
+                        2 => (self.cpu.program_counter >> 8) as u8,
+                        3 => self.cpu.program_counter as u8,
+                        _ => self.cpu.get_pushed_status(self.interrupt.is_none()),
+                    };
+                    if reset {
+                        bus.read(STACK_PAGE | self.cpu.stack_pointer as u16);
+                        self.cpu.stack_pointer = self.cpu.stack_pointer.wrapping_sub(1);
+                    } else {
+                        self.push(bus, byte);
+                    }
 
-            if self.runner_step == M6502RunnerStep::AddressStepLoadByte && self.op_code_lookup[self.op_code as usize].needs_addr_byte(addr) == false {
-                self.cpu.lookup_address.byte = addr.byte;
-                self.runner_step = M6502RunnerStep::OpCodeStep;
+                    // An NMI by now takes over the vector of a BRK or IRQ
+                    if cycle == 4 {
+                        self.address = if reset {
+                            RESET_VECTOR
+                        } else if self.is_nmi_set {
+                            self.is_nmi_set = false;
+                            NMI_VECTOR
+                        } else {
+                            if self.interrupt.is_some() {
+                                self.is_irq_set = false;
+                            }
+                            IRQ_VECTOR
+                        };
+                        self.cpu.set_status_flag(INTERRUPT_FLAG | IGNORED, true);
+                        if self.variant.is_cmos() {
+                            self.cpu.set_status_flag(DECIMAL_MODE, false);
+                        }
+                    }
+                },
+                5 => bus.read(self.address),
+                6 => {
+                    self.byte = data;
+                    bus.read(self.address.wrapping_add(1));
+                },
+                _ => {
+                    self.cpu.program_counter = ((data as u16) << 8) | self.byte as u16;
+                    self.interrupt = None;
+                    // The sequence doesn't poll, the handler's first instruction always runs
+                    self.interrupt_polled = false;
+                    self.fetch_next(bus, data);
+                },
             }
+        }
 
-            if self.runner_step == M6502RunnerStep::OpCodeStep {
-                if self.op_code_lookup[self.op_code as usize].execute(&mut self.cpu, addr, self.op_step) {
-                    self.op_step = 0;
-                    self.runner_step = M6502RunnerStep::OpCodeWrite;
-                } else {
-                    self.op_step += 1;
-                }
-            } 
+        // The CPU locks up until reset, leaving these addresses on the bus
+        fn step_jam<B: CpuBus>(&mut self, bus: &mut B, cycle: u8) {
+            match cycle {
+                1 => {
+                    self.jammed = true;
+                    bus.read(self.cpu.program_counter);
+                },
+                2 => bus.read(0xFFFF),
+                3 | 4 => bus.read(0xFFFE),
+                _ => {
+                    bus.read(0xFFFF);
+                    self.cycle = 5;
+                },
+            }
+        }
+
+        // Like JAM, but the program counter stays on the bus
+        fn step_stp<B: CpuBus>(&mut self, bus: &mut B, cycle: u8) {
+            bus.read(self.cpu.program_counter);
+            if cycle >= 2 {
+                self.jammed = true;
+                self.cycle = 2;
+            }
+        }
+
+        fn step_jsr<B: CpuBus>(&mut self, bus: &mut B, data: u8, cycle: u8) {
+            match cycle {
+                1 => self.read_pc(bus),
+                2 => {
+                    self.byte = data;
+                    bus.read(STACK_PAGE | self.cpu.stack_pointer as u16);
+                },
+                3 => self.push(bus, (self.cpu.program_counter >> 8) as u8),
+                4 => self.push(bus, self.cpu.program_counter as u8),
+                5 => bus.read(self.cpu.program_counter),
+                _ => {
+                    self.cpu.program_counter = ((data as u16) << 8) | self.byte as u16;
+                    self.fetch_next(bus, data);
+                },
+            }
+        }
+
+        fn step_rts<B: CpuBus>(&mut self, bus: &mut B, data: u8, cycle: u8) {
+            match cycle {
+                1 => bus.read(self.cpu.program_counter),
+                2 => bus.read(STACK_PAGE | self.cpu.stack_pointer as u16),
+                3 | 4 => {
+                    if cycle == 4 {
+                        self.byte = data;
+                    }
+                    self.cpu.stack_pointer = self.cpu.stack_pointer.wrapping_add(1);
+                    bus.read(STACK_PAGE | self.cpu.stack_pointer as u16);
+                },
+                5 => {
+                    self.cpu.program_counter = ((data as u16) << 8) | self.byte as u16;
+                    self.read_pc(bus);
+                },
+                _ => self.fetch_next(bus, data),
+            }
+        }
+
+        fn step_rti<B: CpuBus>(&mut self, bus: &mut B, data: u8, cycle: u8) {
+            match cycle {
+                1 => bus.read(self.cpu.program_counter),
+                2 => bus.read(STACK_PAGE | self.cpu.stack_pointer as u16),
+                3..=5 => {
+                    if cycle == 4 {
+                        self.cpu.set_pulled_status(data);
+                    } else if cycle == 5 {
+                        self.byte = data;
+                    }
+                    self.cpu.stack_pointer = self.cpu.stack_pointer.wrapping_add(1);
+                    bus.read(STACK_PAGE | self.cpu.stack_pointer as u16);
+                },
+                _ => {
+                    self.cpu.program_counter = ((data as u16) << 8) | self.byte as u16;
+                    self.fetch_next(bus, data);
+                },
+            }
+        }
+
+        fn step_jmp<B: CpuBus>(&mut self, bus: &mut B, data: u8, cycle: u8, mode: M6502AddressMode) {
+            match (mode, cycle) {
+                (_, 1) => self.read_pc(bus),
+                (_, 2) => {
+                    self.byte = data;
+                    self.read_pc(bus);
+                },
+                (M6502AddressMode::Indirect, 3) if self.variant.is_cmos() => {
+                    // The 65C02 spends a cycle fixing the page wrap
+                    self.address = ((data as u16) << 8) | self.byte as u16;
+                    bus.read(self.last_cycle.address);
+                },
+                (M6502AddressMode::Indirect, 3) => {
+                    self.address = ((data as u16) << 8) | self.byte as u16;
+                    bus.read(self.address);
+                },
+                (M6502AddressMode::Indirect, 4) if self.variant.is_cmos() => bus.read(self.address),
+                (M6502AddressMode::Indirect, 4) => {
+                    // The pointer's high byte is read from the same page
+                    self.byte = data;
+                    bus.read((self.address & 0xFF00) | (self.address.wrapping_add(1) & 0x00FF));
+                },
+                (M6502AddressMode::AbsoluteIndexedIndirect, 3) => {
+                    self.address = (((data as u16) << 8) | self.byte as u16).wrapping_add(self.cpu.register_x as u16);
+                    bus.read(self.last_cycle.address);
+                },
+                (M6502AddressMode::AbsoluteIndexedIndirect, 4) => bus.read(self.address),
+                (M6502AddressMode::Indirect, 5) if self.variant.is_cmos() => {
+                    self.byte = data;
+                    bus.read(self.address.wrapping_add(1));
+                },
+                (M6502AddressMode::AbsoluteIndexedIndirect, 5) => {
+                    self.byte = data;
+                    bus.read(self.address.wrapping_add(1));
+                },
+                _ => {
+                    self.cpu.program_counter = ((data as u16) << 8) | self.byte as u16;
+                    self.fetch_next(bus, data);
+                },
+            }
+        }
+
+        fn is_branch_taken(&self, operation: M6502Operation) -> bool {
+            match operation {
+                M6502Operation::Bbr(bit) => self.byte & (1 << bit) == 0,
+                M6502Operation::Bbs(bit) => self.byte & (1 << bit) != 0,
+                _ => self.cpu.is_branch_taken(operation),
+            }
+        }
+
+        fn step_branch<B: CpuBus>(&mut self, bus: &mut B, data: u8, cycle: u8, operation: M6502Operation) {
+            match cycle {
+                1 => self.read_pc(bus),
+                2 => {
+                    if !self.is_branch_taken(operation) {
+                        self.fetch_next(bus, data);
+                        return;
+                    }
+                    self.byte = data;
+                    bus.read(self.cpu.program_counter);
+
+                    // Taken branches that stay on the page don't poll on their last cycle,
+                    // so interrupts wait for the next instruction
+                    let target = self.cpu.program_counter.wrapping_add(data as i8 as u16);
+                    if target & 0xFF00 == self.cpu.program_counter & 0xFF00 {
+                        self.interrupt_sampled = self.interrupt_polled;
+                    }
+                },
+                3 => {
+                    let target = self.cpu.program_counter.wrapping_add(self.byte as i8 as u16);
+                    if target & 0xFF00 == self.cpu.program_counter & 0xFF00 {
+                        self.cpu.program_counter = target;
+                        self.fetch_next(bus, data);
+                        return;
+                    }
+                    // Crossing a page reads from the uncorrected address first
+                    bus.read((self.cpu.program_counter & 0xFF00) | (target & 0x00FF));
+                    self.cpu.program_counter = target;
+                },
+                _ => self.fetch_next(bus, data),
+            }
+        }
 
-            if self.runner_step == M6502RunnerStep::OpCodeWrite && addr.write == false {
-                self.runner_step = M6502RunnerStep::ReadPc;
-                self.can_halt = true;
-                addr.address = self.cpu.program_counter;
+        // BBR and BBS read the zero page byte twice, then branch on one of its bits
+        fn step_bit_branch<B: CpuBus>(&mut self, bus: &mut B, data: u8, cycle: u8, operation: M6502Operation) {
+            match cycle {
+                1 => self.read_pc(bus),
+                2 => {
+                    self.address = data as u16;
+                    bus.read(self.address);
+                },
+                3 => {
+                    self.byte = data;
+                    bus.read(self.address);
+                },
+                _ => self.step_branch(bus, data, cycle - 3, operation),
             }
+        }
+
+        fn step_mode<B: CpuBus>(&mut self, bus: &mut B, data: u8, cycle: u8,
+            operation: M6502Operation, mode: M6502AddressMode) {
+
+            let operation_type = operation.get_type();
 
+            match mode {
+                M6502AddressMode::Implied | M6502AddressMode::Accumulator => {
+                    bus.read(self.cpu.program_counter);
+                    self.cpu.execute_implied(operation);
+                    self.finish();
+                },
+                M6502AddressMode::Immediate => {
+                    if cycle == 1 {
+                        self.read_pc(bus);
+                        if !self.is_decimal_cycle(operation) {
+                            self.finish_read();
+                        }
+                    } else {
+                        self.decimal_cycle(bus, data);
+                    }
+                },
+                M6502AddressMode::ZeroPage => {
+                    match cycle {
+                        1 => self.read_pc(bus),
+                        2 => {
+                            self.address = data as u16;
+                            self.base_high = 0;
+                            self.access(bus, data, operation, 0);
+                        },
+                        _ => self.access(bus, data, operation, cycle - 2),
+                    }
+                },
+                M6502AddressMode::ZeroPageX | M6502AddressMode::ZeroPageY => {
+                    match cycle {
+                        1 => self.read_pc(bus),
+                        2 => {
+                            self.address = data as u16;
+                            bus.read(self.address);
+                        },
+                        3 => {
+                            let index = if mode == M6502AddressMode::ZeroPageX { self.cpu.register_x } else { self.cpu.register_y };
+                            self.address = (self.address as u8).wrapping_add(index) as u16;
+                            self.base_high = 0;
+                            self.access(bus, data, operation, 0);
+                        },
+                        _ => self.access(bus, data, operation, cycle - 3),
+                    }
+                },
+                M6502AddressMode::Absolute => {
+                    match cycle {
+                        1 => self.read_pc(bus),
+                        2 => {
+                            self.address = data as u16;
+                            self.read_pc(bus);
+                        },
+                        3 => {
+                            self.address |= (data as u16) << 8;
+                            self.base_high = data;
+                            self.access(bus, data, operation, 0);
+                        },
+                        _ => self.access(bus, data, operation, cycle - 3),
+                    }
+                },
+                M6502AddressMode::AbsoluteX | M6502AddressMode::AbsoluteY => {
+                    match cycle {
+                        1 => self.read_pc(bus),
+                        2 => {
+                            self.address = data as u16;
+                            self.read_pc(bus);
+                        },
+                        3 => {
+                            let index = if mode == M6502AddressMode::AbsoluteX { self.cpu.register_x } else { self.cpu.register_y };
+                            // The 65C02's shifts and rotates only take the fixup cycle on a page cross
+                            let skip_fixup = operation_type == M6502OperationType::Read || (self.variant.is_cmos() &&
+                                mode == M6502AddressMode::AbsoluteX && operation_type == M6502OperationType::ReadModifyWrite &&
+                                !matches!(operation, M6502Operation::Inc | M6502Operation::Dec));
+                            self.index_address(bus, data, index, skip_fixup, operation);
+                        },
+                        _ => self.access(bus, data, operation, cycle - 4),
+                    }
+                },
+                M6502AddressMode::IndirectX => {
+                    match cycle {
+                        1 => self.read_pc(bus),
+                        2 => {
+                            self.pointer = data;
+                            bus.read(self.pointer as u16);
+                        },
+                        3 => {
+                            self.pointer = self.pointer.wrapping_add(self.cpu.register_x);
+                            bus.read(self.pointer as u16);
+                        },
+                        4 => {
+                            self.address = data as u16;
+                            bus.read(self.pointer.wrapping_add(1) as u16);
+                        },
+                        5 => {
+                            self.address |= (data as u16) << 8;
+                            self.base_high = data;
+                            self.access(bus, data, operation, 0);
+                        },
+                        _ => self.access(bus, data, operation, cycle - 5),
+                    }
+                },
+                M6502AddressMode::IndirectY => {
+                    match cycle {
+                        1 => self.read_pc(bus),
+                        2 => {
+                            self.pointer = data;
+                            bus.read(self.pointer as u16);
+                        },
+                        3 => {
+                            self.address = data as u16;
+                            bus.read(self.pointer.wrapping_add(1) as u16);
+                        },
+                        4 => {
+                            let index = self.cpu.register_y;
+                            self.index_address(bus, data, index, operation_type == M6502OperationType::Read, operation);
+                        },
+                        _ => self.access(bus, data, operation, cycle - 5),
+                    }
+                },
+                M6502AddressMode::ZeroPageIndirect => {
+                    match cycle {
+                        1 => self.read_pc(bus),
+                        2 => {
+                            self.pointer = data;
+                            bus.read(self.pointer as u16);
+                        },
+                        3 => {
+                            self.address = data as u16;
+                            bus.read(self.pointer.wrapping_add(1) as u16);
+                        },
+                        4 => {
+                            self.address |= (data as u16) << 8;
+                            self.base_high = data;
+                            self.access(bus, data, operation, 0);
+                        },
+                        _ => self.access(bus, data, operation, cycle - 4),
+                    }
+                },
+                M6502AddressMode::Indirect | M6502AddressMode::AbsoluteIndexedIndirect |
+                M6502AddressMode::Relative | M6502AddressMode::ZeroPageRelative => {
+                    panic!("Op code {:x} can't use {:?}", self.op_code, mode);
+                },
+            }
         }
 
+        // Adds the index to the address with data as its high byte. With skip_fixup
+        // an address on the same page goes straight on, everything else first
+        // reads from the address before the high byte is fixed. The 65C02 reads
+        // the last operand address again instead.
+        fn index_address<B: CpuBus>(&mut self, bus: &mut B, data: u8, index: u8,
+            skip_fixup: bool, operation: M6502Operation) {
+
+            let base = ((data as u16) << 8) | self.address;
+            self.base_high = data;
+            self.address = base.wrapping_add(index as u16);
+            let uncorrected = (base & 0xFF00) | (self.address & 0x00FF);
+
+            if skip_fixup && uncorrected == self.address {
+                // Keeps the access steps counting from the fixup cycle
+                self.cycle += 1;
+                self.access(bus, data, operation, 0);
+            } else if self.variant.is_cmos() {
+                bus.read(self.last_cycle.address);
+            } else {
+                bus.read(uncorrected);
+            }
+        }
+
+        // The 65C02's extra decimal cycle, the operand came in on the read before
+        fn decimal_cycle<B: CpuBus>(&mut self, bus: &mut B, data: u8) {
+            self.execute_read(self.get_op_code(), data);
+            bus.read(self.last_cycle.address);
+            self.finish();
+        }
+
+        // Cycles once the address is known, step counts from the first access
+        fn access<B: CpuBus>(&mut self, bus: &mut B, data: u8, operation: M6502Operation, step: u8) {
+            match operation.get_type() {
+                M6502OperationType::Read => {
+                    if step == 0 {
+                        bus.read(self.address);
+                        if !self.is_decimal_cycle(operation) {
+                            self.finish_read();
+                        }
+                    } else {
+                        self.decimal_cycle(bus, data);
+                    }
+                },
+                M6502OperationType::Write => {
+                    let byte = self.cpu.get_write_byte(operation, self.base_high);
+                    if operation.is_high_byte_store() && (self.address >> 8) as u8 != self.base_high {
+                        self.address = ((byte as u16) << 8) | (self.address & 0x00FF);
+                    }
+                    bus.write(self.address, byte);
+                    self.finish();
+                },
+                M6502OperationType::ReadModifyWrite => {
+                    match step {
+                        0 => bus.read(self.address),
+                        1 => {
+                            // The unmodified byte is written back first, the 65C02 reads it again
+                            if self.variant.is_cmos() {
+                                bus.read(self.address);
+                            } else {
+                                bus.write(self.address, data);
+                            }
+                            self.byte = self.cpu.execute_modify(operation, data, self.variant);
+                        },
+                        _ => {
+                            bus.write(self.address, self.byte);
+                            self.finish();
+                        },
+                    }
+                },
+                M6502OperationType::Other => {
+                    panic!("Op code {:x} has no memory access", self.op_code);
+                },
+            }
+        }
+    }
+
+    impl BaseCpu for M6502Runner {
+
+        fn execute_tick<B: CpuBus>(&mut self, bus: &mut B) {
+            let data = bus.get_data();
+            self.cycle_count += 1;
+            self.op_code_fetched = false;
+            self.can_halt = false;
+
+            // RDY low holds a read cycle, it's repeated until RDY goes high. Writes go on.
+            if self.is_halted() {
+                bus.read(self.last_cycle.address);
+                return;
+            }
+
+            self.interrupt_polled = self.interrupt_sampled;
+            self.interrupt_sampled = self.is_nmi_set || (self.is_irq_set && !self.cpu.get_status_flag(INTERRUPT_FLAG));
+
+            // WAI wakes on any interrupt line, masked IRQs just carry on with the next instruction
+            if self.waiting {
+                if !self.is_nmi_set && !self.is_irq_set {
+                    bus.read(self.cpu.program_counter);
+                    return;
+                }
+                self.waiting = false;
+                self.interrupt_polled = self.interrupt_sampled;
+            }
+
+            let mut bus = M6502CycleBus { bus, cycle: self.last_cycle };
+            if self.cycle == 0 {
+                self.fetch(&mut bus, data);
+            } else {
+                self.step(&mut bus, data);
+            }
+            self.last_cycle = bus.cycle;
+        }
+
+        fn reset(&mut self) {
+            self.cycle = 0;
+            self.jammed = false;
+            self.waiting = false;
+            self.read_pending = false;
+            self.interrupt = None;
+            self.is_reset_set = true;
+            self.is_nmi_set = false;
+            self.is_irq_set = false;
+            self.interrupt_sampled = false;
+            self.interrupt_polled = false;
+        }
     }
 
 }
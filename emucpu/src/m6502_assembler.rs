@@ -0,0 +1,682 @@
+
+pub mod massembler {
+
+    use std::collections::HashMap;
+    use std::fmt;
+
+    use crate::m6502::emu_cpu::M6502Variant;
+    use crate::m6502_address::maddress::M6502AddressMode;
+    use crate::m6502_disassembler::mdisassembler::{is_undocumented, M6502Symbols};
+
+    // Other names the undocumented op codes go by
+    const ALIASES: [(&str, &str); 9] = [("ISC", "ISB"), ("SBX", "AXS"), ("ALR", "ASR"), ("XAA", "ANE"),
+        ("LAS", "LAE"), ("AHX", "SHA"), ("TAS", "SHS"), ("KIL", "JAM"), ("HLT", "JAM")];
+
+    // Binary operators from the loosest to the tightest binding
+    const OPERATORS: [&[&str]; 6] = [&["|"], &["^"], &["&"], &["<<", ">>"], &["+", "-"], &["*", "/", "%"]];
+
+    // The line the error is on, counted from 1, and the text at fault
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub enum M6502AssemblerError {
+        UnknownMnemonic(usize, String),
+        UnknownDirective(usize, String),
+        BadOperand(usize, String),
+        BadExpression(usize, String),
+        UnknownLabel(usize, String),
+        DuplicateLabel(usize, String),
+        OutOfRange(usize, String),
+    }
+
+    impl fmt::Display for M6502AssemblerError {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                M6502AssemblerError::UnknownMnemonic(line, text) => write!(f, "Line {}: unknown mnemonic '{}'", line, text),
+                M6502AssemblerError::UnknownDirective(line, text) => write!(f, "Line {}: unknown directive '{}'", line, text),
+                M6502AssemblerError::BadOperand(line, text) => write!(f, "Line {}: the instruction can't take '{}'", line, text),
+                M6502AssemblerError::BadExpression(line, text) => write!(f, "Line {}: couldn't read '{}'", line, text),
+                M6502AssemblerError::UnknownLabel(line, text) => write!(f, "Line {}: unknown label '{}'", line, text),
+                M6502AssemblerError::DuplicateLabel(line, text) => write!(f, "Line {}: '{}' is already defined", line, text),
+                M6502AssemblerError::OutOfRange(line, text) => write!(f, "Line {}: '{}' is out of range", line, text),
+            }
+        }
+    }
+
+    impl std::error::Error for M6502AssemblerError {}
+
+    enum ExpressionError {
+        // A label that isn't defined yet, only an error on the second pass
+        Unknown(String),
+        Bad(String),
+    }
+
+    // Recursive descent over one expression. Numbers are decimal, $ or 0x hex,
+    // % binary or 'c' characters, * is the current address, < and > take the
+    // low and high byte.
+    struct Expression<'a> {
+        chars: Vec<char>,
+        position: usize,
+        labels: &'a HashMap<String, u16>,
+        address: u16,
+    }
+
+    impl Expression<'_> {
+
+        fn evaluate(text: &str, labels: &HashMap<String, u16>, address: u16) -> Result<i64, ExpressionError> {
+            let mut expression = Expression { chars: text.chars().collect(), position: 0, labels, address };
+            let value = expression.parse_level(0)?;
+            expression.skip_spaces();
+            if expression.position < expression.chars.len() {
+                return Err(ExpressionError::Bad(text.to_string()));
+            }
+            Ok(value)
+        }
+
+        fn skip_spaces(&mut self) {
+            while self.chars.get(self.position).is_some_and(|c| c.is_whitespace()) {
+                self.position += 1;
+            }
+        }
+
+        fn peek(&self) -> Option<char> {
+            self.chars.get(self.position).copied()
+        }
+
+        fn bad(&self) -> ExpressionError {
+            ExpressionError::Bad(self.chars.iter().collect())
+        }
+
+        fn take_operator(&mut self, level: usize) -> Option<&'static str> {
+            self.skip_spaces();
+            let operator = OPERATORS[level].iter().find(|operator| {
+                operator.chars().enumerate().all(|(offset, c)| self.chars.get(self.position + offset) == Some(&c))
+            })?;
+            self.position += operator.len();
+            Some(operator)
+        }
+
+        fn parse_level(&mut self, level: usize) -> Result<i64, ExpressionError> {
+            if level == OPERATORS.len() {
+                return self.parse_unary();
+            }
+
+            let mut value = self.parse_level(level + 1)?;
+            while let Some(operator) = self.take_operator(level) {
+                let right = self.parse_level(level + 1)?;
+                value = match operator {
+                    "|" => value | right,
+                    "^" => value ^ right,
+                    "&" => value & right,
+                    "<<" => value << (right & 0x1f),
+                    ">>" => value >> (right & 0x1f),
+                    "+" => value + right,
+                    "-" => value - right,
+                    "*" => value * right,
+                    _ if right == 0 => return Err(self.bad()),
+                    "/" => value / right,
+                    _ => value % right,
+                };
+            }
+            Ok(value)
+        }
+
+        fn parse_unary(&mut self) -> Result<i64, ExpressionError> {
+            self.skip_spaces();
+            let operator = self.peek();
+            if matches!(operator, Some('-' | '~' | '<' | '>')) {
+                self.position += 1;
+                let value = self.parse_unary()?;
+                return Ok(match operator {
+                    Some('-') => -value,
+                    Some('~') => !value & 0xffff,
+                    Some('<') => value & 0xff,
+                    _ => (value >> 8) & 0xff,
+                });
+            }
+            self.parse_primary()
+        }
+
+        fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+            let start = self.position;
+            while self.peek().is_some_and(&accept) {
+                self.position += 1;
+            }
+            self.chars[start..self.position].iter().collect()
+        }
+
+        fn parse_number(&self, digits: &str, radix: u32) -> Result<i64, ExpressionError> {
+            i64::from_str_radix(digits, radix).map_err(|_| self.bad())
+        }
+
+        fn parse_primary(&mut self) -> Result<i64, ExpressionError> {
+            match self.peek() {
+                Some('(') => {
+                    self.position += 1;
+                    let value = self.parse_level(0)?;
+                    self.skip_spaces();
+                    if self.peek() != Some(')') {
+                        return Err(self.bad());
+                    }
+                    self.position += 1;
+                    Ok(value)
+                },
+                Some('*') => {
+                    self.position += 1;
+                    Ok(self.address as i64)
+                },
+                Some('$') => {
+                    self.position += 1;
+                    let digits = self.take_while(|c| c.is_ascii_hexdigit());
+                    self.parse_number(&digits, 16)
+                },
+                Some('%') => {
+                    self.position += 1;
+                    let digits = self.take_while(|c| c == '0' || c == '1');
+                    self.parse_number(&digits, 2)
+                },
+                Some('\'') => {
+                    let c = self.chars.get(self.position + 1).copied().ok_or_else(|| self.bad())?;
+                    self.position += 2;
+                    if self.peek() == Some('\'') {
+                        self.position += 1;
+                    }
+                    Ok(c as i64)
+                },
+                Some(c) if c.is_ascii_digit() => {
+                    let digits = self.take_while(|c| c.is_ascii_alphanumeric());
+                    match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+                        Some(hex) => self.parse_number(hex, 16),
+                        None => self.parse_number(&digits, 10),
+                    }
+                },
+                Some(c) if is_label_start(c) => {
+                    let name = self.take_while(is_label_char);
+                    match self.labels.get(&name) {
+                        Some(value) => Ok(*value as i64),
+                        None => Err(ExpressionError::Unknown(name)),
+                    }
+                },
+                _ => Err(self.bad()),
+            }
+        }
+    }
+
+    fn is_label_start(c: char) -> bool {
+        c.is_ascii_alphabetic() || c == '_'
+    }
+
+    fn is_label_char(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_'
+    }
+
+    fn is_label(text: &str) -> bool {
+        text.chars().next().is_some_and(is_label_start) && text.chars().all(is_label_char)
+    }
+
+    // Splits at the commas that aren't in brackets or quotes
+    fn split_list(text: &str) -> Vec<&str> {
+        let mut items = Vec::new();
+        let mut depth = 0;
+        let mut quote = None;
+        let mut start = 0;
+        for (index, c) in text.char_indices() {
+            match (quote, c) {
+                (Some(open), _) if c == open => quote = None,
+                (Some(_), _) => {},
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') => depth -= 1,
+                (None, ',') if depth == 0 => {
+                    items.push(text[start..index].trim());
+                    start = index + 1;
+                },
+                _ => {},
+            }
+        }
+        items.push(text[start..].trim());
+        items
+    }
+
+    fn strip_comment(line: &str) -> &str {
+        let mut quote = None;
+        for (index, c) in line.char_indices() {
+            match (quote, c) {
+                (Some(open), _) if c == open => quote = None,
+                (Some(_), _) => {},
+                (None, '"' | '\'') => quote = Some(c),
+                (None, ';') => return &line[..index],
+                _ => {},
+            }
+        }
+        line
+    }
+
+    // The index of the bracket closing the one text starts with
+    fn find_closing(text: &str) -> Option<usize> {
+        let mut depth = 0;
+        for (index, c) in text.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(index);
+                    }
+                },
+                _ => {},
+            }
+        }
+        None
+    }
+
+    enum Statement {
+        Empty,
+        Constant(String, String),
+        Org(String),
+        Bytes(Vec<String>),
+        Words(Vec<String>),
+        Instruction(String, String),
+    }
+
+    struct Line {
+        number: usize,
+        label: Option<String>,
+        statement: Statement,
+    }
+
+    // An instruction's op code and addressing mode, picked on the first pass
+    struct Encoding {
+        op_code: u8,
+        mode: M6502AddressMode,
+        operands: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct M6502Segment {
+        pub origin: u16,
+        pub bytes: Vec<u8>,
+    }
+
+    // The bytes from each .org, and the labels and constants with their values
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct M6502Assembly {
+        pub segments: Vec<M6502Segment>,
+        labels: HashMap<String, u16>,
+    }
+
+    impl M6502Assembly {
+
+        pub fn get_label(&self, name: &str) -> Option<u16> {
+            self.labels.get(name).copied()
+        }
+
+        // For the disassembler and debugger views
+        pub fn get_symbols(&self) -> M6502Symbols {
+            let mut labels: Vec<(&String, &u16)> = self.labels.iter().collect();
+            labels.sort();
+            let mut symbols = M6502Symbols::new();
+            for (name, address) in labels {
+                symbols.add(*address, name);
+            }
+            symbols
+        }
+
+        // Everything from the lowest address to the highest, gaps are zeros
+        pub fn get_bytes(&self) -> Vec<u8> {
+            let Some(start) = self.segments.iter().map(|segment| segment.origin as usize).min() else {
+                return Vec::new();
+            };
+            let end = self.segments.iter().map(|segment| segment.origin as usize + segment.bytes.len()).max().unwrap();
+            let mut bytes = vec![0; end - start];
+            for segment in &self.segments {
+                let offset = segment.origin as usize - start;
+                bytes[offset..offset + segment.bytes.len()].copy_from_slice(&segment.bytes);
+            }
+            bytes
+        }
+
+        // Hands every byte to write with its address, for loading into memory
+        pub fn write_to<F: FnMut(u16, u8)>(&self, mut write: F) {
+            for segment in &self.segments {
+                for (offset, byte) in segment.bytes.iter().enumerate() {
+                    write(segment.origin.wrapping_add(offset as u16), *byte);
+                }
+            }
+        }
+    }
+
+    // Two pass assembler for tests and patching code in memory. A line is an
+    // optional "label:", then an instruction, a directive or "name = value":
+    //   .org $f000        code that follows goes here, *= also works
+    //   .byte 1, "text"   bytes and strings
+    //   .word Reset       little endian words
+    // Comments start with ;. Zero page modes are used when the address is
+    // known on the first pass, forward references get the absolute modes.
+    pub struct M6502Assembler {
+        variant: M6502Variant,
+    }
+
+    impl M6502Assembler {
+
+        pub fn new(variant: M6502Variant) -> M6502Assembler {
+            Self {
+                variant,
+            }
+        }
+
+        pub fn assemble(&self, source: &str, origin: u16) -> Result<M6502Assembly, M6502AssemblerError> {
+            let lines = source.lines().enumerate()
+                .map(|(index, text)| self.parse_line(index + 1, text))
+                .collect::<Result<Vec<Line>, M6502AssemblerError>>()?;
+
+            // The first pass gives the labels their addresses
+            let mut labels = HashMap::new();
+            let mut encodings = Vec::new();
+            // Constants that need labels defined after them
+            let mut pending = Vec::new();
+            let mut address = origin;
+            for line in &lines {
+                if let Some(label) = &line.label && labels.insert(label.clone(), address).is_some() {
+                    return Err(M6502AssemblerError::DuplicateLabel(line.number, label.clone()));
+                }
+
+                let mut encoding = None;
+                let size = match &line.statement {
+                    Statement::Empty => 0,
+                    Statement::Constant(name, value) => {
+                        match self.try_evaluate(line.number, value, &labels, address)? {
+                            Some(value) => Self::define(line.number, name, value, &mut labels)?,
+                            None => pending.push((line.number, name, value, address)),
+                        }
+                        0
+                    },
+                    Statement::Org(value) => {
+                        address = self.evaluate_address(line.number, value, &labels, address)?;
+                        0
+                    },
+                    Statement::Bytes(items) => items.iter().map(|item| match item.strip_prefix('"') {
+                        Some(text) => text.trim_end_matches('"').len(),
+                        None => 1,
+                    }).sum(),
+                    Statement::Words(items) => items.len() * 2,
+                    Statement::Instruction(mnemonic, operand) => {
+                        let selected = self.encode(line.number, mnemonic, operand, &labels, address)?;
+                        let size = 1 + selected.mode.get_operand_bytes() as usize;
+                        encoding = Some(selected);
+                        size
+                    },
+                };
+                encodings.push(encoding);
+                address = address.wrapping_add(size as u16);
+            }
+
+            // Every constant is resolved before any bytes are made, constants can
+            // use each other in any order so it takes a round per level
+            while !pending.is_empty() {
+                let mut unresolved = Vec::new();
+                for &(number, name, value, address) in &pending {
+                    match self.try_evaluate(number, value, &labels, address)? {
+                        Some(value) => Self::define(number, name, value, &mut labels)?,
+                        None => unresolved.push((number, name, value, address)),
+                    }
+                }
+                if unresolved.len() == pending.len() {
+                    let (number, _, value, address) = unresolved[0];
+                    return Err(self.evaluate(number, value, &labels, address).unwrap_err());
+                }
+                pending = unresolved;
+            }
+
+            // The second pass fills in the bytes with every label known
+            let mut segments = vec![M6502Segment { origin, bytes: Vec::new() }];
+            let mut address = origin;
+            for (line, encoding) in lines.iter().zip(&encodings) {
+                let mut bytes = Vec::new();
+                match &line.statement {
+                    Statement::Empty | Statement::Constant(..) => {},
+                    Statement::Org(value) => {
+                        address = self.evaluate_address(line.number, value, &labels, address)?;
+                        if segments.last().unwrap().bytes.is_empty() {
+                            segments.pop();
+                        }
+                        segments.push(M6502Segment { origin: address, bytes: Vec::new() });
+                    },
+                    Statement::Bytes(items) => {
+                        for item in items {
+                            match item.strip_prefix('"') {
+                                Some(text) => bytes.extend(text.trim_end_matches('"').bytes()),
+                                None => bytes.push(self.evaluate_byte(line.number, item, &labels, address)?),
+                            }
+                        }
+                    },
+                    Statement::Words(items) => {
+                        for item in items {
+                            let value = self.evaluate(line.number, item, &labels, address)?;
+                            if !(-0x8000..=0xffff).contains(&value) {
+                                return Err(M6502AssemblerError::OutOfRange(line.number, item.clone()));
+                            }
+                            bytes.extend((value as u16).to_le_bytes());
+                        }
+                    },
+                    Statement::Instruction(..) => {
+                        let encoding = encoding.as_ref().unwrap();
+                        bytes = self.emit(line.number, encoding, &labels, address)?;
+                    },
+                }
+                address = address.wrapping_add(bytes.len() as u16);
+                segments.last_mut().unwrap().bytes.extend(bytes);
+            }
+
+            segments.retain(|segment| !segment.bytes.is_empty());
+            Ok(M6502Assembly { segments, labels })
+        }
+
+        fn define(number: usize, name: &str, value: i64, labels: &mut HashMap<String, u16>) -> Result<(), M6502AssemblerError> {
+            if labels.insert(name.to_string(), value as u16).is_some() {
+                return Err(M6502AssemblerError::DuplicateLabel(number, name.to_string()));
+            }
+            Ok(())
+        }
+
+        fn parse_line(&self, number: usize, text: &str) -> Result<Line, M6502AssemblerError> {
+            let mut text = strip_comment(text).trim();
+            let mut label = None;
+
+            if let Some((name, rest)) = text.split_once(':') && is_label(name.trim()) {
+                label = Some(name.trim().to_string());
+                text = rest.trim();
+            }
+
+            let (word, rest) = match text.find(char::is_whitespace) {
+                Some(index) => (&text[..index], text[index..].trim()),
+                None => (text, ""),
+            };
+
+            // name = value, name equ value, *= address
+            if let Some(value) = text.strip_prefix("*=") {
+                return Ok(Line { number, label, statement: Statement::Org(value.trim().to_string()) });
+            }
+            if let Some((name, value)) = text.split_once('=') && is_label(name.trim()) {
+                return Ok(Line { number, label, statement: Statement::Constant(name.trim().to_string(), value.trim().to_string()) });
+            }
+            if let Some((equ, value)) = rest.split_once(char::is_whitespace)
+                && equ.eq_ignore_ascii_case("equ") && is_label(word) {
+                return Ok(Line { number, label, statement: Statement::Constant(word.to_string(), value.trim().to_string()) });
+            }
+
+            let items = || split_list(rest).into_iter().map(String::from).collect::<Vec<String>>();
+            let statement = match word.to_ascii_lowercase().as_str() {
+                "" => Statement::Empty,
+                ".org" => Statement::Org(rest.to_string()),
+                ".byte" => Statement::Bytes(items()),
+                ".word" => Statement::Words(items()),
+                directive if directive.starts_with('.') => {
+                    return Err(M6502AssemblerError::UnknownDirective(number, word.to_string()));
+                },
+                _ => Statement::Instruction(word.to_ascii_uppercase(), rest.to_string()),
+            };
+            Ok(Line { number, label, statement })
+        }
+
+        fn evaluate(&self, number: usize, text: &str, labels: &HashMap<String, u16>, address: u16) -> Result<i64, M6502AssemblerError> {
+            match Expression::evaluate(text, labels, address) {
+                Ok(value) => Ok(value),
+                Err(ExpressionError::Unknown(name)) => Err(M6502AssemblerError::UnknownLabel(number, name)),
+                Err(ExpressionError::Bad(text)) => Err(M6502AssemblerError::BadExpression(number, text)),
+            }
+        }
+
+        // None when a label isn't known yet
+        fn try_evaluate(&self, number: usize, text: &str, labels: &HashMap<String, u16>, address: u16) -> Result<Option<i64>, M6502AssemblerError> {
+            match self.evaluate(number, text, labels, address) {
+                Ok(value) => Ok(Some(value)),
+                Err(M6502AssemblerError::UnknownLabel(..)) => Ok(None),
+                Err(error) => Err(error),
+            }
+        }
+
+        fn evaluate_address(&self, number: usize, text: &str, labels: &HashMap<String, u16>, address: u16) -> Result<u16, M6502AssemblerError> {
+            let value = self.evaluate(number, text, labels, address)?;
+            u16::try_from(value).map_err(|_| M6502AssemblerError::OutOfRange(number, text.to_string()))
+        }
+
+        fn evaluate_byte(&self, number: usize, text: &str, labels: &HashMap<String, u16>, address: u16) -> Result<u8, M6502AssemblerError> {
+            let value = self.evaluate(number, text, labels, address)?;
+            if !(-0x80..=0xff).contains(&value) {
+                return Err(M6502AssemblerError::OutOfRange(number, text.to_string()));
+            }
+            Ok(value as u8)
+        }
+
+        // The op code for each addressing mode the mnemonic has, documented ones first
+        fn get_modes(&self, mnemonic: &str) -> HashMap<M6502AddressMode, u8> {
+            let mnemonic = ALIASES.iter().find(|(alias, _)| *alias == mnemonic).map_or(mnemonic, |(_, name)| name);
+            let mut modes = HashMap::new();
+            let op_codes = self.variant.get_op_codes();
+            for documented in [true, false] {
+                for (op_code, entry) in op_codes.iter().enumerate() {
+                    if is_undocumented(op_code as u8, entry.operation) != documented &&
+                        entry.operation.get_mnemonic() == mnemonic {
+                        modes.entry(entry.mode).or_insert(op_code as u8);
+                    }
+                }
+            }
+            modes
+        }
+
+        // The addressing modes an operand could be, most likely first, and its expressions
+        fn get_candidates(&self, operand: &str) -> Vec<(Vec<M6502AddressMode>, Vec<String>)> {
+            let mut candidates = Vec::new();
+            let upper = operand.to_ascii_uppercase();
+
+            if operand.is_empty() {
+                candidates.push((vec![M6502AddressMode::Implied, M6502AddressMode::Accumulator], vec![]));
+            } else if upper == "A" {
+                candidates.push((vec![M6502AddressMode::Accumulator], vec![]));
+            } else if let Some(value) = operand.strip_prefix('#') {
+                candidates.push((vec![M6502AddressMode::Immediate], vec![value.trim().to_string()]));
+            } else if operand.starts_with('(') && let Some(close) = find_closing(operand) {
+                let inner = &operand[1..close];
+                let rest = operand[close + 1..].trim().to_ascii_uppercase();
+                let items = split_list(inner);
+                match (items.as_slice(), rest.replace(' ', "").as_str()) {
+                    ([value, index], "") if index.eq_ignore_ascii_case("X") => candidates.push((
+                        vec![M6502AddressMode::IndirectX, M6502AddressMode::AbsoluteIndexedIndirect], vec![value.to_string()])),
+                    ([value], ",Y") => candidates.push((vec![M6502AddressMode::IndirectY], vec![value.to_string()])),
+                    ([value], "") => candidates.push((
+                        vec![M6502AddressMode::Indirect, M6502AddressMode::ZeroPageIndirect], vec![value.to_string()])),
+                    _ => {},
+                }
+
+                // Brackets only group a plain expression when an operator follows, like (1+2)*3
+                if !rest.starts_with(['|', '^', '&', '<', '>', '+', '-', '*', '/', '%']) {
+                    return candidates;
+                }
+            }
+
+            // Anything else is a plain expression
+            let items = split_list(operand);
+            match items.as_slice() {
+                [value, index] if index.eq_ignore_ascii_case("X") => candidates.push((
+                    vec![M6502AddressMode::ZeroPageX, M6502AddressMode::AbsoluteX], vec![value.to_string()])),
+                [value, index] if index.eq_ignore_ascii_case("Y") => candidates.push((
+                    vec![M6502AddressMode::ZeroPageY, M6502AddressMode::AbsoluteY], vec![value.to_string()])),
+                [value, target] => candidates.push((
+                    vec![M6502AddressMode::ZeroPageRelative], vec![value.to_string(), target.to_string()])),
+                [value] if !value.is_empty() && upper != "A" && !value.starts_with('#') => candidates.push((
+                    vec![M6502AddressMode::Relative, M6502AddressMode::ZeroPage, M6502AddressMode::Absolute],
+                    vec![value.to_string()])),
+                _ => {},
+            }
+            candidates
+        }
+
+        fn encode(&self, number: usize, mnemonic: &str, operand: &str, labels: &HashMap<String, u16>, address: u16) -> Result<Encoding, M6502AssemblerError> {
+            let modes = self.get_modes(mnemonic);
+            if modes.is_empty() {
+                return Err(M6502AssemblerError::UnknownMnemonic(number, mnemonic.to_string()));
+            }
+
+            for (candidates, operands) in self.get_candidates(operand) {
+                let available: Vec<M6502AddressMode> = candidates.into_iter().filter(|mode| modes.contains_key(mode)).collect();
+                let mode = match available.as_slice() {
+                    [] => continue,
+                    [mode] => *mode,
+                    [M6502AddressMode::Implied, ..] | [M6502AddressMode::Relative, ..] => available[0],
+                    // The zero page mode of a pair when the address is known to fit
+                    [zero_page, absolute, ..] => {
+                        let value = self.try_evaluate(number, &operands[0], labels, address)?;
+                        if value.is_some_and(|value| (0..=0xff).contains(&value)) { *zero_page } else { *absolute }
+                    },
+                };
+                return Ok(Encoding { op_code: modes[&mode], mode, operands });
+            }
+            Err(M6502AssemblerError::BadOperand(number, operand.to_string()))
+        }
+
+        // next is the address after the instruction, the offset is from there
+        fn get_branch_offset(&self, number: usize, text: &str, labels: &HashMap<String, u16>, address: u16, next: u16) -> Result<u8, M6502AssemblerError> {
+            let target = self.evaluate_address(number, text, labels, address)?;
+            let offset = target.wrapping_sub(next) as i16;
+            i8::try_from(offset).map(|offset| offset as u8)
+                .map_err(|_| M6502AssemblerError::OutOfRange(number, text.to_string()))
+        }
+
+        fn emit(&self, number: usize, encoding: &Encoding, labels: &HashMap<String, u16>, address: u16) -> Result<Vec<u8>, M6502AssemblerError> {
+            let mut bytes = vec![encoding.op_code];
+            let next = address.wrapping_add(1 + encoding.mode.get_operand_bytes());
+            match encoding.mode {
+                M6502AddressMode::Implied | M6502AddressMode::Accumulator => {},
+                M6502AddressMode::Immediate | M6502AddressMode::ZeroPage | M6502AddressMode::ZeroPageX |
+                M6502AddressMode::ZeroPageY | M6502AddressMode::IndirectX | M6502AddressMode::IndirectY |
+                M6502AddressMode::ZeroPageIndirect => {
+                    let text = &encoding.operands[0];
+                    let value = self.evaluate(number, text, labels, address)?;
+                    let range = if encoding.mode == M6502AddressMode::Immediate { -0x80..=0xff } else { 0..=0xff };
+                    if !range.contains(&value) {
+                        return Err(M6502AssemblerError::OutOfRange(number, text.clone()));
+                    }
+                    bytes.push(value as u8);
+                },
+                M6502AddressMode::Absolute | M6502AddressMode::AbsoluteX | M6502AddressMode::AbsoluteY |
+                M6502AddressMode::Indirect | M6502AddressMode::AbsoluteIndexedIndirect => {
+                    let value = self.evaluate_address(number, &encoding.operands[0], labels, address)?;
+                    bytes.extend(value.to_le_bytes());
+                },
+                M6502AddressMode::Relative => {
+                    bytes.push(self.get_branch_offset(number, &encoding.operands[0], labels, address, next)?);
+                },
+                M6502AddressMode::ZeroPageRelative => {
+                    let text = &encoding.operands[0];
+                    let value = self.evaluate(number, text, labels, address)?;
+                    if !(0..=0xff).contains(&value) {
+                        return Err(M6502AssemblerError::OutOfRange(number, text.clone()));
+                    }
+                    bytes.push(value as u8);
+                    bytes.push(self.get_branch_offset(number, &encoding.operands[1], labels, address, next)?);
+                },
+            }
+            Ok(bytes)
+        }
+    }
+}
@@ -0,0 +1,369 @@
+
+pub mod mdebugger {
+
+    use std::fmt;
+
+    use emumemory::base_memory::emu_memory::{BaseMemory, MemoryError};
+
+    use crate::m6502::emu_cpu::{M6502, M6502Runner, CARRY_FLAG, ZERO_FLAG, INTERRUPT_FLAG, DECIMAL_MODE,
+        BREAK_COMMAND, IGNORED, OVERFLOW_FLAG, NEGATIVE_FLAG};
+
+    // Status flags from bit 7 down, upper case when set
+    const FLAG_NAMES: [(u8, char); 8] = [(NEGATIVE_FLAG, 'N'), (OVERFLOW_FLAG, 'V'), (IGNORED, '-'),
+        (BREAK_COMMAND, 'B'), (DECIMAL_MODE, 'D'), (INTERRUPT_FLAG, 'I'), (ZERO_FLAG, 'Z'), (CARRY_FLAG, 'C')];
+
+    impl M6502 {
+        pub fn get_flags(&self) -> String {
+            FLAG_NAMES.iter()
+                .map(|(flag, name)| if self.status_register & flag != 0 { *name } else { name.to_ascii_lowercase() })
+                .collect()
+        }
+    }
+
+    impl fmt::Display for M6502 {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "PC:{:04X} A:{:02X} X:{:02X} Y:{:02X} SP:{:02X} P:{:02X} {}", self.program_counter,
+                self.accumulator, self.register_x, self.register_y, self.stack_pointer, self.status_register,
+                self.get_flags())
+        }
+    }
+
+    #[derive(Debug)]
+    pub enum M6502ConditionError {
+        UnknownRegister(String),
+        UnknownComparison(String),
+        BadValue(String),
+        Incomplete(String),
+    }
+
+    impl fmt::Display for M6502ConditionError {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                M6502ConditionError::UnknownRegister(name) => write!(f, "Unknown register '{}'", name),
+                M6502ConditionError::UnknownComparison(name) => write!(f, "Unknown comparison '{}'", name),
+                M6502ConditionError::BadValue(value) => write!(f, "Couldn't read value '{}'", value),
+                M6502ConditionError::Incomplete(term) => write!(f, "Expected register, comparison and value in '{}'", term),
+            }
+        }
+    }
+
+    impl std::error::Error for M6502ConditionError {}
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum M6502Register {
+        A,
+        X,
+        Y,
+        Sp,
+        Pc,
+        P,
+        // A single status flag, 0 or 1
+        Flag(u8),
+    }
+
+    impl M6502Register {
+
+        fn parse(name: &str) -> Result<M6502Register, M6502ConditionError> {
+            let register = match name.to_ascii_uppercase().as_str() {
+                "A" => M6502Register::A,
+                "X" => M6502Register::X,
+                "Y" => M6502Register::Y,
+                "SP" | "S" => M6502Register::Sp,
+                "PC" => M6502Register::Pc,
+                "P" => M6502Register::P,
+                "N" => M6502Register::Flag(NEGATIVE_FLAG),
+                "V" => M6502Register::Flag(OVERFLOW_FLAG),
+                "D" => M6502Register::Flag(DECIMAL_MODE),
+                "I" => M6502Register::Flag(INTERRUPT_FLAG),
+                "Z" => M6502Register::Flag(ZERO_FLAG),
+                "C" => M6502Register::Flag(CARRY_FLAG),
+                _ => return Err(M6502ConditionError::UnknownRegister(name.to_string())),
+            };
+            Ok(register)
+        }
+
+        pub fn get_value(&self, cpu: &M6502) -> u16 {
+            match self {
+                M6502Register::A => cpu.accumulator as u16,
+                M6502Register::X => cpu.register_x as u16,
+                M6502Register::Y => cpu.register_y as u16,
+                M6502Register::Sp => cpu.stack_pointer as u16,
+                M6502Register::Pc => cpu.program_counter,
+                M6502Register::P => cpu.status_register as u16,
+                M6502Register::Flag(flag) => cpu.get_status_flag(*flag) as u16,
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum M6502Comparison {
+        Equal,
+        NotEqual,
+        Less,
+        LessEqual,
+        Greater,
+        GreaterEqual,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    struct M6502Term {
+        register: M6502Register,
+        comparison: M6502Comparison,
+        value: u16,
+    }
+
+    impl M6502Term {
+
+        fn is_true(&self, cpu: &M6502) -> bool {
+            let register = self.register.get_value(cpu);
+            match self.comparison {
+                M6502Comparison::Equal => register == self.value,
+                M6502Comparison::NotEqual => register != self.value,
+                M6502Comparison::Less => register < self.value,
+                M6502Comparison::LessEqual => register <= self.value,
+                M6502Comparison::Greater => register > self.value,
+                M6502Comparison::GreaterEqual => register >= self.value,
+            }
+        }
+    }
+
+    // Register comparisons joined by &&, like "A == $10 && C == 1". Values are
+    // decimal, or hex with $ or 0x in front.
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct M6502Condition {
+        terms: Vec<M6502Term>,
+    }
+
+    impl M6502Condition {
+
+        pub fn parse(text: &str) -> Result<M6502Condition, M6502ConditionError> {
+            let mut terms = Vec::new();
+            for term in text.split("&&") {
+                let words: Vec<&str> = term.split_whitespace().collect();
+                let [register, comparison, value] = words[..] else {
+                    return Err(M6502ConditionError::Incomplete(term.trim().to_string()));
+                };
+
+                let comparison = match comparison {
+                    "==" | "=" => M6502Comparison::Equal,
+                    "!=" => M6502Comparison::NotEqual,
+                    "<" => M6502Comparison::Less,
+                    "<=" => M6502Comparison::LessEqual,
+                    ">" => M6502Comparison::Greater,
+                    ">=" => M6502Comparison::GreaterEqual,
+                    _ => return Err(M6502ConditionError::UnknownComparison(comparison.to_string())),
+                };
+
+                terms.push(M6502Term {
+                    register: M6502Register::parse(register)?,
+                    comparison,
+                    value: parse_value(value)?,
+                });
+            }
+            Ok(M6502Condition { terms })
+        }
+
+        pub fn is_true(&self, cpu: &M6502) -> bool {
+            self.terms.iter().all(|term| term.is_true(cpu))
+        }
+    }
+
+    fn parse_value(value: &str) -> Result<u16, M6502ConditionError> {
+        let result = if let Some(hex) = value.strip_prefix('$') {
+            u16::from_str_radix(hex, 16)
+        } else if let Some(hex) = value.strip_prefix("0x") {
+            u16::from_str_radix(hex, 16)
+        } else {
+            value.parse()
+        };
+        result.map_err(|_| M6502ConditionError::BadValue(value.to_string()))
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum M6502WatchKind {
+        Read,
+        Write,
+        // An op code fetched from the address
+        Execute,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct M6502Watchpoint {
+        pub kind: M6502WatchKind,
+        pub start: u16,
+        pub end: u16,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct M6502Breakpoint {
+        pub address: u16,
+        pub condition: Option<M6502Condition>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum M6502Step {
+        Cycle,
+        Instruction,
+        // Scanlines and frames are ended by the console
+        Scanline,
+        Frame,
+        // Until the op code at the address is fetched
+        RunTo(u16),
+    }
+
+    // Why the debugger stopped the console
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub enum M6502Break {
+        Paused,
+        Step,
+        Breakpoint(u16),
+        // The watchpoint and the address that hit it
+        Watchpoint(M6502Watchpoint, u16),
+        // A memory with the Trap policy had a bad access
+        Memory(MemoryError),
+    }
+
+    // Consoles call check_tick after every CPU tick, and end_scanline and
+    // end_frame as their video does. They stop ticking while it's stopped.
+    pub struct M6502Debugger {
+        breakpoints: Vec<M6502Breakpoint>,
+        watchpoints: Vec<M6502Watchpoint>,
+        step: Option<M6502Step>,
+        stopped: Option<M6502Break>,
+    }
+
+    impl Default for M6502Debugger {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl M6502Debugger {
+
+        pub fn new() -> M6502Debugger {
+            Self {
+                breakpoints: Vec::new(),
+                watchpoints: Vec::new(),
+                step: None,
+                stopped: None,
+            }
+        }
+
+        // Stops when the op code at address is fetched and the condition, if any, holds
+        pub fn add_breakpoint(&mut self, address: u16, condition: Option<M6502Condition>) {
+            self.remove_breakpoint(address);
+            self.breakpoints.push(M6502Breakpoint { address, condition });
+        }
+
+        pub fn remove_breakpoint(&mut self, address: u16) {
+            self.breakpoints.retain(|breakpoint| breakpoint.address != address);
+        }
+
+        pub fn get_breakpoints(&self) -> &[M6502Breakpoint] {
+            &self.breakpoints
+        }
+
+        // Watches the addresses from start to end, both included
+        pub fn add_watchpoint(&mut self, kind: M6502WatchKind, start: u16, end: u16) {
+            let watchpoint = M6502Watchpoint { kind, start, end };
+            if !self.watchpoints.contains(&watchpoint) {
+                self.watchpoints.push(watchpoint);
+            }
+        }
+
+        pub fn remove_watchpoint(&mut self, kind: M6502WatchKind, start: u16) {
+            self.watchpoints.retain(|watchpoint| watchpoint.kind != kind || watchpoint.start != start);
+        }
+
+        pub fn get_watchpoints(&self) -> &[M6502Watchpoint] {
+            &self.watchpoints
+        }
+
+        pub fn pause(&mut self) {
+            self.step = None;
+            self.stopped = Some(M6502Break::Paused);
+        }
+
+        pub fn resume(&mut self) {
+            self.step = None;
+            self.stopped = None;
+        }
+
+        // Runs until the step is done, or a breakpoint or watchpoint stops it first
+        pub fn step(&mut self, step: M6502Step) {
+            self.step = Some(step);
+            self.stopped = None;
+        }
+
+        pub fn is_stopped(&self) -> bool {
+            self.stopped.is_some()
+        }
+
+        pub fn get_break(&self) -> Option<M6502Break> {
+            self.stopped.clone()
+        }
+
+        fn stop(&mut self, reason: M6502Break) {
+            if self.stopped.is_none() {
+                self.step = None;
+                self.stopped = Some(reason);
+            }
+        }
+
+        pub fn check_tick(&mut self, runner: &M6502Runner) {
+            if self.stopped.is_some() || (self.step.is_none() && self.breakpoints.is_empty() && self.watchpoints.is_empty()) {
+                return;
+            }
+
+            let cpu = runner.get_cpu();
+            let cycle = runner.get_last_cycle();
+            let fetch = runner.is_op_code_fetch();
+
+            match self.step {
+                Some(M6502Step::Cycle) => self.stop(M6502Break::Step),
+                Some(M6502Step::Instruction) if fetch => self.stop(M6502Break::Step),
+                Some(M6502Step::RunTo(address)) if fetch && cpu.program_counter == address => self.stop(M6502Break::Step),
+                _ => {},
+            }
+
+            let hit = self.watchpoints.iter().find_map(|watchpoint| {
+                let address = match watchpoint.kind {
+                    M6502WatchKind::Execute if fetch => cpu.program_counter,
+                    M6502WatchKind::Read if !cycle.write => cycle.address,
+                    M6502WatchKind::Write if cycle.write => cycle.address,
+                    _ => return None,
+                };
+                (watchpoint.start..=watchpoint.end).contains(&address).then_some(M6502Break::Watchpoint(*watchpoint, address))
+            });
+            if let Some(hit) = hit {
+                self.stop(hit);
+            }
+
+            if fetch && self.breakpoints.iter().any(|breakpoint| breakpoint.address == cpu.program_counter &&
+                breakpoint.condition.as_ref().is_none_or(|condition| condition.is_true(cpu))) {
+                self.stop(M6502Break::Breakpoint(cpu.program_counter));
+            }
+        }
+
+        // Stops on the error the memory trapped, if it has one
+        pub fn check_memory(&mut self, memory: &mut dyn BaseMemory) {
+            if let Some(error) = memory.take_error() {
+                self.stop(M6502Break::Memory(error));
+            }
+        }
+
+        pub fn end_scanline(&mut self) {
+            if self.step == Some(M6502Step::Scanline) {
+                self.stop(M6502Break::Step);
+            }
+        }
+
+        pub fn end_frame(&mut self) {
+            if self.step == Some(M6502Step::Frame) {
+                self.stop(M6502Break::Step);
+            }
+        }
+    }
+}
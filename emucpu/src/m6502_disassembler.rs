@@ -0,0 +1,350 @@
+
+pub mod mdisassembler {
+
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::fs;
+    use std::path::Path;
+
+    use crate::m6502::emu_cpu::M6502Variant;
+    use crate::m6502_address::maddress::M6502AddressMode;
+    use crate::m6502_opcodes::mopcodes::M6502Operation;
+
+    // DASM directives that can start a listing line's source, anything else
+    // that isn't a mnemonic is taken as a label
+    const DASM_DIRECTIVES: [&str; 30] = ["processor", "org", "rorg", "rend", "seg", "seg.u", "equ", "eqm", "set",
+        "include", "incbin", "incdir", "ds", "dc", "byte", "word", "long", "hex", "align", "echo", "if", "else",
+        "endif", "ifconst", "ifnconst", "repeat", "repend", "mac", "endm", "subroutine"];
+
+    #[derive(Debug)]
+    pub enum M6502SymbolError {
+        Read(String),
+        UnknownFormat(String),
+    }
+
+    impl fmt::Display for M6502SymbolError {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                M6502SymbolError::Read(e) => write!(f, "Couldn't read symbol file: {}", e),
+                M6502SymbolError::UnknownFormat(name) => write!(f, "Unknown symbol file type '{}'", name),
+            }
+        }
+    }
+
+    impl std::error::Error for M6502SymbolError {}
+
+    // Names for addresses. The first name loaded for an address is the one shown.
+    #[derive(Default, Debug, Clone)]
+    pub struct M6502Symbols {
+        names: HashMap<u16, String>,
+    }
+
+    impl M6502Symbols {
+
+        pub fn new() -> M6502Symbols {
+            M6502Symbols::default()
+        }
+
+        // Picks the format from the extension: ca65 .dbg, VICE or DASM .sym, DASM .lst
+        pub fn load(file: &str) -> Result<M6502Symbols, M6502SymbolError> {
+            let text = match fs::read_to_string(file) {
+                Ok(text) => text,
+                Err(e) => return Err(M6502SymbolError::Read(e.to_string())),
+            };
+            let extension = Path::new(file).extension().and_then(|extension| extension.to_str()).unwrap_or("");
+
+            let mut symbols = M6502Symbols::new();
+            match extension.to_ascii_lowercase().as_str() {
+                "dbg" => symbols.add_ca65_dbg(&text),
+                "sym" | "lbl" | "vs" if text.lines().any(|line| line.starts_with("al ")) => symbols.add_vice_labels(&text),
+                "sym" => symbols.add_dasm_sym(&text),
+                "lst" => symbols.add_dasm_lst(&text),
+                _ => return Err(M6502SymbolError::UnknownFormat(file.to_string())),
+            }
+            Ok(symbols)
+        }
+
+        pub fn add(&mut self, address: u16, name: &str) {
+            self.names.entry(address).or_insert_with(|| name.to_string());
+        }
+
+        pub fn get_name(&self, address: u16) -> Option<&str> {
+            self.names.get(&address).map(|name| name.as_str())
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.names.is_empty()
+        }
+
+        // ld65's --dbgfile, sym lines like: sym id=3,name="reset",addrsize=absolute,...,val=0xC000,type=lab
+        pub fn add_ca65_dbg(&mut self, text: &str) {
+            for line in text.lines() {
+                let Some(fields) = line.strip_prefix("sym\t").or_else(|| line.strip_prefix("sym ")) else {
+                    continue;
+                };
+
+                let mut name = None;
+                let mut value = None;
+                for field in fields.split(',') {
+                    match field.split_once('=') {
+                        Some(("name", text)) => name = Some(text.trim_matches('"')),
+                        Some(("val", text)) => value = parse_number(text),
+                        _ => {},
+                    }
+                }
+                if let (Some(name), Some(value)) = (name, value) {
+                    self.add(value as u16, name);
+                }
+            }
+        }
+
+        // VICE label files from ld65 -Ln, lines like: al 00C000 .reset
+        pub fn add_vice_labels(&mut self, text: &str) {
+            for line in text.lines() {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                if let ["al", address, name] = words[..] && let Ok(address) = u32::from_str_radix(address, 16) {
+                    self.add(address as u16, name.trim_start_matches('.'));
+                }
+            }
+        }
+
+        // DASM's -s symbol dump, lines like: reset    f000    (R )
+        pub fn add_dasm_sym(&mut self, text: &str) {
+            for line in text.lines().filter(|line| !line.starts_with("---")) {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                if words.len() >= 2 && let Ok(value) = u32::from_str_radix(words[1], 16) {
+                    self.add(value as u16, words[0]);
+                }
+            }
+        }
+
+        // DASM's -l listing, lines like: 21  f000  a9 00  reset  lda #0. Labels are
+        // the first word of the source when it isn't a mnemonic or directive.
+        pub fn add_dasm_lst(&mut self, text: &str) {
+            for line in text.lines() {
+                let mut words = line.split_whitespace().peekable();
+                let (Some(number), Some(address)) = (words.next(), words.next()) else {
+                    continue;
+                };
+                if number.parse::<u32>().is_err() {
+                    continue;
+                }
+                // ???? before the first org
+                let address = u16::from_str_radix(address.trim_start_matches('U'), 16).ok();
+                words.next_if_eq(&"????");
+
+                // Assembled bytes come before the source
+                while words.peek().is_some_and(|word| word.len() == 2 && u8::from_str_radix(word, 16).is_ok()) {
+                    words.next();
+                }
+
+                let Some(label) = words.next() else {
+                    continue;
+                };
+                if label.starts_with(';') || is_mnemonic(label) || DASM_DIRECTIVES.contains(&label.to_ascii_lowercase().as_str()) {
+                    continue;
+                }
+
+                let label = label.trim_end_matches(':');
+                match (words.next(), words.next()) {
+                    (Some(directive), Some(value)) if matches!(directive.to_ascii_lowercase().as_str(), "equ" | "=" | "set") => {
+                        if let Some(value) = parse_number(value) {
+                            self.add(value as u16, label);
+                        }
+                    },
+                    _ => if let Some(address) = address {
+                        self.add(address, label);
+                    },
+                }
+            }
+        }
+    }
+
+    fn parse_number(text: &str) -> Option<u32> {
+        if let Some(hex) = text.strip_prefix('$').or_else(|| text.strip_prefix("0x")).or_else(|| text.strip_prefix("0X")) {
+            u32::from_str_radix(hex, 16).ok()
+        } else {
+            text.parse().ok()
+        }
+    }
+
+    fn is_mnemonic(word: &str) -> bool {
+        let word = word.to_ascii_uppercase();
+        M6502Variant::Rockwell65C02.get_op_codes().iter().chain(M6502Variant::Nmos.get_op_codes().iter())
+            .any(|op_code| op_code.operation.get_mnemonic() == word)
+    }
+
+    // Not in the data sheets, the duplicate NOPs and SBC included
+    pub(crate) fn is_undocumented(op_code: u8, operation: M6502Operation) -> bool {
+        match operation {
+            M6502Operation::Nop | M6502Operation::Nop1 => op_code != 0xEA,
+            M6502Operation::Sbc => op_code == 0xEB,
+            M6502Operation::Anc | M6502Operation::Ane | M6502Operation::Arr | M6502Operation::Asr |
+            M6502Operation::Axs | M6502Operation::Dcp | M6502Operation::Isc | M6502Operation::Jam |
+            M6502Operation::Lae | M6502Operation::Lax | M6502Operation::Lxa | M6502Operation::Rla |
+            M6502Operation::Rra | M6502Operation::Sax | M6502Operation::Sha | M6502Operation::Shs |
+            M6502Operation::Shx | M6502Operation::Shy | M6502Operation::Slo | M6502Operation::Sre => true,
+            _ => false,
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct M6502Instruction {
+        pub address: u16,
+        pub bytes: Vec<u8>,
+        pub operation: M6502Operation,
+        pub mode: M6502AddressMode,
+        // Not in the variant's data sheet, shown with a * like nestest.log
+        pub undocumented: bool,
+    }
+
+    impl M6502Instruction {
+
+        fn get_byte(&self) -> u8 {
+            self.bytes[1]
+        }
+
+        fn get_word(&self) -> u16 {
+            ((self.bytes[2] as u16) << 8) | self.bytes[1] as u16
+        }
+
+        // The address the operand refers to, for modes that have one
+        pub fn get_target(&self) -> Option<u16> {
+            match self.mode {
+                M6502AddressMode::ZeroPage | M6502AddressMode::ZeroPageX | M6502AddressMode::ZeroPageY |
+                M6502AddressMode::IndirectX | M6502AddressMode::IndirectY | M6502AddressMode::ZeroPageIndirect |
+                M6502AddressMode::ZeroPageRelative => Some(self.get_byte() as u16),
+                M6502AddressMode::Absolute | M6502AddressMode::AbsoluteX | M6502AddressMode::AbsoluteY |
+                M6502AddressMode::Indirect | M6502AddressMode::AbsoluteIndexedIndirect => Some(self.get_word()),
+                M6502AddressMode::Relative => Some(self.get_branch_target()),
+                _ => None,
+            }
+        }
+
+        pub fn get_branch_target(&self) -> u16 {
+            let offset = *self.bytes.last().unwrap() as i8 as u16;
+            self.address.wrapping_add(self.bytes.len() as u16).wrapping_add(offset)
+        }
+
+        pub fn get_mnemonic(&self) -> String {
+            let prefix = if self.undocumented { "*" } else { "" };
+            format!("{}{}", prefix, self.operation.get_mnemonic())
+        }
+
+        // The operand as assembler source, with names from symbols where there are any
+        pub fn get_operand(&self, symbols: Option<&M6502Symbols>) -> String {
+            let name = |address: u16, zero_page: bool| match symbols.and_then(|symbols| symbols.get_name(address)) {
+                Some(name) => name.to_string(),
+                None if zero_page => format!("${:02X}", address),
+                None => format!("${:04X}", address),
+            };
+
+            match self.mode {
+                M6502AddressMode::Implied => String::new(),
+                M6502AddressMode::Accumulator => String::from("A"),
+                M6502AddressMode::Immediate => format!("#${:02X}", self.get_byte()),
+                M6502AddressMode::ZeroPage => name(self.get_byte() as u16, true),
+                M6502AddressMode::ZeroPageX => format!("{},X", name(self.get_byte() as u16, true)),
+                M6502AddressMode::ZeroPageY => format!("{},Y", name(self.get_byte() as u16, true)),
+                M6502AddressMode::Absolute => name(self.get_word(), false),
+                M6502AddressMode::AbsoluteX => format!("{},X", name(self.get_word(), false)),
+                M6502AddressMode::AbsoluteY => format!("{},Y", name(self.get_word(), false)),
+                M6502AddressMode::Indirect => format!("({})", name(self.get_word(), false)),
+                M6502AddressMode::IndirectX => format!("({},X)", name(self.get_byte() as u16, true)),
+                M6502AddressMode::IndirectY => format!("({}),Y", name(self.get_byte() as u16, true)),
+                M6502AddressMode::Relative => name(self.get_branch_target(), false),
+                M6502AddressMode::ZeroPageIndirect => format!("({})", name(self.get_byte() as u16, true)),
+                M6502AddressMode::AbsoluteIndexedIndirect => format!("({},X)", name(self.get_word(), false)),
+                M6502AddressMode::ZeroPageRelative => format!("{},{}", name(self.get_byte() as u16, true),
+                    name(self.get_branch_target(), false)),
+            }
+        }
+
+        pub fn format(&self, symbols: Option<&M6502Symbols>) -> String {
+            let operand = self.get_operand(symbols);
+            if operand.is_empty() {
+                self.get_mnemonic()
+            } else {
+                format!("{} {}", self.get_mnemonic(), operand)
+            }
+        }
+    }
+
+    impl fmt::Display for M6502Instruction {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.format(None))
+        }
+    }
+
+    pub struct M6502Disassembler {
+        variant: M6502Variant,
+        symbols: M6502Symbols,
+    }
+
+    impl M6502Disassembler {
+
+        pub fn new(variant: M6502Variant) -> M6502Disassembler {
+            Self {
+                variant,
+                symbols: M6502Symbols::new(),
+            }
+        }
+
+        pub fn set_symbols(&mut self, symbols: M6502Symbols) {
+            self.symbols = symbols;
+        }
+
+        pub fn get_symbols(&self) -> &M6502Symbols {
+            &self.symbols
+        }
+
+        pub fn get_variant(&self) -> M6502Variant {
+            self.variant
+        }
+
+        // Reads the instruction at address, read doesn't have to have side effects
+        pub fn disassemble<F: Fn(u16) -> u8>(&self, address: u16, read: F) -> M6502Instruction {
+            let op_code = read(address);
+            let entry = self.variant.get_op_codes()[op_code as usize];
+            let length = 1 + entry.mode.get_operand_bytes();
+
+            M6502Instruction {
+                address,
+                bytes: (0..length).map(|offset| read(address.wrapping_add(offset))).collect(),
+                operation: entry.operation,
+                mode: entry.mode,
+                undocumented: is_undocumented(op_code, entry.operation),
+            }
+        }
+
+        // Every instruction in data, loaded at origin. Bytes left over at the end are dropped.
+        pub fn disassemble_all(&self, data: &[u8], origin: u16) -> Vec<M6502Instruction> {
+            let mut instructions = Vec::new();
+            let mut offset = 0;
+            while offset < data.len() {
+                let length = 1 + self.variant.get_op_codes()[data[offset] as usize].mode.get_operand_bytes() as usize;
+                if offset + length > data.len() {
+                    break;
+                }
+                let address = origin.wrapping_add(offset as u16);
+                instructions.push(self.disassemble(address, |at| data[offset + at.wrapping_sub(address) as usize]));
+                offset += length;
+            }
+            instructions
+        }
+
+        // One listing line, with a label line first when the address has a name
+        pub fn format_line(&self, instruction: &M6502Instruction) -> String {
+            let bytes: Vec<String> = instruction.bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let line = format!("{:04X}  {:<9} {}", instruction.address, bytes.join(" "),
+                instruction.format(Some(&self.symbols)));
+
+            match self.symbols.get_name(instruction.address) {
+                Some(name) => format!("{}:\n{}", name, line),
+                None => line,
+            }
+        }
+    }
+}
@@ -1,1781 +1,617 @@
 
 pub mod mopcodes {
 
-    use crate::m6502::emu_cpu::M6502;
-    use crate::m6502::emu_cpu::AddressBus;
-    use crate::m6502::emu_cpu::{CARRY_FLAG, ZERO_FLAG, INTERRUPT_FLAG, DECIMAL_MODE, BREAK_COMMAND, IGNORED, OVERFLOW_FLAG, NEGATIVE_FLAG};
-
-    const RESET_FIRST_READ: u16 = 0xFFFC;
-    const RESET_SECOND_READ: u16 = 0xFFFD;
-
-    pub struct OpCodesUtils {}
-    impl OpCodesUtils {
-        pub fn get_opcodes() -> Vec<Box<dyn CpuOperation>> {
-            let mut op_code_lookup: Vec<Box<dyn CpuOperation>> = Vec::with_capacity(0x200);
-            for _i in 0..0x200 {
-                op_code_lookup.push(Box::new(CpuOpPanic {}));
-            }
-            op_code_lookup[0x00] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x01] = Box::new(CpuOpOra {});
-            op_code_lookup[0x02] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x03] = Box::new(CpuOpSlo {});
-            op_code_lookup[0x04] = Box::new(CpuOpNop {});
-            op_code_lookup[0x05] = Box::new(CpuOpOra {});
-            op_code_lookup[0x06] = Box::new(CpuOpAsl {});
-            op_code_lookup[0x07] = Box::new(CpuOpSlo {});
-            op_code_lookup[0x08] = Box::new(CpuOpPhp {});
-            op_code_lookup[0x09] = Box::new(CpuOpOra {});
-            op_code_lookup[0x0a] = Box::new(CpuOpAsl {});
-            op_code_lookup[0x0b] = Box::new(CpuOpAnc {});
-            op_code_lookup[0x0c] = Box::new(CpuOpNop {});
-            op_code_lookup[0x0d] = Box::new(CpuOpOra {});
-            op_code_lookup[0x0e] = Box::new(CpuOpAsl {});
-            op_code_lookup[0x0f] = Box::new(CpuOpSlo {});
-
-            op_code_lookup[0x10] = Box::new(CpuOpBpl {});
-            op_code_lookup[0x11] = Box::new(CpuOpOra {});
-            op_code_lookup[0x12] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x13] = Box::new(CpuOpSlo {});
-            op_code_lookup[0x14] = Box::new(CpuOpNop {});
-            op_code_lookup[0x15] = Box::new(CpuOpOra {});
-            op_code_lookup[0x16] = Box::new(CpuOpAsl {});
-            op_code_lookup[0x17] = Box::new(CpuOpSlo {});
-            op_code_lookup[0x18] = Box::new(CpuOpClc {});
-            op_code_lookup[0x19] = Box::new(CpuOpOra {});
-            op_code_lookup[0x1a] = Box::new(CpuOpNop {});
-            op_code_lookup[0x1b] = Box::new(CpuOpSlo {});
-            op_code_lookup[0x1c] = Box::new(CpuOpNop {});
-            op_code_lookup[0x1d] = Box::new(CpuOpOra {});
-            op_code_lookup[0x1e] = Box::new(CpuOpAsl {});
-            op_code_lookup[0x1f] = Box::new(CpuOpSlo {});
-
-            op_code_lookup[0x20] = Box::new(CpuOpJsr {});
-            op_code_lookup[0x21] = Box::new(CpuOpAnd {});
-            op_code_lookup[0x22] = Box::new(CpuOpRla {});
-            op_code_lookup[0x23] = Box::new(CpuOpRla {});
-            op_code_lookup[0x24] = Box::new(CpuOpBit {});
-            op_code_lookup[0x25] = Box::new(CpuOpAnd {});
-            op_code_lookup[0x26] = Box::new(CpuOpRol {});
-            op_code_lookup[0x27] = Box::new(CpuOpRla {});
-            op_code_lookup[0x28] = Box::new(CpuOpPlp {});
-            op_code_lookup[0x29] = Box::new(CpuOpAnd {});
-            op_code_lookup[0x2a] = Box::new(CpuOpRol {});
-            op_code_lookup[0x2b] = Box::new(CpuOpAnc {});
-            op_code_lookup[0x2c] = Box::new(CpuOpBit {});
-            op_code_lookup[0x2d] = Box::new(CpuOpAnd {});
-            op_code_lookup[0x2e] = Box::new(CpuOpRol {});
-            op_code_lookup[0x2f] = Box::new(CpuOpRla {});
-
-            op_code_lookup[0x30] = Box::new(CpuOpBmi {});
-            op_code_lookup[0x31] = Box::new(CpuOpAnd {});
-            op_code_lookup[0x32] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x33] = Box::new(CpuOpRla {});
-            op_code_lookup[0x34] = Box::new(CpuOpNop {});
-            op_code_lookup[0x35] = Box::new(CpuOpAnd {});
-            op_code_lookup[0x36] = Box::new(CpuOpRol {});
-            op_code_lookup[0x37] = Box::new(CpuOpRla {});
-            op_code_lookup[0x38] = Box::new(CpuOpSec {});
-            op_code_lookup[0x39] = Box::new(CpuOpAnd {});
-            op_code_lookup[0x3a] = Box::new(CpuOpNop {});
-            op_code_lookup[0x3b] = Box::new(CpuOpRla {});
-            op_code_lookup[0x3c] = Box::new(CpuOpNop {});
-            op_code_lookup[0x3d] = Box::new(CpuOpAnd {});
-            op_code_lookup[0x3e] = Box::new(CpuOpRol {});
-            op_code_lookup[0x3f] = Box::new(CpuOpRla {});
-
-            op_code_lookup[0x40] = Box::new(CpuOpRti {});
-            op_code_lookup[0x41] = Box::new(CpuOpEor {});
-            op_code_lookup[0x42] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x43] = Box::new(CpuOpSre {});
-            op_code_lookup[0x44] = Box::new(CpuOpNop {});
-            op_code_lookup[0x45] = Box::new(CpuOpEor {});
-            op_code_lookup[0x46] = Box::new(CpuOpLsr {});
-            op_code_lookup[0x47] = Box::new(CpuOpSre {});
-            op_code_lookup[0x48] = Box::new(CpuOpPha {});
-            op_code_lookup[0x49] = Box::new(CpuOpEor {});
-            op_code_lookup[0x4a] = Box::new(CpuOpLsr {});
-            op_code_lookup[0x4b] = Box::new(CpuOpAsr {});
-            op_code_lookup[0x4c] = Box::new(CpuOpJmp {});
-            op_code_lookup[0x4d] = Box::new(CpuOpEor {});
-            op_code_lookup[0x4e] = Box::new(CpuOpLsr {});
-            op_code_lookup[0x4f] = Box::new(CpuOpSre {});
-
-            op_code_lookup[0x50] = Box::new(CpuOpBvc {});
-            op_code_lookup[0x51] = Box::new(CpuOpEor {});
-            op_code_lookup[0x52] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x53] = Box::new(CpuOpSre {});
-            op_code_lookup[0x54] = Box::new(CpuOpNop {});
-            op_code_lookup[0x55] = Box::new(CpuOpEor {});
-            op_code_lookup[0x56] = Box::new(CpuOpLsr {});
-            op_code_lookup[0x57] = Box::new(CpuOpSre {});
-            op_code_lookup[0x58] = Box::new(CpuOpCli {});
-            op_code_lookup[0x59] = Box::new(CpuOpEor {});
-            op_code_lookup[0x5a] = Box::new(CpuOpNop {});
-            op_code_lookup[0x5b] = Box::new(CpuOpSre {});
-            op_code_lookup[0x5c] = Box::new(CpuOpNop {});
-            op_code_lookup[0x5d] = Box::new(CpuOpEor {});
-            op_code_lookup[0x5e] = Box::new(CpuOpLsr {});
-            op_code_lookup[0x5f] = Box::new(CpuOpSre {});
-
-            op_code_lookup[0x60] = Box::new(CpuOpRts {});
-            op_code_lookup[0x61] = Box::new(CpuOpAdc {});
-            op_code_lookup[0x62] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x63] = Box::new(CpuOpRra {});
-            op_code_lookup[0x64] = Box::new(CpuOpNop {});
-            op_code_lookup[0x65] = Box::new(CpuOpAdc {});
-            op_code_lookup[0x66] = Box::new(CpuOpRor {});
-            op_code_lookup[0x67] = Box::new(CpuOpRra {});
-            op_code_lookup[0x68] = Box::new(CpuOpPla {});
-            op_code_lookup[0x69] = Box::new(CpuOpAdc {});
-            op_code_lookup[0x6a] = Box::new(CpuOpRor {});
-            op_code_lookup[0x6b] = Box::new(CpuOpArr {});
-            op_code_lookup[0x6c] = Box::new(CpuOpJmp {});
-            op_code_lookup[0x6d] = Box::new(CpuOpAdc {});
-            op_code_lookup[0x6e] = Box::new(CpuOpRor {});
-            op_code_lookup[0x6f] = Box::new(CpuOpRra {});
-
-            op_code_lookup[0x70] = Box::new(CpuOpBvs {});
-            op_code_lookup[0x71] = Box::new(CpuOpAdc {});
-            op_code_lookup[0x72] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x73] = Box::new(CpuOpRra {});
-            op_code_lookup[0x74] = Box::new(CpuOpNop {});
-            op_code_lookup[0x75] = Box::new(CpuOpAdc {});
-            op_code_lookup[0x76] = Box::new(CpuOpRor {});
-            op_code_lookup[0x77] = Box::new(CpuOpRra {});
-            op_code_lookup[0x78] = Box::new(CpuOpSei {});
-            op_code_lookup[0x79] = Box::new(CpuOpAdc {});
-            op_code_lookup[0x7a] = Box::new(CpuOpNop {});
-            op_code_lookup[0x7b] = Box::new(CpuOpRra {});
-            op_code_lookup[0x7c] = Box::new(CpuOpNop {});
-            op_code_lookup[0x7d] = Box::new(CpuOpAdc {});
-            op_code_lookup[0x7e] = Box::new(CpuOpRor {});
-            op_code_lookup[0x7f] = Box::new(CpuOpRra {});
-
-            op_code_lookup[0x80] = Box::new(CpuOpNop {});
-            op_code_lookup[0x81] = Box::new(CpuOpSta {});
-            op_code_lookup[0x82] = Box::new(CpuOpNop {});
-            op_code_lookup[0x83] = Box::new(CpuOpSax {});
-            op_code_lookup[0x84] = Box::new(CpuOpSty {});
-            op_code_lookup[0x85] = Box::new(CpuOpSta {});
-            op_code_lookup[0x86] = Box::new(CpuOpStx {});
-            op_code_lookup[0x87] = Box::new(CpuOpSax {});
-            op_code_lookup[0x88] = Box::new(CpuOpDey {});
-            op_code_lookup[0x89] = Box::new(CpuOpNop {});
-            op_code_lookup[0x8a] = Box::new(CpuOpTxa {});
-            op_code_lookup[0x8b] = Box::new(CpuOpAne {});
-            op_code_lookup[0x8c] = Box::new(CpuOpSty {});
-            op_code_lookup[0x8d] = Box::new(CpuOpSta {});
-            op_code_lookup[0x8e] = Box::new(CpuOpStx {});
-            op_code_lookup[0x8f] = Box::new(CpuOpSax {});
-
-            op_code_lookup[0x90] = Box::new(CpuOpBcc {});
-            op_code_lookup[0x91] = Box::new(CpuOpSta {});
-            op_code_lookup[0x92] = Box::new(CpuOpBrk {});
-            op_code_lookup[0x93] = Box::new(CpuOpSha {});
-            op_code_lookup[0x94] = Box::new(CpuOpSty {});
-            op_code_lookup[0x95] = Box::new(CpuOpSta {});
-            op_code_lookup[0x96] = Box::new(CpuOpStx {});
-            op_code_lookup[0x97] = Box::new(CpuOpSax {});
-            op_code_lookup[0x98] = Box::new(CpuOpTya {});
-            op_code_lookup[0x99] = Box::new(CpuOpSta {});
-            op_code_lookup[0x9a] = Box::new(CpuOpTxs {});
-            op_code_lookup[0x9b] = Box::new(CpuOpShs {});
-            op_code_lookup[0x9c] = Box::new(CpuOpShy {});
-            op_code_lookup[0x9d] = Box::new(CpuOpStaAbx {});
-            op_code_lookup[0x9e] = Box::new(CpuOpShx {});
-            op_code_lookup[0x9f] = Box::new(CpuOpSha {});
-
-            op_code_lookup[0xa0] = Box::new(CpuOpLdy {});
-            op_code_lookup[0xa1] = Box::new(CpuOpLda {});
-            op_code_lookup[0xa2] = Box::new(CpuOpLdx {});
-            op_code_lookup[0xa3] = Box::new(CpuOpLax {});
-            op_code_lookup[0xa4] = Box::new(CpuOpLdy {});
-            op_code_lookup[0xa5] = Box::new(CpuOpLda {});
-            op_code_lookup[0xa6] = Box::new(CpuOpLdx {});
-            op_code_lookup[0xa7] = Box::new(CpuOpLax {});
-            op_code_lookup[0xa8] = Box::new(CpuOpTay {});
-            op_code_lookup[0xa9] = Box::new(CpuOpLda {});
-            op_code_lookup[0xaa] = Box::new(CpuOpTax {});
-            op_code_lookup[0xab] = Box::new(CpuOpLxa {});
-            op_code_lookup[0xac] = Box::new(CpuOpLdy {});
-            op_code_lookup[0xad] = Box::new(CpuOpLda {});
-            op_code_lookup[0xae] = Box::new(CpuOpLdx {});
-            op_code_lookup[0xaf] = Box::new(CpuOpLax {});
-
-            op_code_lookup[0xb0] = Box::new(CpuOpBcs {});
-            op_code_lookup[0xb1] = Box::new(CpuOpLda {});
-            op_code_lookup[0xb2] = Box::new(CpuOpBrk {});
-            op_code_lookup[0xb3] = Box::new(CpuOpLax {});
-            op_code_lookup[0xb4] = Box::new(CpuOpLdy {});
-            op_code_lookup[0xb5] = Box::new(CpuOpLda {});
-            op_code_lookup[0xb6] = Box::new(CpuOpLdx {});
-            op_code_lookup[0xb7] = Box::new(CpuOpLax {});
-            op_code_lookup[0xb8] = Box::new(CpuOpCly {});
-            op_code_lookup[0xb9] = Box::new(CpuOpLda {});
-            op_code_lookup[0xba] = Box::new(CpuOpTsx {});
-            op_code_lookup[0xbb] = Box::new(CpuOpLae {});
-            op_code_lookup[0xbc] = Box::new(CpuOpLdy {});
-            op_code_lookup[0xbd] = Box::new(CpuOpLda {});
-            op_code_lookup[0xbe] = Box::new(CpuOpLdx {});
-            op_code_lookup[0xbf] = Box::new(CpuOpLax {});
-
-            op_code_lookup[0xc0] = Box::new(CpuOpCpy {});
-            op_code_lookup[0xc1] = Box::new(CpuOpCmp {});
-            op_code_lookup[0xc2] = Box::new(CpuOpNop {});
-            op_code_lookup[0xc3] = Box::new(CpuOpDcp {});
-            op_code_lookup[0xc4] = Box::new(CpuOpCpy {});
-            op_code_lookup[0xc5] = Box::new(CpuOpCmp {});
-            op_code_lookup[0xc6] = Box::new(CpuOpDec {});
-            op_code_lookup[0xc7] = Box::new(CpuOpDcp {});
-            op_code_lookup[0xc8] = Box::new(CpuOpIny {});
-            op_code_lookup[0xc9] = Box::new(CpuOpCmp {});
-            op_code_lookup[0xca] = Box::new(CpuOpDex {});
-            op_code_lookup[0xcb] = Box::new(CpuOpAxs {});
-            op_code_lookup[0xcc] = Box::new(CpuOpCpy {});
-            op_code_lookup[0xcd] = Box::new(CpuOpCmp {});
-            op_code_lookup[0xce] = Box::new(CpuOpDec {});
-            op_code_lookup[0xcf] = Box::new(CpuOpDcp {});
-
-            op_code_lookup[0xd0] = Box::new(CpuOpBne {});
-            op_code_lookup[0xd1] = Box::new(CpuOpCmp {});
-            op_code_lookup[0xd2] = Box::new(CpuOpDcp {});
-            op_code_lookup[0xd3] = Box::new(CpuOpDcp {});
-            op_code_lookup[0xd4] = Box::new(CpuOpNop {});
-            op_code_lookup[0xd5] = Box::new(CpuOpCmp {});
-            op_code_lookup[0xd6] = Box::new(CpuOpDec {});
-            op_code_lookup[0xd7] = Box::new(CpuOpDcp {});
-            op_code_lookup[0xd8] = Box::new(CpuOpCld {});
-            op_code_lookup[0xd9] = Box::new(CpuOpCmp {});
-            op_code_lookup[0xda] = Box::new(CpuOpNop {});
-            op_code_lookup[0xdb] = Box::new(CpuOpDcp {});
-            op_code_lookup[0xdc] = Box::new(CpuOpNop {});
-            op_code_lookup[0xdd] = Box::new(CpuOpCmp {});
-            op_code_lookup[0xde] = Box::new(CpuOpDec {});
-            op_code_lookup[0xdf] = Box::new(CpuOpDcp {});
-
-            op_code_lookup[0xe0] = Box::new(CpuOpCpx {});
-            op_code_lookup[0xe1] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xe2] = Box::new(CpuOpNop {});
-            op_code_lookup[0xe3] = Box::new(CpuOpIsc {});
-            op_code_lookup[0xe4] = Box::new(CpuOpCpx {});
-            op_code_lookup[0xe5] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xe6] = Box::new(CpuOpInc {});
-            op_code_lookup[0xe7] = Box::new(CpuOpIsc {});
-            op_code_lookup[0xe8] = Box::new(CpuOpInx {});
-            op_code_lookup[0xe9] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xea] = Box::new(CpuOpNop {});
-            op_code_lookup[0xeb] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xec] = Box::new(CpuOpCpx {});
-            op_code_lookup[0xed] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xee] = Box::new(CpuOpInc {});
-            op_code_lookup[0xef] = Box::new(CpuOpIsc {});
-
-            op_code_lookup[0xf0] = Box::new(CpuOpBeq {});
-            op_code_lookup[0xf1] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xf2] = Box::new(CpuOpBrk {});
-            op_code_lookup[0xf3] = Box::new(CpuOpIsc {});
-            op_code_lookup[0xf4] = Box::new(CpuOpNop {});
-            op_code_lookup[0xf5] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xf6] = Box::new(CpuOpInc {});
-            op_code_lookup[0xf7] = Box::new(CpuOpIsc {});
-            op_code_lookup[0xf8] = Box::new(CpuOpSed {});
-            op_code_lookup[0xf9] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xfa] = Box::new(CpuOpNop {});
-            op_code_lookup[0xfb] = Box::new(CpuOpIsc {});
-            op_code_lookup[0xfc] = Box::new(CpuOpNop {});
-            op_code_lookup[0xfd] = Box::new(CpuOpSbc {});
-            op_code_lookup[0xfe] = Box::new(CpuOpInc {});
-            op_code_lookup[0xff] = Box::new(CpuOpIsc {});
-
-            op_code_lookup[0x100] = Box::new(CpuOpReset {});
-            op_code_lookup[0x101] = Box::new(CpuOpNmi {});
-            op_code_lookup[0x102] = Box::new(CpuOpIrq {});
-            op_code_lookup
-        }
-
-        pub fn get_ticks() -> Vec<u8> {
-            let mut op_code_ticks: Vec<u8> = Vec::with_capacity(0x200);
-            for _i in 0..0x200 {
-                op_code_ticks.push(1);
-            }
-            op_code_ticks[0x01] = 6;
-            op_code_ticks[0x02] = 7;
-            op_code_ticks[0x05] = 3;
-            op_code_ticks[0x06] = 5;
-            op_code_ticks[0x08] = 3;
-            op_code_ticks[0x09] = 2;
-            op_code_ticks[0x0a] = 2;
-
-            op_code_ticks[0x10] = 2;
-            op_code_ticks[0x11] = 5;
-            op_code_ticks[0x15] = 4;
-            op_code_ticks[0x18] = 2;
-            op_code_ticks[0x19] = 4;
-
-            op_code_ticks[0x20] = 6;
-            op_code_ticks[0x24] = 3;
-            op_code_ticks[0x25] = 3;
-            op_code_ticks[0x26] = 5;
-            op_code_ticks[0x29] = 2;
-            op_code_ticks[0x2a] = 2;
-            op_code_ticks[0x2c] = 4;
-
-            op_code_ticks[0x30] = 2;
-            op_code_ticks[0x35] = 4;
-            op_code_ticks[0x38] = 2;
-            op_code_ticks[0x3d] = 4;
-
-            op_code_ticks[0x45] = 3;
-            op_code_ticks[0x46] = 5;
-            op_code_ticks[0x48] = 3;
-            op_code_ticks[0x49] = 2;
-            op_code_ticks[0x4a] = 2;
-            op_code_ticks[0x4c] = 3;
-            op_code_ticks[0x4e] = 6;
-
-            op_code_ticks[0x50] = 2;
-            op_code_ticks[0x56] = 6;
-
-            op_code_ticks[0x60] = 6;
-            op_code_ticks[0x65] = 3;
-            op_code_ticks[0x66] = 5;
-            op_code_ticks[0x68] = 4;
-            op_code_ticks[0x69] = 2;
-            op_code_ticks[0x6a] = 2;
-
-            op_code_ticks[0x70] = 2;
-            op_code_ticks[0x75] = 4;
-            op_code_ticks[0x78] = 2;
-            op_code_ticks[0x79] = 4;
-            op_code_ticks[0x7d] = 4;
-
-            op_code_ticks[0x84] = 3;
-            op_code_ticks[0x85] = 3;
-            op_code_ticks[0x86] = 3;
-            op_code_ticks[0x88] = 2;
-            op_code_ticks[0x8a] = 2;
-            op_code_ticks[0x8c] = 4;
-            op_code_ticks[0x8d] = 4;
-
-            op_code_ticks[0x90] = 2;
-            op_code_ticks[0x91] = 6;
-            op_code_ticks[0x94] = 4;
-            op_code_ticks[0x95] = 4;
-            op_code_ticks[0x96] = 4;
-            op_code_ticks[0x98] = 2;
-            op_code_ticks[0x99] = 5;
-            op_code_ticks[0x9a] = 2;
-
-            op_code_ticks[0xa0] = 2;
-            op_code_ticks[0xa1] = 6;
-            op_code_ticks[0xa2] = 2;
-            op_code_ticks[0xa4] = 3;
-            op_code_ticks[0xa5] = 3;
-            op_code_ticks[0xa6] = 3;
-            op_code_ticks[0xa8] = 2;
-            op_code_ticks[0xa9] = 2;
-            op_code_ticks[0xaa] = 2;
-            op_code_ticks[0xad] = 4;
-            op_code_ticks[0xae] = 4;
-
-            op_code_ticks[0xb0] = 2;
-            op_code_ticks[0xb1] = 5;
-            op_code_ticks[0xb4] = 4;
-            op_code_ticks[0xb5] = 4;
-            op_code_ticks[0xb6] = 4;
-            op_code_ticks[0xb9] = 4;
-            op_code_ticks[0xba] = 2;
-            op_code_ticks[0xbc] = 4;
-            op_code_ticks[0xbd] = 4;
-            op_code_ticks[0xbe] = 4;
-
-            op_code_ticks[0xc0] = 2;
-            op_code_ticks[0xc4] = 3;
-            op_code_ticks[0xc5] = 3;
-            op_code_ticks[0xc6] = 5;
-            op_code_ticks[0xc8] = 2;
-            op_code_ticks[0xc9] = 2;
-            op_code_ticks[0xca] = 2;
-
-            op_code_ticks[0xd0] = 2;
-            op_code_ticks[0xd1] = 5;
-            op_code_ticks[0xd5] = 4;
-            op_code_ticks[0xd6] = 6;
-            op_code_ticks[0xd8] = 2;
-            op_code_ticks[0xd9] = 4;
-            op_code_ticks[0xdd] = 4;
-
-            op_code_ticks[0xe0] = 2;
-            op_code_ticks[0xe4] = 3;
-            op_code_ticks[0xe5] = 3;
-            op_code_ticks[0xe6] = 5;
-            op_code_ticks[0xe8] = 2;
-            op_code_ticks[0xe9] = 2;
-            op_code_ticks[0xea] = 2;
-            op_code_ticks[0xec] = 4;
-
-            op_code_ticks[0xf0] = 2;
-            op_code_ticks[0xf6] = 6;
-            op_code_ticks[0xf8] = 2;
-            op_code_ticks[0xf9] = 4;
-            op_code_ticks[0xfd] = 4;
-            op_code_ticks[0xff] = 7;
-            op_code_ticks
-        }
-
-        fn push_stack(cpu: &mut M6502, addr: &mut AddressBus, byte: u8) {
-            if cpu.stack_pointer == 0 {
-                //println!("Stack overflow PC:{}  sp:{}", cpu.program_counter, cpu.stack_pointer.wrapping_sub(1));
-            }
-            addr.address = cpu.stack_pointer.wrapping_add(cpu.stack_pointer_page);
-            addr.byte = byte;
-            addr.write = true;
-            cpu.stack_pointer = cpu.stack_pointer.wrapping_sub(1);
-        }
-
-        fn pop_stack(cpu: &mut M6502, addr: &mut AddressBus) {
-            if cpu.stack_pointer > 256 {
-                //println!("Stack underflow PC:{}  sp:{}", cpu.program_counter, cpu.stack_pointer);
-            }
-            cpu.stack_pointer = cpu.stack_pointer.wrapping_add(1);
-            addr.address = cpu.stack_pointer.wrapping_add(cpu.stack_pointer_page);
-        }
-
-        pub fn get_status_flag(cpu: &mut M6502, flag: u8) -> u8 {
-            cpu.status_register & flag
-        }
-
-        fn set_status_flag(cpu: &mut M6502, flag: u8, set_clear: bool) {
-            if set_clear {
-                cpu.status_register |= flag;
-            }
-            else {
-                cpu.status_register &= !flag;
-            }
-        }
-
-        fn set_negative(cpu: &mut M6502, byte: u8) {
-            OpCodesUtils::set_status_flag(cpu, NEGATIVE_FLAG, (byte & 0x80) != 0);
-        }
-
-        fn set_zero(cpu: &mut M6502, byte: u8) {
-            OpCodesUtils::set_status_flag(cpu, ZERO_FLAG, byte == 0);
-        }
-
-        fn set_negative_zero(cpu: &mut M6502, byte: u8) {
-            OpCodesUtils::set_negative(cpu, byte);
-            OpCodesUtils::set_zero(cpu, byte);
-        }
-    }
-
-    pub trait CpuOperation: Sync + Send {
-        fn execute(&self, cpu: &mut M6502, addr: &mut AddressBus, step: u8) -> bool {
-            let mut result = false;
-            match step {
-                0 => result = self.step_0(cpu, addr),
-                1 => result = self.step_1(cpu, addr),
-                2 => result = self.step_2(cpu, addr),
-                3 => result = self.step_3(cpu, addr),
-                4 => result = self.step_4(cpu, addr),
-                5 => result = self.step_5(cpu, addr),
-                _ => {}
-            }
-            result            
-        }
-        fn needs_addr_byte(&self, addr: &mut AddressBus) -> bool { 
-            if addr.is_accumulator {
-                return false;
-            }
-            true 
-        }
-        fn step_0(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool { true }
-        fn step_1(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool { true }
-        fn step_2(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool { true }
-        fn step_3(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool { true }
-        fn step_4(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool { true }
-        fn step_5(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool { true }
-    }
-
-    struct CpuOpPanic {}
-    impl CpuOperation for CpuOpPanic {
-        fn step_0(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            true
-        }
-    }
-
-    // NES Opcodes
-    struct CpuOpRla {}
-    impl CpuOperation for CpuOpRla {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let mut byte = cpu.lookup_address.byte;
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 0x01;
+    use crate::m6502::emu_cpu::{M6502, M6502Variant, CARRY_FLAG, ZERO_FLAG, INTERRUPT_FLAG, DECIMAL_MODE, BREAK_COMMAND,
+        IGNORED, OVERFLOW_FLAG, NEGATIVE_FLAG};
+    use crate::m6502_address::maddress::M6502AddressMode;
+    use crate::m6502_address::maddress::M6502AddressMode::*;
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum M6502Operation {
+        Adc, And, Asl, Bcc, Bcs, Beq, Bit, Bmi, Bne, Bpl, Brk, Bvc, Bvs, Clc,
+        Cld, Cli, Clv, Cmp, Cpx, Cpy, Dec, Dex, Dey, Eor, Inc, Inx, Iny, Jmp,
+        Jsr, Lda, Ldx, Ldy, Lsr, Nop, Ora, Pha, Php, Pla, Plp, Rol, Ror, Rti,
+        Rts, Sbc, Sec, Sed, Sei, Sta, Stx, Sty, Tax, Tay, Tsx, Txa, Txs, Tya,
+        // Undocumented
+        Anc, Ane, Arr, Asr, Axs, Dcp, Isc, Jam, Lae, Lax, Lxa, Rla, Rra, Sax,
+        Sha, Shs, Shx, Shy, Slo, Sre,
+        // 65C02, Nop1 is a one cycle NOP
+        Bra, Nop1, Phx, Phy, Plx, Ply, Stp, Stz, Trb, Tsb, Wai,
+        // Rockwell and WDC 65C02 bit operations, with the bit number
+        Bbr(u8), Bbs(u8), Rmb(u8), Smb(u8),
+    }
+
+    // How an operation uses the address its mode works out
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum M6502OperationType {
+        Read,
+        Write,
+        ReadModifyWrite,
+        // Implied, stack, jump and branch operations with their own cycles
+        Other,
+    }
+
+    use M6502Operation::*;
+
+    // ANE and LXA OR the accumulator with a value that depends on the chip
+    // and temperature before the AND, this is the common one
+    const UNSTABLE_MAGIC: u8 = 0xEE;
+
+    impl M6502Operation {
+
+        pub fn get_type(&self) -> M6502OperationType {
+            match self {
+                Adc | And | Bit | Cmp | Cpx | Cpy | Eor | Lda | Ldx | Ldy | Nop | Ora | Sbc |
+                Anc | Ane | Arr | Asr | Axs | Lae | Lax | Lxa => M6502OperationType::Read,
+                Sta | Stx | Sty | Sax | Sha | Shs | Shx | Shy | Stz => M6502OperationType::Write,
+                Asl | Dec | Inc | Lsr | Rol | Ror |
+                Dcp | Isc | Rla | Rra | Slo | Sre |
+                Trb | Tsb | Rmb(_) | Smb(_) => M6502OperationType::ReadModifyWrite,
+                _ => M6502OperationType::Other,
+            }
+        }
+
+        // ISC is ISB in most listings and traces
+        pub fn get_mnemonic(&self) -> String {
+            match self {
+                Isc => String::from("ISB"),
+                Bbr(bit) => format!("BBR{}", bit),
+                Bbs(bit) => format!("BBS{}", bit),
+                Rmb(bit) => format!("RMB{}", bit),
+                Smb(bit) => format!("SMB{}", bit),
+                Nop1 => String::from("NOP"),
+                _ => format!("{:?}", self).to_uppercase(),
+            }
+        }
+
+        // SHA, SHX, SHY and TAS write their byte as the high byte of the address
+        // when the index crosses a page
+        pub fn is_high_byte_store(&self) -> bool {
+            matches!(self, Sha | Shs | Shx | Shy)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct M6502OpCode {
+        pub operation: M6502Operation,
+        pub mode: M6502AddressMode,
+    }
+
+    const fn op(operation: M6502Operation, mode: M6502AddressMode) -> M6502OpCode {
+        M6502OpCode { operation, mode }
+    }
+
+    pub const OP_CODES: [M6502OpCode; 0x100] = [
+        // 0x00
+        op(Brk, Implied), op(Ora, IndirectX), op(Jam, Implied), op(Slo, IndirectX), op(Nop, ZeroPage), op(Ora, ZeroPage), op(Asl, ZeroPage), op(Slo, ZeroPage),
+        op(Php, Implied), op(Ora, Immediate), op(Asl, Accumulator), op(Anc, Immediate), op(Nop, Absolute), op(Ora, Absolute), op(Asl, Absolute), op(Slo, Absolute),
+        // 0x10
+        op(Bpl, Relative), op(Ora, IndirectY), op(Jam, Implied), op(Slo, IndirectY), op(Nop, ZeroPageX), op(Ora, ZeroPageX), op(Asl, ZeroPageX), op(Slo, ZeroPageX),
+        op(Clc, Implied), op(Ora, AbsoluteY), op(Nop, Implied), op(Slo, AbsoluteY), op(Nop, AbsoluteX), op(Ora, AbsoluteX), op(Asl, AbsoluteX), op(Slo, AbsoluteX),
+        // 0x20
+        op(Jsr, Absolute), op(And, IndirectX), op(Jam, Implied), op(Rla, IndirectX), op(Bit, ZeroPage), op(And, ZeroPage), op(Rol, ZeroPage), op(Rla, ZeroPage),
+        op(Plp, Implied), op(And, Immediate), op(Rol, Accumulator), op(Anc, Immediate), op(Bit, Absolute), op(And, Absolute), op(Rol, Absolute), op(Rla, Absolute),
+        // 0x30
+        op(Bmi, Relative), op(And, IndirectY), op(Jam, Implied), op(Rla, IndirectY), op(Nop, ZeroPageX), op(And, ZeroPageX), op(Rol, ZeroPageX), op(Rla, ZeroPageX),
+        op(Sec, Implied), op(And, AbsoluteY), op(Nop, Implied), op(Rla, AbsoluteY), op(Nop, AbsoluteX), op(And, AbsoluteX), op(Rol, AbsoluteX), op(Rla, AbsoluteX),
+        // 0x40
+        op(Rti, Implied), op(Eor, IndirectX), op(Jam, Implied), op(Sre, IndirectX), op(Nop, ZeroPage), op(Eor, ZeroPage), op(Lsr, ZeroPage), op(Sre, ZeroPage),
+        op(Pha, Implied), op(Eor, Immediate), op(Lsr, Accumulator), op(Asr, Immediate), op(Jmp, Absolute), op(Eor, Absolute), op(Lsr, Absolute), op(Sre, Absolute),
+        // 0x50
+        op(Bvc, Relative), op(Eor, IndirectY), op(Jam, Implied), op(Sre, IndirectY), op(Nop, ZeroPageX), op(Eor, ZeroPageX), op(Lsr, ZeroPageX), op(Sre, ZeroPageX),
+        op(Cli, Implied), op(Eor, AbsoluteY), op(Nop, Implied), op(Sre, AbsoluteY), op(Nop, AbsoluteX), op(Eor, AbsoluteX), op(Lsr, AbsoluteX), op(Sre, AbsoluteX),
+        // 0x60
+        op(Rts, Implied), op(Adc, IndirectX), op(Jam, Implied), op(Rra, IndirectX), op(Nop, ZeroPage), op(Adc, ZeroPage), op(Ror, ZeroPage), op(Rra, ZeroPage),
+        op(Pla, Implied), op(Adc, Immediate), op(Ror, Accumulator), op(Arr, Immediate), op(Jmp, Indirect), op(Adc, Absolute), op(Ror, Absolute), op(Rra, Absolute),
+        // 0x70
+        op(Bvs, Relative), op(Adc, IndirectY), op(Jam, Implied), op(Rra, IndirectY), op(Nop, ZeroPageX), op(Adc, ZeroPageX), op(Ror, ZeroPageX), op(Rra, ZeroPageX),
+        op(Sei, Implied), op(Adc, AbsoluteY), op(Nop, Implied), op(Rra, AbsoluteY), op(Nop, AbsoluteX), op(Adc, AbsoluteX), op(Ror, AbsoluteX), op(Rra, AbsoluteX),
+        // 0x80
+        op(Nop, Immediate), op(Sta, IndirectX), op(Nop, Immediate), op(Sax, IndirectX), op(Sty, ZeroPage), op(Sta, ZeroPage), op(Stx, ZeroPage), op(Sax, ZeroPage),
+        op(Dey, Implied), op(Nop, Immediate), op(Txa, Implied), op(Ane, Immediate), op(Sty, Absolute), op(Sta, Absolute), op(Stx, Absolute), op(Sax, Absolute),
+        // 0x90
+        op(Bcc, Relative), op(Sta, IndirectY), op(Jam, Implied), op(Sha, IndirectY), op(Sty, ZeroPageX), op(Sta, ZeroPageX), op(Stx, ZeroPageY), op(Sax, ZeroPageY),
+        op(Tya, Implied), op(Sta, AbsoluteY), op(Txs, Implied), op(Shs, AbsoluteY), op(Shy, AbsoluteX), op(Sta, AbsoluteX), op(Shx, AbsoluteY), op(Sha, AbsoluteY),
+        // 0xA0
+        op(Ldy, Immediate), op(Lda, IndirectX), op(Ldx, Immediate), op(Lax, IndirectX), op(Ldy, ZeroPage), op(Lda, ZeroPage), op(Ldx, ZeroPage), op(Lax, ZeroPage),
+        op(Tay, Implied), op(Lda, Immediate), op(Tax, Implied), op(Lxa, Immediate), op(Ldy, Absolute), op(Lda, Absolute), op(Ldx, Absolute), op(Lax, Absolute),
+        // 0xB0
+        op(Bcs, Relative), op(Lda, IndirectY), op(Jam, Implied), op(Lax, IndirectY), op(Ldy, ZeroPageX), op(Lda, ZeroPageX), op(Ldx, ZeroPageY), op(Lax, ZeroPageY),
+        op(Clv, Implied), op(Lda, AbsoluteY), op(Tsx, Implied), op(Lae, AbsoluteY), op(Ldy, AbsoluteX), op(Lda, AbsoluteX), op(Ldx, AbsoluteY), op(Lax, AbsoluteY),
+        // 0xC0
+        op(Cpy, Immediate), op(Cmp, IndirectX), op(Nop, Immediate), op(Dcp, IndirectX), op(Cpy, ZeroPage), op(Cmp, ZeroPage), op(Dec, ZeroPage), op(Dcp, ZeroPage),
+        op(Iny, Implied), op(Cmp, Immediate), op(Dex, Implied), op(Axs, Immediate), op(Cpy, Absolute), op(Cmp, Absolute), op(Dec, Absolute), op(Dcp, Absolute),
+        // 0xD0
+        op(Bne, Relative), op(Cmp, IndirectY), op(Jam, Implied), op(Dcp, IndirectY), op(Nop, ZeroPageX), op(Cmp, ZeroPageX), op(Dec, ZeroPageX), op(Dcp, ZeroPageX),
+        op(Cld, Implied), op(Cmp, AbsoluteY), op(Nop, Implied), op(Dcp, AbsoluteY), op(Nop, AbsoluteX), op(Cmp, AbsoluteX), op(Dec, AbsoluteX), op(Dcp, AbsoluteX),
+        // 0xE0
+        op(Cpx, Immediate), op(Sbc, IndirectX), op(Nop, Immediate), op(Isc, IndirectX), op(Cpx, ZeroPage), op(Sbc, ZeroPage), op(Inc, ZeroPage), op(Isc, ZeroPage),
+        op(Inx, Implied), op(Sbc, Immediate), op(Nop, Implied), op(Sbc, Immediate), op(Cpx, Absolute), op(Sbc, Absolute), op(Inc, Absolute), op(Isc, Absolute),
+        // 0xF0
+        op(Beq, Relative), op(Sbc, IndirectY), op(Jam, Implied), op(Isc, IndirectY), op(Nop, ZeroPageX), op(Sbc, ZeroPageX), op(Inc, ZeroPageX), op(Isc, ZeroPageX),
+        op(Sed, Implied), op(Sbc, AbsoluteY), op(Nop, Implied), op(Isc, AbsoluteY), op(Nop, AbsoluteX), op(Sbc, AbsoluteX), op(Inc, AbsoluteX), op(Isc, AbsoluteX),
+    ];
+
+    // WDC 65C02, the NMOS undocumented op codes are NOPs of various lengths
+    pub const WDC_OP_CODES: [M6502OpCode; 0x100] = [
+        // 0x00
+        op(Brk, Implied), op(Ora, IndirectX), op(Nop, Immediate), op(Nop1, Implied), op(Tsb, ZeroPage), op(Ora, ZeroPage), op(Asl, ZeroPage), op(Rmb(0), ZeroPage),
+        op(Php, Implied), op(Ora, Immediate), op(Asl, Accumulator), op(Nop1, Implied), op(Tsb, Absolute), op(Ora, Absolute), op(Asl, Absolute), op(Bbr(0), ZeroPageRelative),
+        // 0x10
+        op(Bpl, Relative), op(Ora, IndirectY), op(Ora, ZeroPageIndirect), op(Nop1, Implied), op(Trb, ZeroPage), op(Ora, ZeroPageX), op(Asl, ZeroPageX), op(Rmb(1), ZeroPage),
+        op(Clc, Implied), op(Ora, AbsoluteY), op(Inc, Accumulator), op(Nop1, Implied), op(Trb, Absolute), op(Ora, AbsoluteX), op(Asl, AbsoluteX), op(Bbr(1), ZeroPageRelative),
+        // 0x20
+        op(Jsr, Absolute), op(And, IndirectX), op(Nop, Immediate), op(Nop1, Implied), op(Bit, ZeroPage), op(And, ZeroPage), op(Rol, ZeroPage), op(Rmb(2), ZeroPage),
+        op(Plp, Implied), op(And, Immediate), op(Rol, Accumulator), op(Nop1, Implied), op(Bit, Absolute), op(And, Absolute), op(Rol, Absolute), op(Bbr(2), ZeroPageRelative),
+        // 0x30
+        op(Bmi, Relative), op(And, IndirectY), op(And, ZeroPageIndirect), op(Nop1, Implied), op(Bit, ZeroPageX), op(And, ZeroPageX), op(Rol, ZeroPageX), op(Rmb(3), ZeroPage),
+        op(Sec, Implied), op(And, AbsoluteY), op(Dec, Accumulator), op(Nop1, Implied), op(Bit, AbsoluteX), op(And, AbsoluteX), op(Rol, AbsoluteX), op(Bbr(3), ZeroPageRelative),
+        // 0x40
+        op(Rti, Implied), op(Eor, IndirectX), op(Nop, Immediate), op(Nop1, Implied), op(Nop, ZeroPage), op(Eor, ZeroPage), op(Lsr, ZeroPage), op(Rmb(4), ZeroPage),
+        op(Pha, Implied), op(Eor, Immediate), op(Lsr, Accumulator), op(Nop1, Implied), op(Jmp, Absolute), op(Eor, Absolute), op(Lsr, Absolute), op(Bbr(4), ZeroPageRelative),
+        // 0x50
+        op(Bvc, Relative), op(Eor, IndirectY), op(Eor, ZeroPageIndirect), op(Nop1, Implied), op(Nop, ZeroPageX), op(Eor, ZeroPageX), op(Lsr, ZeroPageX), op(Rmb(5), ZeroPage),
+        op(Cli, Implied), op(Eor, AbsoluteY), op(Phy, Implied), op(Nop1, Implied), op(Nop, Absolute), op(Eor, AbsoluteX), op(Lsr, AbsoluteX), op(Bbr(5), ZeroPageRelative),
+        // 0x60
+        op(Rts, Implied), op(Adc, IndirectX), op(Nop, Immediate), op(Nop1, Implied), op(Stz, ZeroPage), op(Adc, ZeroPage), op(Ror, ZeroPage), op(Rmb(6), ZeroPage),
+        op(Pla, Implied), op(Adc, Immediate), op(Ror, Accumulator), op(Nop1, Implied), op(Jmp, Indirect), op(Adc, Absolute), op(Ror, Absolute), op(Bbr(6), ZeroPageRelative),
+        // 0x70
+        op(Bvs, Relative), op(Adc, IndirectY), op(Adc, ZeroPageIndirect), op(Nop1, Implied), op(Stz, ZeroPageX), op(Adc, ZeroPageX), op(Ror, ZeroPageX), op(Rmb(7), ZeroPage),
+        op(Sei, Implied), op(Adc, AbsoluteY), op(Ply, Implied), op(Nop1, Implied), op(Jmp, AbsoluteIndexedIndirect), op(Adc, AbsoluteX), op(Ror, AbsoluteX), op(Bbr(7), ZeroPageRelative),
+        // 0x80
+        op(Bra, Relative), op(Sta, IndirectX), op(Nop, Immediate), op(Nop1, Implied), op(Sty, ZeroPage), op(Sta, ZeroPage), op(Stx, ZeroPage), op(Smb(0), ZeroPage),
+        op(Dey, Implied), op(Bit, Immediate), op(Txa, Implied), op(Nop1, Implied), op(Sty, Absolute), op(Sta, Absolute), op(Stx, Absolute), op(Bbs(0), ZeroPageRelative),
+        // 0x90
+        op(Bcc, Relative), op(Sta, IndirectY), op(Sta, ZeroPageIndirect), op(Nop1, Implied), op(Sty, ZeroPageX), op(Sta, ZeroPageX), op(Stx, ZeroPageY), op(Smb(1), ZeroPage),
+        op(Tya, Implied), op(Sta, AbsoluteY), op(Txs, Implied), op(Nop1, Implied), op(Stz, Absolute), op(Sta, AbsoluteX), op(Stz, AbsoluteX), op(Bbs(1), ZeroPageRelative),
+        // 0xA0
+        op(Ldy, Immediate), op(Lda, IndirectX), op(Ldx, Immediate), op(Nop1, Implied), op(Ldy, ZeroPage), op(Lda, ZeroPage), op(Ldx, ZeroPage), op(Smb(2), ZeroPage),
+        op(Tay, Implied), op(Lda, Immediate), op(Tax, Implied), op(Nop1, Implied), op(Ldy, Absolute), op(Lda, Absolute), op(Ldx, Absolute), op(Bbs(2), ZeroPageRelative),
+        // 0xB0
+        op(Bcs, Relative), op(Lda, IndirectY), op(Lda, ZeroPageIndirect), op(Nop1, Implied), op(Ldy, ZeroPageX), op(Lda, ZeroPageX), op(Ldx, ZeroPageY), op(Smb(3), ZeroPage),
+        op(Clv, Implied), op(Lda, AbsoluteY), op(Tsx, Implied), op(Nop1, Implied), op(Ldy, AbsoluteX), op(Lda, AbsoluteX), op(Ldx, AbsoluteY), op(Bbs(3), ZeroPageRelative),
+        // 0xC0
+        op(Cpy, Immediate), op(Cmp, IndirectX), op(Nop, Immediate), op(Nop1, Implied), op(Cpy, ZeroPage), op(Cmp, ZeroPage), op(Dec, ZeroPage), op(Smb(4), ZeroPage),
+        op(Iny, Implied), op(Cmp, Immediate), op(Dex, Implied), op(Wai, Implied), op(Cpy, Absolute), op(Cmp, Absolute), op(Dec, Absolute), op(Bbs(4), ZeroPageRelative),
+        // 0xD0
+        op(Bne, Relative), op(Cmp, IndirectY), op(Cmp, ZeroPageIndirect), op(Nop1, Implied), op(Nop, ZeroPageX), op(Cmp, ZeroPageX), op(Dec, ZeroPageX), op(Smb(5), ZeroPage),
+        op(Cld, Implied), op(Cmp, AbsoluteY), op(Phx, Implied), op(Stp, Implied), op(Nop, Absolute), op(Cmp, AbsoluteX), op(Dec, AbsoluteX), op(Bbs(5), ZeroPageRelative),
+        // 0xE0
+        op(Cpx, Immediate), op(Sbc, IndirectX), op(Nop, Immediate), op(Nop1, Implied), op(Cpx, ZeroPage), op(Sbc, ZeroPage), op(Inc, ZeroPage), op(Smb(6), ZeroPage),
+        op(Inx, Implied), op(Sbc, Immediate), op(Nop, Implied), op(Nop1, Implied), op(Cpx, Absolute), op(Sbc, Absolute), op(Inc, Absolute), op(Bbs(6), ZeroPageRelative),
+        // 0xF0
+        op(Beq, Relative), op(Sbc, IndirectY), op(Sbc, ZeroPageIndirect), op(Nop1, Implied), op(Nop, ZeroPageX), op(Sbc, ZeroPageX), op(Inc, ZeroPageX), op(Smb(7), ZeroPage),
+        op(Sed, Implied), op(Sbc, AbsoluteY), op(Plx, Implied), op(Nop1, Implied), op(Nop, Absolute), op(Sbc, AbsoluteX), op(Inc, AbsoluteX), op(Bbs(7), ZeroPageRelative),
+    ];
+
+    // The Rockwell 65C02 has the bit operations but not WAI and STP
+    pub const ROCKWELL_OP_CODES: [M6502OpCode; 0x100] = {
+        let mut op_codes = WDC_OP_CODES;
+        op_codes[0xCB] = op(Nop1, Implied);
+        op_codes[0xDB] = op(Nop1, Implied);
+        op_codes
+    };
+
+    impl M6502 {
+
+        pub fn get_status_flag(&self, flag: u8) -> bool {
+            self.status_register & flag > 0
+        }
+
+        pub fn set_status_flag(&mut self, flag: u8, value: bool) {
+            if value {
+                self.status_register |= flag;
+            } else {
+                self.status_register &= !flag;
             }
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, byte & 0x80 != 0);
-
-            let temp: u8 = (byte << 1) | carry;
-            byte = temp;
-            addr.byte = byte;
-            addr.address = cpu.lookup_address.address;
-            addr.write = true;
-
-            cpu.accumulator &= byte;
-            OpCodesUtils::set_negative_zero(cpu, byte);
-            true
         }
-    }
-
-    struct CpuOpSre {}
-    impl CpuOperation for CpuOpSre {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let mut byte = cpu.lookup_address.byte;
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, (byte & 0x01) != 0);
-            byte >>= 1;
 
-            addr.byte = byte;
-            addr.address = cpu.lookup_address.address;
-            addr.write = true;
-            cpu.accumulator ^= byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
+        fn set_negative_zero(&mut self, value: u8) {
+            self.set_status_flag(NEGATIVE_FLAG, value & 0x80 > 0);
+            self.set_status_flag(ZERO_FLAG, value == 0);
         }
-    }
 
-    struct CpuOpRra {}
-    impl CpuOperation for CpuOpRra {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            // ROR
-            let mut byte = cpu.lookup_address.byte;
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 0x80;
+        // B only exists on the stack, bit 5 always reads as set
+        pub fn get_pushed_status(&self, break_command: bool) -> u8 {
+            let mut result = self.status_register | IGNORED;
+            if break_command {
+                result |= BREAK_COMMAND;
             }
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, byte & 0x01 != 0);
-
-            byte = (byte >> 1) | carry;
-            cpu.lookup_address.byte = byte;
-            addr.byte = byte;
-            addr.address = cpu.lookup_address.address;
-            addr.write = true;
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            // ADC
-            let byte = cpu.lookup_address.byte;
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 0x01;
-            }
-
-            let(value1, overflow1) = cpu.accumulator.overflowing_add(addr.byte);
-            let(value2, overflow2) = value1.overflowing_add(carry);
-            let value = value2;
-            OpCodesUtils::set_status_flag(cpu, OVERFLOW_FLAG, ((cpu.accumulator ^ value) & (byte ^ value) & 0x080) == 0x80);
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, overflow1 || overflow2);
-            cpu.accumulator = value;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpLax {}
-    impl CpuOperation for CpuOpLax {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.accumulator = cpu.lookup_address.byte;
-            cpu.register_x = cpu.accumulator;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);    
-            true 
-        }
-    }        
-
-    struct CpuOpDcp{}
-    impl CpuOperation for CpuOpDcp {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte = cpu.lookup_address.byte.wrapping_sub(1);
-            addr.byte = byte;
-            addr.write = true;
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, cpu.accumulator >= byte);
-            OpCodesUtils::set_status_flag(cpu, ZERO_FLAG, cpu.accumulator == byte);
-            let negzerocheck= cpu.accumulator.wrapping_sub(byte);
-            OpCodesUtils::set_status_flag(cpu,NEGATIVE_FLAG, negzerocheck & 0x80 != 0);
-            true
+            result
         }
-    }
 
-    // Load Store Operations
-    struct CpuOpLda {}
-    impl CpuOperation for CpuOpLda {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.accumulator = cpu.lookup_address.byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
+        pub fn set_pulled_status(&mut self, byte: u8) {
+            self.status_register = (byte & !BREAK_COMMAND) | IGNORED;
         }
-    }
 
-    struct CpuOpLdx {}
-    impl CpuOperation for CpuOpLdx {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_x = cpu.lookup_address.byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_x);
-            true
+        pub fn get_carry(&self) -> u8 {
+            self.status_register & CARRY_FLAG
         }
-    }
 
-    struct CpuOpLdy {}
-    impl CpuOperation for CpuOpLdy {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_y = cpu.lookup_address.byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_y);
-            true
-        }
-    }
+        fn adc(&mut self, byte: u8, variant: M6502Variant) {
+            let carry = self.get_carry() as u16;
+            let binary = self.accumulator as u16 + byte as u16 + carry;
 
-    struct CpuOpSta {}
-    impl CpuOperation for CpuOpSta {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.address = cpu.lookup_address.address;
-            addr.byte = cpu.accumulator;
-            addr.write = true;
-            if cpu.lookup_address.is_abs_y {
-                return false;
+            if !variant.has_decimal() || !self.get_status_flag(DECIMAL_MODE) {
+                self.set_status_flag(OVERFLOW_FLAG, (!(self.accumulator ^ byte) & (self.accumulator ^ binary as u8) & 0x80) > 0);
+                self.set_status_flag(CARRY_FLAG, binary > 0xFF);
+                self.accumulator = binary as u8;
+                self.set_negative_zero(self.accumulator);
+                return;
             }
-            true
-        }
-        fn step_1(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            false
-        }
-    }
 
-    struct CpuOpStaAbx {}
-    impl CpuOperation for CpuOpStaAbx {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.address = cpu.lookup_address.address;
-            addr.byte = cpu.accumulator;
-            addr.write = true;
-            if cpu.lookup_address.is_abs_y {
-                return false;
+            // NMOS decimal mode, N and V come from the sum before the high digit
+            // is adjusted and Z from the binary sum
+            let mut low = (self.accumulator & 0x0F) as u16 + (byte & 0x0F) as u16 + carry;
+            if low >= 0x0A {
+                low = ((low + 0x06) & 0x0F) + 0x10;
             }
-            true
-        }
-        fn step_1(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            false
-        }
-    }
-
-    struct CpuOpStx {}
-    impl CpuOperation for CpuOpStx {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.address = cpu.lookup_address.address;
-            addr.byte = cpu.register_x;
-            addr.write = true;
-            true
-        }
-    }
-
-    struct CpuOpSty {}
-    impl CpuOperation for CpuOpSty {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.address = cpu.lookup_address.address;
-            addr.byte = cpu.register_y;
-            addr.write = true;
-            true
-        }
-    }
+            let mut sum = (self.accumulator & 0xF0) as u16 + (byte & 0xF0) as u16 + low;
 
-    // Register Transfers
-    struct CpuOpTax {}
-    impl CpuOperation for CpuOpTax {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_x = cpu.accumulator;
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_x);
-            true
-        }
-    }
-
-    struct CpuOpTay {}
-    impl CpuOperation for CpuOpTay {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_y = cpu.accumulator;
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_y);
-            true
-        }
-    }
-
-    struct CpuOpTxa {}
-    impl CpuOperation for CpuOpTxa {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.accumulator = cpu.register_x;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpTya {}
-    impl CpuOperation for CpuOpTya {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.accumulator = cpu.register_y;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    // Stack Operations
-    struct CpuOpTsx {}
-    impl CpuOperation for CpuOpTsx {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_x = cpu.stack_pointer as u8;
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_x);
-            //println!("TSX sp:{:x} combined:{:x}", cpu.stack_pointer, cpu.stack_pointer + cpu.stack_pointer_page);
-            true
-        }
-    }
-
-    struct CpuOpTxs {}
-    impl CpuOperation for CpuOpTxs {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.stack_pointer = cpu.register_x as u16;
-            //println!("TXS set stack sp:{:x}", cpu.stack_pointer);
-            true
-        }
-    }
-
-    struct CpuOpPha {}
-    impl CpuOperation for CpuOpPha {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            //println!("Pha push stack sp:{:x}", cpu.stack_pointer);
-            OpCodesUtils::push_stack(cpu, addr, cpu.accumulator);
-            true
-        }
-    }
-    
-    struct CpuOpPhp {}
-    impl CpuOperation for CpuOpPhp {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, BREAK_COMMAND, true);
-            OpCodesUtils::set_status_flag(cpu, IGNORED, true);
-            OpCodesUtils::push_stack(cpu, addr, cpu.status_register);
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, BREAK_COMMAND, false);
-            OpCodesUtils::set_status_flag(cpu, IGNORED, false);
-            true
-        }
-    }
+            self.set_status_flag(NEGATIVE_FLAG, sum & 0x80 > 0);
+            self.set_status_flag(OVERFLOW_FLAG, (!(self.accumulator ^ byte) & (self.accumulator ^ sum as u8) & 0x80) > 0);
+            self.set_status_flag(ZERO_FLAG, binary & 0xFF == 0);
 
-    struct CpuOpPla {}
-    impl CpuOperation for CpuOpPla {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::pop_stack(cpu, addr);
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.accumulator = addr.byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpPlp {}
-    impl CpuOperation for CpuOpPlp {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::pop_stack(cpu, addr);
-            //println!("Plp pop stack sp:{:x}", cpu.stack_pointer);
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.status_register = addr.byte;
-            true
-        }
-    }
-
-    // Logical Operations
-    struct CpuOpAnd {}
-    impl CpuOperation for CpuOpAnd {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            cpu.accumulator &= byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpEor {}
-    impl CpuOperation for CpuOpEor {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            cpu.accumulator ^= byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpOra {}
-    impl CpuOperation for CpuOpOra {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            cpu.accumulator |= byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpBit {}
-    impl CpuOperation for CpuOpBit {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte = cpu.lookup_address.byte;
-            OpCodesUtils::set_status_flag(cpu, OVERFLOW_FLAG, (byte & 0x40) != 0);
-            OpCodesUtils::set_negative(cpu, byte);
-            OpCodesUtils::set_zero(cpu, byte & cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpAdc {}
-    impl CpuOperation for CpuOpAdc {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            let mut value: u8;
-
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 1;
+            if sum >= 0xA0 {
+                sum += 0x60;
             }
+            self.set_status_flag(CARRY_FLAG, sum > 0xFF);
+            self.accumulator = sum as u8;
 
-            let(value1, overflow1) = cpu.accumulator.overflowing_add(byte);
-            let(value2, overflow2) = value1.overflowing_add(carry);
-            let mut overflow3 = false;
-            let mut overflow4 = false;
-            value = value2;
-
-            if OpCodesUtils::get_status_flag(cpu, DECIMAL_MODE) != 0 {
-                if (value & 0x0f) > 0x09 {
-                    let (value4, overflow5) = value.overflowing_add(0x06);
-                    value = value4;
-                    overflow3 = overflow5;
-                }
-                if (value & 0xf0) > 0x90 {
-                    let (value5, overflow6) = value.overflowing_add(0x60);
-                    value = value5;
-                    overflow4 = overflow6;
-                }
+            // The 65C02 sets N and Z from the result
+            if variant.is_cmos() {
+                self.set_negative_zero(self.accumulator);
             }
-            OpCodesUtils::set_status_flag(cpu, OVERFLOW_FLAG, ((cpu.accumulator ^ value) & (byte ^ value) & 0x80) == 0x80);
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, overflow1 || overflow2 || overflow3 || overflow4);
-            OpCodesUtils::set_negative_zero(cpu, value);
-            cpu.accumulator = value;
-            true
         }
-    }
-
-    pub struct CpuOpAdcNoDecimal {}
-    impl CpuOperation for CpuOpAdcNoDecimal {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 1;
-            }
-
-            let(value1, overflow1) = cpu.accumulator.overflowing_add(byte);
-            let(value2, overflow2) = value1.overflowing_add(carry);
-            let value = value2;
 
-            OpCodesUtils::set_status_flag(cpu, OVERFLOW_FLAG, ((cpu.accumulator ^ value) & (byte ^ value) & 0x80) == 0x80);
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, overflow1 || overflow2);
-            OpCodesUtils::set_negative_zero(cpu, value);
-            cpu.accumulator = value;
-            true
-        }
-    }
+        fn sbc(&mut self, byte: u8, variant: M6502Variant) {
+            let borrow = 1 - self.get_carry() as i16;
+            let binary = self.accumulator as i16 - byte as i16 - borrow;
 
-    struct CpuOpSbc {}
-    impl CpuOperation for CpuOpSbc {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = !cpu.lookup_address.byte;
-            let mut value: u8;
+            // Flags are always from the binary subtraction
+            self.set_status_flag(OVERFLOW_FLAG, ((self.accumulator ^ byte) & (self.accumulator ^ binary as u8) & 0x80) > 0);
+            self.set_status_flag(CARRY_FLAG, binary >= 0);
+            let accumulator = self.accumulator;
+            self.accumulator = binary as u8;
+            self.set_negative_zero(self.accumulator);
 
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 1;
+            if !variant.has_decimal() || !self.get_status_flag(DECIMAL_MODE) {
+                return;
             }
 
-            let(value1, overflow1) = cpu.accumulator.overflowing_add(byte);
-            let(value2, overflow2) = value1.overflowing_add(carry);
-            value = value2;
-            let mut overflow3 = false;
-            let mut overflow4 = false;
-
-            if OpCodesUtils::get_status_flag(cpu, DECIMAL_MODE) != 0 {
-                if (value & 0x0f) > 0x09 {
-                    let (value4, overflow5) = value.overflowing_sub(0x06);
-                    value = value4;
-                    overflow3 = overflow5;
+            if variant.is_cmos() {
+                // The 65C02 adjusts the binary difference, N and Z come from the result
+                let low = (accumulator & 0x0F) as i16 - (byte & 0x0F) as i16 - borrow;
+                let mut result = binary;
+                if result < 0 {
+                    result -= 0x60;
                 }
-                if (value & 0xf0) > 0x90 {
-                    let (value5, overflow6) = value.overflowing_sub(0x60);
-                    value = value5;
-                    overflow4 = overflow6;
+                if low < 0 {
+                    result -= 0x06;
                 }
-            }
-            OpCodesUtils::set_status_flag(cpu, OVERFLOW_FLAG, ((cpu.accumulator ^ value) & (byte ^ value) & 0x80) == 0x80);
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, overflow1 || overflow2 || overflow3 || overflow4);
-            OpCodesUtils::set_negative_zero(cpu, value);
-            cpu.accumulator = value;
-            true
-        }
-    }
-
-    pub struct CpuOpSbcNoDecimal {}
-    impl CpuOperation for CpuOpSbcNoDecimal {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = !cpu.lookup_address.byte;
-
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 1;
-            }
-
-            let(value1, overflow1) = cpu.accumulator.overflowing_add(byte);
-            let(value2, overflow2) = value1.overflowing_add(carry);
-            let value = value2;
-
-            OpCodesUtils::set_status_flag(cpu, OVERFLOW_FLAG, ((cpu.accumulator ^ value) & (byte ^ value) & 0x80) == 0x80);
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, overflow1 || overflow2);
-            OpCodesUtils::set_negative_zero(cpu, value);
-            cpu.accumulator = value;
-            true
-        }
-    }
-
-    struct CpuOpCmp {}
-    impl CpuOperation for CpuOpCmp {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, cpu.accumulator >= byte);
-            OpCodesUtils::set_status_flag(cpu,ZERO_FLAG, cpu.accumulator == byte);
-            let negzerocheck= cpu.accumulator.wrapping_sub(byte);
-            OpCodesUtils::set_status_flag(cpu,NEGATIVE_FLAG, negzerocheck & 0x80 != 0);
-            true
-        }
-    }
-
-    struct CpuOpCpx {}
-    impl CpuOperation for CpuOpCpx {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, cpu.register_x >= byte);
-            OpCodesUtils::set_status_flag(cpu, ZERO_FLAG, cpu.register_x == byte);
-            let negzerocheck= cpu.register_x.wrapping_sub(byte);
-            OpCodesUtils::set_status_flag(cpu, NEGATIVE_FLAG, negzerocheck & 0x80 != 0);
-            true
-        }
-    }
-
-    struct CpuOpCpy {}
-    impl CpuOperation for CpuOpCpy {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, cpu.register_y >= byte);
-            OpCodesUtils::set_status_flag(cpu, ZERO_FLAG, cpu.register_y == byte);
-            let negzerocheck= cpu.register_y.wrapping_sub(byte);
-            OpCodesUtils::set_status_flag(cpu, NEGATIVE_FLAG, negzerocheck & 0x80 != 0);
-            true
-        }
-    }
-
-    // Increment and Decrement Operations
-    struct CpuOpInc {}
-    impl CpuOperation for CpuOpInc {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            addr.byte = byte.wrapping_add(1);
-            addr.address = cpu.lookup_address.address;
-            addr.write = true;
-            OpCodesUtils::set_negative_zero(cpu, addr.byte);
-            false
-        }
-        fn step_1(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            false
-        }
-        fn step_2(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            true
-        }
-    }
-
-    struct CpuOpInx {}
-    impl CpuOperation for CpuOpInx {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_x = cpu.register_x.wrapping_add(1);
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_x);
-            true
-        }
-    }
-
-    struct CpuOpIny {}
-    impl CpuOperation for CpuOpIny {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_y = cpu.register_y.wrapping_add(1);
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_y);
-            true
-        }
-    }
-
-    struct CpuOpDec {}
-    impl CpuOperation for CpuOpDec {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.lookup_address.byte;
-            addr.byte = byte.wrapping_sub(1);
-            addr.address = cpu.lookup_address.address;
-            addr.write = true;
-            OpCodesUtils::set_negative_zero(cpu, addr.byte);
-            false
-        }
-        fn step_1(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            false
-        }
-    }
-
-    struct CpuOpDex {}
-    impl CpuOperation for CpuOpDex {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_x = cpu.register_x.wrapping_sub(1);
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_x);
-            true
-        }
-    }
-
-    struct CpuOpDey {}
-    impl CpuOperation for CpuOpDey {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.register_y = cpu.register_y.wrapping_sub(1);
-            OpCodesUtils::set_negative_zero(cpu, cpu.register_y);
-            false
-        }
-    }
-
-    // Shift Operations
-    struct CpuOpAsl {}
-    impl CpuOperation for CpuOpAsl {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { true }
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let mut byte: u8 = cpu.lookup_address.byte;
- 
-            if cpu.lookup_address.is_accumulator {
-                byte = cpu.accumulator;
-            }
-
-            OpCodesUtils::set_status_flag(cpu,CARRY_FLAG, (byte & 0x80) != 0);
-            byte <<= 1;
-            OpCodesUtils::set_negative_zero(cpu, byte);
-            cpu.lookup_address.byte = byte;
-            if cpu.lookup_address.is_accumulator {
-                cpu.accumulator = cpu.lookup_address.byte;
-                return true;
-            }
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.byte = cpu.lookup_address.byte;
-            addr.address = cpu.lookup_address.address;
-            addr.write = true;
-            true
-        }
-    }
-
-    struct CpuOpLsr {}
-    impl CpuOperation for CpuOpLsr {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let mut byte: u8 = cpu.lookup_address.byte;
-            if cpu.lookup_address.is_accumulator {
-                byte = cpu.accumulator;
-            }
-
-            OpCodesUtils::set_status_flag(cpu,CARRY_FLAG, (byte & 0x01) != 0);
-            byte >>= 1;
-            OpCodesUtils::set_negative_zero(cpu, byte);
-            cpu.lookup_address.byte = byte;
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if cpu.lookup_address.is_accumulator {
-                cpu.accumulator = cpu.lookup_address.byte;
+                self.accumulator = result as u8;
+                self.set_negative_zero(self.accumulator);
             } else {
-                addr.byte = cpu.lookup_address.byte;
-                addr.address = cpu.lookup_address.address;
-                addr.write = true;
-            }
-            true
-        }
-    }
-
-    struct CpuOpRol {}
-    impl CpuOperation for CpuOpRol {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let mut byte: u8 = cpu.lookup_address.byte;
-
-            if cpu.lookup_address.is_accumulator {
-                byte = cpu.accumulator;
-            }
-
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 0x01;
-            }
-
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, (byte & 0x80) != 0);
-            byte = (byte << 1) | carry;
-            OpCodesUtils::set_negative_zero(cpu, byte);
-
-            if cpu.lookup_address.is_accumulator {
-                cpu.accumulator = byte;
-            } else {
-                addr.byte = byte;
-                addr.address = cpu.lookup_address.address;
-                addr.write = true;
-            }
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            if cpu.lookup_address.is_accumulator {
-                return true;
-            }
-            false
-        }
-    }
-
-    struct CpuOpRor {}
-    impl CpuOperation for CpuOpRor {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let mut byte: u8 = cpu.lookup_address.byte;
-
-            let mut carry: u8 = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                carry = 0x80;
-            }
-
-            OpCodesUtils::set_status_flag(cpu,CARRY_FLAG, (byte & 0x01) != 0);
-            byte = (byte >> 1) | carry;
-            OpCodesUtils::set_negative_zero(cpu, byte);
-
-            if cpu.lookup_address.is_accumulator {
-                cpu.accumulator = byte;
-            } else {
-                addr.byte = byte;
-                addr.address = cpu.lookup_address.address;
-                addr.write = true;
-            }
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            if cpu.lookup_address.is_accumulator {
-                return true;
-            }
-            false
-        }
-    }
-
-    // Jumps and Call Operations
-    struct CpuOpJmp {}
-    impl CpuOperation for CpuOpJmp {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { true }
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.program_counter = _addr.address;
-            //println!("JMP to addr:{:x}", cpu.program_counter);
-            true
-        }
-    }
-
-    struct CpuOpJsr {}
-    impl CpuOperation for CpuOpJsr {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        // Read first operand
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.address = cpu.program_counter;
-            cpu.program_counter += 1;
-            false
-        }
-        // dummy read stack
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.lookup_address.address = addr.byte as u16;
-            addr.address = cpu.stack_pointer.wrapping_add(cpu.stack_pointer_page);
-            false
-        }
-        // push pc high to stack
-        fn step_2(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::push_stack(cpu, addr, ((cpu.program_counter & 0xff00) >> 8) as u8);
-            false
-        }
-        // push PC low to stack
-        fn step_3(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::push_stack(cpu, addr, (cpu.program_counter & 0x00ff) as u8);
-            false
-        }
-        // read second operand
-        fn step_4(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.address = cpu.program_counter;
-            false
-        }
-        // perform jsr
-        fn step_5(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.lookup_address.address += (addr.byte as u16) << 8;
-            cpu.lookup_address.byte = addr.byte;
-            cpu.program_counter = cpu.lookup_address.address;
-            true
-        }
-    }
-
-    struct CpuOpRts {}
-    impl CpuOperation for CpuOpRts {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            //sprintln!("Rts1 pop stack sp:{:x}", cpu.stack_pointer);
-            OpCodesUtils::pop_stack(cpu, addr);
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter = addr.byte as u16;
-            //print!("Rts2 pop stack sp:{:x}", cpu.stack_pointer);
-            OpCodesUtils::pop_stack(cpu, addr);
-            false
-        }
-        fn step_2(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter = cpu.program_counter.wrapping_add((addr.byte as u16) << 8);
-            cpu.program_counter = cpu.program_counter.wrapping_add(1);
-            //println!(" PC: {:x}", cpu.program_counter);
-            false
-        }
-        fn step_3(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            false
-        }
-        fn step_4(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            false
-        }
-    }
-
-    // Branch Operations
-    struct CpuOpBcc {}
-    impl CpuOperation for CpuOpBcc {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) == 0 {
-                let relative_address: i8 = addr.byte as i8;
-                let addr_high_orig = cpu.program_counter & 0xff00;
-                cpu.program_counter = (cpu.program_counter as i16 + relative_address as i16) as u16;
-                // Set dummy read, old page if page bounds jumped
-                addr.address += 1;
-                if addr_high_orig != (cpu.program_counter & 0xff00) {
-                    addr.address = addr_high_orig + (cpu.program_counter & 0x00ff);
-                }
-                return false;
-            }
-            true
-        }
-    }
-
-    struct CpuOpBcs {}
-    impl CpuOperation for CpuOpBcs {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) != 0 {
-                let relative_address: i8 = addr.byte as i8;
-                let addr_high_orig = cpu.program_counter & 0xff00;
-                cpu.program_counter = (cpu.program_counter as i16 + relative_address as i16) as u16;
-                // Set dummy read, old page if page bounds jumped
-                addr.address += 1;
-                if addr_high_orig != (cpu.program_counter & 0xff00) {
-                    addr.address = addr_high_orig + (cpu.program_counter & 0x00ff);
-                }
-                return false;
-            }
-            true
-        }
-    }
-
-    struct CpuOpBne {}
-    impl CpuOperation for CpuOpBne {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if OpCodesUtils::get_status_flag(cpu, ZERO_FLAG) == 0 {
-                let relative_address: i8 = addr.byte as i8;
-                let addr_high_orig = cpu.program_counter & 0xff00;
-                cpu.program_counter = (cpu.program_counter as i16 + relative_address as i16) as u16;
-                // Set dummy read, old page if page bounds jumped
-                addr.address += 1;
-                if addr_high_orig != (cpu.program_counter & 0xff00) {
-                    addr.address = addr_high_orig + (cpu.program_counter & 0x00ff);
-                }
-                return false;
-            }
-            true
-        }
-    }
-
-    struct CpuOpBeq {}
-    impl CpuOperation for CpuOpBeq {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if OpCodesUtils::get_status_flag(cpu, ZERO_FLAG) != 0 {
-                let relative_address: i8 = addr.byte as i8;
-                let addr_high_orig = cpu.program_counter & 0xff00;
-                cpu.program_counter = (cpu.program_counter as i16 + relative_address as i16) as u16;
-                // Set dummy read, old page if page bounds jumped
-                addr.address += 1;
-                if addr_high_orig != (cpu.program_counter & 0xff00) {
-                    addr.address = addr_high_orig + (cpu.program_counter & 0x00ff);
-                }
-                return false;
-            }
-            true
-        }
-    }
-
-    struct CpuOpBpl {}
-    impl CpuOperation for CpuOpBpl {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if OpCodesUtils::get_status_flag(cpu, NEGATIVE_FLAG) == 0 {
-                let relative_address: i8 = addr.byte as i8;
-                let addr_high_orig = cpu.program_counter & 0xff00;
-                cpu.program_counter = (cpu.program_counter as i16 + relative_address as i16) as u16;
-                // Set dummy read, old page if page bounds jumped
-                addr.address += 1;
-                if addr_high_orig != (cpu.program_counter & 0xff00) {
-                    addr.address = addr_high_orig + (cpu.program_counter & 0x00ff);
-                }
-                return false;
-            }
-            true
-        }
-    }
-
-    struct CpuOpBmi {}
-    impl CpuOperation for CpuOpBmi {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if OpCodesUtils::get_status_flag(cpu, NEGATIVE_FLAG) != 0 {
-                let relative_address: i8 = addr.byte as i8;
-                let addr_high_orig = cpu.program_counter & 0xff00;
-                cpu.program_counter = (cpu.program_counter as i16 + relative_address as i16) as u16;
-                // Set dummy read, old page if page bounds jumped
-                addr.address += 1;
-                if addr_high_orig != (cpu.program_counter & 0xff00) {
-                    addr.address = addr_high_orig + (cpu.program_counter & 0x00ff);
-                }
-                return false;
-            }
-            true
-        }
-    }
-
-    struct CpuOpBvc {}
-    impl CpuOperation for CpuOpBvc {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if OpCodesUtils::get_status_flag(cpu, OVERFLOW_FLAG) == 0 {
-                let relative_address: i8 = addr.byte as i8;
-                let addr_high_orig = cpu.program_counter & 0xff00;
-                cpu.program_counter = (cpu.program_counter as i16 + relative_address as i16) as u16;
-                // Set dummy read, old page if page bounds jumped
-                addr.address += 1;
-                if addr_high_orig != (cpu.program_counter & 0xff00) {
-                    addr.address = addr_high_orig + (cpu.program_counter & 0x00ff);
+                let mut low = (accumulator & 0x0F) as i16 - (byte & 0x0F) as i16 - borrow;
+                if low < 0 {
+                    low = ((low - 0x06) & 0x0F) - 0x10;
                 }
-                return false;
-            }
-            true
-        }
-    }
-
-    struct CpuOpBvs {}
-    impl CpuOperation for CpuOpBvs {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            if OpCodesUtils::get_status_flag(cpu, OVERFLOW_FLAG) != 0 {
-                let relative_address: i8 = addr.byte as i8;
-                let addr_high_orig = cpu.program_counter & 0xff00;
-                cpu.program_counter = (cpu.program_counter as i16 + relative_address as i16) as u16;
-                // Set dummy read, old page if page bounds jumped
-                addr.address += 1;
-                if addr_high_orig != (cpu.program_counter & 0xff00) {
-                    addr.address = addr_high_orig + (cpu.program_counter & 0x00ff);
+                let mut result = (accumulator & 0xF0) as i16 - (byte & 0xF0) as i16 + low;
+                if result < 0 {
+                    result -= 0x60;
                 }
-                return false;
-            }
-            true
-        }
-    }
-
-    // Status Flag Operations
-    struct CpuOpClc {}
-    impl CpuOperation for CpuOpClc {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, false);
-            true
-        }
-    }
-
-    struct CpuOpCld {}
-    impl CpuOperation for CpuOpCld {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, DECIMAL_MODE, false);
-            true
-        }
-    }
-
-    struct CpuOpCli {}
-    impl CpuOperation for CpuOpCli {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, INTERRUPT_FLAG, false);
-            true
-        }
-    }
-
-    struct CpuOpCly {}
-    impl CpuOperation for CpuOpCly {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, OVERFLOW_FLAG, false);
-            true
-        }
-    }
-
-    struct CpuOpSec {}
-    impl CpuOperation for CpuOpSec {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, true);
-            true
-        }
-    }
-
-    struct CpuOpSed {}
-    impl CpuOperation for CpuOpSed {
-        //fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, DECIMAL_MODE, true);
-            true
-        }
-    }
-
-    struct CpuOpSei {}
-    impl CpuOperation for CpuOpSei {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, INTERRUPT_FLAG, true);
-            true
-        }
-    }
-
-    // System Opcodes
-    struct CpuOpNop {}
-    impl CpuOperation for CpuOpNop {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        fn step_0(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            false
-        }
-        fn step_1(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            true
-        }
-    }
-
-    struct CpuOpBrk {}
-    impl CpuOperation for CpuOpBrk {
-        fn needs_addr_byte(&self, _addr: &mut AddressBus) -> bool { false }
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-println!("brk");
-            OpCodesUtils::set_status_flag(cpu, INTERRUPT_FLAG, true);
-            cpu.program_counter += 3;
-            OpCodesUtils::push_stack(cpu, addr, (cpu.program_counter >> 8) as u8);
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::push_stack(cpu, addr, (cpu.program_counter & 0xff) as u8);
-            false
-        }
-        fn step_2(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, BREAK_COMMAND, true);
-            OpCodesUtils::set_status_flag(cpu, IGNORED, true);
-            OpCodesUtils::push_stack(cpu, addr, cpu.status_register);
-            OpCodesUtils::set_status_flag(cpu, BREAK_COMMAND, false);
-            false
-        }
-        fn step_3(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter = 0;
-            addr.address = 0xfffe;
-            false
-        }
-        fn step_4(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter = addr.byte as u16;
-            addr.address = 0xffff;
-            false
-        }
-        fn step_5(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter += (addr.byte as u16) << 8;
-            true
-        }
-    }
-
-    struct CpuOpNmi {}
-    impl CpuOperation for CpuOpNmi {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.in_interrupt = true;
-            OpCodesUtils::push_stack(cpu, addr, (cpu.program_counter >> 8) as u8);
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::push_stack(cpu, addr, (cpu.program_counter & 0xff) as u8);
-            false
-        } 
-        fn step_2(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::push_stack(cpu, addr, cpu.status_register);
-            false
-        }
-        fn step_3(&self, _cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.address = 0xfffa;
-            false
-        }
-        fn step_4(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter = addr.byte as u16;
-            addr.address += 1;
-            false
-        }
-        fn step_5(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter += (addr.byte as u16) << 8;
-            cpu.in_interrupt = false;
-            true
-        }
-    }
-
-    struct CpuOpIrq {}
-    impl CpuOperation for CpuOpIrq {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.in_interrupt = true;
-            OpCodesUtils::push_stack(cpu, addr, (cpu.program_counter >> 8) as u8);
-            false
-       }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::push_stack(cpu, addr, (cpu.program_counter & 0xff) as u8);
-            false
-        } 
-        fn step_2(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::push_stack(cpu, addr, cpu.status_register);
-            false
-        }
-        fn step_3(&self, _cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            addr.address = 0xfffe;
-            false
-        }
-        fn step_4(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter = addr.byte as u16;
-            addr.address += 1;
-            false
-        }
-        fn step_5(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter += (addr.byte as u16) << 8;
-            //cpu.in_interrupt = false;
-            true
-        }
-    }
-
-    struct CpuOpRti {}
-    impl CpuOperation for CpuOpRti {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, BREAK_COMMAND, false);
-            OpCodesUtils::pop_stack(cpu, addr);
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.status_register = addr.byte;
-            OpCodesUtils::pop_stack(cpu, addr);
-            false
-        }
-        fn step_2(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter = addr.byte as u16;
-            OpCodesUtils::pop_stack(cpu, addr);
-            false
-        }
-        fn step_3(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter += (addr.byte as u16) << 8;
-            OpCodesUtils::set_status_flag(cpu, BREAK_COMMAND, false);
-            cpu.in_interrupt = false;
-            true
-        }
-    }
-
-    // unofficial opcodes
-    struct CpuOpSha {}
-    impl CpuOperation for CpuOpSha {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte = cpu.accumulator & (cpu.register_x & (cpu.lookup_address.address >> 8).wrapping_sub(1) as u8);
-            addr.address = byte as u16;
-            //addr.address = cpu.lookup_address.address;
-            addr.write = true;
-            true
-        }
-    }
-
-    struct CpuOpAnc {}
-    impl CpuOperation for CpuOpAnc {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte = cpu.lookup_address.byte;
-            cpu.accumulator &= byte;
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, (cpu.accumulator & 0x80) != 0);
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpArr {}
-    impl CpuOperation for CpuOpArr {
-        fn step_0(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            true
-        }
-    }
-
-    struct CpuOpAsr {}
-    impl CpuOperation for CpuOpAsr {
-        fn step_0(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            true
-        }
-    }
-
-    struct CpuOpAxs {}
-    impl CpuOperation for CpuOpAxs {
-        fn step_0(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            true
-        }
-    }
-       
-    struct CpuOpIsc {}
-    impl CpuOperation for CpuOpIsc {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.lookup_address.byte = cpu.lookup_address.byte.overflowing_add(1).0;
-            addr.byte = cpu.lookup_address.byte;
-            addr.address = cpu.lookup_address.address;
-            addr.write = true;
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            cpu.lookup_address.byte = !cpu.lookup_address.byte;
-            let mut carry = 0;
-            if OpCodesUtils::get_status_flag(cpu, CARRY_FLAG) > 0 {
-                carry = 1;
+                self.accumulator = result as u8;
+            }
+        }
+
+        // AND then ROR, with ADC's decimal fix up applied to each digit
+        fn arr(&mut self, byte: u8, variant: M6502Variant) {
+            let value = self.accumulator & byte;
+            self.accumulator = (value >> 1) | (self.get_carry() << 7);
+            self.set_negative_zero(self.accumulator);
+
+            if !variant.has_decimal() || !self.get_status_flag(DECIMAL_MODE) {
+                self.set_status_flag(CARRY_FLAG, self.accumulator & 0x40 > 0);
+                self.set_status_flag(OVERFLOW_FLAG, ((self.accumulator >> 6) ^ (self.accumulator >> 5)) & 0x01 > 0);
+                return;
+            }
+
+            self.set_status_flag(OVERFLOW_FLAG, (value ^ self.accumulator) & 0x40 > 0);
+            if (value & 0x0F) + (value & 0x01) > 0x05 {
+                self.accumulator = (self.accumulator & 0xF0) | (self.accumulator.wrapping_add(0x06) & 0x0F);
+            }
+            let carry = (value >> 4) + ((value >> 4) & 0x01) > 0x05;
+            self.set_status_flag(CARRY_FLAG, carry);
+            if carry {
+                self.accumulator = self.accumulator.wrapping_add(0x60);
+            }
+        }
+
+        fn compare(&mut self, register: u8, byte: u8) {
+            self.set_status_flag(CARRY_FLAG, register >= byte);
+            self.set_negative_zero(register.wrapping_sub(byte));
+        }
+
+        fn asl(&mut self, byte: u8) -> u8 {
+            self.set_status_flag(CARRY_FLAG, byte & 0x80 > 0);
+            let result = byte << 1;
+            self.set_negative_zero(result);
+            result
+        }
+
+        fn lsr(&mut self, byte: u8) -> u8 {
+            self.set_status_flag(CARRY_FLAG, byte & 0x01 > 0);
+            let result = byte >> 1;
+            self.set_negative_zero(result);
+            result
+        }
+
+        fn rol(&mut self, byte: u8) -> u8 {
+            let result = (byte << 1) | self.get_carry();
+            self.set_status_flag(CARRY_FLAG, byte & 0x80 > 0);
+            self.set_negative_zero(result);
+            result
+        }
+
+        fn ror(&mut self, byte: u8) -> u8 {
+            let result = (byte >> 1) | (self.get_carry() << 7);
+            self.set_status_flag(CARRY_FLAG, byte & 0x01 > 0);
+            self.set_negative_zero(result);
+            result
+        }
+
+        // Read operations, run once the operand has been read
+        pub fn execute_read(&mut self, operation: M6502Operation, byte: u8, variant: M6502Variant) {
+            match operation {
+                Adc => self.adc(byte, variant),
+                And => {
+                    self.accumulator &= byte;
+                    self.set_negative_zero(self.accumulator);
+                },
+                Bit => {
+                    self.set_status_flag(ZERO_FLAG, self.accumulator & byte == 0);
+                    self.set_status_flag(NEGATIVE_FLAG, byte & 0x80 > 0);
+                    self.set_status_flag(OVERFLOW_FLAG, byte & 0x40 > 0);
+                },
+                Cmp => self.compare(self.accumulator, byte),
+                Cpx => self.compare(self.register_x, byte),
+                Cpy => self.compare(self.register_y, byte),
+                Eor => {
+                    self.accumulator ^= byte;
+                    self.set_negative_zero(self.accumulator);
+                },
+                Lda => {
+                    self.accumulator = byte;
+                    self.set_negative_zero(byte);
+                },
+                Ldx => {
+                    self.register_x = byte;
+                    self.set_negative_zero(byte);
+                },
+                Ldy => {
+                    self.register_y = byte;
+                    self.set_negative_zero(byte);
+                },
+                Ora => {
+                    self.accumulator |= byte;
+                    self.set_negative_zero(self.accumulator);
+                },
+                Sbc => self.sbc(byte, variant),
+                Pla => {
+                    self.accumulator = byte;
+                    self.set_negative_zero(byte);
+                },
+                Plx => {
+                    self.register_x = byte;
+                    self.set_negative_zero(byte);
+                },
+                Ply => {
+                    self.register_y = byte;
+                    self.set_negative_zero(byte);
+                },
+                Plp => self.set_pulled_status(byte),
+                Anc => {
+                    self.accumulator &= byte;
+                    self.set_negative_zero(self.accumulator);
+                    self.set_status_flag(CARRY_FLAG, self.accumulator & 0x80 > 0);
+                },
+                Ane => {
+                    self.accumulator = (self.accumulator | UNSTABLE_MAGIC) & self.register_x & byte;
+                    self.set_negative_zero(self.accumulator);
+                },
+                Arr => self.arr(byte, variant),
+                Asr => {
+                    self.accumulator &= byte;
+                    self.accumulator = self.lsr(self.accumulator);
+                },
+                Axs => {
+                    let register = self.accumulator & self.register_x;
+                    self.set_status_flag(CARRY_FLAG, register >= byte);
+                    self.register_x = register.wrapping_sub(byte);
+                    self.set_negative_zero(self.register_x);
+                },
+                Lae => {
+                    let result = byte & self.stack_pointer;
+                    self.accumulator = result;
+                    self.register_x = result;
+                    self.stack_pointer = result;
+                    self.set_negative_zero(result);
+                },
+                Lax => {
+                    self.accumulator = byte;
+                    self.register_x = byte;
+                    self.set_negative_zero(byte);
+                },
+                Lxa => {
+                    self.accumulator = (self.accumulator | UNSTABLE_MAGIC) & byte;
+                    self.register_x = self.accumulator;
+                    self.set_negative_zero(self.accumulator);
+                },
+                _ => {},
+            }
+        }
+
+        // The 65C02's BIT immediate only sets Z
+        pub fn bit_immediate(&mut self, byte: u8) {
+            self.set_status_flag(ZERO_FLAG, self.accumulator & byte == 0);
+        }
+
+        // Implied and accumulator operations
+        pub fn execute_implied(&mut self, operation: M6502Operation) {
+            match operation {
+                Clc => self.set_status_flag(CARRY_FLAG, false),
+                Cld => self.set_status_flag(DECIMAL_MODE, false),
+                Cli => self.set_status_flag(INTERRUPT_FLAG, false),
+                Clv => self.set_status_flag(OVERFLOW_FLAG, false),
+                Sec => self.set_status_flag(CARRY_FLAG, true),
+                Sed => self.set_status_flag(DECIMAL_MODE, true),
+                Sei => self.set_status_flag(INTERRUPT_FLAG, true),
+                Dex => {
+                    self.register_x = self.register_x.wrapping_sub(1);
+                    self.set_negative_zero(self.register_x);
+                },
+                Dey => {
+                    self.register_y = self.register_y.wrapping_sub(1);
+                    self.set_negative_zero(self.register_y);
+                },
+                Inx => {
+                    self.register_x = self.register_x.wrapping_add(1);
+                    self.set_negative_zero(self.register_x);
+                },
+                Iny => {
+                    self.register_y = self.register_y.wrapping_add(1);
+                    self.set_negative_zero(self.register_y);
+                },
+                Tax => {
+                    self.register_x = self.accumulator;
+                    self.set_negative_zero(self.register_x);
+                },
+                Tay => {
+                    self.register_y = self.accumulator;
+                    self.set_negative_zero(self.register_y);
+                },
+                Tsx => {
+                    self.register_x = self.stack_pointer;
+                    self.set_negative_zero(self.register_x);
+                },
+                Txa => {
+                    self.accumulator = self.register_x;
+                    self.set_negative_zero(self.accumulator);
+                },
+                Txs => self.stack_pointer = self.register_x,
+                Tya => {
+                    self.accumulator = self.register_y;
+                    self.set_negative_zero(self.accumulator);
+                },
+                Asl => self.accumulator = self.asl(self.accumulator),
+                Lsr => self.accumulator = self.lsr(self.accumulator),
+                Rol => self.accumulator = self.rol(self.accumulator),
+                Ror => self.accumulator = self.ror(self.accumulator),
+                Dec => {
+                    self.accumulator = self.accumulator.wrapping_sub(1);
+                    self.set_negative_zero(self.accumulator);
+                },
+                Inc => {
+                    self.accumulator = self.accumulator.wrapping_add(1);
+                    self.set_negative_zero(self.accumulator);
+                },
+                _ => {},
+            }
+        }
+
+        // Read-modify-write operations, returns the byte written back
+        pub fn execute_modify(&mut self, operation: M6502Operation, byte: u8, variant: M6502Variant) -> u8 {
+            match operation {
+                Asl => self.asl(byte),
+                Lsr => self.lsr(byte),
+                Rol => self.rol(byte),
+                Ror => self.ror(byte),
+                Dec => {
+                    let result = byte.wrapping_sub(1);
+                    self.set_negative_zero(result);
+                    result
+                },
+                Inc => {
+                    let result = byte.wrapping_add(1);
+                    self.set_negative_zero(result);
+                    result
+                },
+                Dcp => {
+                    let result = byte.wrapping_sub(1);
+                    self.compare(self.accumulator, result);
+                    result
+                },
+                Isc => {
+                    let result = byte.wrapping_add(1);
+                    self.sbc(result, variant);
+                    result
+                },
+                Rla => {
+                    let result = self.rol(byte);
+                    self.execute_read(And, result, variant);
+                    result
+                },
+                Rra => {
+                    let result = self.ror(byte);
+                    self.adc(result, variant);
+                    result
+                },
+                Slo => {
+                    let result = self.asl(byte);
+                    self.execute_read(Ora, result, variant);
+                    result
+                },
+                Sre => {
+                    let result = self.lsr(byte);
+                    self.execute_read(Eor, result, variant);
+                    result
+                },
+                Trb => {
+                    self.set_status_flag(ZERO_FLAG, self.accumulator & byte == 0);
+                    byte & !self.accumulator
+                },
+                Tsb => {
+                    self.set_status_flag(ZERO_FLAG, self.accumulator & byte == 0);
+                    byte | self.accumulator
+                },
+                Rmb(bit) => byte & !(1 << bit),
+                Smb(bit) => byte | (1 << bit),
+                _ => byte,
+            }
+        }
+
+        // Byte for write operations, STZ's is 0. High is the high byte of the unindexed address.
+        // SHA, SHX, SHY and TAS AND it in as the index is added.
+        pub fn get_write_byte(&mut self, operation: M6502Operation, high: u8) -> u8 {
+            match operation {
+                Sta => self.accumulator,
+                Stx => self.register_x,
+                Sty => self.register_y,
+                Sax => self.accumulator & self.register_x,
+                Sha => self.accumulator & self.register_x & high.wrapping_add(1),
+                Shx => self.register_x & high.wrapping_add(1),
+                Shy => self.register_y & high.wrapping_add(1),
+                Shs => {
+                    self.stack_pointer = self.accumulator & self.register_x;
+                    self.stack_pointer & high.wrapping_add(1)
+                },
+                _ => 0,
+            }
+        }
+
+        pub fn is_branch_taken(&self, operation: M6502Operation) -> bool {
+            match operation {
+                Bcc => !self.get_status_flag(CARRY_FLAG),
+                Bcs => self.get_status_flag(CARRY_FLAG),
+                Beq => self.get_status_flag(ZERO_FLAG),
+                Bmi => self.get_status_flag(NEGATIVE_FLAG),
+                Bne => !self.get_status_flag(ZERO_FLAG),
+                Bpl => !self.get_status_flag(NEGATIVE_FLAG),
+                Bvc => !self.get_status_flag(OVERFLOW_FLAG),
+                Bvs => self.get_status_flag(OVERFLOW_FLAG),
+                Bra => true,
+                _ => false,
             }
-            let value = cpu.accumulator as u16 + cpu.lookup_address.byte as u16 + carry;
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, value > 255);
-            OpCodesUtils::set_status_flag(cpu, OVERFLOW_FLAG, (cpu.accumulator as u16 ^ value) & (cpu.lookup_address.byte as u16 ^ value) & 0x80 != 0);
-            cpu.accumulator = value as u8;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpLae {}
-    impl CpuOperation for CpuOpLae {
-        fn step_0(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            true
-        }
-    }
-
-    struct CpuOpLxa {}
-    impl CpuOperation for CpuOpLxa {
-        fn step_0(&self, _cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            true
-        }
-    }
-
-    struct CpuOpSax {}
-    impl CpuOperation for CpuOpSax {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte: u8 = cpu.accumulator & cpu.register_x;
-            addr.byte = byte;
-            addr.write = true;
-            true
-        }
-    }
-
-    struct CpuOpShs {}
-    impl CpuOperation for CpuOpShs {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte = cpu.accumulator & cpu.register_x;
-            println!("Shs push stack sp:{:x}", cpu.stack_pointer);
-            OpCodesUtils::push_stack(cpu, addr, byte);
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte = (cpu.accumulator & (cpu.register_x & (cpu.lookup_address.address & 0xff00) as u8)) + 1;
-            addr.byte = byte;
-            addr.address = cpu.lookup_address.address;
-            addr.write = true;
-            true
-        }
-    }
-
-    struct CpuOpShx {}
-    impl CpuOperation for CpuOpShx {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte = cpu.register_x & (((cpu.lookup_address.address & 0xff00) >> 8) + 1) as u8;
-            addr.address = cpu.lookup_address.address;
-            addr.byte = byte;
-            addr.write = true;
-            true
-        }
-    }
-
-    struct CpuOpShy {}
-    impl CpuOperation for CpuOpShy {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            let byte = cpu.register_y & (((cpu.lookup_address.address & 0xff00) >> 8) + 1) as u8;
-            addr.address = cpu.lookup_address.address;
-            addr.byte = byte;
-            addr.write = true;
-            true
-        }
-    }
-
-    struct CpuOpSlo {}
-    impl CpuOperation for CpuOpSlo {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            OpCodesUtils::set_status_flag(cpu, CARRY_FLAG, (addr.byte & 0x80) != 0);
-            addr.byte <<= 1;
-            addr.write = true;
-            addr.address = cpu.lookup_address.address;
-            cpu.accumulator |= addr.byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-    struct CpuOpAne {}
-    impl CpuOperation for CpuOpAne {
-        fn step_0(&self, cpu: &mut M6502, _addr: &mut AddressBus) -> bool {
-            let byte = cpu.lookup_address.byte;
-            cpu.accumulator &= cpu.register_x;
-            cpu.accumulator &= byte;
-            OpCodesUtils::set_negative_zero(cpu, cpu.accumulator);
-            true
-        }
-    }
-
-
-
-    // Reset and Interrupts
-    struct CpuOpReset {}
-    impl CpuOperation for CpuOpReset {
-        fn step_0(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.stack_pointer = 0xff;
-            cpu.accumulator = 0;
-            cpu.register_x = 0;
-            cpu.register_y = 0;
-            cpu.status_register = 0;
-            addr.address = RESET_FIRST_READ;
-            false
-        }
-        fn step_1(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter = addr.byte as u16;
-            addr.address = RESET_SECOND_READ;
-            false
-        } 
-        fn step_2(&self, cpu: &mut M6502, addr: &mut AddressBus) -> bool {
-            cpu.program_counter += (addr.byte as u16) << 8;
-            true
         }
     }
-
 }
@@ -0,0 +1,129 @@
+
+pub mod mtracer {
+
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+
+    use crate::m6502::emu_cpu::{M6502, M6502Variant};
+    use crate::m6502_address::maddress::M6502AddressMode;
+    use crate::m6502_disassembler::mdisassembler::{M6502Disassembler, M6502Instruction};
+    use crate::m6502_opcodes::mopcodes::M6502Operation;
+
+    // Writes one line per instruction in the format of nestest.log:
+    //   C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+    // The memory an operand refers to is shown as it was before the instruction ran.
+    // Without a file the lines are kept until they're taken.
+    pub struct M6502Tracer {
+        disassembler: M6502Disassembler,
+        output: Option<BufWriter<File>>,
+        lines: Vec<String>,
+    }
+
+    impl M6502Tracer {
+
+        pub fn new(variant: M6502Variant) -> M6502Tracer {
+            Self {
+                disassembler: M6502Disassembler::new(variant),
+                output: None,
+                lines: Vec::new(),
+            }
+        }
+
+        pub fn to_file(variant: M6502Variant, file: &str) -> io::Result<M6502Tracer> {
+            let mut tracer = Self::new(variant);
+            tracer.output = Some(BufWriter::new(File::create(file)?));
+            Ok(tracer)
+        }
+
+        pub fn take_lines(&mut self) -> Vec<String> {
+            std::mem::take(&mut self.lines)
+        }
+
+        // cpu is at the op code, cycles is how many ran before it and video the
+        // scan line and dot if the console has them. read mustn't have side effects.
+        pub fn trace<F: Fn(u16) -> u8>(&mut self, cpu: &M6502, cycles: u64, video: Option<(i32, i32)>, read: F) {
+            let line = self.format_line(cpu, cycles, video, read);
+            match self.output.as_mut() {
+                Some(output) => {
+                    if let Err(e) = writeln!(output, "{}", line) {
+                        eprintln!("Stopped tracing: {}", e);
+                        self.output = None;
+                    }
+                },
+                None => self.lines.push(line),
+            }
+        }
+
+        pub fn format_line<F: Fn(u16) -> u8>(&self, cpu: &M6502, cycles: u64, video: Option<(i32, i32)>, read: F) -> String {
+            let instruction = self.disassembler.disassemble(cpu.program_counter, &read);
+            let bytes: Vec<String> = instruction.bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let marker = if instruction.undocumented { '*' } else { ' ' };
+            let text = format!("{} {}{}", instruction.operation.get_mnemonic(), instruction.get_operand(None),
+                self.get_memory(&instruction, cpu, &read));
+
+            let mut line = format!("{:04X}  {:<8} {}{:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}",
+                instruction.address, bytes.join(" "), marker, text.trim_end(), cpu.accumulator, cpu.register_x,
+                cpu.register_y, cpu.status_register, cpu.stack_pointer);
+            if let Some((scan_line, dot)) = video {
+                line.push_str(&format!(" PPU:{:>3},{:>3}", scan_line, dot));
+            }
+            line.push_str(&format!(" CYC:{}", cycles));
+            line
+        }
+
+        // The addresses the operand goes through and the value at the end, like " @ 0300 = 89"
+        fn get_memory<F: Fn(u16) -> u8>(&self, instruction: &M6502Instruction, cpu: &M6502, read: &F) -> String {
+            let word = |address: u16, next: u16| ((read(next) as u16) << 8) | read(address) as u16;
+            let zero_page_word = |address: u8| word(address as u16, address.wrapping_add(1) as u16);
+            let Some(target) = instruction.get_target() else {
+                return String::new();
+            };
+
+            match instruction.mode {
+                M6502AddressMode::ZeroPage => format!(" = {:02X}", read(target)),
+                M6502AddressMode::ZeroPageX | M6502AddressMode::ZeroPageY => {
+                    let index = if instruction.mode == M6502AddressMode::ZeroPageX { cpu.register_x } else { cpu.register_y };
+                    let address = (target as u8).wrapping_add(index) as u16;
+                    format!(" @ {:02X} = {:02X}", address, read(address))
+                },
+                M6502AddressMode::Absolute => match instruction.operation {
+                    M6502Operation::Jmp | M6502Operation::Jsr => String::new(),
+                    _ => format!(" = {:02X}", read(target)),
+                },
+                M6502AddressMode::AbsoluteX | M6502AddressMode::AbsoluteY => {
+                    let index = if instruction.mode == M6502AddressMode::AbsoluteX { cpu.register_x } else { cpu.register_y };
+                    let address = target.wrapping_add(index as u16);
+                    format!(" @ {:04X} = {:02X}", address, read(address))
+                },
+                M6502AddressMode::Indirect => {
+                    // The NMOS parts don't carry into the high byte of the pointer
+                    let next = if self.disassembler.get_variant().is_cmos() {
+                        target.wrapping_add(1)
+                    } else {
+                        (target & 0xff00) | (target.wrapping_add(1) & 0x00ff)
+                    };
+                    format!(" = {:04X}", word(target, next))
+                },
+                M6502AddressMode::IndirectX => {
+                    let pointer = (target as u8).wrapping_add(cpu.register_x);
+                    let address = zero_page_word(pointer);
+                    format!(" @ {:02X} = {:04X} = {:02X}", pointer, address, read(address))
+                },
+                M6502AddressMode::IndirectY => {
+                    let base = zero_page_word(target as u8);
+                    let address = base.wrapping_add(cpu.register_y as u16);
+                    format!(" = {:04X} @ {:04X} = {:02X}", base, address, read(address))
+                },
+                M6502AddressMode::ZeroPageIndirect => {
+                    let address = zero_page_word(target as u8);
+                    format!(" = {:04X} = {:02X}", address, read(address))
+                },
+                M6502AddressMode::AbsoluteIndexedIndirect => {
+                    let pointer = target.wrapping_add(cpu.register_x as u16);
+                    format!(" @ {:04X} = {:04X}", pointer, word(pointer, pointer.wrapping_add(1)))
+                },
+                _ => String::new(),
+            }
+        }
+    }
+}
@@ -1,1366 +0,0 @@
-
-pub mod emu_cpu {
-
-    use std::{ptr::fn_addr_eq};
-
-    use emumemory::memory_mapper::emu_memory::MemoryMapper;
-    use crate::base_cpu::emu_cpu::BaseCpu;
-
-    const CARRY_FLAG: u8 = 1;
-    const ZERO_FLAG: u8 = 2;
-    const INTERRUPT_FLAG: u8 = 4;
-    const DECIMAL_MODE: u8 = 8;
-    const BREAK_COMMAND: u8 = 16;
-    const IGNORED: u8 = 32;
-    const OVERFLOW_FLAG: u8 =64;
-    const NEGATIVE_FLAG: u8 = 128;
-
-    
-    pub struct OperationStruct<T: MemoryMapper> {
-        operation: fn(&mut NM6502<T>, fn(&mut NM6502<T>)),
-        address_method: fn(&mut NM6502<T>),
-        ticks: u8
-    }
-
-    pub struct AddressStruct {
-        loadl: u8,
-        loadh: u8,
-    }
-
-    impl AddressStruct {
-        fn address(&self) -> u16 {
-             ((self.loadh as u16) << 8) + (self.loadl as u16)
-        }
-    }
-
-    impl<T:MemoryMapper> Copy for OperationStruct<T> {}
-
-    impl<T:MemoryMapper> Clone for OperationStruct<T> {
-        fn clone (&self) -> Self {
-            OperationStruct {
-                operation: self.operation,
-                address_method: self.address_method,
-                ticks: self.ticks,
-            }
-        }
-    }
-
-    pub struct NM6502<T: MemoryMapper> {
-        pub memory: T,
-        overflow_ticks: i32,
-        program_counter: u16,
-        stack_pointer_page: u16,
-        stack_pointer: u16,
-        accumulator: u8,
-        register_x: u8,
-        register_y: u8,
-        status_register: u8,
-        _debug: u8,
-        op_code_lookup: [OperationStruct<T>; 0x100],
-        operation: OperationStruct<T>,
-        instruction: u8,
-        is_nmi_set: bool,
-        is_irq_set: bool,
-        in_interrupt: bool,
-        dec_disabled: bool,
-        address_bus: AddressStruct,
-    }
-
-    impl<T: MemoryMapper> BaseCpu for NM6502<T> {
-
-        fn execute_tick(&mut self) {
-
-            if self.memory.get_dma_write() > 0 {
-                self.memory.execute_tick();
-                return;
-            }
-
-            if self.is_nmi_set {
-                self.is_nmi_set = false;
-                self.handle_interrupt(0xfffa);
-            }
-            else if self.is_irq_set && self.get_status_flag(INTERRUPT_FLAG) == 0 {
-                self.is_irq_set = false;
-                self.handle_interrupt(0xfffe);
-            }
-            else {
-                if self.overflow_ticks  > 1 {
-                    self.overflow_ticks -= 1;
-                    return;
-                }
-                
-                self.call_op_method(self.operation.operation, self.operation.address_method);
-                self.overflow_ticks -= 1;
-                self.get_next_operation();
-            }
-        }   
-
-        fn reset(&mut self) {
-            self.operation = self.op_code_lookup[0xea];
-            self.stack_pointer_page = 0x100;  // should be 0100 but is 0x00 for atari
-            self.stack_pointer = 0xff;
-            let pcl: u8 = self.memory.cpu_read(0xfffc);
-            let pch: u8 = self.memory.cpu_read(0xfffd);
-            self.program_counter = ((pch as u16) << 8) + pcl as u16;
-            self.accumulator = 0;
-            self.register_x = 0;
-            self.register_y = 0;
-            self.status_register = 0;
-            self.set_status_flag(IGNORED, true);
-            self.is_nmi_set = false;
-            self.is_irq_set = false;
-        } 
-    }
-
-    impl<T: MemoryMapper> NM6502<T> {
-
-        pub fn new(memory_mapper: T) -> Self {
-
-            let op_code_lookup: [OperationStruct<T>; 0x100] = NM6502::get_op_codes();
-            let operation: OperationStruct<T> = op_code_lookup[0xea];
-
-            Self {
-                    memory: memory_mapper,
-                    overflow_ticks: 0,
-                    program_counter: 0,
-                    stack_pointer_page: 0x100,
-                    stack_pointer: 0xfd,
-                    accumulator: 0,
-                    register_x: 0,
-                    register_y: 0,
-                    status_register: 0x40,
-                    op_code_lookup: op_code_lookup,
-                    operation: operation,
-                    instruction: 0,
-                    _debug: 0,
-                    is_nmi_set: false,
-                    is_irq_set: false,
-                    dec_disabled: false,
-                    in_interrupt: false,
-                    address_bus : AddressStruct { loadl: 0, loadh: 0 },
-            }
-        }
-
-        pub fn dma_suspend(&mut self) {
-            self.overflow_ticks += 154;
-        }
-
-        pub fn set_nmi (&mut self) {
-            self.is_nmi_set = true;
-        }
-        
-        pub fn set_irq (&mut self) {
-            self.is_irq_set = true;
-        }
-
-        pub fn disable_dec(&mut self) {
-            self.dec_disabled = true;
-        }
-
-        fn handle_interrupt(&mut self, address: u16) {
-            self.in_interrupt = true;
-            self.program_counter = self.program_counter.overflowing_sub(1).0;
-            self.push_stack(((self.program_counter & 0xff00) >> 8) as u8);
-            self.push_stack((self.program_counter & 0x00ff) as u8);
-            self.op_php(NM6502::null_address);
-            let loadl: u8 = self.memory.cpu_read(address);
-            let loadh: u8 = self.memory.cpu_read(address + 1);
-            let load: u16 = (((loadh as u16) << 8) + loadl as u16) as u16;
-            self.program_counter = load;
-            self.get_next_operation();
-            self.in_interrupt = false;
-        }
-
-        fn get_op_codes() -> [OperationStruct<T>; 0x100] {
-            let panic_operation = OperationStruct { operation: NM6502::op_panic, address_method: NM6502::null_address, ticks: 2 };
-            let mut op_code_lookup: [OperationStruct<T>; 0x100] = [panic_operation; 0x100];
-
-            op_code_lookup[0x00] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x01] = OperationStruct { operation: NM6502::op_ora, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0x02] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x03] = OperationStruct { operation: NM6502::op_slo, address_method: NM6502::indirect_x_address, ticks: 8 };
-            op_code_lookup[0x04] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x05] = OperationStruct { operation: NM6502::op_ora, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x06] = OperationStruct { operation: NM6502::op_asl, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0x07] = OperationStruct { operation: NM6502::op_slo, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0x08] = OperationStruct { operation: NM6502::op_php, address_method: NM6502::null_address, ticks: 3 };
-            op_code_lookup[0x09] = OperationStruct { operation: NM6502::op_ora, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x0a] = OperationStruct { operation: NM6502::op_asl, address_method: NM6502::accumulator_address, ticks: 2 };
-            op_code_lookup[0x0b] = OperationStruct { operation: NM6502::op_anc, address_method: NM6502::immediate_address, ticks: 7 };
-            op_code_lookup[0x0c] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x0d] = OperationStruct { operation: NM6502::op_ora, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x0e] = OperationStruct { operation: NM6502::op_asl, address_method: NM6502::absolute_address, ticks: 6 };
-            op_code_lookup[0x0f] = OperationStruct { operation: NM6502::op_slo, address_method: NM6502::absolute_address, ticks: 6 };
-
-            op_code_lookup[0x10] = OperationStruct { operation: NM6502::op_bpl, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x11] = OperationStruct { operation: NM6502::op_ora, address_method: NM6502::indirect_y_address, ticks: 5 };
-            op_code_lookup[0x12] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x13] = OperationStruct { operation: NM6502::op_slo, address_method: NM6502::indirect_y_address_no_overflow, ticks: 8 };
-            op_code_lookup[0x14] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x15] = OperationStruct { operation: NM6502::op_ora, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x16] = OperationStruct { operation: NM6502::op_asl, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0x17] = OperationStruct { operation: NM6502::op_slo, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0x18] = OperationStruct { operation: NM6502::op_clc, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x19] = OperationStruct { operation: NM6502::op_ora, address_method: NM6502::absolute_y_address, ticks: 4 };
-            op_code_lookup[0x1a] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x1b] = OperationStruct { operation: NM6502::op_slo, address_method: NM6502::absolute_y_address_no_overflow, ticks: 7 };
-            op_code_lookup[0x1c] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0x1d] = OperationStruct { operation: NM6502::op_ora, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0x1e] = OperationStruct { operation: NM6502::op_asl, address_method: NM6502::absolute_x_address_no_overflow, ticks: 7 };
-            op_code_lookup[0x1f] = OperationStruct { operation: NM6502::op_slo, address_method: NM6502::absolute_x_address_no_overflow, ticks: 7 };
-
-            op_code_lookup[0x20] = OperationStruct { operation: NM6502::op_jsr, address_method: NM6502::absolute_address, ticks: 6 };
-            op_code_lookup[0x21] = OperationStruct { operation: NM6502::op_and, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0x22] = OperationStruct { operation: NM6502::op_rla, address_method: NM6502::indirect_x_address, ticks: 8 };
-            op_code_lookup[0x23] = OperationStruct { operation: NM6502::op_rla, address_method: NM6502::indirect_x_address, ticks: 7};
-            op_code_lookup[0x24] = OperationStruct { operation: NM6502::op_bit, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x25] = OperationStruct { operation: NM6502::op_and, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x26] = OperationStruct { operation: NM6502::op_rol, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0x27] = OperationStruct { operation: NM6502::op_rla, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0x28] = OperationStruct { operation: NM6502::op_plp, address_method: NM6502::null_address, ticks: 4 };
-            op_code_lookup[0x29] = OperationStruct { operation: NM6502::op_and, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x2a] = OperationStruct { operation: NM6502::op_rol, address_method: NM6502::accumulator_address, ticks: 2 };
-            op_code_lookup[0x2b] = OperationStruct { operation: NM6502::op_anc, address_method: NM6502::immediate_address, ticks: 7 };
-            op_code_lookup[0x2c] = OperationStruct { operation: NM6502::op_bit, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x2d] = OperationStruct { operation: NM6502::op_and, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x2e] = OperationStruct { operation: NM6502::op_rol, address_method: NM6502::absolute_address, ticks: 6 };
-            op_code_lookup[0x2f] = OperationStruct { operation: NM6502::op_rla, address_method: NM6502::absolute_address, ticks: 6 };
-
-            op_code_lookup[0x30] = OperationStruct { operation: NM6502::op_bmi, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x31] = OperationStruct { operation: NM6502::op_and, address_method: NM6502::indirect_y_address, ticks: 5 };
-            op_code_lookup[0x32] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x33] = OperationStruct { operation: NM6502::op_rla, address_method: NM6502::indirect_y_address, ticks: 8 };
-            op_code_lookup[0x34] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x35] = OperationStruct { operation: NM6502::op_and, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x36] = OperationStruct { operation: NM6502::op_rol, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0x37] = OperationStruct { operation: NM6502::op_rla, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0x38] = OperationStruct { operation: NM6502::op_sec, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x39] = OperationStruct { operation: NM6502::op_and, address_method: NM6502::absolute_y_address, ticks: 4 };
-            op_code_lookup[0x3a] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x3b] = OperationStruct { operation: NM6502::op_rla, address_method: NM6502::absolute_y_address, ticks: 7 };
-            op_code_lookup[0x3c] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0x3d] = OperationStruct { operation: NM6502::op_and, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0x3e] = OperationStruct { operation: NM6502::op_rol, address_method: NM6502::absolute_x_address_no_overflow, ticks: 7 };
-            op_code_lookup[0x3f] = OperationStruct { operation: NM6502::op_rla, address_method: NM6502::absolute_x_address, ticks: 7 };
-
-            op_code_lookup[0x40] = OperationStruct { operation: NM6502::op_rti, address_method: NM6502::null_address, ticks: 6 };
-            op_code_lookup[0x41] = OperationStruct { operation: NM6502::op_eor, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0x42] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x43] = OperationStruct { operation: NM6502::op_sre, address_method: NM6502::indirect_x_address, ticks: 8 };
-            op_code_lookup[0x44] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x45] = OperationStruct { operation: NM6502::op_eor, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x46] = OperationStruct { operation: NM6502::op_lsr, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0x47] = OperationStruct { operation: NM6502::op_sre, address_method: NM6502::zero_address, ticks: 6 };
-            op_code_lookup[0x48] = OperationStruct { operation: NM6502::op_pha, address_method: NM6502::null_address, ticks: 3 };
-            op_code_lookup[0x49] = OperationStruct { operation: NM6502::op_eor, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x4a] = OperationStruct { operation: NM6502::op_lsr, address_method: NM6502::accumulator_address, ticks: 2 };
-            op_code_lookup[0x4b] = OperationStruct { operation: NM6502::op_asr, address_method: NM6502::immediate_address, ticks: 7 };
-            op_code_lookup[0x4c] = OperationStruct { operation: NM6502::op_jmp, address_method: NM6502::absolute_address, ticks: 3 };
-            op_code_lookup[0x4d] = OperationStruct { operation: NM6502::op_eor, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x4e] = OperationStruct { operation: NM6502::op_lsr, address_method: NM6502::absolute_address, ticks: 6 };
-            op_code_lookup[0x4f] = OperationStruct { operation: NM6502::op_sre, address_method: NM6502::absolute_address, ticks: 6 };
-
-            op_code_lookup[0x50] = OperationStruct { operation: NM6502::op_bvc, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x51] = OperationStruct { operation: NM6502::op_eor, address_method: NM6502::indirect_y_address, ticks: 5 };
-            op_code_lookup[0x52] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x53] = OperationStruct { operation: NM6502::op_sre, address_method: NM6502::indirect_y_address, ticks: 8 };
-            op_code_lookup[0x54] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x55] = OperationStruct { operation: NM6502::op_eor, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x56] = OperationStruct { operation: NM6502::op_lsr, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0x57] = OperationStruct { operation: NM6502::op_sre, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0x58] = OperationStruct { operation: NM6502::op_cli, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x59] = OperationStruct { operation: NM6502::op_eor, address_method: NM6502::absolute_y_address, ticks: 4 };
-            op_code_lookup[0x5a] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x5b] = OperationStruct { operation: NM6502::op_sre, address_method: NM6502::absolute_y_address, ticks: 7 };
-            op_code_lookup[0x5c] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0x5d] = OperationStruct { operation: NM6502::op_eor, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0x5e] = OperationStruct { operation: NM6502::op_lsr, address_method: NM6502::absolute_x_address_no_overflow, ticks: 7 };
-            op_code_lookup[0x5f] = OperationStruct { operation: NM6502::op_sre, address_method: NM6502::absolute_x_address, ticks: 7 };
-
-            op_code_lookup[0x60] = OperationStruct { operation: NM6502::op_rts, address_method: NM6502::null_address, ticks: 6 };
-            op_code_lookup[0x61] = OperationStruct { operation: NM6502::op_adc, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0x62] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x63] = OperationStruct { operation: NM6502::op_rra, address_method: NM6502::indirect_x_address, ticks: 8 };
-            op_code_lookup[0x64] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x65] = OperationStruct { operation: NM6502::op_adc, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x66] = OperationStruct { operation: NM6502::op_ror, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0x67] = OperationStruct { operation: NM6502::op_rra, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0x68] = OperationStruct { operation: NM6502::op_pla, address_method: NM6502::null_address, ticks: 4 };
-            op_code_lookup[0x69] = OperationStruct { operation: NM6502::op_adc, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x6a] = OperationStruct { operation: NM6502::op_ror, address_method: NM6502::accumulator_address, ticks: 2 };
-            op_code_lookup[0x6b] = OperationStruct { operation: NM6502::op_arr, address_method: NM6502::immediate_address, ticks: 7 };
-            op_code_lookup[0x6c] = OperationStruct { operation: NM6502::op_jmp, address_method: NM6502::indirect_address, ticks: 5 };
-            op_code_lookup[0x6d] = OperationStruct { operation: NM6502::op_adc, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x6e] = OperationStruct { operation: NM6502::op_ror, address_method: NM6502::absolute_address, ticks: 6 };
-            op_code_lookup[0x6f] = OperationStruct { operation: NM6502::op_rra, address_method: NM6502::absolute_address, ticks: 6 };
-
-            op_code_lookup[0x70] = OperationStruct { operation: NM6502::op_bvs, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x71] = OperationStruct { operation: NM6502::op_adc, address_method: NM6502::indirect_y_address, ticks: 5 };
-            op_code_lookup[0x72] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x73] = OperationStruct { operation: NM6502::op_rra, address_method: NM6502::indirect_y_address, ticks: 8 };
-            op_code_lookup[0x74] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_x_address, ticks: 3 };
-            op_code_lookup[0x75] = OperationStruct { operation: NM6502::op_adc, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x76] = OperationStruct { operation: NM6502::op_ror, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0x77] = OperationStruct { operation: NM6502::op_rra, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0x78] = OperationStruct { operation: NM6502::op_sei, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x79] = OperationStruct { operation: NM6502::op_adc, address_method: NM6502::absolute_y_address, ticks: 4 };
-            op_code_lookup[0x7a] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x7b] = OperationStruct { operation: NM6502::op_rra, address_method: NM6502::absolute_y_address, ticks: 7 };
-            op_code_lookup[0x7c] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0x7d] = OperationStruct { operation: NM6502::op_adc, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0x7e] = OperationStruct { operation: NM6502::op_ror, address_method: NM6502::absolute_x_address_no_overflow, ticks: 7 };
-            op_code_lookup[0x7f] = OperationStruct { operation: NM6502::op_rra, address_method: NM6502::absolute_x_address, ticks: 7 };
-
-            op_code_lookup[0x80] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x81] = OperationStruct { operation: NM6502::op_sta, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0x82] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x83] = OperationStruct { operation: NM6502::op_sax, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0x84] = OperationStruct { operation: NM6502::op_sty, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x85] = OperationStruct { operation: NM6502::op_sta, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x86] = OperationStruct { operation: NM6502::op_stx, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x87] = OperationStruct { operation: NM6502::op_sax, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0x88] = OperationStruct { operation: NM6502::op_dey, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x89] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x8a] = OperationStruct { operation: NM6502::op_txa, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x8b] = OperationStruct { operation: NM6502::op_ane, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x8c] = OperationStruct { operation: NM6502::op_sty, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x8d] = OperationStruct { operation: NM6502::op_sta, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x8e] = OperationStruct { operation: NM6502::op_stx, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0x8f] = OperationStruct { operation: NM6502::op_sax, address_method: NM6502::absolute_address, ticks: 4 };
-
-            op_code_lookup[0x90] = OperationStruct { operation: NM6502::op_bcc, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0x91] = OperationStruct { operation: NM6502::op_sta, address_method: NM6502::indirect_y_address_no_overflow, ticks: 6 };
-            op_code_lookup[0x92] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0x93] = OperationStruct { operation: NM6502::op_sha, address_method: NM6502::indirect_y_address, ticks: 7 };
-            op_code_lookup[0x94] = OperationStruct { operation: NM6502::op_sty, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x95] = OperationStruct { operation: NM6502::op_sta, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0x96] = OperationStruct { operation: NM6502::op_stx, address_method: NM6502::zero_y_address, ticks: 4 };
-            op_code_lookup[0x97] = OperationStruct { operation: NM6502::op_sax, address_method: NM6502::zero_y_address, ticks: 4 };
-            op_code_lookup[0x98] = OperationStruct { operation: NM6502::op_tya, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x99] = OperationStruct { operation: NM6502::op_sta, address_method: NM6502::absolute_y_address_no_overflow, ticks: 5 };
-            op_code_lookup[0x9a] = OperationStruct { operation: NM6502::op_txs, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0x9b] = OperationStruct { operation: NM6502::op_shs, address_method: NM6502::absolute_y_address, ticks: 7 };
-            op_code_lookup[0x9c] = OperationStruct { operation: NM6502::op_shy, address_method: NM6502::absolute_x_address, ticks: 7 };
-            op_code_lookup[0x9d] = OperationStruct { operation: NM6502::op_sta, address_method: NM6502::absolute_x_address_no_overflow, ticks: 5 };
-            op_code_lookup[0x9e] = OperationStruct { operation: NM6502::op_shx, address_method: NM6502::absolute_y_address, ticks: 7 };
-            op_code_lookup[0x9f] = OperationStruct { operation: NM6502::op_sha, address_method: NM6502::absolute_y_address, ticks: 7 };
-              
-            op_code_lookup[0xa0] = OperationStruct { operation: NM6502::op_ldy, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xa1] = OperationStruct { operation: NM6502::op_lda, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0xa2] = OperationStruct { operation: NM6502::op_ldx, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xa3] = OperationStruct { operation: NM6502::op_lax, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0xa4] = OperationStruct { operation: NM6502::op_ldy, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0xa5] = OperationStruct { operation: NM6502::op_lda, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0xa6] = OperationStruct { operation: NM6502::op_ldx, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0xa7] = OperationStruct { operation: NM6502::op_lax, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0xa8] = OperationStruct { operation: NM6502::op_tay, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xa9] = OperationStruct { operation: NM6502::op_lda, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xaa] = OperationStruct { operation: NM6502::op_tax, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xab] = OperationStruct { operation: NM6502::op_lxa, address_method: NM6502::immediate_address, ticks: 7 };
-            op_code_lookup[0xac] = OperationStruct { operation: NM6502::op_ldy, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0xad] = OperationStruct { operation: NM6502::op_lda, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0xae] = OperationStruct { operation: NM6502::op_ldx, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0xaf] = OperationStruct { operation: NM6502::op_lax, address_method: NM6502::absolute_address, ticks: 4 };
-
-            op_code_lookup[0xb0] = OperationStruct { operation: NM6502::op_bcs, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xb1] = OperationStruct { operation: NM6502::op_lda, address_method: NM6502::indirect_y_address, ticks: 5 };
-            op_code_lookup[0xb2] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0xb3] = OperationStruct { operation: NM6502::op_lax, address_method: NM6502::indirect_y_address, ticks: 5 };
-            op_code_lookup[0xb4] = OperationStruct { operation: NM6502::op_ldy, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0xb5] = OperationStruct { operation: NM6502::op_lda, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0xb6] = OperationStruct { operation: NM6502::op_ldx, address_method: NM6502::zero_y_address, ticks: 4 };
-            op_code_lookup[0xb7] = OperationStruct { operation: NM6502::op_lax, address_method: NM6502::zero_y_address, ticks: 4 };
-            op_code_lookup[0xb8] = OperationStruct { operation: NM6502::op_clv, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xb9] = OperationStruct { operation: NM6502::op_lda, address_method: NM6502::absolute_y_address, ticks: 4 };
-            op_code_lookup[0xba] = OperationStruct { operation: NM6502::op_tsx, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xbb] = OperationStruct { operation: NM6502::op_lae, address_method: NM6502::absolute_y_address, ticks: 7 };
-            op_code_lookup[0xbc] = OperationStruct { operation: NM6502::op_ldy, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0xbd] = OperationStruct { operation: NM6502::op_lda, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0xbe] = OperationStruct { operation: NM6502::op_ldx, address_method: NM6502::absolute_y_address, ticks: 4 };
-            op_code_lookup[0xbf] = OperationStruct { operation: NM6502::op_lax, address_method: NM6502::absolute_y_address, ticks: 4 };
-
-            op_code_lookup[0xc0] = OperationStruct { operation: NM6502::op_cpy, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xc1] = OperationStruct { operation: NM6502::op_cmp, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0xc2] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xc3] = OperationStruct { operation: NM6502::op_dcp, address_method: NM6502::indirect_x_address, ticks: 8 };
-            op_code_lookup[0xc4] = OperationStruct { operation: NM6502::op_cpy, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0xc5] = OperationStruct { operation: NM6502::op_cmp, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0xc6] = OperationStruct { operation: NM6502::op_dec, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0xc7] = OperationStruct { operation: NM6502::op_dcp, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0xc8] = OperationStruct { operation: NM6502::op_iny, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xc9] = OperationStruct { operation: NM6502::op_cmp, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xca] = OperationStruct { operation: NM6502::op_dex, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xcb] = OperationStruct { operation: NM6502::op_axs, address_method: NM6502::immediate_address, ticks: 7 };
-            op_code_lookup[0xcc] = OperationStruct { operation: NM6502::op_cpy, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0xcd] = OperationStruct { operation: NM6502::op_cmp, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0xce] = OperationStruct { operation: NM6502::op_dec, address_method: NM6502::absolute_address, ticks: 3 };
-            op_code_lookup[0xcf] = OperationStruct { operation: NM6502::op_dcp, address_method: NM6502::absolute_address, ticks: 6 };
-
-            op_code_lookup[0xd0] = OperationStruct { operation: NM6502::op_bne, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xd1] = OperationStruct { operation: NM6502::op_cmp, address_method: NM6502::indirect_y_address, ticks: 5 };
-            op_code_lookup[0xd2] = OperationStruct { operation: NM6502::op_dcp, address_method: NM6502::indirect_y_address, ticks: 8 };
-            op_code_lookup[0xd3] = OperationStruct { operation: NM6502::op_dcp, address_method: NM6502::indirect_y_address, ticks: 7 };
-            op_code_lookup[0xd4] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_x_address, ticks: 3 };
-            op_code_lookup[0xd5] = OperationStruct { operation: NM6502::op_cmp, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0xd6] = OperationStruct { operation: NM6502::op_dec, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0xd7] = OperationStruct { operation: NM6502::op_dcp, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0xd8] = OperationStruct { operation: NM6502::op_cld, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xd9] = OperationStruct { operation: NM6502::op_cmp, address_method: NM6502::absolute_y_address, ticks: 4 };
-            op_code_lookup[0xda] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xdb] = OperationStruct { operation: NM6502::op_dcp, address_method: NM6502::absolute_y_address, ticks: 7 };
-            op_code_lookup[0xdc] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0xdd] = OperationStruct { operation: NM6502::op_cmp, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0xde] = OperationStruct { operation: NM6502::op_dec, address_method: NM6502::absolute_x_address_no_overflow, ticks: 7 };
-            op_code_lookup[0xdf] = OperationStruct { operation: NM6502::op_dcp, address_method: NM6502::absolute_x_address, ticks: 7 };
-
-            op_code_lookup[0xe0] = OperationStruct { operation: NM6502::op_cpx, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xe1] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::indirect_x_address, ticks: 6 };
-            op_code_lookup[0xe2] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xe3] = OperationStruct { operation: NM6502::op_isc, address_method: NM6502::indirect_x_address, ticks: 8 };
-            op_code_lookup[0xe4] = OperationStruct { operation: NM6502::op_cpx, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0xe5] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::zero_address, ticks: 3 };
-            op_code_lookup[0xe6] = OperationStruct { operation: NM6502::op_inc, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0xe7] = OperationStruct { operation: NM6502::op_isc, address_method: NM6502::zero_address, ticks: 5 };
-            op_code_lookup[0xe8] = OperationStruct { operation: NM6502::op_inx, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xe9] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xea] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xeb] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::immediate_address, ticks: 3 };
-            op_code_lookup[0xec] = OperationStruct { operation: NM6502::op_cpx, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0xed] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0xee] = OperationStruct { operation: NM6502::op_inc, address_method: NM6502::absolute_address, ticks: 6 };
-            op_code_lookup[0xef] = OperationStruct { operation: NM6502::op_isc, address_method: NM6502::absolute_address, ticks: 6 };
-
-            op_code_lookup[0xf0] = OperationStruct { operation: NM6502::op_beq, address_method: NM6502::immediate_address, ticks: 2 };
-            op_code_lookup[0xf1] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::indirect_y_address, ticks: 5 };
-            op_code_lookup[0xf2] = OperationStruct { operation: NM6502::op_brk, address_method: NM6502::null_address, ticks: 7 };
-            op_code_lookup[0xf3] = OperationStruct { operation: NM6502::op_isc, address_method: NM6502::indirect_y_address_no_overflow, ticks: 4 };
-            op_code_lookup[0xf4] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::zero_x_address, ticks: 3 };
-            op_code_lookup[0xf5] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::zero_x_address, ticks: 4 };
-            op_code_lookup[0xf6] = OperationStruct { operation: NM6502::op_inc, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0xf7] = OperationStruct { operation: NM6502::op_isc, address_method: NM6502::zero_x_address, ticks: 6 };
-            op_code_lookup[0xf8] = OperationStruct { operation: NM6502::op_sed, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xf9] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::absolute_address, ticks: 4 };
-            op_code_lookup[0xfa] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::null_address, ticks: 2 };
-            op_code_lookup[0xfb] = OperationStruct { operation: NM6502::op_isc, address_method: NM6502::absolute_y_address_no_overflow, ticks: 7 };
-            op_code_lookup[0xfc] = OperationStruct { operation: NM6502::op_nop, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0xfd] = OperationStruct { operation: NM6502::op_sbc, address_method: NM6502::absolute_x_address, ticks: 4 };
-            op_code_lookup[0xfe] = OperationStruct { operation: NM6502::op_inc, address_method: NM6502::absolute_x_address_no_overflow, ticks: 7 };
-            op_code_lookup[0xff] = OperationStruct { operation: NM6502::op_isc, address_method: NM6502::absolute_x_address_no_overflow, ticks: 7 };
-
-            op_code_lookup
-        }
-
-        fn call_op_method(&mut self, op: fn(&mut NM6502<T>, fn(&mut NM6502<T>)), address_method: fn(&mut NM6502<T>)) {
-            op(self, address_method);
-        }
-
-        pub fn get_next_operation(&mut self) {
-            self.instruction = self.memory.cpu_read(self.program_counter);
-            self.operation = self.op_code_lookup[self.memory.cpu_read(self.program_counter) as usize];
-            self.program_counter = self.program_counter.overflowing_add(1).0;
-
-            self.overflow_ticks += self.operation.ticks as i32;
-            self.set_overflow_for_operation();
-        }
-
-        fn set_overflow_for_operation(&mut self)
-        {
-            // If branch operaion takes a branch it causes extra tick
-            if (fn_addr_eq(self.operation.operation, NM6502::op_bcc as fn(&mut NM6502<T>, fn(&mut NM6502<T>))) && self.get_status_flag(CARRY_FLAG) == 0) 
-                || (fn_addr_eq(self.operation.operation, NM6502::op_bcs as fn(&mut NM6502<T>, fn(&mut NM6502<T>))) && self.get_status_flag(CARRY_FLAG) != 0) 
-                || (fn_addr_eq(self.operation.operation, NM6502::op_beq as fn(&mut NM6502<T>, fn(&mut NM6502<T>))) && self.get_status_flag(ZERO_FLAG) != 0) 
-                || (fn_addr_eq(self.operation.operation, NM6502::op_bmi as fn(&mut NM6502<T>, fn(&mut NM6502<T>))) && self.get_status_flag(NEGATIVE_FLAG) != 0) 
-                || (fn_addr_eq(self.operation.operation, NM6502::op_bne as fn(&mut NM6502<T>, fn(&mut NM6502<T>))) && self.get_status_flag(ZERO_FLAG) == 0) 
-                || (fn_addr_eq(self.operation.operation, NM6502::op_bpl as fn(&mut NM6502<T>, fn(&mut NM6502<T>))) && self.get_status_flag(NEGATIVE_FLAG) == 0) 
-                || (fn_addr_eq(self.operation.operation, NM6502::op_bvc as fn(&mut NM6502<T>, fn(&mut NM6502<T>))) && self.get_status_flag(OVERFLOW_FLAG) == 0) 
-                || (fn_addr_eq(self.operation.operation, NM6502::op_bvs as fn(&mut NM6502<T>, fn(&mut NM6502<T>))) && self.get_status_flag(OVERFLOW_FLAG) != 0) {
-                self.overflow_ticks += 1;
-                let address_met = self.operation.address_method;
-                address_met(self);
-                let diff = self.program_counter.abs_diff(self.address_bus.address());
-                if (diff & 0xFF00) > 0 {
-                    self.overflow_ticks += 1;
-                }
-                self.program_counter -= 1;
-            }
-        }
-        
-        fn push_stack(&mut self, byte: u8) {
-            if self.stack_pointer == 0 {
-                eprintln!("Stack overflow");
-            }
-            self.memory.cpu_write(self.stack_pointer + self.stack_pointer_page, byte);
-            self.stack_pointer -= 1;
-        }
-
-        fn pop_stack(&mut self) -> u8 {
-            if self.stack_pointer > 255 {
-                eprintln!("Stack underflow");
-            }
-            self.stack_pointer += 1;
-            self.memory.cpu_read(self.stack_pointer + self.stack_pointer_page)
-        }
-
-        fn null_address(&mut self) {
-            self.address_bus.loadl = 0;
-            self.address_bus.loadh = 0;
-        }
-
-        fn accumulator_address(&mut self) {
-            self.address_bus.loadl = self.accumulator;
-            self.address_bus.loadh = 0;
-        }
-
-        fn immediate_address(&mut self) {
-            self.address_bus.loadl = (self.program_counter & 0x00FF) as u8;
-            self.address_bus.loadh = (self.program_counter >> 8) as u8;
-            self.program_counter += 1;
-        }
-        
-        fn zero_address(&mut self) {
-            let address: u16 = self.memory.cpu_read(self.program_counter) as u16 & 0xFF;
-            self.address_bus.loadl = (address & 0x00FF) as u8;
-            self.address_bus.loadh = (address >> 8) as u8;
-            self.program_counter += 1;
-        }
-        
-        fn zero_x_address(&mut self) {
-            let address: u16 = self.memory.cpu_read(self.program_counter) as u16;
-            self.address_bus.loadl = ((address + self.register_x as u16) & 0x00FF) as u8;
-            self.address_bus.loadh = (address >> 8) as u8;
-            self.program_counter += 1;
-        }
-
-        fn zero_y_address(&mut self) {
-            let address: u16 = self.memory.cpu_read(self.program_counter) as u16;
-            self.address_bus.loadl = ((address + self.register_y as u16) & 0x00FF) as u8;
-            self.address_bus.loadh = (address >> 8) as u8;
-            self.program_counter += 1;
-        }
-
-        fn absolute_address(&mut self) {
-            self.address_bus.loadl = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-            self.address_bus.loadh = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-        }
-
-        fn absolute_x_address(&mut self) {
-            self.address_bus.loadl = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-            self.address_bus.loadh = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-
-            let increment = self.address_bus.loadl as u16 + self.register_x as u16;
-            if increment > 0x00FF {
-                let dummy = ((self.address_bus.loadh as u16) << 8) + (increment & 0xff);
-                self.overflow_ticks += 1;
-                self.memory.cpu_read(dummy);
-            }
-
-            self.address_bus.loadl = self.address_bus.loadl.overflowing_add(self.register_x).0;
-            self.address_bus.loadh += (increment >> 8) as u8;
-        }
-
-        fn absolute_x_address_no_overflow(&mut self) {
-            self.absolute_x_address();
-        }
-
-        fn absolute_y_address(&mut self) {
-            self.address_bus.loadl = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-
-            let increment = self.address_bus.loadl as u16 + self.register_y as u16;
-            if increment > 0x00FF {
-                let dummy = ((self.address_bus.loadh as u16) << 8) + (increment & 0xff);
-                self.overflow_ticks += 1;
-                self.memory.cpu_read(dummy);
-            }
-
-            self.address_bus.loadh = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-            self.address_bus.loadl = self.address_bus.loadl.overflowing_add(self.register_y).0;
-            self.address_bus.loadh += (increment >> 8) as u8;
-        }
-
-        fn absolute_y_address_no_overflow(&mut self) {
-            self.absolute_y_address();
-        }
-
-        fn indirect_address(&mut self) {
-            let loadl: u8 = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-            let loadh: u8 = self.memory.cpu_read(self.program_counter);
-            let mut load: u16 = ((loadh as u16) << 8) + loadl as u16;
-            // Bug in NM6502 if low nibble is end of page
-            if loadl == 0x00ff {
-                self.address_bus.loadl = self.memory.cpu_read(load);
-                self.address_bus.loadh = self.memory.cpu_read(load & 0xff00);
-            }
-            else {
-                self.address_bus.loadl = self.memory.cpu_read(load);
-                load += 1;
-                self.address_bus.loadh = self.memory.cpu_read(load);
-            }
-        }
-
-        fn indirect_x_address(&mut self) {
-            let mut indirect: u16 = self.memory.cpu_read(self.program_counter) as u16 + self.register_x as u16;
-            self.program_counter += 1;
-            self.address_bus.loadl = self.memory.cpu_read(indirect & 0xff);
-            indirect += 1;
-            self.address_bus.loadh = self.memory.cpu_read(indirect & 0xff);
-        }
-
-        fn indirect_y_address(&mut self) {
-            let mut indirect: u16 = self.memory.cpu_read(self.program_counter) as u16;
-            self.program_counter += 1;
-            self.address_bus.loadl = self.memory.cpu_read(indirect & 0xff);
-            indirect += 1;
-            self.address_bus.loadh = self.memory.cpu_read(indirect & 0xff);
-
-            let increment = self.address_bus.loadl as u16 + self.register_y as u16;
-            if increment > 0x00FF {
-                let dummy = ((self.address_bus.loadh as u16) << 8) + (increment & 0xff);
-                self.overflow_ticks += 1;
-                self.memory.cpu_read(dummy);
-            }
-
-            self.address_bus.loadl = self.address_bus.loadl.overflowing_add(self.register_y).0;
-            self.address_bus.loadh += (increment >> 8) as u8;
-        }
-
-        fn indirect_y_address_no_overflow(&mut self) {
-            self.indirect_y_address();
-        }
-
-        pub fn get_accumulator(&self) -> u8 {
-            self.accumulator
-        }
-
-        pub fn get_register_x(&self) -> u8 {
-            self.register_x
-        }
-
-        pub fn get_register_y(&self) -> u8 {
-            self.register_y
-        }
-
-        pub fn get_status_register(&self) -> u8 {
-            self.status_register
-        }
-
-        fn get_status_flag(&self, flag: u8) -> u8 {
-            self.status_register & flag
-        }
-
-        fn set_status_flag(&mut self, flag: u8, set_clear: bool) {
-            if set_clear {
-                self.status_register |= flag;
-            }
-            else {
-                self.status_register &= !flag;
-            }
-        }
-
-        fn set_negative(&mut self, byte: u8) {
-            self.set_status_flag(NEGATIVE_FLAG, (byte & 0x80) != 0);
-        }
-
-        fn set_zero(&mut self, byte: u8) {
-            self.set_status_flag(ZERO_FLAG, byte == 0);
-        }
-
-        fn set_negative_zero(&mut self, byte: u8) {
-            self.set_negative(byte);
-            self.set_zero(byte);
-        }
-
-        // NES specific operations
-        fn op_rla(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let mut byte: u8 = self.memory.cpu_read(self.address_bus.address());
-
-            let mut carry: u8 = 0;
-            if self.get_status_flag(CARRY_FLAG) != 0 {
-                carry = 0x01;
-            }
-            self.set_status_flag(CARRY_FLAG, (byte & 0x80) != 0);
-
-            let temp: u8 = (byte << 1) | carry;
-            byte = temp;
-
-            self.memory.cpu_write(self.address_bus.address(), byte);
-            self.accumulator &= byte;
-            self.set_negative_zero(byte);
-        }
-
-        fn op_sre(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let mut byte: u8 = self.memory.cpu_read(self.address_bus.address());
-
-            self.set_status_flag(CARRY_FLAG, (byte & 0x01) != 0);
-
-            byte >>= 1;
-
-            self.memory.cpu_write(self.address_bus.address(), byte);
-            self.accumulator ^= byte;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_rra(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-
-            // ROR
-            let mut byte: u8 = self.memory.cpu_read(self.address_bus.address());
-
-            let mut carry: u8 = 0;
-            if self.get_status_flag(CARRY_FLAG) != 0 {
-                carry = 0x80;
-            }
-            self.set_status_flag(CARRY_FLAG, (byte & 0x01) != 0);
-
-            byte = (byte >> 1) | carry;
-
-            self.memory.cpu_write(self.address_bus.address(), byte);
-
-            // ADC
-            carry = 0;
-            if self.get_status_flag(CARRY_FLAG) != 0 {
-                carry = 0x01;
-            }
-
-            let(value1, overflow1) = self.accumulator.overflowing_add(byte);
-            let(value2, overflow2) = value1.overflowing_add(carry);
-            let value = value2;
-            self.set_status_flag(OVERFLOW_FLAG, ((self.accumulator ^ value) & (byte ^ value) & 0x080) == 0x80);
-            self.set_status_flag(CARRY_FLAG, overflow1 || overflow2);
-            self.accumulator = value;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_lax(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            self.accumulator = self.memory.cpu_read(self.address_bus.address());
-            self.register_x = self.accumulator;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_dcp(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let mut byte = self.memory.cpu_read(self.address_bus.address());
-            byte = byte.wrapping_sub(1);
-            self.memory.cpu_write(self.address_bus.address(), byte);
-            self.set_status_flag(CARRY_FLAG, self.accumulator >= byte);
-            self.set_status_flag(ZERO_FLAG, self.accumulator == byte);
-            let (negzerocheck, _overflow) = self.accumulator.overflowing_sub(byte);
-            self.set_status_flag(NEGATIVE_FLAG, negzerocheck & 0x80 != 0);
-        }
-
-        // Load Store operations
-        fn op_lda(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            self.accumulator = self.memory.cpu_read(self.address_bus.address());
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_ldx(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            self.register_x = self.memory.cpu_read(self.address_bus.address());
-            self.set_negative_zero(self.register_x);
-        }
-
-        fn op_ldy(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            self.register_y = self.memory.cpu_read(self.address_bus.address());
-            self.set_negative_zero(self.register_y);
-        }
-
-        fn op_sta(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            if fn_addr_eq(address_method, NM6502::<T>::absolute_x_address_no_overflow as fn(&mut NM6502<T>)) ||
-               fn_addr_eq(address_method, NM6502::<T>::absolute_x_address as fn(&mut NM6502<T>)) {
-                self.memory.cpu_read(self.address_bus.address());
-            }
-            self.memory.cpu_write(self.address_bus.address(), self.accumulator);
-        }   
-        
-        fn op_stx(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            self.memory.cpu_write(self.address_bus.address(), self.register_x);
-        }
-
-        fn op_sty(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            self.memory.cpu_write(self.address_bus.address(), self.register_y);
-        }
-
-        // Register transfers
-        fn op_tax(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.register_x = self.accumulator;
-            self.set_negative_zero(self.register_x);
-        }
-
-        fn op_tay(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.register_y = self.accumulator;
-            self.set_negative_zero(self.register_y);
-        }
-
-        fn op_txa(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.accumulator = self.register_x;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_tya(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.accumulator = self.register_y;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        // Stack operaions
-        fn op_tsx(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.register_x = self.stack_pointer as u8;
-            self.set_negative_zero(self.register_x);
-        }
-
-        fn op_txs(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.stack_pointer = self.register_x as u16;
-        }
-
-        fn op_pha(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.push_stack(self.accumulator);
-        }
-
-        fn op_php(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            if !self.in_interrupt {
-                self.set_status_flag(BREAK_COMMAND, true);
-            }
-            self.set_status_flag(IGNORED, true);
-            self.push_stack(self.status_register);
-            if !self.in_interrupt {
-                self.set_status_flag(BREAK_COMMAND, false);
-            }
-            self.set_status_flag(IGNORED, false);
-        }
-
-        fn op_pla(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.accumulator = self.pop_stack();
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_plp(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.status_register = self.pop_stack();
-        }
-
-        // Logical operations
-        fn op_and(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.accumulator &= byte;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_eor(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.accumulator ^= byte;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_ora(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.accumulator |= byte;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_bit(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.set_status_flag(OVERFLOW_FLAG, (byte & 0x40) != 0);
-            self.set_negative(byte);
-            self.set_zero(byte & self.accumulator);
-        }
-
-        fn op_adc(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            let mut value: u8;
-
-            let mut carry: u8 = 0;
-            if self.get_status_flag(CARRY_FLAG) != 0 {
-                carry = 1;
-            }
-
-            let(value1, overflow1) = self.accumulator.overflowing_add(byte);
-            let(value2, overflow2) = value1.overflowing_add(carry);
-            value = value2;
-
-            if self.get_status_flag(DECIMAL_MODE) != 0 && !self.dec_disabled {
-                if ((self.accumulator ^ byte ^ value) & 0x10) == 0x10 {
-                    value += 0x06;
-                }
-                if (value & 0xf0) > 0x90 {
-                    value += 0x60;
-                }
-            }
-            self.set_status_flag(OVERFLOW_FLAG, ((self.accumulator ^ value) & (byte ^ value) & 0x080) == 0x80);
-            self.set_status_flag(CARRY_FLAG, overflow1 || overflow2);
-            self.set_negative_zero(value);
-            self.accumulator = value;
-        }
-
-        // Same as ADC, except switch input byte to 1s-Compliment
-        fn op_sbc(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = !self.memory.cpu_read(self.address_bus.address());
-            let mut value: u8;
-
-            let mut carry: u8 = 0;
-            if self.get_status_flag(CARRY_FLAG) != 0 {
-                carry = 1;
-            }
-
-            let(value1, overflow1) = self.accumulator.overflowing_add(byte);
-            let(value2, overflow2) = value1.overflowing_add(carry);
-            value = value2;
-
-            if self.get_status_flag(DECIMAL_MODE) != 0 && !self.dec_disabled {
-                if ((self.accumulator ^ byte ^ value) & 0x10) == 0x10 {
-                    value += 0x06;
-                }
-                if (value & 0xf0) > 0x90 {
-                    value += 0x60;
-                }
-            }
-            self.set_status_flag(OVERFLOW_FLAG, ((self.accumulator ^ value) & (byte ^ value) & 0x080) == 0x80);
-            self.set_status_flag(CARRY_FLAG, overflow1 || overflow2);
-            self.set_negative_zero(value);
-            self.accumulator = value;
-        }
-
-        fn op_cmp(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.set_status_flag(CARRY_FLAG, self.accumulator >= byte);
-            self.set_status_flag(ZERO_FLAG, self.accumulator == byte);
-            let (negzerocheck, _overflow) = self.accumulator.overflowing_sub(byte);
-            self.set_status_flag(NEGATIVE_FLAG, negzerocheck & 0x80 != 0);
-        }
-
-        fn op_cpx(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.set_status_flag(CARRY_FLAG, self.register_x >= byte);
-            self.set_status_flag(ZERO_FLAG, self.register_x == byte);
-            let (negzerocheck, _overflow) = self.register_x.overflowing_sub(byte);
-            self.set_status_flag(NEGATIVE_FLAG, negzerocheck & 0x80 != 0);
-        }
-
-        fn op_cpy(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.set_status_flag(CARRY_FLAG, self.register_y >= byte);
-            self.set_status_flag(ZERO_FLAG, self.register_y == byte);
-            let (negzerocheck, _overflow) = self.register_y.overflowing_sub(byte);
-            self.set_status_flag(NEGATIVE_FLAG, negzerocheck & 0x80 != 0);
-        }
-
-        // Increment and decrement operations
-        fn op_inc(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.memory.cpu_write(self.address_bus.address(), byte);
-            let(byte2, _overflow) = byte.overflowing_add(1);
-            self.memory.cpu_write(self.address_bus.address(), byte2);
-            self.set_negative_zero(byte2);
-        }
-
-        fn op_inx(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            let (value, _overflow) = self.register_x.overflowing_add(1);
-            self.register_x = value;
-            self.set_negative_zero(self.register_x);
-        }
-
-        fn op_iny(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            let (value, _overflow) = self.register_y.overflowing_add(1);
-            self.register_y = value;
-            self.set_negative_zero(self.register_y);
-        }
-
-        fn op_dec(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.memory.cpu_write(self.address_bus.address(), byte);
-            let (byte, _overflow) = byte.overflowing_sub(1);
-            self.memory.cpu_write(self.address_bus.address(), byte);
-            self.set_negative_zero(byte);
-        }
-
-        fn op_dex(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            let (result, _overflow) = self.register_x.overflowing_sub(1);
-            self.register_x = result;
-            self.set_negative_zero(self.register_x);
-        }
-
-        fn op_dey(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            let (new_y, _overflow) = self.register_y.overflowing_sub(1);
-            self.register_y = new_y;
-            self.set_negative_zero(self.register_y);
-        }
-
-        // Shift operations
-        fn op_asl(&mut self, address_method: fn(&mut NM6502<T>)) {
-            let mut byte: u8;
-
-            if fn_addr_eq(address_method, NM6502::accumulator_address as fn(&mut NM6502<T>)) {
-                byte = self.accumulator;
-            }
-            else {
-                address_method(self);
-                byte = self.memory.cpu_read(self.address_bus.address());
-                self.memory.cpu_write(self.address_bus.address(), byte);
-            }
-            self.set_status_flag(CARRY_FLAG, (byte & 0x80) != 0);
-            byte = byte << 1;
-            self.set_negative_zero(byte);
-            if fn_addr_eq(address_method, NM6502::accumulator_address as fn(&mut NM6502<T>)) {
-                self.accumulator = byte;
-            }
-            else {
-                self.memory.cpu_write(self.address_bus.address(), byte);
-            }
-        }    
-
-        fn op_lsr(&mut self, address_method: fn(&mut NM6502<T>)) {
-            let mut byte: u8;
-
-            if fn_addr_eq(address_method, NM6502::accumulator_address as fn(&mut NM6502<T>)) {
-                address_method(self);
-                byte = self.address_bus.loadl as u8;
-            }
-            else {
-                address_method(self);
-                byte = self.memory.cpu_read(self.address_bus.address());
-                self.memory.cpu_write(self.address_bus.address(), byte);
-            }
-            self.set_status_flag(CARRY_FLAG, (byte & 0x01) != 0);
-            byte = byte >> 1;
-            self.set_negative_zero(byte);
-            if fn_addr_eq(address_method, NM6502::accumulator_address as fn(&mut NM6502<T>)) {
-                self.accumulator = byte;
-            }
-            else {
-                self.memory.cpu_write(self.address_bus.address(), byte);
-            }
-        }
-
-        fn op_rol(&mut self, address_method: fn(&mut NM6502<T>)) {
-            let mut byte: u8;
-
-            let mut carry: u8 = 0;
-            if self.get_status_flag(CARRY_FLAG) != 0 {
-                carry = 0x01;
-            }
-
-            if fn_addr_eq(address_method, NM6502::accumulator_address as fn(&mut NM6502<T>)) {
-                address_method(self);
-                byte = self.address_bus.loadl;
-            }
-            else {
-                address_method(self);
-                byte = self.memory.cpu_read(self.address_bus.address());
-                self.memory.cpu_write(self.address_bus.address(), byte);
-            }
-            self.set_status_flag(CARRY_FLAG, (byte & 0x80) != 0);
-            let temp: u8 = (byte << 1) | carry;
-            byte = temp;
-            self.set_negative_zero(byte);
-            if fn_addr_eq(address_method, NM6502::accumulator_address as fn(&mut NM6502<T>)) {
-                self.accumulator  = byte;
-            }
-            else {
-                self.memory.cpu_write(self.address_bus.address(), byte);
-            }
-        }
-
-        fn op_ror(&mut self, address_method: fn(&mut NM6502<T>)) {
-            let mut byte: u8;
-
-            let mut carry: u8 = 0;
-            if self.get_status_flag(CARRY_FLAG) != 0 {
-                carry = 0x80;
-            }
-
-            if fn_addr_eq(address_method, NM6502::accumulator_address as fn(&mut NM6502<T>)) {
-                address_method(self);
-                byte = self.address_bus.loadl;
-            }
-            else {
-                address_method(self);
-                byte = self.memory.cpu_read(self.address_bus.address());
-                self.memory.cpu_write(self.address_bus.address(), byte);
-            }
-            self.set_status_flag(CARRY_FLAG, byte & 0x01 != 0);
-            let temp: u8 = (byte >> 1) | carry;
-            byte = temp;
-            self.set_negative_zero(byte);
-            if fn_addr_eq(address_method, NM6502::accumulator_address as fn(&mut NM6502<T>)) {
-                self.accumulator  = byte;
-            }
-            else
-            {
-                self.memory.cpu_write(self.address_bus.address(), byte);
-            }
-        }
-
-        // Jumps and Call operations
-        fn op_jmp(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            self.program_counter = self.address_bus.address();
-        }
-
-        fn op_jsr(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            // Address method is always absolute, 
-            // but JSR pushes stack between 2 reads.
-            self.address_bus.loadl = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-
-            self.push_stack((((self.program_counter) & 0xff00) >> 8) as u8);
-            self.push_stack(((self.program_counter) & 0x00ff) as u8);
-   
-            self.address_bus.loadh = self.memory.cpu_read(self.program_counter);
-            self.program_counter += 1;
-            let jump_address: u16 = self.address_bus.address();
-
-            self.program_counter = jump_address;
-        }
-
-        fn op_rts(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.address_bus.loadl = self.pop_stack();
-            self.address_bus.loadh = self.pop_stack();
-            self.program_counter = self.address_bus.address();
-            self.program_counter += 1;
-        }
-
-        // Branch operations
-        fn op_bcc(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let relative_address: i8 = self.memory.cpu_read(self.address_bus.address()) as i8;
-            if self.get_status_flag(CARRY_FLAG) == 0 {
-                self.program_counter = (self.program_counter as i16 + relative_address as i16) as u16;
-            }
-        }
-
-        fn op_bcs(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let relative_address: i8 = self.memory.cpu_read(self.address_bus.address()) as i8;
-            if self.get_status_flag(CARRY_FLAG) != 0 {
-                self.program_counter = (self.program_counter as i16 + relative_address as i16) as u16;
-            }
-        }
-
-        fn op_beq(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let relative_address: i8 = self.memory.cpu_read(self.address_bus.address()) as i8;
-            if self.get_status_flag(ZERO_FLAG) != 0 {
-                self.program_counter = (self.program_counter as i16 + relative_address as i16) as u16;
-            }
-        }
-        
-        fn op_bmi(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let relative_address: i8 = self.memory.cpu_read(self.address_bus.address()) as i8;
-            if self.get_status_flag(NEGATIVE_FLAG) != 0 {
-                self.program_counter = (self.program_counter as i16 + relative_address as i16) as u16;
-            }
-        }  
-
-        fn op_bne(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let relative_address: i8 = self.memory.cpu_read(self.address_bus.address()) as i8;
-            if self.get_status_flag(ZERO_FLAG) == 0 {
-                self.program_counter = (self.program_counter as i16 + relative_address as i16) as u16;
-            }
-        }
-        
-        fn op_bpl(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let relative_address: i8 = self.memory.cpu_read(self.address_bus.address()) as i8;
-            if self.get_status_flag(NEGATIVE_FLAG) == 0 {
-                self.program_counter = (self.program_counter as i16 + relative_address as i16) as u16;
-            }
-        }
-
-        fn op_bvc(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let relative_address: i8 = self.memory.cpu_read(self.address_bus.address()) as i8;
-            if self.get_status_flag(OVERFLOW_FLAG) == 0 {
-                self.program_counter = (self.program_counter as i16 + relative_address as i16) as u16;
-            }
-        }
-
-        fn op_bvs(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let relative_address: i8 = self.memory.cpu_read(self.address_bus.address()) as i8;
-            if self.get_status_flag(OVERFLOW_FLAG) != 0 {
-                self.program_counter = (self.program_counter as i16 + relative_address as i16) as u16;
-            }
-        }
-
-        // Status Flag operations
-        fn op_clc(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.set_status_flag(CARRY_FLAG, false);
-        }
-
-        fn op_cld(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.set_status_flag(DECIMAL_MODE, false);
-        }
-
-        fn op_cli(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.set_status_flag(INTERRUPT_FLAG, false);
-        }
-
-        fn op_clv(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.set_status_flag(OVERFLOW_FLAG, false);
-        }
-
-        fn op_sec(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.set_status_flag(CARRY_FLAG, true);
-        }
-
-        fn op_sed(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.set_status_flag(DECIMAL_MODE, true);
-        }
-
-        fn op_sei(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.set_status_flag(INTERRUPT_FLAG, true);
-        }
-
-        // System operations
-        fn op_brk(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.set_status_flag(INTERRUPT_FLAG, true);
-            self.program_counter += 1;
-            self.push_stack(((self.program_counter & 0xff00) >> 8) as u8);
-            self.push_stack((self.program_counter & 0xff) as u8);
-            self.op_php(NM6502::null_address);
-            let loadl: u8 = self.memory.cpu_read(0xfffe);
-            let loadh: u8 = self.memory.cpu_read(0xffff);
-            let load: u16 = ((loadh as u16) << 8) + loadl as u16;
-            self.program_counter = load;
-        }
-
-        fn op_nop(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            self.memory.cpu_read(self.address_bus.address());
-        }
-
-        fn op_rti(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            self.status_register = self.pop_stack();
-            self.set_status_flag(BREAK_COMMAND, false);
-            let loadl: u8 = self.pop_stack();
-            let loadh: u8 = self.pop_stack();
-            let load: u16 = ((loadh as u16) << 8) + loadl as u16;
-            self.program_counter = load;
-        }
-
-        // unofficial opcodes
-        fn op_sha(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte = self.accumulator & self.register_x & (self.address_bus.loadh + 1);
-            self.memory.cpu_write(self.address_bus.address(), byte);
-        }
-
-        fn op_anc(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte = self.memory.cpu_read(self.address_bus.address());
-            self.accumulator &= byte;
-            self.set_status_flag(CARRY_FLAG, (self.accumulator & 0x80) != 0);
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_arr(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);            
-        }
-
-        fn op_asr(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);            
-        }
-
-        fn op_axs(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);            
-        }
-
-        fn op_isc(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let mut byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            byte = byte.overflowing_add(1).0;
-            self.memory.cpu_write(self.address_bus.address(), byte);
-            
-            byte = !byte;
-            let value: u16 = self.accumulator as u16 + byte as u16 + self.get_status_flag(CARRY_FLAG) as u16;
-            self.set_status_flag(CARRY_FLAG, value > 255);
-            self.set_status_flag(OVERFLOW_FLAG, (self.accumulator as u16 ^ value) & (byte as u16 ^ value) & 0x80 != 0);
-            self.accumulator = value as u8;
-            self.set_negative_zero(self.accumulator);
-        }
-
-        fn op_lae(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);            
-        }
-
-        fn op_lxa(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);            
-        }
-
-        fn op_sax(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte: u8 = self.accumulator & self.register_x;
-            self.memory.cpu_write(self.address_bus.address(), byte);
-        }
-
-        fn op_shs(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let mut byte = self.accumulator & self.register_x;
-            self.push_stack(byte);
-            byte = self.accumulator & self.register_x & (self.address_bus.loadh + 1);
-            self.memory.cpu_write(self.address_bus.address(), byte);
-        }
-
-        fn op_shx(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte = self.register_x & (self.address_bus.loadh + 1);
-            self.memory.cpu_write(self.address_bus.address(), byte);
-        }
-
-        fn op_shy(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte = self.register_y & (self.address_bus.loadh + 1);
-            self.memory.cpu_write(self.address_bus.address(), byte);
-        }
-
-        fn op_slo(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let mut byte: u8 = self.memory.cpu_read(self.address_bus.address());
-            self.set_status_flag(CARRY_FLAG, (byte & 0x80) != 0);
-            byte <<= 1;
-            self.memory.cpu_write(self.address_bus.address(), byte);
-
-            self.accumulator |= byte;
-            self.set_negative_zero(self.accumulator);
-        }     
-
-        fn op_ane(&mut self, address_method: fn(&mut NM6502<T>)) {
-            address_method(self);
-            let byte = self.memory.cpu_read(self.address_bus.address());
-            self.accumulator &= self.register_x;
-            self.accumulator &= byte;
-            self.set_negative_zero(self.accumulator);
-        }     
-
-        fn op_panic(&mut self, _address_method: fn(&mut NM6502<T>)) {
-            panic!("operation not implemented yet 0x{:x}", self.instruction);
-        }
-
-    }
-
-}
\ No newline at end of file
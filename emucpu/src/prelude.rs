@@ -1,2 +1,6 @@
-
-pub use crate::m6502::emu_cpu::{M6502Runner, M6502Version, AddressBus};
+pub use crate::base_cpu::emu_cpu::{BaseCpu, CpuBus};
+pub use crate::m6502::emu_cpu::{M6502, M6502Runner, M6502Variant, M6502Version, AddressBus};
+pub use crate::m6502_assembler::massembler::{M6502Assembler, M6502AssemblerError, M6502Assembly};
+pub use crate::m6502_debugger::mdebugger::{M6502Break, M6502Condition, M6502Debugger, M6502Step, M6502WatchKind};
+pub use crate::m6502_disassembler::mdisassembler::{M6502Disassembler, M6502Instruction, M6502Symbols};
+pub use crate::m6502_tracer::mtracer::M6502Tracer;
@@ -0,0 +1,34 @@
+#!/bin/sh
+# Downloads the CPU test binaries into emucpu/src/tests/roms, which git ignores.
+# The tests that need them are ignored by default, run them with
+#   emucpu/src/tests/fetch_roms.sh && cargo test -p emucpu -- --ignored --skip test_decimal
+# The decimal test isn't downloaded, see below.
+set -e
+cd "$(dirname "$0")"
+mkdir -p roms
+
+fetch() {
+    [ -s "roms/$2" ] || curl -fsSL -o "roms/$2" "$1"
+}
+
+# Klaus Dormann's suites, GPL-3. The decimal test only ships as source, assemble
+# 6502_decimal_test.a65 with as65 and end_of_test as "jmp *" into roms/6502_decimal_test.bin.
+DORMANN=https://raw.githubusercontent.com/Klaus2m5/6502_65C02_functional_tests/master/bin_files
+fetch $DORMANN/6502_functional_test.bin 6502_functional_test.bin
+fetch $DORMANN/6502_interrupt_test.bin 6502_interrupt_test.bin
+
+# Tom Harte's single step tests, one JSON file per op code
+harte() {
+    mkdir -p "roms/$2"
+    for op_code in $(seq 0 255); do
+        fetch "$1/$(printf %02x "$op_code").json" "$2/$(printf %02x "$op_code").json"
+    done
+}
+harte https://raw.githubusercontent.com/SingleStepTests/ProcessorTests/main/6502/v1 6502
+harte https://raw.githubusercontent.com/SingleStepTests/ProcessorTests/main/nes6502/v1 nes6502
+harte https://raw.githubusercontent.com/SingleStepTests/65x02/main/wdc65c02/v1 wdc65c02
+harte https://raw.githubusercontent.com/SingleStepTests/65x02/main/rockwell65c02/v1 rockwell65c02
+
+# nestest and the log of a run on real hardware
+fetch https://www.qmtpro.com/~nes/misc/nestest.nes nestest.nes
+fetch https://www.qmtpro.com/~nes/misc/nestest.log nestest.log
@@ -0,0 +1,173 @@
+mod m6502_test_bus;
+
+pub mod emu_cpu {
+
+use emucpu::base_cpu::emu_cpu::BaseCpu;
+use emucpu::m6502::emu_cpu::{M6502Runner, M6502Variant};
+use emucpu::m6502_assembler::massembler::{M6502Assembler, M6502AssemblerError, M6502Segment};
+use emucpu::m6502_disassembler::mdisassembler::M6502Disassembler;
+
+use crate::m6502_test_bus::M6502TestBus;
+
+fn assemble(variant: M6502Variant, source: &str) -> Vec<u8> {
+    M6502Assembler::new(variant).assemble(source, 0x0200).unwrap().get_bytes()
+}
+
+#[test]
+fn test_address_modes() {
+    let source = "
+        nop
+        asl
+        lsr a
+        lda #$10
+        lda $80
+        lda $80,x
+        ldx $80,Y
+        lda $0200
+        lda $1234,X
+        lda $1234,y
+        jmp ($fffc)
+        lda ($20,X)
+        lda ($20),y
+        lda (2 + 3) * 4,x
+        bne *";
+
+    assert_eq!(assemble(M6502Variant::Nmos, source), [0xea, 0x0a, 0x4a, 0xa9, 0x10, 0xa5, 0x80, 0xb5, 0x80,
+        0xb6, 0x80, 0xad, 0x00, 0x02, 0xbd, 0x34, 0x12, 0xb9, 0x34, 0x12, 0x6c, 0xfc, 0xff, 0xa1, 0x20,
+        0xb1, 0x20, 0xb5, 0x14, 0xd0, 0xfe]);
+}
+
+#[test]
+fn test_labels_and_branches() {
+    let source = "
+        start:  ldx #0
+        loop:   inx
+                stx counter     ; forward, so absolute
+                bne loop
+                beq done
+                jmp start
+        done:   rts
+        counter = $10
+                stx counter     ; known now, so zero page";
+
+    let assembly = M6502Assembler::new(M6502Variant::Nmos).assemble(source, 0x0200).unwrap();
+    assert_eq!(assembly.get_bytes(), [0xa2, 0x00, 0xe8, 0x8e, 0x10, 0x00, 0xd0, 0xfa, 0xf0, 0x03, 0x4c, 0x00,
+        0x02, 0x60, 0x86, 0x10]);
+    assert_eq!(assembly.get_label("done"), Some(0x020d));
+    assert_eq!(assembly.get_symbols().get_name(0x0202), Some("loop"));
+}
+
+#[test]
+fn test_forward_constants() {
+    // Constants are all known before the second pass, whatever order they're in
+    let source = "
+                lda foo
+                ldx #<(bar - 1) * 2
+        foo = bar + 1
+        bar:    rts";
+
+    assert_eq!(assemble(M6502Variant::Nmos, source), [0xad, 0x06, 0x02, 0xa2, 0x08, 0x60]);
+}
+
+#[test]
+fn test_expressions_and_directives() {
+    let source = "
+        SCREEN equ $2000 + 3 * $10
+                lda #<SCREEN
+                ldx #>SCREEN
+                lda #%1010 | 1
+                lda #'A'
+                lda #(2 + 3) * 4
+                lda #-1
+        table:  .byte 1, $ff, \"Hi\", 'x' - 'a'
+                .word table, * + 2
+                .org $fffc
+                .word $f000";
+
+    let assembly = M6502Assembler::new(M6502Variant::Nmos).assemble(source, 0xf000).unwrap();
+    assert_eq!(assembly.segments, vec![
+        M6502Segment { origin: 0xf000, bytes: vec![0xa9, 0x30, 0xa2, 0x20, 0xa9, 0x0b, 0xa9, 0x41, 0xa9, 0x14, 0xa9,
+            0xff, 0x01, 0xff, b'H', b'i', 23, 0x0c, 0xf0, 0x13, 0xf0] },
+        M6502Segment { origin: 0xfffc, bytes: vec![0x00, 0xf0] },
+    ]);
+}
+
+#[test]
+fn test_undocumented() {
+    let source = "lax $10\nisc $1234,x\nisb ($10),y\nsbc #1\nnop\nnop $10\nslo $10,x\njam";
+
+    assert_eq!(assemble(M6502Variant::Nmos, source), [0xa7, 0x10, 0xff, 0x34, 0x12, 0xf3, 0x10, 0xe9, 0x01, 0xea,
+        0x04, 0x10, 0x17, 0x10, 0x02]);
+}
+
+#[test]
+fn test_cmos() {
+    let source = "bra next\nnext: lda ($10)\njmp ($1000,x)\nstz $0200\nbbr0 $10,next\nsmb7 $20";
+
+    assert_eq!(assemble(M6502Variant::Rockwell65C02, source), [0x80, 0x00, 0xb2, 0x10, 0x7c, 0x00, 0x10, 0x9c,
+        0x00, 0x02, 0x0f, 0x10, 0xf5, 0xf7, 0x20]);
+}
+
+#[test]
+fn test_disassembly_round_trip() {
+    // Every op code assembles back from its disassembly, the operand bytes
+    // keep absolute addresses out of the zero page. Duplicates like the one
+    // byte NOPs come back as the documented op code.
+    for variant in [M6502Variant::Nmos, M6502Variant::Wdc65C02, M6502Variant::Rockwell65C02] {
+        let disassembler = M6502Disassembler::new(variant);
+        for op_code in 0..=0xff {
+            let bytes = [op_code, 0x34, 0x12];
+            let instruction = disassembler.disassemble(0x0200, |address| bytes[address as usize - 0x0200]);
+            let text = instruction.to_string().trim_start_matches('*').to_string();
+
+            let assembled = assemble(variant, &text);
+            let reassembled = disassembler.disassemble(0x0200, |address| assembled[address as usize - 0x0200]);
+            assert_eq!(reassembled.to_string().trim_start_matches('*'), text, "{:?} op code {:02x}", variant, op_code);
+        }
+    }
+}
+
+#[test]
+fn test_errors() {
+    let assembler = M6502Assembler::new(M6502Variant::Nmos);
+
+    assert_eq!(assembler.assemble("nop\nfoo #1", 0), Err(M6502AssemblerError::UnknownMnemonic(2, String::from("FOO"))));
+    assert_eq!(assembler.assemble("ldx $10,x", 0), Err(M6502AssemblerError::BadOperand(1, String::from("$10,x"))));
+    assert_eq!(assembler.assemble("jmp nowhere", 0), Err(M6502AssemblerError::UnknownLabel(1, String::from("nowhere"))));
+    assert_eq!(assembler.assemble("a: nop\na: nop", 0), Err(M6502AssemblerError::DuplicateLabel(2, String::from("a"))));
+    assert_eq!(assembler.assemble("lda #256", 0), Err(M6502AssemblerError::OutOfRange(1, String::from("256"))));
+    assert_eq!(assembler.assemble("bne $0300", 0x0200), Err(M6502AssemblerError::OutOfRange(1, String::from("$0300"))));
+    assert_eq!(assembler.assemble(".fill 4", 0), Err(M6502AssemblerError::UnknownDirective(1, String::from(".fill"))));
+    assert_eq!(assembler.assemble("lda #1 +", 0), Err(M6502AssemblerError::BadExpression(1, String::from("1 +"))));
+    assert_eq!(assembler.assemble("lda ($10)", 0), Err(M6502AssemblerError::BadOperand(1, String::from("($10)"))));
+    assert_eq!(assembler.assemble("lda ($10),x", 0), Err(M6502AssemblerError::BadOperand(1, String::from("($10),x"))));
+    assert_eq!(assembler.assemble("a = b\nb = a", 0), Err(M6502AssemblerError::UnknownLabel(1, String::from("b"))));
+}
+
+#[test]
+fn test_run_assembled() {
+    let source = "
+                .org $0200
+        reset:  ldx #5
+                lda #0
+        loop:   clc
+                adc #3
+                dex
+                bne loop
+                sta $10
+        halt:   jmp halt
+                .org $fffc
+                .word reset";
+
+    let assembly = M6502Assembler::new(M6502Variant::Nmos).assemble(source, 0).unwrap();
+    let mut bus = M6502TestBus::new(&[]);
+    bus.load(&assembly);
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Nmos);
+
+    while !(cpu.is_op_code_fetch() && cpu.get_cpu().program_counter == assembly.get_label("halt").unwrap()) {
+        cpu.execute_tick(&mut bus);
+    }
+    assert_eq!(bus.memory[0x10], 15);
+}
+
+}
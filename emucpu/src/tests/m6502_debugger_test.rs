@@ -0,0 +1,151 @@
+mod m6502_test_bus;
+
+pub mod emu_cpu {
+
+use emucpu::base_cpu::emu_cpu::BaseCpu;
+use emucpu::m6502::emu_cpu::{M6502Runner, M6502Variant, M6502Version};
+use emucpu::m6502_debugger::mdebugger::{M6502Break, M6502Condition, M6502Debugger, M6502Step, M6502WatchKind,
+    M6502Watchpoint};
+use emumemory::base_memory::emu_memory::{BaseMemory, MemoryError, MemoryErrorPolicy};
+use emumemory::memory_ram::emu_memory::MemoryRam;
+
+use crate::m6502_test_bus::M6502TestBus;
+
+// Ticks like a console until the debugger stops, returns the ticks run
+fn run(cpu: &mut M6502Runner, bus: &mut M6502TestBus, debugger: &mut M6502Debugger) -> u32 {
+    for ticks in 1..1000 {
+        cpu.execute_tick(bus);
+        debugger.check_tick(cpu);
+        if debugger.is_stopped() {
+            return ticks;
+        }
+    }
+    panic!("Debugger never stopped");
+}
+
+// INX at $0202, STX at $0203 and JMP at $0205
+const COUNTER: &str = "
+        ldx #0
+loop:   inx
+        stx $10
+        jmp loop";
+
+#[test]
+fn test_breakpoint() {
+    let mut bus = M6502TestBus::assemble(M6502Variant::Nmos, COUNTER);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+    let mut debugger = M6502Debugger::new();
+
+    debugger.add_breakpoint(0x0203, None);
+    run(&mut cpu, &mut bus, &mut debugger);
+    assert_eq!(debugger.get_break(), Some(M6502Break::Breakpoint(0x0203)));
+    assert_eq!(cpu.get_cpu().program_counter, 0x0203);
+    assert_eq!(cpu.get_cpu().register_x, 0x01);
+
+    // Stays stopped until resumed, then the next pass stops again
+    debugger.check_tick(&cpu);
+    assert!(debugger.is_stopped());
+    debugger.resume();
+    assert_eq!(run(&mut cpu, &mut bus, &mut debugger), 8);
+    assert_eq!(cpu.get_cpu().register_x, 0x02);
+}
+
+#[test]
+fn test_conditional_breakpoint() {
+    let mut bus = M6502TestBus::assemble(M6502Variant::Nmos, COUNTER);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+    let mut debugger = M6502Debugger::new();
+
+    debugger.add_breakpoint(0x0205, Some(M6502Condition::parse("X >= $05 && z == 0").unwrap()));
+    run(&mut cpu, &mut bus, &mut debugger);
+    assert_eq!(cpu.get_cpu().register_x, 0x05);
+    assert_eq!(bus.memory[0x10], 0x05);
+}
+
+#[test]
+fn test_condition_errors() {
+    assert!(M6502Condition::parse("Q == 1").is_err());
+    assert!(M6502Condition::parse("A =< 1").is_err());
+    assert!(M6502Condition::parse("A == $1g").is_err());
+    assert!(M6502Condition::parse("A == 1 && X").is_err());
+    assert!(M6502Condition::parse("pc != 0x0200").is_ok());
+}
+
+#[test]
+fn test_stepping() {
+    let mut bus = M6502TestBus::assemble(M6502Variant::Nmos, COUNTER);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+    let mut debugger = M6502Debugger::new();
+
+    debugger.step(M6502Step::RunTo(0x0202));
+    run(&mut cpu, &mut bus, &mut debugger);
+    assert_eq!(cpu.get_cpu().program_counter, 0x0202);
+
+    // INX, then STX
+    debugger.step(M6502Step::Instruction);
+    assert_eq!(run(&mut cpu, &mut bus, &mut debugger), 2);
+    debugger.step(M6502Step::Instruction);
+    assert_eq!(run(&mut cpu, &mut bus, &mut debugger), 3);
+    assert_eq!(debugger.get_break(), Some(M6502Break::Step));
+
+    debugger.step(M6502Step::Cycle);
+    assert_eq!(run(&mut cpu, &mut bus, &mut debugger), 1);
+
+    // Scanlines and frames end when the console says so
+    debugger.step(M6502Step::Scanline);
+    cpu.execute_tick(&mut bus);
+    debugger.check_tick(&cpu);
+    debugger.end_frame();
+    assert!(!debugger.is_stopped());
+    debugger.end_scanline();
+    assert!(debugger.is_stopped());
+}
+
+#[test]
+fn test_watchpoints() {
+    let mut bus = M6502TestBus::assemble(M6502Variant::Nmos, COUNTER);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+    let mut debugger = M6502Debugger::new();
+
+    debugger.add_watchpoint(M6502WatchKind::Write, 0x0010, 0x001f);
+    run(&mut cpu, &mut bus, &mut debugger);
+    let watchpoint = M6502Watchpoint { kind: M6502WatchKind::Write, start: 0x0010, end: 0x001f };
+    assert_eq!(debugger.get_break(), Some(M6502Break::Watchpoint(watchpoint, 0x0010)));
+    assert_eq!(bus.memory[0x10], 0x01);
+
+    // The STX reads its operand from $0204, the JMP is fetched from $0205
+    debugger.remove_watchpoint(M6502WatchKind::Write, 0x0010);
+    debugger.add_watchpoint(M6502WatchKind::Execute, 0x0204, 0x0205);
+    debugger.resume();
+    run(&mut cpu, &mut bus, &mut debugger);
+    assert_eq!(cpu.get_cpu().program_counter, 0x0205);
+    assert!(cpu.is_op_code_fetch());
+}
+
+#[test]
+fn test_register_view() {
+    let mut bus = M6502TestBus::assemble(M6502Variant::Nmos, COUNTER);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+    let mut debugger = M6502Debugger::new();
+
+    debugger.step(M6502Step::RunTo(0x0203));
+    run(&mut cpu, &mut bus, &mut debugger);
+
+    assert_eq!(cpu.get_cpu().to_string(), "PC:0203 A:00 X:01 Y:00 SP:FD P:24 nv-bdIzc");
+}
+
+#[test]
+fn test_memory_trap() {
+    let mut memory = MemoryRam::new(String::from("Work RAM"), 0x10);
+    let mut debugger = M6502Debugger::new();
+
+    debugger.check_memory(&mut memory);
+    assert!(!debugger.is_stopped());
+
+    memory.set_policy(MemoryErrorPolicy::Trap);
+    memory.write(0x10, 1);
+    debugger.check_memory(&mut memory);
+    assert_eq!(debugger.get_break(), Some(M6502Break::Memory(MemoryError::OutOfRange(String::from("Work RAM"), 0x10))));
+}
+
+}
@@ -0,0 +1,110 @@
+pub mod emu_cpu {
+
+use emucpu::m6502::emu_cpu::M6502Variant;
+use emucpu::m6502_disassembler::mdisassembler::{M6502Disassembler, M6502Symbols};
+
+fn disassemble(variant: M6502Variant, bytes: &[u8], origin: u16) -> Vec<String> {
+    M6502Disassembler::new(variant).disassemble_all(bytes, origin).iter()
+        .map(|instruction| instruction.to_string())
+        .collect()
+}
+
+#[test]
+fn test_address_modes() {
+    let program = [0xea, 0x0a, 0xa9, 0x10, 0xa5, 0x80, 0xb5, 0x80, 0xb6, 0x80, 0xad, 0x00, 0x02,
+        0xbd, 0x00, 0x02, 0xb9, 0x00, 0x02, 0x6c, 0xfc, 0xff, 0xa1, 0x20, 0xb1, 0x20, 0xd0, 0xfe];
+
+    assert_eq!(disassemble(M6502Variant::Nmos, &program, 0xf000), vec!["NOP", "ASL A", "LDA #$10",
+        "LDA $80", "LDA $80,X", "LDX $80,Y", "LDA $0200", "LDA $0200,X", "LDA $0200,Y", "JMP ($FFFC)",
+        "LDA ($20,X)", "LDA ($20),Y", "BNE $F01A"]);
+}
+
+#[test]
+fn test_undocumented() {
+    let program = [0xa7, 0x10, 0xeb, 0x01, 0x04, 0x10, 0xe7, 0x10, 0x02];
+
+    assert_eq!(disassemble(M6502Variant::Nmos, &program, 0x0000), vec!["*LAX $10", "*SBC #$01", "*NOP $10",
+        "*ISB $10", "*JAM"]);
+}
+
+#[test]
+fn test_cmos() {
+    let program = [0x80, 0x02, 0xb2, 0x10, 0x7c, 0x00, 0x10, 0x9c, 0x00, 0x02, 0x0f, 0x10, 0xfd, 0x03];
+
+    assert_eq!(disassemble(M6502Variant::Rockwell65C02, &program, 0x0200), vec!["BRA $0204", "LDA ($10)",
+        "JMP ($1000,X)", "STZ $0200", "BBR0 $10,$020A", "*NOP"]);
+}
+
+#[test]
+fn test_partial_instruction() {
+    // The LDA's operand is missing, so it's left out
+    assert_eq!(disassemble(M6502Variant::Nmos, &[0xea, 0xad, 0x00], 0xf000), vec!["NOP"]);
+}
+
+#[test]
+fn test_symbols_in_listing() {
+    let mut symbols = M6502Symbols::new();
+    symbols.add(0xf000, "Reset");
+    symbols.add(0x0009, "COLUBK");
+    let mut disassembler = M6502Disassembler::new(M6502Variant::Nmos);
+    disassembler.set_symbols(symbols);
+
+    let lines: Vec<String> = disassembler.disassemble_all(&[0x85, 0x09, 0x4c, 0x00, 0xf0], 0xf000).iter()
+        .map(|instruction| disassembler.format_line(instruction))
+        .collect();
+
+    assert_eq!(lines, vec!["Reset:\nF000  85 09     STA COLUBK", "F002  4C 00 F0  JMP Reset"]);
+}
+
+#[test]
+fn test_ca65_dbg() {
+    let mut symbols = M6502Symbols::new();
+    symbols.add_ca65_dbg("version\tmajor=2,minor=0\n\
+        sym\tid=0,name=\"reset\",addrsize=absolute,scope=0,def=2,ref=5,val=0xC000,seg=0,type=lab\n\
+        sym\tid=1,name=\"PPUCTRL\",addrsize=absolute,scope=0,def=1,val=0x2000,type=equ\n\
+        sym\tid=2,name=\"imported\",addrsize=absolute,scope=0,def=3,type=imp\n");
+
+    assert_eq!(symbols.get_name(0xc000), Some("reset"));
+    assert_eq!(symbols.get_name(0x2000), Some("PPUCTRL"));
+}
+
+#[test]
+fn test_vice_labels() {
+    let mut symbols = M6502Symbols::new();
+    symbols.add_vice_labels("al 00C000 .reset\nal 00C010 .nmi\n");
+
+    assert_eq!(symbols.get_name(0xc000), Some("reset"));
+    assert_eq!(symbols.get_name(0xc010), Some("nmi"));
+}
+
+#[test]
+fn test_dasm_sym() {
+    let mut symbols = M6502Symbols::new();
+    symbols.add_dasm_sym("--- Symbol List (sorted by symbol)\n\
+        COLUBK                   0009\n\
+        Reset                    f000              (R )\n\
+        --- End of Symbol List.\n");
+
+    assert_eq!(symbols.get_name(0x0009), Some("COLUBK"));
+    assert_eq!(symbols.get_name(0xf000), Some("Reset"));
+}
+
+#[test]
+fn test_dasm_lst() {
+    let mut symbols = M6502Symbols::new();
+    symbols.add_dasm_lst("------- FILE game.asm LEVEL 1 PASS 2\n\
+              1  0000 ????                      processor 6502\n\
+              2  0000 ????       00 09     COLUBK     equ $09\n\
+              3  f000                      org $f000\n\
+              4  f000              Reset\n\
+              5  f000        78              sei\n\
+              6  f001        a9 00     Loop       lda #0\n\
+              7  f003        85 09            sta COLUBK\n");
+
+    assert_eq!(symbols.get_name(0x0009), Some("COLUBK"));
+    assert_eq!(symbols.get_name(0xf000), Some("Reset"));
+    assert_eq!(symbols.get_name(0xf001), Some("Loop"));
+    assert_eq!(symbols.get_name(0xf003), None);
+}
+
+}
@@ -0,0 +1,157 @@
+pub mod emu_cpu {
+
+// Klaus Dormann's 6502 test suites (https://github.com/Klaus2m5/6502_65C02_functional_tests).
+// The binaries aren't part of the repository, fetch_roms.sh puts the first two in
+// emucpu/src/tests/roms:
+//   6502_functional_test.bin  the 64K image from bin_files, run from 0x0400
+//   6502_interrupt_test.bin   the 64K image from bin_files, run from 0x0400
+// The decimal test only ships as source, it has to be put there by hand:
+//   6502_decimal_test.bin     6502_decimal_test.a65 assembled with as65 and end_of_test
+//                             as "jmp *", loaded and run at 0x0200
+// The tests are ignored by default and fail when their binary is missing. The success
+// addresses are the ones in the listings of the prebuilt binaries, reassembled tests
+// need them updated.
+
+use std::fs;
+use std::path::PathBuf;
+
+use emucpu::base_cpu::emu_cpu::{BaseCpu, CpuBus};
+use emucpu::m6502::emu_cpu::{M6502Runner, M6502Version};
+use emumemory::base_memory::emu_memory::BaseMemory;
+use emumemory::memory_ram::emu_memory::MemoryRam;
+
+const FUNCTIONAL_SUCCESS: u16 = 0x3469;
+const INTERRUPT_SUCCESS: u16 = 0x06f5;
+
+// Where the functional and interrupt tests keep the current test number
+const TEST_CASE: u16 = 0x0200;
+// The decimal test leaves 0 here when it passes, the operands are at 0x00 and 0x01
+const DECIMAL_ERROR: u16 = 0x000b;
+
+// Writes here drive IRQ (bit 0, held while set) and NMI (bit 1, on a rising edge)
+const FEEDBACK_PORT: u16 = 0xbffc;
+const FEEDBACK_IRQ: u8 = 0x01;
+const FEEDBACK_NMI: u8 = 0x02;
+
+const MAX_TICKS: u64 = 200_000_000;
+
+pub struct DormannBus {
+    memory: MemoryRam,
+    data: u8,
+    feedback: u8,
+    nmi_edge: bool,
+}
+
+impl DormannBus {
+    pub fn new(image: &[u8], load_address: u16, start_address: u16) -> Self {
+        let mut flat = vec![0u8; 0x10000];
+        flat[load_address as usize..load_address as usize + image.len()].copy_from_slice(image);
+
+        // The tests' own reset vectors point at traps
+        flat[0xfffc] = start_address as u8;
+        flat[0xfffd] = (start_address >> 8) as u8;
+
+        let mut memory = MemoryRam::new(String::from("Dormann RAM"), 0);
+        memory.load_data(&flat);
+
+        Self {
+            memory,
+            data: 0,
+            feedback: 0,
+            nmi_edge: false,
+        }
+    }
+}
+
+impl CpuBus for DormannBus {
+    fn get_data(&self) -> u8 {
+        self.data
+    }
+
+    fn read(&mut self, address: u16) {
+        self.data = self.memory.read(address);
+    }
+
+    fn write(&mut self, address: u16, byte: u8) {
+        if address == FEEDBACK_PORT {
+            if byte & FEEDBACK_NMI != 0 && self.feedback & FEEDBACK_NMI == 0 {
+                self.nmi_edge = true;
+            }
+            self.feedback = byte;
+        }
+        self.memory.write(address, byte);
+    }
+}
+
+fn load_image(name: &str) -> Vec<u8> {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "tests", "roms", name].iter().collect();
+    fs::read(&path).unwrap_or_else(|e| panic!("Couldn't read {}: {}, run fetch_roms.sh", path.display(), e))
+}
+
+// Runs until an op code jumps or branches to itself, returns its address
+fn run_to_trap(bus: &mut DormannBus, version: M6502Version) -> u16 {
+    let mut cpu = M6502Runner::new(version);
+    let mut last_op_code = None;
+
+    for _ in 0..MAX_TICKS {
+        if bus.feedback & FEEDBACK_IRQ != 0 {
+            cpu.set_irq();
+        } else {
+            cpu.clear_irq();
+        }
+        if bus.nmi_edge {
+            bus.nmi_edge = false;
+            cpu.set_nmi();
+        }
+
+        cpu.execute_tick(bus);
+
+        if cpu.is_op_code_fetch() {
+            let address = cpu.get_cpu().program_counter;
+            if last_op_code == Some(address) {
+                return address;
+            }
+            last_op_code = Some(address);
+        }
+    }
+    panic!("No trap after {} ticks, last op code at {:x?}", MAX_TICKS, last_op_code);
+}
+
+fn run_trap_test(name: &str, start_address: u16, success: u16) {
+    let image = load_image(name);
+    let mut bus = DormannBus::new(&image, 0x0000, start_address);
+
+    let trap = run_to_trap(&mut bus, M6502Version::AtariVcs);
+
+    if trap != success {
+        panic!("{} trapped at {:04x} in test {:02x}", name, trap, bus.memory.read(TEST_CASE));
+    }
+}
+
+#[test]
+#[ignore = "needs the Dormann binaries, run emucpu/src/tests/fetch_roms.sh"]
+fn test_functional() {
+    run_trap_test("6502_functional_test.bin", 0x0400, FUNCTIONAL_SUCCESS);
+}
+
+#[test]
+#[ignore = "needs the Dormann binaries, run emucpu/src/tests/fetch_roms.sh"]
+fn test_interrupt() {
+    run_trap_test("6502_interrupt_test.bin", 0x0400, INTERRUPT_SUCCESS);
+}
+
+#[test]
+#[ignore = "needs 6502_decimal_test.bin assembled by hand, fetch_roms.sh can't download it"]
+fn test_decimal() {
+    let image = load_image("6502_decimal_test.bin");
+    let mut bus = DormannBus::new(&image, 0x0200, 0x0200);
+
+    run_to_trap(&mut bus, M6502Version::AtariVcs);
+
+    let error = bus.memory.read(DECIMAL_ERROR);
+    if error != 0 {
+        panic!("Decimal test failed for {:02x} and {:02x}", bus.memory.read(0x0000), bus.memory.read(0x0001));
+    }
+}
+
+}
@@ -0,0 +1,187 @@
+pub mod emu_cpu {
+
+// Tom Harte's single step tests (https://github.com/SingleStepTests/ProcessorTests and
+// https://github.com/SingleStepTests/65x02).
+// Each op code file holds 10000 instructions with the registers and memory before
+// and after, plus every bus cycle. They aren't part of the repository, copy them into
+// emucpu/src/tests/roms:
+//   6502/00.json ... 6502/ff.json        from 6502/v1, run as the VCS's 6507
+//   nes6502/00.json ... nes6502/ff.json  from nes6502/v1, run as the NES 2A03
+//   wdc65c02/00.json ... wdc65c02/ff.json, rockwell65c02/00.json ... rockwell65c02/ff.json
+//                                        from 65x02, run as those variants
+// The tests are ignored by default and fail when an op code file is missing.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use emucpu::base_cpu::emu_cpu::{BaseCpu, CpuBus};
+use emucpu::m6502::emu_cpu::{M6502, M6502Runner, M6502Variant, BREAK_COMMAND, IGNORED};
+
+// Mismatches shown for each op code
+const REPORT_LIMIT: usize = 3;
+
+// B and bit 5 only exist in pushed copies of the status, which the RAM checks cover
+const STATUS_MASK: u8 = !(BREAK_COMMAND | IGNORED);
+
+struct HarteBus {
+    memory: HashMap<u16, u8>,
+    data: u8,
+    cycles: Vec<(u16, u8, bool)>,
+}
+
+impl HarteBus {
+    fn new() -> Self {
+        Self {
+            memory: HashMap::new(),
+            data: 0,
+            cycles: Vec::new(),
+        }
+    }
+}
+
+impl CpuBus for HarteBus {
+    fn get_data(&self) -> u8 {
+        self.data
+    }
+
+    fn read(&mut self, address: u16) {
+        self.data = *self.memory.get(&address).unwrap_or(&0);
+        self.cycles.push((address, self.data, false));
+    }
+
+    fn write(&mut self, address: u16, byte: u8) {
+        self.memory.insert(address, byte);
+        self.cycles.push((address, byte, true));
+    }
+}
+
+fn get_u16(state: &Value, name: &str) -> u16 {
+    state[name].as_u64().unwrap_or_else(|| panic!("Missing {}", name)) as u16
+}
+
+fn get_cpu(state: &Value) -> M6502 {
+    M6502 {
+        program_counter: get_u16(state, "pc"),
+        stack_pointer: get_u16(state, "s") as u8,
+        accumulator: get_u16(state, "a") as u8,
+        register_x: get_u16(state, "x") as u8,
+        register_y: get_u16(state, "y") as u8,
+        status_register: get_u16(state, "p") as u8,
+    }
+}
+
+fn get_ram(state: &Value) -> Vec<(u16, u8)> {
+    state["ram"].as_array().unwrap().iter()
+        .map(|entry| (entry[0].as_u64().unwrap() as u16, entry[1].as_u64().unwrap() as u8))
+        .collect()
+}
+
+fn get_cycles(test: &Value) -> Vec<(u16, u8, bool)> {
+    test["cycles"].as_array().unwrap().iter()
+        .map(|entry| (entry[0].as_u64().unwrap() as u16, entry[1].as_u64().unwrap() as u8,
+            entry[2].as_str() == Some("write")))
+        .collect()
+}
+
+// Runs one instruction, returns what differs from the expected result
+fn run_test(test: &Value, variant: M6502Variant) -> Option<String> {
+    let initial = &test["initial"];
+    let expected = &test["final"];
+    let expected_cycles = get_cycles(test);
+
+    let mut bus = HarteBus::new();
+    for (address, byte) in get_ram(initial) {
+        bus.memory.insert(address, byte);
+    }
+    let mut cpu = M6502Runner::new_variant(variant);
+    cpu.set_cpu(get_cpu(initial));
+
+    for _ in 0..expected_cycles.len() {
+        cpu.execute_tick(&mut bus);
+    }
+    if bus.cycles != expected_cycles {
+        return Some(format!("cycles {:x?}, expected {:x?}", bus.cycles, expected_cycles));
+    }
+
+    // The next op code fetch finishes the instruction's last read, JAM, STP and WAI never get there
+    if !cpu.is_jammed() && !cpu.is_waiting() {
+        cpu.execute_tick(&mut bus);
+        if !cpu.is_op_code_fetch() {
+            return Some(format!("still running after {} cycles", expected_cycles.len()));
+        }
+    }
+
+    let mut result = *cpu.get_cpu();
+    let mut expected_cpu = get_cpu(expected);
+    result.status_register &= STATUS_MASK;
+    expected_cpu.status_register &= STATUS_MASK;
+    if result != expected_cpu {
+        return Some(format!("registers {:x?}, expected {:x?}", result, expected_cpu));
+    }
+
+    for (address, byte) in get_ram(expected) {
+        let actual = *bus.memory.get(&address).unwrap_or(&0);
+        if actual != byte {
+            return Some(format!("ram {:04x} is {:02x}, expected {:02x}", address, actual, byte));
+        }
+    }
+    None
+}
+
+fn run_suite(directory: &str, variant: M6502Variant) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "tests", "roms", directory].iter().collect();
+    let mut failures = Vec::new();
+
+    for op_code in 0..=0xffu8 {
+        let file = path.join(format!("{:02x}.json", op_code));
+        let json = fs::read_to_string(&file)
+            .unwrap_or_else(|e| panic!("Couldn't read {}: {}, run fetch_roms.sh", file.display(), e));
+
+        let tests: Value = serde_json::from_str(&json)
+            .unwrap_or_else(|e| panic!("Couldn't parse {}: {}", file.display(), e));
+        let tests = tests.as_array().unwrap();
+
+        let mismatches: Vec<String> = tests.iter()
+            .filter_map(|test| run_test(test, variant)
+                .map(|mismatch| format!("  {}: {}", test["name"].as_str().unwrap_or("?"), mismatch)))
+            .collect();
+
+        if !mismatches.is_empty() {
+            failures.push(format!("op code {:02x} failed {} of {}\n{}", op_code, mismatches.len(), tests.len(),
+                mismatches.iter().take(REPORT_LIMIT).cloned().collect::<Vec<String>>().join("\n")));
+        }
+    }
+
+    if !failures.is_empty() {
+        panic!("{} of 256 op codes failed for {:?}\n{}", failures.len(), variant, failures.join("\n"));
+    }
+}
+
+#[test]
+#[ignore = "needs the single step tests, run emucpu/src/tests/fetch_roms.sh"]
+fn test_nmos() {
+    run_suite("6502", M6502Variant::Nmos);
+}
+
+#[test]
+#[ignore = "needs the single step tests, run emucpu/src/tests/fetch_roms.sh"]
+fn test_nes() {
+    run_suite("nes6502", M6502Variant::Ricoh2A03);
+}
+
+#[test]
+#[ignore = "needs the single step tests, run emucpu/src/tests/fetch_roms.sh"]
+fn test_wdc65c02() {
+    run_suite("wdc65c02", M6502Variant::Wdc65C02);
+}
+
+#[test]
+#[ignore = "needs the single step tests, run emucpu/src/tests/fetch_roms.sh"]
+fn test_rockwell65c02() {
+    run_suite("rockwell65c02", M6502Variant::Rockwell65C02);
+}
+
+}
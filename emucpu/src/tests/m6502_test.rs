@@ -1,60 +1,558 @@
-   
+mod m6502_test_bus;
+
 pub mod emu_cpu {
 
-use emucpu::base_cpu::emu_cpu::BaseCpu;
-use emucpu::nm6502::emu_cpu::NM6502;
-use emumemory::base_memory::emu_memory::BaseMemory;
+use emucpu::base_cpu::emu_cpu::{BaseCpu, CpuBus};
+use emucpu::m6502::emu_cpu::{M6502Runner, M6502Variant, M6502Version, BREAK_COMMAND, CARRY_FLAG, DECIMAL_MODE,
+    INTERRUPT_FLAG, ZERO_FLAG};
+use emucpu::m6502_assembler::massembler::M6502Assembler;
+
+use crate::m6502_test_bus::{M6502TestBus, PROGRAM_START};
+
+// Ticks until the op code at address is fetched, returns the ticks taken.
+// That's the cycle count of the instructions before it. Implied op codes
+// do a dummy read of the next op code, so they're never the last one run.
+fn run_to(cpu: &mut M6502Runner, bus: &mut M6502TestBus, address: u16) -> u32 {
+    for ticks in 1..100 {
+        cpu.execute_tick(bus);
+        if bus.cycles.last() == Some(&(address, bus.memory[address as usize], false)) {
+            return ticks;
+        }
+    }
+    panic!("Op code at {:x} never fetched", address);
+}
+
+#[test]
+fn test_reset() {
+    let mut bus = M6502TestBus::new(&[0xea]);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    assert_eq!(run_to(&mut cpu, &mut bus, PROGRAM_START), 8);
+    assert_eq!(cpu.get_cpu().status_register & INTERRUPT_FLAG, INTERRUPT_FLAG);
+    assert_eq!(cpu.get_cpu().stack_pointer, 0xfd);
+}
 
 #[test]
 fn test_opcode_0x01() {
-    let mut cpu = NM6502::new(Box::new(M6502TestMemory::new()));
+    // LDX #$02, ORA ($10,X) with $12 pointing at $0300
+    let mut bus = M6502TestBus::new(&[0xa2, 0x02, 0x01, 0x10, 0xea]);
+    bus.memory[0x12] = 0x00;
+    bus.memory[0x13] = 0x03;
+    bus.memory[0x0300] = 0x41;
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, 0x0202);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0204), 6);
+    assert_eq!(cpu.get_cpu().accumulator, 0x41);
+    assert_eq!(cpu.get_cpu().status_register & ZERO_FLAG, 0);
+}
+
+#[test]
+fn test_absolute_indexed_page_cross() {
+    // LDX #$01, LDA $02ff,X crosses a page, LDA $0280,X doesn't, STA $0280,X always takes 5
+    let mut bus = M6502TestBus::new(&[0xa2, 0x01, 0xbd, 0xff, 0x02, 0xbd, 0x80, 0x02, 0x9d, 0x80, 0x02, 0xea]);
+    let mut cpu = M6502Runner::new(M6502Version::Nes);
+
+    run_to(&mut cpu, &mut bus, 0x0202);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0205), 5);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0208), 4);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x020b), 5);
+}
+
+#[test]
+fn test_read_modify_write_cycles() {
+    // INC $10 reads, writes the old byte back, then writes the new one
+    let mut bus = M6502TestBus::new(&[0xe6, 0x10, 0xea]);
+    bus.memory[0x10] = 0x7f;
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, PROGRAM_START);
+    bus.cycles.clear();
+    run_to(&mut cpu, &mut bus, 0x0202);
+
+    assert_eq!(bus.cycles, vec![(0x0201, 0x10, false), (0x0010, 0x7f, false),
+        (0x0010, 0x7f, true), (0x0010, 0x80, true), (0x0202, 0xea, false)]);
+}
+
+#[test]
+fn test_branch_cycles() {
+    // LDA #$00, BNE not taken, CMP #$00, BCS taken on the same page, BCS taken back across a page
+    let mut bus = M6502TestBus::new(&[0xa9, 0x00, 0xd0, 0x10, 0xc9, 0x00, 0xb0, 0x01, 0xea, 0xb0, 0xf0]);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, 0x0202);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0204), 2);
+    run_to(&mut cpu, &mut bus, 0x0206);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0209), 3);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x01fb), 4);
+}
+
+#[test]
+fn test_decimal_mode() {
+    // SED, CLC, LDA #$19, ADC #$28 gives $47, then SEC, SBC #$48 borrows to $99
+    let mut bus = M6502TestBus::new(&[0xf8, 0x18, 0xa9, 0x19, 0x69, 0x28, 0x38, 0xe9, 0x48, 0xea]);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, 0x0206);
+    assert_eq!(cpu.get_cpu().accumulator, 0x47);
+
+    run_to(&mut cpu, &mut bus, 0x0209);
+    assert_eq!(cpu.get_cpu().accumulator, 0x99);
+    assert_eq!(cpu.get_cpu().status_register & CARRY_FLAG, 0);
+}
+
+#[test]
+fn test_nes_has_no_decimal_mode() {
+    let mut bus = M6502TestBus::new(&[0xf8, 0x18, 0xa9, 0x19, 0x69, 0x28, 0xea]);
+    let mut cpu = M6502Runner::new(M6502Version::Nes);
+
+    run_to(&mut cpu, &mut bus, 0x0206);
+
+    assert_eq!(cpu.get_cpu().accumulator, 0x41);
+}
+
+#[test]
+fn test_jsr_rts() {
+    // JSR $0210, LDX #$01 ... $0210: LDA #$05, RTS
+    let mut program = vec![0x20, 0x10, 0x02, 0xa2, 0x01, 0xea];
+    program.resize(0x10, 0xea);
+    program.extend_from_slice(&[0xa9, 0x05, 0x60]);
+    let mut bus = M6502TestBus::new(&program);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
 
+    run_to(&mut cpu, &mut bus, PROGRAM_START);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0210), 6);
+    assert_eq!(cpu.get_cpu().stack_pointer, 0xfb);
+    run_to(&mut cpu, &mut bus, 0x0212);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0203), 6);
+    run_to(&mut cpu, &mut bus, 0x0205);
+
+    assert_eq!(cpu.get_cpu().accumulator, 0x05);
+    assert_eq!(cpu.get_cpu().register_x, 0x01);
+    assert_eq!(cpu.get_cpu().stack_pointer, 0xfd);
+}
+
+#[test]
+fn test_jmp_indirect_page_wrap() {
+    // JMP ($02ff) takes its high byte from $0200, not $0300
+    let mut bus = M6502TestBus::new(&[0x6c, 0xff, 0x02]);
+    bus.memory[0x02ff] = 0x34;
+    bus.memory[0x0300] = 0x56;
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, PROGRAM_START);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x6c34), 5);
+}
+
+#[test]
+fn test_irq_pushes_status() {
+    // CLI, LDA #$00, NOP with the IRQ handler at $0300
+    let mut bus = M6502TestBus::new(&[0x58, 0xa9, 0x00, 0xea, 0xea]);
+    bus.memory[0xfffe] = 0x00;
+    bus.memory[0xffff] = 0x03;
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, 0x0203);
+    cpu.set_irq();
+    // The NOP's last cycle, 7 for the interrupt and the handler's fetch
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0300), 9);
+
+    // The status is pushed with B clear
+    assert_eq!(bus.memory[0x01fb] & BREAK_COMMAND, 0);
+    assert_eq!(bus.memory[0x01fd], 0x02);
+    assert_eq!(bus.memory[0x01fc], 0x04);
+}
+
+#[test]
+fn test_shx_page_cross() {
+    // LDY #$10, LDX #$01, SHX $02f8,Y crosses into page 3 so X & 3 becomes the high byte
+    let mut bus = M6502TestBus::new(&[0xa0, 0x10, 0xa2, 0x01, 0x9e, 0xf8, 0x02, 0xea]);
+    bus.memory[0x0308] = 0x55;
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, 0x0204);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0207), 5);
+
+    assert_eq!(bus.memory[0x0108], 0x01);
+    assert_eq!(bus.memory[0x0308], 0x55);
+}
+
+#[test]
+fn test_lxa() {
+    // LDA #$00, LXA #$7f
+    let mut bus = M6502TestBus::new(&[0xa9, 0x00, 0xab, 0x7f, 0xea]);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, 0x0204);
+
+    assert_eq!(cpu.get_cpu().accumulator, 0x6e);
+    assert_eq!(cpu.get_cpu().register_x, 0x6e);
+}
+
+#[test]
+fn test_jam_and_reset() {
+    let mut bus = M6502TestBus::new(&[0x02]);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, PROGRAM_START);
+    for _ in 0..10 {
+        cpu.execute_tick(&mut bus);
+    }
+    assert!(cpu.is_jammed());
+    assert_eq!(bus.cycles.last(), Some(&(0xffff, 0x00, false)));
+
+    // Interrupts don't get it going again, reset does
+    cpu.set_nmi();
+    cpu.execute_tick(&mut bus);
+    assert!(cpu.is_jammed());
+
+    bus.memory[0x0200] = 0xea;
     cpu.reset();
-    let operation = cpu.op_code_lookup[0x01];
+    run_to(&mut cpu, &mut bus, PROGRAM_START);
+    assert!(!cpu.is_jammed());
+}
+
+// Sets up an IRQ handler at $0300 and raises IRQ the given ticks after the op code
+// at irq_at is fetched, returns the address pushed by the interrupt
+fn run_irq(program: &[u8], irq_at: u16, ticks: u32) -> u16 {
+    let mut bus = M6502TestBus::new(program);
+    bus.memory[0xfffe] = 0x00;
+    bus.memory[0xffff] = 0x03;
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
 
-    cpu.call_op_method(operation.operation, operation.address_method);
+    run_to(&mut cpu, &mut bus, irq_at);
+    for _ in 0..ticks {
+        cpu.execute_tick(&mut bus);
+    }
+    cpu.set_irq();
+    run_to(&mut cpu, &mut bus, 0x0300);
 
-    assert_eq!(cpu.get_overflow_ticks(), 6);
-    assert_eq!(cpu.get_accumulator(), 1);
-    assert_eq!(cpu.get_status_register(), 1);
+    ((bus.memory[0x01fd] as u16) << 8) | bus.memory[0x01fc] as u16
 }
 
-pub struct NM6502TestMemory {
-    ram: emumemory::memory_ram::emu_memory::MemoryRam
+#[test]
+fn test_cli_latency() {
+    // CLI, NOP, NOP with IRQ held from the start, the instruction after CLI still runs
+    assert_eq!(run_irq(&[0x58, 0xea, 0xea, 0xea], PROGRAM_START, 0), 0x0202);
 }
 
-impl NM6502TestMemory {
-    pub fn new() -> Self {
-        let mut ram = emumemory::memory_ram::emu_memory::MemoryRam::new(String::from("RAM Test"));
-        ram.load_data(&[1, 0, 0, 4, 5, 6, 7, 8, 9, 10]);
+#[test]
+fn test_sei_latency() {
+    // CLI, LDA #$00, SEI, NOP, the IRQ is taken after the SEI
+    assert_eq!(run_irq(&[0x58, 0xa9, 0x00, 0x78, 0xea, 0xea], 0x0203, 0), 0x0204);
+}
 
-        Self {
-            ram: ram
-        }
+#[test]
+fn test_branch_delays_interrupt() {
+    // CLI, LDA #$01, BNE +1, NOP, NOP: the IRQ comes after the taken branch has read
+    // its offset. It doesn't poll on its last cycle, so the NOP at $0206 runs first.
+    assert_eq!(run_irq(&[0x58, 0xa9, 0x01, 0xd0, 0x01, 0xea, 0xea, 0xea], 0x0203, 1), 0x0207);
+
+    // A branch crossing a page polls on its last cycle
+    let mut program = vec![0x58, 0xa9, 0x01, 0x4c, 0xf0, 0x02];
+    program.resize(0xf0, 0xea);
+    program.extend_from_slice(&[0xd0, 0x10]);
+    assert_eq!(run_irq(&program, 0x02f0, 2), 0x0302);
+}
+
+#[test]
+fn test_nmi_hijacks_brk() {
+    // BRK with the NMI coming in while the return address is pushed
+    let mut bus = M6502TestBus::new(&[0x00, 0x00]);
+    bus.memory[0xfffa] = 0x00;
+    bus.memory[0xfffb] = 0x04;
+    bus.memory[0xfffe] = 0x00;
+    bus.memory[0xffff] = 0x03;
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, PROGRAM_START);
+    cpu.execute_tick(&mut bus);
+    cpu.execute_tick(&mut bus);
+    cpu.set_nmi();
+    run_to(&mut cpu, &mut bus, 0x0400);
+
+    // Still pushed as a BRK
+    assert_eq!(bus.memory[0x01fb] & BREAK_COMMAND, BREAK_COMMAND);
+    assert_eq!(bus.memory[0x01fc], 0x02);
+}
+
+#[test]
+fn test_rdy_holds_reads_only() {
+    // LDA #$42, STA $10, NOP with RDY pulled low during the STA's write
+    let mut bus = M6502TestBus::new(&[0xa9, 0x42, 0x85, 0x10, 0xea, 0xea]);
+    let mut cpu = M6502Runner::new(M6502Version::AtariVcs);
+
+    run_to(&mut cpu, &mut bus, 0x0202);
+    cpu.execute_tick(&mut bus);
+    cpu.execute_tick(&mut bus);
+    cpu.set_rdy(false);
+    assert!(!cpu.is_halted());
+
+    // The write goes through, then the op code fetch is held
+    bus.cycles.clear();
+    for _ in 0..4 {
+        cpu.execute_tick(&mut bus);
     }
+    assert!(cpu.is_halted());
+    assert_eq!(bus.memory[0x0010], 0x42);
+    assert_eq!(bus.cycles, vec![(0x0204, 0xea, false); 4]);
+
+    // The NOP's dummy read follows straight away
+    cpu.set_rdy(true);
+    cpu.execute_tick(&mut bus);
+    assert_eq!(bus.cycles.last(), Some(&(0x0205, 0xea, false)));
+    assert_eq!(cpu.get_cpu().program_counter, 0x0205);
 }
 
-impl emumemory::memory_mapper::emu_memory::MemoryMapper for NM6502TestMemory {
-        fn cpu_read(&self, location: u16) -> u8 {
+#[test]
+fn test_skipped_cycles_count() {
+    let mut bus = M6502TestBus::new(&[0xea, 0xea]);
+    let mut cpu = M6502Runner::new(M6502Version::Nes);
 
-            let result: u8;
+    run_to(&mut cpu, &mut bus, PROGRAM_START);
+    let cycles = cpu.get_cycle_count();
+    for _ in 0..513 {
+        cpu.skip_cycle();
+    }
+    assert!(!cpu.is_op_code_fetch());
+    cpu.execute_tick(&mut bus);
+    assert_eq!(cpu.get_cycle_count(), cycles + 514);
+    assert_eq!(cpu.get_cpu().program_counter, PROGRAM_START + 1);
+}
 
-            if location == 0xfffc {
-                result = 0;
-            }
-            else if location == 0xfffd {
-                result = 0;
-            }
-            else {
-                result = self.ram.read(location);
+// OAM DMA as the NES runs it: once RDY holds the CPU on a read it reads on
+// get cycles and writes on put cycles, then lets go a cycle after the last write
+struct OamDma {
+    remaining: u16,
+    address: u16,
+    byte: Option<u8>,
+    release: bool,
+    oam: Vec<u8>,
+}
+
+impl OamDma {
+    fn is_active(&self) -> bool {
+        self.remaining > 0 || self.release
+    }
+
+    // Whether the DMA had the bus this cycle
+    fn tick(&mut self, bus: &mut M6502TestBus, cpu_halted: bool, put_cycle: bool) -> bool {
+        let mut dma_cycle = false;
+        if !cpu_halted {
+            // Waiting for the CPU to reach a read
+        } else if self.remaining > 0 {
+            match self.byte.take() {
+                // A put cycle here is the alignment cycle
+                None if put_cycle => {},
+                None => {
+                    bus.read(self.address);
+                    self.byte = Some(bus.get_data());
+                    self.address += 1;
+                    dma_cycle = true;
+                },
+                Some(byte) => {
+                    self.oam[256 - self.remaining as usize] = byte;
+                    self.remaining -= 1;
+                    dma_cycle = true;
+                },
             }
+        } else {
+            self.release = false;
+        }
+        if dma_cycle && self.remaining == 0 {
+            self.release = true;
+        }
+        dma_cycle
+    }
+}
+
+// The cycles the DMA stole and whether STA $4014 wrote on a put cycle
+fn run_oam_dma(preamble: &str) -> (u64, bool) {
+    let source = format!("
+                {}
+                lda #$03
+                sta $4014
+                nop
+        done:   nop", preamble);
+    let assembly = M6502Assembler::new(M6502Variant::Nmos).assemble(&source, PROGRAM_START).unwrap();
+    let done = assembly.get_label("done").unwrap();
+    let mut bus = M6502TestBus::new(&[]);
+    bus.load(&assembly);
+    for (index, byte) in bus.memory[0x0300..0x0400].iter_mut().enumerate() {
+        *byte = !(index as u8);
+    }
+    let mut cpu = M6502Runner::new(M6502Version::Nes);
+    let mut dma = OamDma { remaining: 0, address: 0, byte: None, release: false, oam: vec![0; 256] };
+    let mut write_cycle = None;
+
+    for _ in 0..1000 {
+        // Odd cycles are put cycles
+        let put_cycle = cpu.get_cycle_count() % 2 == 1;
+        cpu.set_rdy(!dma.is_active());
+        if dma.tick(&mut bus, cpu.is_halted(), put_cycle) {
+            cpu.skip_cycle();
+            continue;
+        }
+        cpu.execute_tick(&mut bus);
 
-            result
+        if let Some(&(0x4014, page, true)) = bus.cycles.last() && write_cycle.is_none() {
+            dma.remaining = 256;
+            dma.address = (page as u16) << 8;
+            write_cycle = Some((cpu.get_cycle_count() - 1, put_cycle));
         }
+        if cpu.is_op_code_fetch() && cpu.get_cpu().program_counter == done {
+            let (write_cycle, put_cycle) = write_cycle.unwrap();
+            assert_eq!(dma.oam, bus.memory[0x0300..0x0400]);
+            // Without the DMA the NOP's two cycles come between them
+            return (cpu.get_cycle_count() - 1 - write_cycle - 3, put_cycle);
+        }
+    }
+    panic!("The DMA never finished");
+}
 
-        fn cpu_write(&mut self, location: u16, byte: u8) {
-            self.ram.write(location, byte);
+#[test]
+fn test_oam_dma_alignment() {
+    // A NOP and a BIT zero page put the write on different cycles
+    let mut runs = [run_oam_dma("nop"), run_oam_dma("bit $00")];
+    runs.sort_by_key(|&(_, put_cycle)| put_cycle);
+    assert_eq!(runs, [(513, false), (514, true)]);
+}
+
+#[test]
+fn test_can_halt_after_op_code_fetch() {
+    // The JMP ends fetching the next op code itself
+    let mut bus = M6502TestBus::assemble(M6502Variant::Nmos, "
+                nop
+        loop:   jmp loop");
+    let mut cpu = M6502Runner::new(M6502Version::Atari7800);
+    let mut halts = 0;
+
+    for _ in 0..1000 {
+        cpu.execute_tick(&mut bus);
+        assert!(cpu.can_halt || !cpu.is_op_code_fetch());
+        if cpu.can_halt {
+            halts += 1;
         }
+    }
+    assert!(halts > 300);
+}
+
+#[test]
+fn test_cmos_jmp_indirect() {
+    // The 65C02 takes the high byte from $0300 and a cycle longer
+    let mut bus = M6502TestBus::new(&[0x6c, 0xff, 0x02]);
+    bus.memory[0x02ff] = 0x34;
+    bus.memory[0x0300] = 0x56;
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Wdc65C02);
+
+    run_to(&mut cpu, &mut bus, PROGRAM_START);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x5634), 6);
+}
+
+#[test]
+fn test_cmos_decimal_flags() {
+    // SED, CLC, LDA #$99, ADC #$01 gives $00 with Z set, in an extra cycle
+    let mut bus = M6502TestBus::new(&[0xf8, 0x18, 0xa9, 0x99, 0x69, 0x01, 0xea]);
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Wdc65C02);
+
+    run_to(&mut cpu, &mut bus, 0x0204);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0206), 3);
+
+    assert_eq!(cpu.get_cpu().accumulator, 0x00);
+    assert_eq!(cpu.get_cpu().status_register & (ZERO_FLAG | CARRY_FLAG), ZERO_FLAG | CARRY_FLAG);
+}
+
+#[test]
+fn test_cmos_op_codes() {
+    // LDX #$5a, PHX, PLY, STZ $10, LDA #$0f, TSB $11, BIT #$f0, BRA +1, NOP, LDA ($12)
+    let mut bus = M6502TestBus::new(&[0xa2, 0x5a, 0xda, 0x7a, 0x64, 0x10, 0xa9, 0x0f, 0x04, 0x11,
+        0x89, 0xf0, 0x80, 0x01, 0xea, 0xb2, 0x12, 0xea, 0xea]);
+    bus.memory[0x10] = 0xff;
+    bus.memory[0x11] = 0x30;
+    bus.memory[0x12] = 0x00;
+    bus.memory[0x13] = 0x03;
+    bus.memory[0x0300] = 0x77;
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Wdc65C02);
+
+    run_to(&mut cpu, &mut bus, 0x020c);
+    assert_eq!(cpu.get_cpu().register_y, 0x5a);
+    assert_eq!(bus.memory[0x10], 0x00);
+    assert_eq!(bus.memory[0x11], 0x3f);
+    // TSB found no bits in common, BIT # only sets Z
+    assert_eq!(cpu.get_cpu().status_register & ZERO_FLAG, ZERO_FLAG);
+
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x020f), 3);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0211), 5);
+    assert_eq!(cpu.get_cpu().accumulator, 0x77);
+}
+
+#[test]
+fn test_cmos_read_modify_write() {
+    // LDX #$01, ASL $0280,X reads twice and skips the fixup cycle, INC $0280,X doesn't
+    let mut bus = M6502TestBus::new(&[0xa2, 0x01, 0x1e, 0x80, 0x02, 0xfe, 0x80, 0x02, 0xea]);
+    bus.memory[0x0281] = 0x40;
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Wdc65C02);
+
+    run_to(&mut cpu, &mut bus, 0x0202);
+    bus.cycles.clear();
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0205), 6);
+    assert_eq!(bus.cycles, vec![(0x0203, 0x80, false), (0x0204, 0x02, false), (0x0281, 0x40, false),
+        (0x0281, 0x40, false), (0x0281, 0x80, true), (0x0205, 0xfe, false)]);
+
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0208), 7);
+    assert_eq!(bus.memory[0x0281], 0x81);
+}
+
+#[test]
+fn test_cmos_brk_clears_decimal() {
+    // SED, BRK with the handler at $0300
+    let mut bus = M6502TestBus::new(&[0xf8, 0x00, 0x00]);
+    bus.memory[0xfffe] = 0x00;
+    bus.memory[0xffff] = 0x03;
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Wdc65C02);
+
+    run_to(&mut cpu, &mut bus, 0x0300);
+
+    assert_eq!(cpu.get_cpu().status_register & DECIMAL_MODE, 0);
+    assert_eq!(bus.memory[0x01fb] & DECIMAL_MODE, DECIMAL_MODE);
+}
+
+#[test]
+fn test_rockwell_bit_op_codes() {
+    // SMB3 $10, BBS3 $10,+2, NOP, NOP, RMB3 $10, BBR3 $10,+1, NOP, then $cb which is
+    // WAI on the WDC part and a one cycle NOP here
+    let mut bus = M6502TestBus::new(&[0xb7, 0x10, 0xbf, 0x10, 0x02, 0xea, 0xea, 0x37, 0x10,
+        0x3f, 0x10, 0x01, 0xea, 0xcb, 0xea, 0xea]);
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Rockwell65C02);
+
+    run_to(&mut cpu, &mut bus, 0x0202);
+    assert_eq!(bus.memory[0x10], 0x08);
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x0207), 6);
+
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x020d), 11);
+    assert_eq!(bus.memory[0x10], 0x00);
+
+    assert_eq!(run_to(&mut cpu, &mut bus, 0x020e), 1);
+    assert!(!cpu.is_waiting());
+}
+
+#[test]
+fn test_wai() {
+    // SEI, LDX #$00, WAI, LDA #$01: a masked IRQ wakes the CPU without being taken
+    let mut bus = M6502TestBus::new(&[0x78, 0xa2, 0x00, 0xcb, 0xa9, 0x01, 0xea, 0xea]);
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Wdc65C02);
+
+    run_to(&mut cpu, &mut bus, 0x0203);
+    for _ in 0..20 {
+        cpu.execute_tick(&mut bus);
+    }
+    assert!(cpu.is_waiting());
+    assert_eq!(cpu.get_cpu().program_counter, 0x0204);
+
+    cpu.set_irq();
+    run_to(&mut cpu, &mut bus, 0x0206);
+    assert!(!cpu.is_waiting());
+    assert_eq!(cpu.get_cpu().accumulator, 0x01);
 }
 
 }
@@ -0,0 +1,65 @@
+// Shared by the CPU test crates, which each use only some of it
+#![allow(dead_code)]
+
+use emucpu::base_cpu::emu_cpu::CpuBus;
+use emucpu::m6502::emu_cpu::M6502Variant;
+use emucpu::m6502_assembler::massembler::{M6502Assembler, M6502Assembly};
+
+pub const PROGRAM_START: u16 = 0x0200;
+
+// Flat 64K answering each read straight away, every cycle is recorded as
+// address, byte and whether it was a write
+pub struct M6502TestBus {
+    pub memory: Vec<u8>,
+    pub cycles: Vec<(u16, u8, bool)>,
+    data: u8,
+}
+
+impl M6502TestBus {
+    // The program at PROGRAM_START, where the reset vector points
+    pub fn new(program: &[u8]) -> Self {
+        Self::new_at(program, PROGRAM_START, PROGRAM_START)
+    }
+
+    pub fn new_at(program: &[u8], origin: u16, start: u16) -> Self {
+        let mut memory = vec![0u8; 0x10000];
+        memory[origin as usize..origin as usize + program.len()].copy_from_slice(program);
+        memory[0xfffc] = start as u8;
+        memory[0xfffd] = (start >> 8) as u8;
+
+        Self {
+            memory,
+            cycles: Vec::new(),
+            data: 0,
+        }
+    }
+
+    // Assembles source at PROGRAM_START
+    pub fn assemble(variant: M6502Variant, source: &str) -> Self {
+        let assembly = M6502Assembler::new(variant).assemble(source, PROGRAM_START).unwrap();
+        let mut bus = Self::new(&[]);
+        bus.load(&assembly);
+        bus
+    }
+
+    // Segments at their own origins, so they can set the vectors
+    pub fn load(&mut self, assembly: &M6502Assembly) {
+        assembly.write_to(|address, byte| self.memory[address as usize] = byte);
+    }
+}
+
+impl CpuBus for M6502TestBus {
+    fn get_data(&self) -> u8 {
+        self.data
+    }
+
+    fn read(&mut self, address: u16) {
+        self.data = self.memory[address as usize];
+        self.cycles.push((address, self.data, false));
+    }
+
+    fn write(&mut self, address: u16, byte: u8) {
+        self.memory[address as usize] = byte;
+        self.cycles.push((address, byte, true));
+    }
+}
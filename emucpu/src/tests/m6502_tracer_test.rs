@@ -0,0 +1,129 @@
+mod m6502_test_bus;
+
+pub mod emu_cpu {
+
+// nestest (https://www.qmtpro.com/~nes/misc/) runs every official and most
+// undocumented op codes from $C000 without a PPU. fetch_roms.sh puts nestest.nes and
+// its reference nestest.log in emucpu/src/tests/roms, the test is ignored by default
+// and fails without them.
+
+use std::fs;
+use std::path::PathBuf;
+
+use emucpu::base_cpu::emu_cpu::{BaseCpu, CpuBus};
+use emucpu::m6502::emu_cpu::{M6502Runner, M6502Variant};
+use emucpu::m6502_tracer::mtracer::M6502Tracer;
+
+use crate::m6502_test_bus::M6502TestBus;
+
+const INES_HEADER: usize = 16;
+const PRG_BANK_SIZE: usize = 0x4000;
+const NESTEST_START: u16 = 0xc000;
+const DOTS_PER_SCAN_LINE: u64 = 341;
+
+// The NES map nestest needs, mirrored RAM, open I/O and one PRG bank at $8000 and $C000
+struct NesTestBus {
+    bus: M6502TestBus,
+}
+
+impl NesTestBus {
+    fn new(prg: &[u8]) -> Self {
+        let mut bus = M6502TestBus::new_at(prg, 0x8000, NESTEST_START);
+        bus.memory[0xc000..0xc000 + prg.len()].copy_from_slice(prg);
+        bus.memory[0x2000..0x6000].fill(0xff);
+        Self { bus }
+    }
+
+    fn map(address: u16) -> u16 {
+        if address < 0x2000 { address & 0x07ff } else { address }
+    }
+
+    fn peek(&self, address: u16) -> u8 {
+        self.bus.memory[Self::map(address) as usize]
+    }
+}
+
+impl CpuBus for NesTestBus {
+    fn get_data(&self) -> u8 {
+        self.bus.get_data()
+    }
+
+    fn read(&mut self, address: u16) {
+        self.bus.read(Self::map(address));
+    }
+
+    fn write(&mut self, address: u16, byte: u8) {
+        if !(0x2000..0x6000).contains(&address) {
+            self.bus.write(Self::map(address), byte);
+        }
+    }
+}
+
+fn load_file(name: &str) -> Vec<u8> {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "tests", "roms", name].iter().collect();
+    fs::read(&path).unwrap_or_else(|e| panic!("Couldn't read {}: {}, run fetch_roms.sh", path.display(), e))
+}
+
+#[test]
+fn test_trace_format() {
+    // The JMP takes its high byte from $0200, not $0300
+    let mut bus = M6502TestBus::assemble(M6502Variant::Nmos, "
+                ldx #$02
+                stx $10
+                lda $0e,x
+                lda ($10),y
+                jmp ($02ff)
+                .org $02ff
+                .byte $0b
+                .org $a20b
+                nop $10");
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Nmos);
+    cpu.set_tracer(Some(M6502Tracer::new(M6502Variant::Nmos)));
+
+    for _ in 0..30 {
+        cpu.execute_tick(&mut bus);
+        let dots = cpu.get_cycle_count().saturating_sub(1) * 3;
+        let video = Some(((dots / DOTS_PER_SCAN_LINE) as i32, (dots % DOTS_PER_SCAN_LINE) as i32));
+        cpu.trace(video, |address| bus.memory[address as usize]);
+    }
+
+    let lines = cpu.get_tracer_mut().unwrap().take_lines();
+    assert_eq!(lines[..6], [
+        "0200  A2 02     LDX #$02                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7",
+        "0202  86 10     STX $10 = 00                    A:00 X:02 Y:00 P:24 SP:FD PPU:  0, 27 CYC:9",
+        "0204  B5 0E     LDA $0E,X @ 10 = 02             A:00 X:02 Y:00 P:24 SP:FD PPU:  0, 36 CYC:12",
+        "0206  B1 10     LDA ($10),Y = 0002 @ 0002 = 00  A:02 X:02 Y:00 P:24 SP:FD PPU:  0, 48 CYC:16",
+        "0208  6C FF 02  JMP ($02FF) = A20B              A:00 X:02 Y:00 P:26 SP:FD PPU:  0, 63 CYC:21",
+        "A20B  04 10    *NOP $10 = 02                    A:00 X:02 Y:00 P:26 SP:FD PPU:  0, 78 CYC:26",
+    ]);
+}
+
+#[test]
+#[ignore = "needs nestest.nes and nestest.log, run emucpu/src/tests/fetch_roms.sh"]
+fn test_nestest() {
+    let rom = load_file("nestest.nes");
+    let log = load_file("nestest.log");
+    let log = String::from_utf8_lossy(&log);
+    let mut bus = NesTestBus::new(&rom[INES_HEADER..INES_HEADER + PRG_BANK_SIZE]);
+    let mut cpu = M6502Runner::new_variant(M6502Variant::Ricoh2A03);
+    cpu.set_tracer(Some(M6502Tracer::new(M6502Variant::Ricoh2A03)));
+
+    let expected: Vec<&str> = log.lines().collect();
+    let mut traced = 0;
+    while traced < expected.len() {
+        cpu.execute_tick(&mut bus);
+        let dots = cpu.get_cycle_count().saturating_sub(1) * 3;
+        let video = Some(((dots / DOTS_PER_SCAN_LINE) as i32, (dots % DOTS_PER_SCAN_LINE) as i32));
+        cpu.trace(video, |address| bus.peek(address));
+
+        for line in cpu.get_tracer_mut().unwrap().take_lines() {
+            assert_eq!(line, expected[traced].trim_end(), "nestest.log line {} differs", traced + 1);
+            traced += 1;
+        }
+    }
+
+    // nestest leaves its error codes in $02 and $03
+    assert_eq!((bus.bus.memory[0x02], bus.bus.memory[0x03]), (0, 0));
+}
+
+}
@@ -1,46 +1,111 @@
-
 pub mod emu_memory
 {
+    use std::collections::HashSet;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+    pub enum MemoryError {
+        // The memory's name and the location
+        OutOfRange(String, u16),
+        ReadOnly(String, u16),
+    }
+
+    impl fmt::Display for MemoryError {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                MemoryError::OutOfRange(name, location) => write!(f, "{}: {:04x} is beyond the memory", name, location),
+                MemoryError::ReadOnly(name, location) => write!(f, "{}: {:04x} is read only", name, location),
+            }
+        }
+    }
+
+    impl std::error::Error for MemoryError {}
+
+    // What read and write do when the checked access fails
+    #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+    pub enum MemoryErrorPolicy {
+        Ignore,
+        // Each failing location once, a game hitting it every frame would flood stderr
+        #[default]
+        Log,
+        // Keep the error until it's taken, consoles hand it to their debugger
+        Trap,
+    }
+
+    #[derive(Debug, Default, Clone)]
+    pub struct MemoryErrors {
+        pub policy: MemoryErrorPolicy,
+        trapped: Option<MemoryError>,
+        logged: HashSet<MemoryError>,
+    }
+
+    impl MemoryErrors {
+
+        pub fn handle(&mut self, error: MemoryError) {
+            match self.policy {
+                MemoryErrorPolicy::Ignore => {},
+                MemoryErrorPolicy::Log => {
+                    if !self.logged.contains(&error) {
+                        eprintln!("{}", error);
+                        self.logged.insert(error);
+                    }
+                },
+                MemoryErrorPolicy::Trap => {
+                    if self.trapped.is_none() {
+                        self.trapped = Some(error);
+                    }
+                },
+            }
+        }
+
+        pub fn take_error(&mut self) -> Option<MemoryError> {
+            self.trapped.take()
+        }
+    }
+
     pub trait BaseMemory {
         fn load_data(&mut self, data: &[u8]);
 
-        fn read(&mut self, location: u16) -> u8;
+        fn try_read(&mut self, location: u16) -> Result<u8, MemoryError>;
 
-        fn write(&mut self, location: u16, byte: u8);
+        fn try_write(&mut self, location: u16, byte: u8) -> Result<(), MemoryError>;
 
-        fn get_name(&self) -> String;// {
-        //   String::from("Unnamed Memory")
-        //};
+        fn get_name(&self) -> String;
 
-        fn check_location(&self, location: u16);
-    }
+        fn get_size(&self) -> usize;
+
+        fn get_errors_mut(&mut self) -> &mut MemoryErrors;
 
-    // Macro to implement the trait for multiple types
-//    #[macro_export]
-//    macro_rules! impl_BaseMemory {
-//        ($structname: ident, BaseMemory $($memory:ident),*) => {
-//            impl crate::base_memory::emu_memory::BaseMemory for $structname {
-//                fn load_data (&mut self, data: &[u8]) {
-//                    self.memory = data.to_vec();
-//                }
-
-//               fn read (&self, location: u16) -> u8 {
-//                    self.check_location(location);
-//                    self.memory[location as usize]
-//                }
-
-//                fn write (&mut self, location: u16, byte: u8) {
-//                    self.check_location(location);
-//                    self.memory[location as usize] = byte;
-//                }
-
-//                fn check_location(&self, location: u16) {
-//                    if location as usize > self.memory.len() {
-//                        panic!("beyond memory");
-//                    }
-//                }
-//            }
-//        };
-//    }
+        fn check_location(&self, location: u16) -> Result<(), MemoryError> {
+            if location as usize >= self.get_size() {
+                return Err(MemoryError::OutOfRange(self.get_name(), location));
+            }
+            Ok(())
+        }
+
+        // Reads that fail go to the policy and give 0
+        fn read(&mut self, location: u16) -> u8 {
+            self.try_read(location).unwrap_or_else(|error| {
+                self.get_errors_mut().handle(error);
+                0
+            })
+        }
+
+        fn write(&mut self, location: u16, byte: u8) {
+            if let Err(error) = self.try_write(location, byte) {
+                self.get_errors_mut().handle(error);
+            }
+        }
+
+        fn set_policy(&mut self, policy: MemoryErrorPolicy) {
+            self.get_errors_mut().policy = policy;
+        }
+
+        // The first error the Trap policy kept since the last call
+        fn take_error(&mut self) -> Option<MemoryError> {
+            self.get_errors_mut().take_error()
+        }
+    }
 
 }
@@ -1,5 +1,6 @@
 
 pub mod base_memory;
+pub mod memory_bus;
 pub mod memory_rom;
 pub mod memory_ram;
 pub mod memory_mapper;
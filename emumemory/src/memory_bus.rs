@@ -0,0 +1,214 @@
+
+pub mod emu_memory {
+
+    use std::fmt;
+
+    use crate::base_memory::emu_memory::BaseMemory;
+
+    // Which addresses a device answers
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum MemoryDecode {
+        // start to end, both included
+        Range(u16, u16),
+        // The address bits in the mask equal the value, the partial decoding
+        // chips like the VCS's use
+        Select(u16, u16),
+    }
+
+    impl MemoryDecode {
+
+        pub fn contains(&self, address: u16) -> bool {
+            match *self {
+                MemoryDecode::Range(start, end) => (start..=end).contains(&address),
+                MemoryDecode::Select(mask, value) => address & mask == value,
+            }
+        }
+
+        // Whether any address is in both, compared as mask and value pairs
+        pub fn overlaps(&self, other: &MemoryDecode) -> bool {
+            let (selects, others) = (self.get_selects(), other.get_selects());
+            selects.iter().any(|(mask, value)| {
+                others.iter().any(|(other_mask, other_value)| (value ^ other_value) & mask & other_mask == 0)
+            })
+        }
+
+        // A range as the aligned power of two blocks that cover it, at most 32
+        fn get_selects(&self) -> Vec<(u16, u16)> {
+            match *self {
+                MemoryDecode::Range(start, end) => {
+                    let (mut start, end) = (start as u32, end as u32 + 1);
+                    let mut selects = Vec::new();
+                    while start < end {
+                        // The largest block start is aligned to that still fits
+                        let mut size = if start == 0 { 0x10000 } else { start & start.wrapping_neg() };
+                        while start + size > end {
+                            size >>= 1;
+                        }
+                        selects.push((!(size - 1) as u16, start as u16));
+                        start += size;
+                    }
+                    selects
+                },
+                MemoryDecode::Select(mask, value) => vec![(mask, value & mask)],
+            }
+        }
+
+        // Where offsets count from
+        fn get_base(&self) -> u16 {
+            match *self {
+                MemoryDecode::Range(start, _) => start,
+                MemoryDecode::Select(..) => 0,
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub enum MemoryBusError {
+        // The new device's name and the one it overlaps
+        Overlap(String, String),
+    }
+
+    impl fmt::Display for MemoryBusError {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                MemoryBusError::Overlap(name, other) => write!(f, "{} overlaps {}", name, other),
+            }
+        }
+    }
+
+    impl std::error::Error for MemoryBusError {}
+
+    #[derive(Debug, Clone)]
+    pub struct MemoryMapping<D> {
+        pub name: String,
+        pub decode: MemoryDecode,
+        // Applied to the offset, a device smaller than its range is mirrored
+        pub mirror: u16,
+        pub device: D,
+    }
+
+    impl<D> MemoryMapping<D> {
+
+        pub fn get_offset(&self, address: u16) -> u16 {
+            address.wrapping_sub(self.decode.get_base()) & self.mirror
+        }
+    }
+
+    // A memory map of devices named by D, usually an enum of the console's chips.
+    // Reads and writes go to a handler with the device and its offset, unmapped
+    // reads get the last byte on the bus.
+    pub struct MemoryMap<D> {
+        mappings: Vec<MemoryMapping<D>>,
+        open_bus: u8,
+    }
+
+    impl<D: Copy> Default for MemoryMap<D> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<D: Copy> MemoryMap<D> {
+
+        pub fn new() -> MemoryMap<D> {
+            Self {
+                mappings: Vec::new(),
+                open_bus: 0,
+            }
+        }
+
+        pub fn add(&mut self, name: &str, decode: MemoryDecode, mirror: u16, device: D) -> Result<(), MemoryBusError> {
+            let overlap = self.mappings.iter().find(|mapping| decode.overlaps(&mapping.decode));
+            if let Some(mapping) = overlap {
+                return Err(MemoryBusError::Overlap(name.to_string(), mapping.name.clone()));
+            }
+
+            self.mappings.push(MemoryMapping { name: name.to_string(), decode, mirror, device });
+            Ok(())
+        }
+
+        pub fn get_mapping(&self, address: u16) -> Option<&MemoryMapping<D>> {
+            self.mappings.iter().find(|mapping| mapping.decode.contains(address))
+        }
+
+        pub fn get_mappings(&self) -> &[MemoryMapping<D>] {
+            &self.mappings
+        }
+
+        // The device at address and the offset into it
+        pub fn decode(&self, address: u16) -> Option<(D, u16)> {
+            self.get_mapping(address).map(|mapping| (mapping.device, mapping.get_offset(address)))
+        }
+
+        pub fn read<F: FnMut(D, u16) -> u8>(&mut self, address: u16, mut read: F) -> u8 {
+            if let Some((device, offset)) = self.decode(address) {
+                self.open_bus = read(device, offset);
+            }
+            self.open_bus
+        }
+
+        // Returns whether a device took the write, the byte stays on the bus either way
+        pub fn write<F: FnMut(D, u16, u8)>(&mut self, address: u16, byte: u8, mut write: F) -> bool {
+            self.open_bus = byte;
+            match self.decode(address) {
+                Some((device, offset)) => {
+                    write(device, offset, byte);
+                    true
+                },
+                None => false,
+            }
+        }
+
+        pub fn get_open_bus(&self) -> u8 {
+            self.open_bus
+        }
+    }
+
+    // A memory map that owns its devices, for cores whose memory is all
+    // BaseMemory. Devices are named by get_name.
+    pub struct MemoryBus {
+        map: MemoryMap<usize>,
+        devices: Vec<Box<dyn BaseMemory>>,
+    }
+
+    impl Default for MemoryBus {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl MemoryBus {
+
+        pub fn new() -> MemoryBus {
+            Self {
+                map: MemoryMap::new(),
+                devices: Vec::new(),
+            }
+        }
+
+        pub fn add(&mut self, decode: MemoryDecode, mirror: u16, device: Box<dyn BaseMemory>) -> Result<(), MemoryBusError> {
+            self.map.add(&device.get_name(), decode, mirror, self.devices.len())?;
+            self.devices.push(device);
+            Ok(())
+        }
+
+        pub fn get_device_mut(&mut self, name: &str) -> Option<&mut Box<dyn BaseMemory>> {
+            self.devices.iter_mut().find(|device| device.get_name() == name)
+        }
+
+        pub fn get_map(&self) -> &MemoryMap<usize> {
+            &self.map
+        }
+
+        pub fn read(&mut self, address: u16) -> u8 {
+            let devices = &mut self.devices;
+            self.map.read(address, |device, offset| devices[device].read(offset))
+        }
+
+        pub fn write(&mut self, address: u16, byte: u8) -> bool {
+            let devices = &mut self.devices;
+            self.map.write(address, byte, |device, offset, byte| devices[device].write(offset, byte))
+        }
+    }
+}
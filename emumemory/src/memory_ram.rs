@@ -1,18 +1,26 @@
-
 pub mod emu_memory {
 
+    use crate::base_memory::emu_memory::{MemoryError, MemoryErrors};
+
     pub struct MemoryRam {
         memory: Vec<u8>,
-        name: String
+        name: String,
+        errors: MemoryErrors,
     }
 
     impl MemoryRam {
         pub fn new(_name: String, size: u16) -> MemoryRam{
             MemoryRam {
                 memory: vec![0; size as usize],
-                name: _name.clone()
+                name: _name.clone(),
+                errors: MemoryErrors::default(),
             }
         }
+
+        // Reads without going through the bus, for tracers and debuggers
+        pub fn peek(&self, location: u16) -> u8 {
+            self.memory.get(location as usize).copied().unwrap_or(0)
+        }
     }
 
     impl crate::base_memory::emu_memory::BaseMemory for MemoryRam{
@@ -20,23 +28,28 @@ pub mod emu_memory {
             self.name.clone()
         }
 
-        fn read(&mut self, _location: u16) -> u8 {
-            self.memory[_location as usize]
+        fn get_size(&self) -> usize {
+            self.memory.len()
         }
 
-        fn write(&mut self, _location: u16, _byte: u8) {
+        fn get_errors_mut(&mut self) -> &mut MemoryErrors {
+            &mut self.errors
+        }
+
+        fn try_read(&mut self, _location: u16) -> Result<u8, MemoryError> {
+            self.check_location(_location)?;
+            Ok(self.memory[_location as usize])
+        }
+
+        fn try_write(&mut self, _location: u16, _byte: u8) -> Result<(), MemoryError> {
+            self.check_location(_location)?;
             self.memory[_location as usize] = _byte;
+            Ok(())
         }
 
         fn load_data(&mut self, data: &[u8]) {
             self.memory = data.to_vec();
         }
-        
-        fn check_location(&self, location: u16) {
-            if location as usize > self.memory.len() {
-                panic!("beyond memory");
-            }            
-        }
     }
     
-}
\ No newline at end of file
+}
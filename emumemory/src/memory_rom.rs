@@ -1,11 +1,53 @@
 
 pub mod emu_memory {
-    use crate::base_memory::emu_memory::BaseMemory;
+    use crate::base_memory::emu_memory::{BaseMemory, MemoryError, MemoryErrors};
 
+    // Writes are refused with MemoryError::ReadOnly, the data only changes with load_data
+    pub struct MemoryRom {
+        memory: Vec<u8>,
+        name: String,
+        errors: MemoryErrors,
+    }
+
+    impl MemoryRom {
+        pub fn new(name: String, data: &[u8]) -> MemoryRom {
+            MemoryRom {
+                memory: data.to_vec(),
+                name,
+                errors: MemoryErrors::default(),
+            }
+        }
+
+        pub fn peek(&self, location: u16) -> u8 {
+            self.memory.get(location as usize).copied().unwrap_or(0)
+        }
+    }
+
+    impl BaseMemory for MemoryRom {
+        fn get_name(&self) -> String {
+            self.name.clone()
+        }
+
+        fn get_size(&self) -> usize {
+            self.memory.len()
+        }
+
+        fn get_errors_mut(&mut self) -> &mut MemoryErrors {
+            &mut self.errors
+        }
+
+        fn try_read(&mut self, location: u16) -> Result<u8, MemoryError> {
+            self.check_location(location)?;
+            Ok(self.memory[location as usize])
+        }
+
+        fn try_write(&mut self, location: u16, _byte: u8) -> Result<(), MemoryError> {
+            self.check_location(location)?;
+            Err(MemoryError::ReadOnly(self.get_name(), location))
+        }
 
-    pub trait MemoryRom: BaseMemory {
-        fn write(&mut self, _location: u16, _byte: u8) {
-            panic!("Cannot write to ROM");
+        fn load_data(&mut self, data: &[u8]) {
+            self.memory = data.to_vec();
         }
     }
        
@@ -1,5 +1,7 @@
 
-pub use crate::base_memory::emu_memory::BaseMemory;
+pub use crate::base_memory::emu_memory::{BaseMemory, MemoryError, MemoryErrorPolicy};
+
+pub use crate::memory_bus::emu_memory::{MemoryBus, MemoryDecode, MemoryMap};
 
 pub use crate::memory_rom::emu_memory::MemoryRom;
 
@@ -1,6 +1,8 @@
 
-use emumemory::base_memory::emu_memory::BaseMemory;
+use emumemory::base_memory::emu_memory::{BaseMemory, MemoryError, MemoryErrorPolicy};
+use emumemory::memory_bus::emu_memory::{MemoryBus, MemoryBusError, MemoryDecode, MemoryMap};
 use emumemory::memory_ram::emu_memory::MemoryRam;
+use emumemory::memory_rom::emu_memory::MemoryRom;
 
 #[test]
 fn test_ram_load_read() {
@@ -37,3 +39,132 @@ fn test_ram_name() {
     assert_eq!(memory.get_name(), String::from("RAM Test"));
 }
 
+#[test]
+fn test_ram_out_of_range() {
+    let mut memory = MemoryRam::new(String::from("RAM Test"), 10);
+
+    assert_eq!(memory.try_read(9), Ok(0));
+    assert_eq!(memory.try_read(10), Err(MemoryError::OutOfRange(String::from("RAM Test"), 10)));
+    assert_eq!(memory.try_write(10, 1), Err(MemoryError::OutOfRange(String::from("RAM Test"), 10)));
+
+    // The unchecked calls go by the policy instead of panicking
+    memory.set_policy(MemoryErrorPolicy::Ignore);
+    memory.write(10, 1);
+    assert_eq!(memory.read(10), 0);
+    assert_eq!(memory.take_error(), None);
+}
+
+#[test]
+fn test_trap_policy() {
+    let mut memory = MemoryRam::new(String::from("RAM Test"), 10);
+    memory.set_policy(MemoryErrorPolicy::Trap);
+
+    memory.read(20);
+    memory.write(30, 1);
+    assert_eq!(memory.take_error(), Some(MemoryError::OutOfRange(String::from("RAM Test"), 20)));
+    assert_eq!(memory.take_error(), None);
+}
+
+#[test]
+fn test_rom_is_read_only() {
+    let mut memory = MemoryRom::new(String::from("ROM Test"), &[1, 2, 3]);
+    memory.set_policy(MemoryErrorPolicy::Trap);
+
+    assert_eq!(memory.try_write(1, 9), Err(MemoryError::ReadOnly(String::from("ROM Test"), 1)));
+    memory.write(2, 9);
+    assert_eq!(memory.take_error(), Some(MemoryError::ReadOnly(String::from("ROM Test"), 2)));
+    assert_eq!(memory.read(2), 3);
+    assert_eq!(memory.try_read(3), Err(MemoryError::OutOfRange(String::from("ROM Test"), 3)));
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum TestDevice {
+    Ram,
+    Registers,
+    Rom,
+}
+
+fn nes_map() -> MemoryMap<TestDevice> {
+    let mut map = MemoryMap::new();
+    map.add("RAM", MemoryDecode::Range(0x0000, 0x1fff), 0x07ff, TestDevice::Ram).unwrap();
+    map.add("Registers", MemoryDecode::Range(0x2000, 0x3fff), 0x0007, TestDevice::Registers).unwrap();
+    map.add("ROM", MemoryDecode::Range(0x8000, 0xffff), 0x7fff, TestDevice::Rom).unwrap();
+    map
+}
+
+#[test]
+fn test_map_decode() {
+    let map = nes_map();
+
+    assert_eq!(map.decode(0x0801), Some((TestDevice::Ram, 0x0001)));
+    assert_eq!(map.decode(0x3ffa), Some((TestDevice::Registers, 0x0002)));
+    assert_eq!(map.decode(0xc000), Some((TestDevice::Rom, 0x4000)));
+    assert_eq!(map.decode(0x5000), None);
+    assert_eq!(map.get_mapping(0x2000).unwrap().name, "Registers");
+}
+
+#[test]
+fn test_map_select() {
+    // The VCS's RIOT RAM and I/O, told apart by A9 with A12 and A7 as chip selects
+    let mut map = MemoryMap::new();
+    map.add("RIOT RAM", MemoryDecode::Select(0x1280, 0x0080), 0x007f, TestDevice::Ram).unwrap();
+    map.add("RIOT I/O", MemoryDecode::Select(0x1280, 0x0280), 0x001f, TestDevice::Registers).unwrap();
+
+    assert_eq!(map.decode(0x00f0), Some((TestDevice::Ram, 0x0070)));
+    assert_eq!(map.decode(0x0184), Some((TestDevice::Ram, 0x0004)));
+    assert_eq!(map.decode(0x0284), Some((TestDevice::Registers, 0x0004)));
+    assert_eq!(map.decode(0x1080), None);
+}
+
+#[test]
+fn test_map_overlap() {
+    let mut map = nes_map();
+
+    let error = map.add("Cartridge RAM", MemoryDecode::Range(0x6000, 0x8000), 0x1fff, TestDevice::Ram).unwrap_err();
+    assert!(matches!(error, MemoryBusError::Overlap(ref name, ref other) if name == "Cartridge RAM" && other == "ROM"));
+    assert!(map.add("TIA", MemoryDecode::Select(0x1080, 0x0000), 0x003f, TestDevice::Registers).is_err());
+    assert!(map.add("Cartridge RAM", MemoryDecode::Range(0x6000, 0x7fff), 0x1fff, TestDevice::Ram).is_ok());
+}
+
+#[test]
+fn test_decode_overlaps() {
+    let decodes = [
+        MemoryDecode::Range(0x0000, 0xffff), MemoryDecode::Range(0x0000, 0x007f), MemoryDecode::Range(0x0080, 0x00ff),
+        MemoryDecode::Range(0x0081, 0x027e), MemoryDecode::Range(0x1234, 0x1234), MemoryDecode::Range(0xfff0, 0xffff),
+        MemoryDecode::Select(0x1080, 0x0000), MemoryDecode::Select(0x1280, 0x0080), MemoryDecode::Select(0x1280, 0x0280),
+        MemoryDecode::Select(0x1000, 0x1000), MemoryDecode::Select(0xffff, 0x1234), MemoryDecode::Select(0x0001, 0x0001),
+    ];
+    // Against every address in both
+    for decode in &decodes {
+        for other in &decodes {
+            let expected = (0..=0xffff).any(|address| decode.contains(address) && other.contains(address));
+            assert_eq!(decode.overlaps(other), expected, "{:?} {:?}", decode, other);
+        }
+    }
+}
+
+#[test]
+fn test_map_open_bus() {
+    let mut map = nes_map();
+    let mut ram = [0u8; 0x800];
+
+    assert!(map.write(0x0002, 0x42, |_, offset, byte| ram[offset as usize] = byte));
+    assert_eq!(map.read(0x0802, |_, offset| ram[offset as usize]), 0x42);
+    // Nothing answers, the last byte is read back
+    assert!(!map.write(0x5000, 0x17, |_, _, _| panic!("Nothing is mapped there")));
+    assert_eq!(map.read(0x5000, |_, _| panic!("Nothing is mapped there")), 0x17);
+}
+
+#[test]
+fn test_bus_devices() {
+    let mut bus = MemoryBus::new();
+    bus.add(MemoryDecode::Range(0x0000, 0x1fff), 0x07ff,
+        Box::new(MemoryRam::new(String::from("Work RAM"), 0x0800))).unwrap();
+
+    assert!(bus.write(0x1805, 0x99));
+    assert_eq!(bus.read(0x0005), 0x99);
+    assert_eq!(bus.read(0x4000), 0x99);
+    assert_eq!(bus.get_device_mut("Work RAM").unwrap().read(0x0005), 0x99);
+    assert!(bus.add(MemoryDecode::Range(0x1000, 0x2fff), 0x0fff,
+        Box::new(MemoryRam::new(String::from("More RAM"), 0x1000))).is_err());
+}
@@ -22,13 +22,17 @@ pub mod nes {
         irq_set: bool,
         interrupt_set: bool,
         frame_counter: u16,
-        pub ppu_dma_write: u16,
+        ppu_dma_write: u16,
         ppu_dma_address: u16,
         ppu_dma_read: bool,
-        pub apu_dma_write: u16,
+        apu_dma_write: u16,
         apu_dma_address: u16,
         apu_dma_read: bool,
         apu_dma_delay: u16,
+        // The DMA drove the bus this cycle instead of the CPU
+        dma_cycle: bool,
+        // Finished, RDY stays low while the CPU repeats its held read
+        dma_release: bool,
         channel0: NesApuPulseChannel,
         channel1: NesApuPulseChannel,
         channel2: NesApuTriangleChannel,
@@ -54,6 +58,8 @@ pub mod nes {
                 apu_dma_address: 0,
                 apu_dma_read: true,
                 apu_dma_delay: 0,
+                dma_cycle: false,
+                dma_release: false,
                 channel0: NesApuPulseChannel::new(),
                 channel1: NesApuPulseChannel::new(),
                 channel2: NesApuTriangleChannel::new(),
@@ -61,7 +67,48 @@ pub mod nes {
             }
         }
         
-        pub fn execute_tick(&mut self, addr: &mut AddressBus, ppu: &mut NesPpu) {
+        // RDY is pulled low while a DMA is waiting or running
+        pub fn is_dma_active(&self) -> bool {
+            self.ppu_dma_write > 0 || self.apu_dma_write > 0 || self.dma_release
+        }
+
+        pub fn is_dma_cycle(&self) -> bool {
+            self.dma_cycle
+        }
+
+        // location is from 0x4000, reads of the write only registers get open_bus
+        pub fn register_read(&mut self, location: u16, open_bus: u8) -> u8 {
+            match location {
+                0x16 => (self.get_left_controller() & 0x1f) + (open_bus & 0xe0),
+                0x17 => (self.get_right_controller() & 0x1f) + (open_bus & 0xe0),
+                _ => open_bus,
+            }
+        }
+
+        pub fn register_write(&mut self, location: u16, byte: u8) {
+            match location {
+                0x10 => {},
+                // PPU DMA
+                0x14 => {
+                    self.ppu_dma_write = 256;
+                    self.ppu_dma_address = (byte as u16) << 8;
+                },
+                0x15 if byte & 0x10 > 0 => {
+                    let apu_address: u16 = self.read(0x12) as u16;
+                    self.apu_dma_address = 0xC0 + (apu_address << 8);
+                    let length = self.read(0x13) as u16;
+                    self.apu_dma_write = (length << 4) + 1;
+                },
+                // 0x4018 to 0x401f are the CPU test registers
+                0x18.. => {},
+                _ => self.write(location, byte),
+            }
+        }
+
+        // The DMAs only take the bus once the CPU is halted on a read. OAM DMA
+        // reads on get cycles and writes on put cycles, so it takes 513 or 514
+        // cycles depending on where the CPU halted.
+        pub fn execute_tick(&mut self, addr: &mut AddressBus, ppu: &mut NesPpu, cpu_halted: bool, put_cycle: bool) {
 
             
             if self.apu_dma_delay > 0 {
@@ -78,7 +125,10 @@ pub mod nes {
             }
              
 
-            if self.apu_dma_write > 0 {
+            self.dma_cycle = false;
+            if !cpu_halted {
+                // Waiting for the CPU to reach a read
+            } else if self.apu_dma_write > 0 {
                 if self.apu_dma_read {
                     // read byte
                     addr.address = self.apu_dma_address;
@@ -90,51 +140,32 @@ pub mod nes {
                     self.apu_dma_read = true;
                     self.apu_dma_write -= 1;
                 }
+                self.dma_cycle = true;
 
             } else if self.ppu_dma_write > 0 {
                 if self.ppu_dma_read {
-                    addr.address = self.ppu_dma_address;
-                    self.ppu_dma_address += 1;
-                    self.ppu_dma_read = false;
+                    // A put cycle here is the alignment cycle, the CPU repeats its read
+                    if !put_cycle {
+                        addr.address = self.ppu_dma_address;
+                        self.ppu_dma_address += 1;
+                        self.ppu_dma_read = false;
+                        self.dma_cycle = true;
+                    }
                 } else {
                     NesPpuRunner::oam_write(ppu, (256 - self.ppu_dma_write) as u8, addr.byte);
                     self.ppu_dma_read = true;
                     self.ppu_dma_write -= 1;
+                    self.dma_cycle = true;
                 }
 
-            } 
-            
-            if (0x4000..0x4018).contains(&addr.address) {
-                let location = addr.address - 0x4000;
-
-                if addr.write {
-                    // PPU DMA
-                    if location == 0x10 {
-                        addr.byte = 0x00;
-                    }
-                    else if location == 0x14 {
-                        self.ppu_dma_write = 256;
-                        self.ppu_dma_address = (addr.byte as u16) << 8;
-                    } else if location == 0x15 && ((addr.byte & 0x10) > 0) {
-                        let apu_address: u16 = self.read(0x12) as u16;
-                        self.apu_dma_address = 0xC0 + (apu_address << 8);
-                        let length = self.read(0x13) as u16;
-                        self.apu_dma_write = (length << 4) + 1;
-                    } else {
-                        self.write(location, addr.byte);
-                    }
-                    addr.write = false;
-                } else {
-                    if location == 0x16 {
-                        addr.byte = (self.get_left_controller() & 0x1f) + (addr.byte & 0xe0);
-                    } else if location == 0x17 {
-                        addr.byte = (self.get_right_controller() & 0x1f) + (addr.byte & 0xe0);
-                    }
-                }
-            } else if (0x4018..0x401f).contains(&addr.address) {
-                addr.write = false;
+            } else if self.dma_release {
+                self.dma_release = false;
             }
 
+            if self.dma_cycle && self.ppu_dma_write == 0 && self.apu_dma_write == 0 {
+                self.dma_release = true;
+            }
+            
             if self.frame_counter > 0 {
                 self.frame_counter -= 1;
             }
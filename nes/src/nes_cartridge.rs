@@ -4,7 +4,7 @@ pub mod nes {
         
         fn cpu_read(&self, location: u16) -> u8;
 
-        fn cpu_write(&self, location: u16, byte: u8);
+        fn cpu_write(&mut self, location: u16, byte: u8);
 
         fn ppu_read(&self, location: u16) -> u8;
 
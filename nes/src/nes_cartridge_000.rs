@@ -2,13 +2,13 @@
 
 pub mod nes {
 
-    use emucpu::m6502::emu_cpu::AddressBus;
+use emumemory::prelude::*;
 
 use crate::nes_cartridge::nes::NesCartridge;
 
     pub struct NesCartridge000 {
-        cpu_prog_rom_0: Vec<u8>,
-        cpu_prog_rom_1: Vec<u8>,
+        // $8000 - $FFFF, a 16K ROM is there twice
+        cpu_prog_rom: MemoryRom,
         ppu_char_rom_0: Vec<u8>,
         ppu_char_rom_1: Vec<u8>,
     }
@@ -17,50 +17,34 @@ use crate::nes_cartridge::nes::NesCartridge;
 
         pub fn new() -> NesCartridge000 {
             Self {
-                cpu_prog_rom_0: vec!(0; 0x4000),
-                cpu_prog_rom_1: vec!(0; 0x4000),
+                cpu_prog_rom: MemoryRom::new(String::from("PRG ROM"), &[0; 0x8000]),
                 ppu_char_rom_0: vec!(0; 0x2000),
                 ppu_char_rom_1: vec!(0; 0x2000),
             }
         }
 
-        pub fn execute_tick(&mut self, addr: &mut AddressBus) {
-
-            if addr.address >= 0x6000 {
-                if addr.write {
-                    self.cpu_write(addr.address, addr.byte);
-                    addr.write = false;
-                } else {
-                    addr.byte = self.cpu_read(addr.address);
-                }
-            }
-
+        // Writes to it go to the memory error policy
+        pub fn get_prog_rom_mut(&mut self) -> &mut MemoryRom {
+            &mut self.cpu_prog_rom
         }
     }
 
     impl NesCartridge for NesCartridge000 {    
 
-        fn cpu_read(&self, mut location: u16) -> u8 {
-
-            if location < 0xc000 {
-
-                if location >= 0x8000 {
-                    location -= 0x8000;
-                }
-                else {
-                    location -= 0x4000;
-                }
-                
-                return self.cpu_prog_rom_0[location as usize];
+        fn cpu_read(&self, location: u16) -> u8 {
 
+            if location >= 0x8000 {
+                return self.cpu_prog_rom.peek(location - 0x8000);
             }
 
-            location -= 0xc000;
-            self.cpu_prog_rom_1[location as usize]
+            self.cpu_prog_rom.peek(location - 0x4000)
         }
     
-        fn cpu_write(&self, location: u16, _byte: u8) {
-            //eprintln!("This cartridge does not support cpu write {}", location);
+        // There's no PRG RAM, $6000 - $7FFF ignores writes
+        fn cpu_write(&mut self, location: u16, byte: u8) {
+            if location >= 0x8000 {
+                self.cpu_prog_rom.write(location - 0x8000, byte);
+            }
         }
 
         fn ppu_read(&self, mut location: u16) -> u8 {
@@ -81,12 +65,10 @@ use crate::nes_cartridge::nes::NesCartridge;
         fn load_prog_rom(&mut self, data: Vec<u8>) {
             
             if data.len() == 0x4000 {
-                self.cpu_prog_rom_0 = data[0..0x4000].to_vec();
-                self.cpu_prog_rom_1 = data[0..0x4000].to_vec();
+                self.cpu_prog_rom.load_data(&data.repeat(2));
             }
             if data.len() == 0x8000 {
-                self.cpu_prog_rom_0 = data[0..0x4000].to_vec();
-                self.cpu_prog_rom_1 = data[0x4000..0x8000].to_vec();
+                self.cpu_prog_rom.load_data(&data);
             }
         }
     
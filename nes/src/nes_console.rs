@@ -19,9 +19,22 @@ pub mod nes {
         pub channel_mix: Vec<u16>,
     }
 
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    enum NesDevice {
+        Ram,
+        Ppu,
+        Apu,
+        Cartridge,
+    }
+
     pub struct NesConsole {
         inframe: Mutex<bool>,
+        // $4020 to $5FFF has nothing on it without an expansion cartridge
+        memory_map: MemoryMap<NesDevice>,
         cpu_runner: M6502Runner,
+        debugger: M6502Debugger,
+        // PPU ticks into the frame, kept while the debugger stops it
+        frame_ticks: i32,
         addr: AddressBus,
         apu: NesApu,
         ppu: NesPpu,
@@ -44,10 +57,19 @@ pub mod nes {
             cartridge.load_prog_rom(ines_file.get_prog_rom_data());
             cartridge.load_char_rom(ines_file.get_char_rom_data());
 
+            let mut memory_map = MemoryMap::new();
+            memory_map.add("CPU Work RAM", MemoryDecode::Range(0x0000, 0x1fff), 0x07ff, NesDevice::Ram).unwrap();
+            memory_map.add("PPU Registers", MemoryDecode::Range(0x2000, 0x3fff), 0x0007, NesDevice::Ppu).unwrap();
+            memory_map.add("APU and I/O", MemoryDecode::Range(0x4000, 0x401f), 0x001f, NesDevice::Apu).unwrap();
+            memory_map.add("Cartridge", MemoryDecode::Range(0x6000, 0xffff), 0xffff, NesDevice::Cartridge).unwrap();
+
             let mut temp_instance = Self {
                 inframe: Mutex::new(false),
+                memory_map,
                 cpu_runner: M6502Runner::new(M6502Version::Nes),
-                addr: AddressBus { address: 0 , write: false, byte: 0, is_accumulator: false, is_abs_y: false },
+                debugger: M6502Debugger::new(),
+                frame_ticks: 0,
+                addr: AddressBus { address: 0 , write: false, byte: 0 },
                 apu: NesApu::new(),
                 ppu: NesPpu::new(),
                 cartridge,
@@ -74,35 +96,83 @@ pub mod nes {
             self.apu.get_audio_buffer()
         }
 
-        fn ram_execute_tick(&mut self) {
-        
-            if (0x0000..0x2000).contains(&self.addr.address) {
-                let location = self.addr.address % 0x800;  // mirroring
-                if self.addr.write {
-                    self.cpu_work_ram.write(location, self.addr.byte);
-                    self.addr.write = false;
-                } else {
-                    self.addr.byte = self.cpu_work_ram.read(location);
-                }
-            }   
+        // Serves whichever of the CPU or a DMA drove the bus this cycle
+        fn bus_access(&mut self) {
+            let address = self.addr.address;
+            let (ram, ppu, apu, cartridge) = (&mut self.cpu_work_ram, &mut self.ppu, &mut self.apu, &mut self.cartridge);
+            if self.addr.write {
+                self.memory_map.write(address, self.addr.byte, |device, location, byte| match device {
+                    NesDevice::Ram => ram.write(location, byte),
+                    NesDevice::Ppu => NesPpuRunner::register_write(ppu, location, byte),
+                    NesDevice::Apu => apu.register_write(location, byte),
+                    NesDevice::Cartridge => cartridge.cpu_write(address, byte),
+                });
+                self.addr.write = false;
+            } else {
+                let open_bus = self.memory_map.get_open_bus();
+                self.addr.byte = self.memory_map.read(address, |device, location| match device {
+                    NesDevice::Ram => ram.read(location),
+                    NesDevice::Ppu => NesPpuRunner::register_read(ppu, location, cartridge),
+                    NesDevice::Apu => apu.register_read(location, open_bus),
+                    NesDevice::Cartridge => cartridge.cpu_read(address),
+                });
+            }
+        }
+
+        // The CPU's view of memory without side effects, registers read as open bus
+        fn peek(memory_map: &MemoryMap<NesDevice>, cartridge: &NesCartridge000, ram: &MemoryRam, address: u16) -> u8 {
+            match memory_map.decode(address) {
+                Some((NesDevice::Ram, location)) => ram.peek(location),
+                Some((NesDevice::Cartridge, _)) => cartridge.cpu_read(address),
+                _ => 0xff,
+            }
+        }
 
+        // Traces every instruction in the nestest.log format, with the PPU's scan line and dot
+        pub fn set_tracer(&mut self, tracer: Option<M6502Tracer>) {
+            self.cpu_runner.set_tracer(tracer);
         }
 
+        // Assembles source at address into the work RAM, for patching code while debugging
+        pub fn patch_ram(&mut self, address: u16, source: &str) -> Result<(), M6502AssemblerError> {
+            let assembly = M6502Assembler::new(self.cpu_runner.get_variant()).assemble(source, address)?;
+            let (memory_map, ram) = (&self.memory_map, &mut self.cpu_work_ram);
+            assembly.write_to(|address, byte| match memory_map.decode(address) {
+                Some((NesDevice::Ram, location)) => ram.write(location, byte),
+                _ => eprintln!("{:04x} isn't in RAM, not patched", address),
+            });
+            Ok(())
+        }
+
+        // Trap stops the debugger on a write to the PRG ROM. The work RAM is
+        // behind a 0x07ff mirror so it can't fail.
+        pub fn set_memory_policy(&mut self, policy: MemoryErrorPolicy) {
+            self.cartridge.get_prog_rom_mut().set_policy(policy);
+        }
+
+        pub fn get_cpu(&self) -> &M6502 {
+            self.cpu_runner.get_cpu()
+        }
+
+        pub fn get_debugger_mut(&mut self) -> &mut M6502Debugger {
+            &mut self.debugger
+        }
+
+        // Returns nothing while the debugger has the console stopped
         pub fn run_frame(&mut self) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
 
-            self.frame += 1;
-            let mut ticks: i32 = 0;
+            if self.frame_ticks == 0 {
+                self.frame += 1;
+            }
 
             self.inframe.lock();
 
-            while ticks < TICKS_PER_FRAME as i32 {
+            while self.frame_ticks < TICKS_PER_FRAME as i32 {
 
-                if (0x4020..0x6000).contains(&self.addr.address) {
-                    self.addr.write = false;
-                    //eprintln!("unknown address {}", self.addr.address);
+                if self.debugger.is_stopped() {
+                    return (None, None);
                 }
-                self.cartridge.execute_tick(&mut self.addr);
-                self.ram_execute_tick();
+                let ticks = self.frame_ticks;
 
                 if (ticks % 2) == 0 {
                     // APU should be here?
@@ -115,22 +185,39 @@ pub mod nes {
 
                 if (ticks % 3) == 0 {
                     
-                    self.apu.execute_tick(&mut self.addr, &mut self.ppu);
-                    if self.apu.ppu_dma_write == 0 && self.apu.apu_dma_write == 0 {
+                    // The CPU holds its read while the DMAs have RDY low
+                    self.cpu_runner.set_rdy(!self.apu.is_dma_active());
+                    let put_cycle = (ticks / 3) % 2 == 1;
+                    self.apu.execute_tick(&mut self.addr, &mut self.ppu, self.cpu_runner.is_halted(), put_cycle);
+                    if !self.apu.is_dma_cycle() {
                         self.cpu_runner.execute_tick(&mut self.addr);
+                        self.debugger.check_tick(&self.cpu_runner);
+                        let (memory_map, cartridge, ram) = (&self.memory_map, &self.cartridge, &self.cpu_work_ram);
+                        self.cpu_runner.trace(Some((self.ppu.scan_line, self.ppu.cycle)),
+                            |address| Self::peek(memory_map, cartridge, ram, address));
+                    } else {
+                        // Stolen cycles still count, so traces keep step with other emulators
+                        self.cpu_runner.skip_cycle();
                     }
-                    NesPpuRunner::execute_memory(&mut self.ppu, &mut self.addr, &self.cartridge);
+                    self.bus_access();
+                    self.debugger.check_memory(self.cartridge.get_prog_rom_mut());
                 }
 
+                let scan_line = self.ppu.scan_line;
                 NesPpuRunner::execute_tick(&mut self.ppu, &self.cartridge);
+                if self.ppu.scan_line != scan_line {
+                    self.debugger.end_scanline();
+                }
                 //self.ppu.execute_tick(&mut self.addr, &self.cartridge, ticks);
 
                 if self.ppu.nmi_set {
                     self.cpu_runner.set_nmi();
                     self.ppu.nmi_set = false;
                 }
-                ticks += 1;
+                self.frame_ticks += 1;
             }
+            self.frame_ticks = 0;
+            self.debugger.end_frame();
             self.read_gamepad();
             
             //let video = self.ppu.get_screen();
@@ -1,6 +1,5 @@
 pub mod nes {
 
-    use emucpu::prelude::*;
     use emumemory::prelude::*;
 
     use crate::nes_console::nes::TICKS_PER_FRAME;
@@ -311,65 +310,50 @@ pub mod nes {
 
     impl NesPpuRunner {
     
-        pub fn execute_memory(ppu: &mut NesPpu, addr: &mut AddressBus, cartridge: &NesCartridge000) {
-
-            if (0x2000..0x4000).contains(&addr.address) {
-                if addr.write {
-                    Self::ppu_register_write(ppu, addr);
-                    addr.write = false;
-                } else {
-                    addr.byte = Self::ppu_register_read(ppu,addr.address, cartridge);
-                }
-            }                
-        }
-
-        fn ppu_register_write(ppu: &mut NesPpu, addr: &mut AddressBus) {
-
-
-            let mut location = addr.address;
-            location %= 8;
+        // location is the register, 0 to 7
+        pub fn register_write(ppu: &mut NesPpu, location: u16, byte: u8) {
 
             if location != 0x06 {
-                ppu.video_bus.set_byte(addr.byte);
+                ppu.video_bus.set_byte(byte);
             }
 
             match location {
                 0x00 => {
-                    ppu.registers.write(0, addr.byte);
+                    ppu.registers.write(0, byte);
                     let control_register = PpuControlRegister::new(ppu.registers.read(0));
                     let status_register = PpuStatusRegister::new(ppu.registers.read(2));
-                    if addr.byte & 0x80 != 0 && control_register.vblank_nmi_enable() && status_register.vblank_flag() {
+                    if byte & 0x80 != 0 && control_register.vblank_nmi_enable() && status_register.vblank_flag() {
                         ppu.nmi_set = true;
                     }
                 },
-                0x01 => ppu.registers.write(1, addr.byte),
-                0x03 => ppu.registers.write(3, addr.byte),
+                0x01 => ppu.registers.write(1, byte),
+                0x03 => ppu.registers.write(3, byte),
                 0x04 => {
-                    ppu.registers.write(4, addr.byte);
+                    ppu.registers.write(4, byte);
                     let oam_address = ppu.registers.read(3);
-                    Self::oam_write(ppu, oam_address, addr.byte);
+                    Self::oam_write(ppu, oam_address, byte);
                     ppu.registers.write(3, oam_address.wrapping_add(1));
                 },
                 0x05 => {
                     if ppu.ppu_addr_first {
-                        ppu.ppu_scroll_x = addr.byte;
+                        ppu.ppu_scroll_x = byte;
                         ppu.ppu_addr_first = false;
                     } else {
-                        ppu.ppu_scroll_y = addr.byte;
+                        ppu.ppu_scroll_y = byte;
                         ppu.ppu_addr_first = true;
                     }
                 },
                 0x06 => {
                     if ppu.ppu_addr_first {
-                        ppu.ppu_addr = ((addr.byte & 0x3F) as u16) << 8;
+                        ppu.ppu_addr = ((byte & 0x3F) as u16) << 8;
                         ppu.ppu_addr_first = false;
                     } else {
-                        ppu.ppu_addr |= addr.byte as u16;
+                        ppu.ppu_addr |= byte as u16;
                         ppu.ppu_addr_first = true;
                     }
                 },
                 0x07 => {
-                    Self::write(ppu, ppu.ppu_addr, addr.byte);
+                    Self::write(ppu, ppu.ppu_addr, byte);
                     let control_register = PpuControlRegister::new(ppu.registers.read(0));
                     ppu.ppu_addr = ppu.ppu_addr.wrapping_add(control_register.vram_address_increment());
                 },
@@ -377,10 +361,7 @@ pub mod nes {
             }
         }
 
-        fn ppu_register_read(ppu: &mut NesPpu, mut location: u16, cartridge: &NesCartridge000) -> u8 {
-            // Mirroring, and bring to zero
-            location %= 8;
-            
+        pub fn register_read(ppu: &mut NesPpu, location: u16, cartridge: &NesCartridge000) -> u8 {
             match location {
                 0x02 => {
                     let mut byte = ppu.registers.read(2);
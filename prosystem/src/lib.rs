@@ -0,0 +1,8 @@
+pub mod prosystem_a78;
+pub mod prosystem_cartridge;
+pub mod prosystem_console;
+pub mod prosystem_maria;
+pub mod prosystem_memory;
+pub mod prosystem_tia;
+pub mod prosystem_bevy;
+pub mod prelude;
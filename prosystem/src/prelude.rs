@@ -0,0 +1,6 @@
+
+pub use crate::prosystem_bevy::prosystem::ProsystemBevy;
+
+pub use crate::prosystem_bevy::prosystem::ProsystemRomFile;
+
+pub use crate::prosystem_cartridge::prosystem::ProsystemCartridgeError;
@@ -0,0 +1,112 @@
+pub mod prosystem {
+
+    use vcs::vcs_console_type::vcs::ConsoleType;
+
+    pub const A78_HEADER_SIZE: usize = 128;
+
+    const A78_MAGIC: &[u8] = b"ATARI7800";
+    const A78_MAGIC_OFFSET: usize = 1;
+    const A78_TITLE_OFFSET: usize = 17;
+    const A78_TITLE_SIZE: usize = 32;
+    const A78_ROM_SIZE_OFFSET: usize = 49;
+    const A78_CART_TYPE_OFFSET: usize = 53;
+    const A78_CONTROLLER_OFFSET: usize = 55;
+    const A78_TV_TYPE_OFFSET: usize = 57;
+
+    // Cartridge type bits
+    const CART_POKEY: u16 =           0x0001;
+    const CART_SUPERGAME: u16 =       0x0002;
+    const CART_SUPERGAME_RAM: u16 =   0x0004;
+    const CART_ROM_AT_4000: u16 =     0x0008;
+    const CART_BANK6_AT_4000: u16 =   0x0010;
+    const CART_ACTIVISION: u16 =      0x0100;
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum ProsystemControllerType {
+        None,
+        Joystick,
+        LightGun,
+        Paddle,
+        Other(u8),
+    }
+
+    impl ProsystemControllerType {
+
+        fn from_byte(byte: u8) -> ProsystemControllerType {
+            match byte {
+                0 => ProsystemControllerType::None,
+                1 => ProsystemControllerType::Joystick,
+                2 => ProsystemControllerType::LightGun,
+                3 => ProsystemControllerType::Paddle,
+                _ => ProsystemControllerType::Other(byte),
+            }
+        }
+    }
+
+    // The 128 byte header in front of .a78 ROM images
+    #[derive(Debug, Clone)]
+    pub struct ProsystemA78Header {
+        pub version: u8,
+        pub title: String,
+        pub rom_size: usize,
+        pub cart_type: u16,
+        pub left_controller: ProsystemControllerType,
+        pub right_controller: ProsystemControllerType,
+        pub console_type: ConsoleType,
+    }
+
+    impl ProsystemA78Header {
+
+        // None when the image has no header
+        pub fn parse(image: &[u8]) -> Option<ProsystemA78Header> {
+            if image.len() < A78_HEADER_SIZE ||
+                &image[A78_MAGIC_OFFSET..A78_MAGIC_OFFSET + A78_MAGIC.len()] != A78_MAGIC {
+                return None;
+            }
+
+            let title_bytes = &image[A78_TITLE_OFFSET..A78_TITLE_OFFSET + A78_TITLE_SIZE];
+            let title = String::from_utf8_lossy(title_bytes)
+                .trim_end_matches(['\0', ' '])
+                .to_string();
+
+            let rom_size = u32::from_be_bytes([image[A78_ROM_SIZE_OFFSET], image[A78_ROM_SIZE_OFFSET + 1],
+                image[A78_ROM_SIZE_OFFSET + 2], image[A78_ROM_SIZE_OFFSET + 3]]) as usize;
+
+            let console_type = if image[A78_TV_TYPE_OFFSET] & 0x01 > 0 { ConsoleType::PAL } else { ConsoleType::NTSC };
+
+            Some(Self {
+                version: image[0],
+                title,
+                rom_size,
+                cart_type: u16::from_be_bytes([image[A78_CART_TYPE_OFFSET], image[A78_CART_TYPE_OFFSET + 1]]),
+                left_controller: ProsystemControllerType::from_byte(image[A78_CONTROLLER_OFFSET]),
+                right_controller: ProsystemControllerType::from_byte(image[A78_CONTROLLER_OFFSET + 1]),
+                console_type,
+            })
+        }
+
+        pub fn has_pokey(&self) -> bool {
+            self.cart_type & CART_POKEY > 0
+        }
+
+        pub fn is_supergame(&self) -> bool {
+            self.cart_type & CART_SUPERGAME > 0
+        }
+
+        pub fn has_supergame_ram(&self) -> bool {
+            self.cart_type & CART_SUPERGAME_RAM > 0
+        }
+
+        pub fn has_rom_at_4000(&self) -> bool {
+            self.cart_type & CART_ROM_AT_4000 > 0
+        }
+
+        pub fn has_bank6_at_4000(&self) -> bool {
+            self.cart_type & CART_BANK6_AT_4000 > 0
+        }
+
+        pub fn is_activision(&self) -> bool {
+            self.cart_type & CART_ACTIVISION > 0
+        }
+    }
+}
@@ -0,0 +1,228 @@
+pub mod prosystem {
+
+    use std::fs;
+    use std::sync::Arc;
+
+    use bevy::prelude::*;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::color::{palettes::css};
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use vcs::vcs_riot::vcs::VcsDifficulty;
+
+    use crate::prosystem_cartridge::prosystem::{ProsystemCartridge, ProsystemCartridgeError};
+    use crate::prosystem_console::prosystem::ProsystemConsole;
+
+    #[derive(Resource)]
+    pub struct ProsystemImage(Handle<Image>);
+
+    #[derive(Resource)]
+    pub struct Prosystem(ProsystemConsole);
+
+    // Holds the console until setup, so the menu can report a bad ROM
+    #[derive(Resource)]
+    pub struct ProsystemRomFile(Option<ProsystemConsole>);
+
+    impl ProsystemRomFile {
+
+        pub fn new (rom_file: String) -> Result<ProsystemRomFile, ProsystemCartridgeError> {
+            Ok(Self(Some(ProsystemConsole::new(rom_file)?)))
+        }
+
+        // 2600 cartridges put the 7800 in 2600 mode, the menu runs them on the VCS
+        pub fn is_vcs_rom(rom_file: &str) -> bool {
+            fs::read(rom_file).is_ok_and(|image| ProsystemCartridge::is_vcs_image(&image))
+        }
+
+    }
+
+    pub struct ProsystemBevy {
+    }
+
+    impl ProsystemBevy {
+
+        pub fn setup(
+            mut commands: Commands,
+            mut images: ResMut<Assets<Image>>,
+            mut rom_file:  ResMut<ProsystemRomFile>,
+            mut time: ResMut<Time<Fixed>>,
+            windows: Query<&mut Window>) {
+
+            let Some(console) = rom_file.0.take() else {
+                eprintln!("No 7800 console to set up");
+                return;
+            };
+
+            time.set_timestep_hz(console.get_frames_per_second() as f64);
+
+            let image = Image::new_fill(
+                Extent3d {
+                    width: console.get_x_resolution(),
+                    height: console.get_y_resolution(),
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &(css::BLACK.to_u8_array()),
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+            );
+
+            let image_handle = images.add(image);
+            let mut sprite = Sprite::from_image(image_handle.clone());
+            sprite.custom_size = Some(Vec2::new(windows.single().unwrap().width(), windows.single().unwrap().height()));
+            commands.spawn(sprite);
+            commands.insert_resource(ProsystemImage(image_handle));
+            commands.insert_resource(Prosystem(console));
+        }
+
+        pub fn frame(
+            mut commands: Commands,
+            mut audio_assets: ResMut<Assets<AudioSource>>,
+            video_handle: Res<ProsystemImage>,
+            mut video_assets: ResMut<Assets<Image>>,
+            mut console: ResMut<Prosystem>) {
+
+            let (video, audio) = console.0.run_frame();
+
+            match video {
+                Some(video) => {
+                    let mut image = video_assets.get_mut(&video_handle.0).expect("Image not found");
+                    let image_width = image.width();
+                    let image_height = image.height();
+
+                    for y in 0..image_height {
+                        for x in 0..image_width {
+                            let color = Color::srgb_u8(
+                                video[((y * image_width + x) * 3) as usize],
+                                video[((y * image_width + x) * 3 + 1) as usize],
+                                video[((y * image_width + x) * 3 + 2) as usize],
+                            );
+                            _ = image.set_color_at(x, y, color);
+                        }
+                    }
+                },
+                None => {
+                    eprintln!("No frame video available");
+                }
+            }
+
+            match audio {
+                Some(audio) => {
+
+                    // Single frame WAV file, as for the VCS
+                    let data_size = audio.len() as u32;
+                    let mut buffer: Vec<u8> = Vec::with_capacity(44 + audio.len());
+                    buffer.extend_from_slice(b"RIFF");
+                    buffer.extend_from_slice(&(36 + data_size).to_le_bytes());
+                    buffer.extend_from_slice(b"WAVEfmt ");
+                    buffer.extend_from_slice(&[0x10, 0x0, 0x0, 0x0]);   // Format chunk size
+                    buffer.extend_from_slice(&[0x1, 0x0, 0x1, 0x0]);    // PCM, mono
+                    buffer.extend_from_slice(&[0x44, 0xac, 0x0, 0x0]);  // 44100 Hz
+                    buffer.extend_from_slice(&[0x44, 0xac, 0x0, 0x0]);  // Bytes per second
+                    buffer.extend_from_slice(&[0x1, 0x0, 0x8, 0x0]);    // 1 byte per sample, 8 bits
+                    buffer.extend_from_slice(b"data");
+                    buffer.extend_from_slice(&data_size.to_le_bytes());
+
+                    for sample in audio {
+                        buffer.push(((sample + 1.0) * 127.0) as u8);
+                    }
+                    let audio_source = AudioSource{bytes: Arc::from(buffer)};
+                    let audio_handle = audio_assets.add(audio_source);
+                    commands.spawn((AudioPlayer::new(audio_handle), PlaybackSettings::DESPAWN));
+                },
+                None => {
+                    eprintln!("No frame audio available");
+                }
+            }
+        }
+
+        // F1 select, F2 reset, F3 pause, F5/F6 left difficulty A/B, F7/F8 right difficulty A/B
+        pub fn keyboard_system(keys: Res<ButtonInput<KeyCode>>,
+            mut console: ResMut<Prosystem>) {
+
+            if keys.just_pressed(KeyCode::F1) {
+                console.0.select_pressed(true);
+            } else if keys.just_released(KeyCode::F1) {
+                console.0.select_pressed(false);
+            }
+
+            if keys.just_pressed(KeyCode::F2) {
+                console.0.reset_pressed(true);
+            } else if keys.just_released(KeyCode::F2) {
+                console.0.reset_pressed(false);
+            }
+
+            if keys.just_pressed(KeyCode::F3) {
+                console.0.pause_pressed(true);
+            } else if keys.just_released(KeyCode::F3) {
+                console.0.pause_pressed(false);
+            }
+
+            if keys.just_pressed(KeyCode::F5) {
+                console.0.set_left_difficulty(VcsDifficulty::A);
+            }
+            if keys.just_pressed(KeyCode::F6) {
+                console.0.set_left_difficulty(VcsDifficulty::B);
+            }
+            if keys.just_pressed(KeyCode::F7) {
+                console.0.set_right_difficulty(VcsDifficulty::A);
+            }
+            if keys.just_pressed(KeyCode::F8) {
+                console.0.set_right_difficulty(VcsDifficulty::B);
+            }
+        }
+
+        pub fn gamepad_system(gamepads: Query<(Entity, &Gamepad)>,
+            mut console: ResMut<Prosystem>) {
+
+            // The first two gamepads are the left and right ports
+            for (port, (_entity, gamepad)) in gamepads.iter().enumerate() {
+                if port > 1 {
+                    break;
+                }
+
+                if gamepad.just_pressed(GamepadButton::Select) {
+                    console.0.select_pressed(true);
+                } else if gamepad.just_released(GamepadButton::Select) {
+                    console.0.select_pressed(false);
+                }
+
+                if gamepad.just_pressed(GamepadButton::Start) {
+                    console.0.pause_pressed(true);
+                } else if gamepad.just_released(GamepadButton::Start) {
+                    console.0.pause_pressed(false);
+                }
+
+                for (button, gamepad_button) in [GamepadButton::South, GamepadButton::East].iter().enumerate() {
+                    if gamepad.just_pressed(*gamepad_button) {
+                        console.0.controller_button(port, button, true);
+                    } else if gamepad.just_released(*gamepad_button) {
+                        console.0.controller_button(port, button, false);
+                    }
+                }
+
+                let left_stick_x = gamepad.get(GamepadAxis::LeftStickX).unwrap();
+                if left_stick_x > 0.01 {
+                    console.0.controller_left_right(port, 1);
+                }
+                else if left_stick_x < -0.01 {
+                    console.0.controller_left_right(port, -1);
+                }
+                else {
+                    console.0.controller_left_right(port, 0);
+                }
+
+                let left_stick_y = gamepad.get(GamepadAxis::LeftStickY).unwrap();
+                if left_stick_y > 0.01 {
+                    console.0.controller_up_down(port, 1);
+                }
+                else if left_stick_y < -0.01 {
+                    console.0.controller_up_down(port, -1);
+                }
+                else {
+                    console.0.controller_up_down(port, 0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+pub mod prosystem {
+
+    use std::fmt;
+
+    use emumemory::prelude::*;
+
+    use crate::prosystem_a78::prosystem::{A78_HEADER_SIZE, ProsystemA78Header};
+
+    const BANK_SIZE: usize = 0x4000;
+    const ACTIVISION_BLOCK_SIZE: usize = 0x2000;
+
+    // Largest ROM without bank switching, 0x4000 - 0xFFFF
+    const FLAT_MAX_SIZE: usize = 0xC000;
+
+    // Activision's 8K blocks at 0x4000, 0x6000, 0x8000 and 0xE000
+    const ACTIVISION_FIXED_BLOCKS: [usize; 4] = [13, 12, 15, 14];
+    const ACTIVISION_BANK_SELECT: u16 = 0xFF80;
+
+    // The BIOS checks the low nibble of 0xFFF9 is 3 or 7 before it looks for
+    // the cartridge signature, anything else is run in 2600 mode
+    const REGION_BYTE_FROM_END: usize = 7;
+    const REGION_MASK: u8 = 0x0B;
+    const REGION_7800: u8 = 0x03;
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum ProsystemBankType {
+        Flat,
+        SuperGame,
+        Activision,
+    }
+
+    #[derive(Debug)]
+    pub enum ProsystemCartridgeError {
+        RomRead(String),
+        UnsupportedSize(usize),
+    }
+
+    impl fmt::Display for ProsystemCartridgeError {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ProsystemCartridgeError::RomRead(e) => write!(f, "Couldn't read ROM file: {}", e),
+                ProsystemCartridgeError::UnsupportedSize(size) => write!(f, "Unsupported cartridge size of {} bytes", size),
+            }
+        }
+    }
+
+    impl std::error::Error for ProsystemCartridgeError {}
+
+    pub struct ProsystemCartridge {
+        // A flat ROM is one block, MemoryRom can't hold banked ones whole so
+        // they're split into their 16K banks
+        rom: Vec<MemoryRom>,
+        header: Option<ProsystemA78Header>,
+        bank_type: ProsystemBankType,
+        bank: usize,
+        ram: Option<MemoryRam>,
+    }
+
+    impl ProsystemCartridge {
+
+        // Takes an .a78 image, or a headerless ROM which is mapped to the top of memory
+        pub fn new(image: &[u8]) -> Result<ProsystemCartridge, ProsystemCartridgeError> {
+
+            let header = ProsystemA78Header::parse(image);
+            let rom: Vec<u8> = match &header {
+                Some(header) => {
+                    let data = &image[A78_HEADER_SIZE..];
+                    if header.rom_size != data.len() {
+                        eprintln!("The .a78 header gives {} bytes of ROM, the image has {}", header.rom_size, data.len());
+                    }
+                    // Padding after the size in the header isn't ROM, a size of 0 is taken as missing
+                    let size = if header.rom_size > 0 { header.rom_size.min(data.len()) } else { data.len() };
+                    data[..size].to_vec()
+                },
+                None => image.to_vec(),
+            };
+
+            let bank_type = match &header {
+                Some(header) if header.is_activision() => ProsystemBankType::Activision,
+                Some(header) if header.is_supergame() => ProsystemBankType::SuperGame,
+                _ => ProsystemBankType::Flat,
+            };
+
+            let size = rom.len();
+            let size_ok = match bank_type {
+                ProsystemBankType::Flat => size > 0 && size <= FLAT_MAX_SIZE,
+                ProsystemBankType::SuperGame => size >= BANK_SIZE * 2 && size.is_multiple_of(BANK_SIZE),
+                ProsystemBankType::Activision => size == ACTIVISION_BLOCK_SIZE * 16,
+            };
+            if !size_ok {
+                return Err(ProsystemCartridgeError::UnsupportedSize(size));
+            }
+
+            let rom = match bank_type {
+                ProsystemBankType::Flat => vec![MemoryRom::new(String::from("Cartridge ROM"), &rom)],
+                _ => rom.chunks(BANK_SIZE).enumerate()
+                    .map(|(bank, data)| MemoryRom::new(format!("Cartridge ROM bank {}", bank), data))
+                    .collect(),
+            };
+
+            let ram = match &header {
+                Some(header) if header.has_supergame_ram() => Some(MemoryRam::new(String::from("Cartridge RAM"), BANK_SIZE as u16)),
+                _ => None,
+            };
+
+            Ok(Self {
+                rom,
+                header,
+                bank_type,
+                bank: 0,
+                ram,
+            })
+        }
+
+        // Whether the BIOS would drop a headerless image into 2600 mode. The
+        // signature isn't checked, homebrew 7800 ROMs often don't have one.
+        pub fn is_vcs_image(image: &[u8]) -> bool {
+            if ProsystemA78Header::parse(image).is_some() || image.len() < REGION_BYTE_FROM_END {
+                return false;
+            }
+            image[image.len() - REGION_BYTE_FROM_END] & REGION_MASK != REGION_7800
+        }
+
+        pub fn get_header(&self) -> Option<&ProsystemA78Header> {
+            self.header.as_ref()
+        }
+
+        pub fn get_bank_type(&self) -> ProsystemBankType {
+            self.bank_type
+        }
+
+        // The ROM and RAM, for the memory error policy and the debugger
+        pub fn get_memories_mut(&mut self) -> impl Iterator<Item = &mut dyn BaseMemory> {
+            self.rom.iter_mut().map(|rom| rom as &mut dyn BaseMemory)
+                .chain(self.ram.iter_mut().map(|ram| ram as &mut dyn BaseMemory))
+        }
+
+        // None where the cartridge doesn't drive the bus. The locations are
+        // always inside the ROM, only writes can fail.
+        pub fn read(&self, address: u16) -> Option<u8> {
+            if let Some(ram) = &self.ram
+                && (0x4000..0x8000).contains(&address) {
+                return Some(ram.peek(address - 0x4000));
+            }
+
+            let (bank, location) = self.get_rom_location(address)?;
+            Some(self.rom[bank].peek(location))
+        }
+
+        pub fn write(&mut self, address: u16, byte: u8) {
+            if let Some(ram) = self.ram.as_mut()
+                && (0x4000..0x8000).contains(&address) {
+                ram.write(address - 0x4000, byte);
+                return;
+            }
+
+            match self.bank_type {
+                ProsystemBankType::SuperGame => {
+                    if (0x8000..0xC000).contains(&address) {
+                        self.bank = byte as usize;
+                        return;
+                    }
+                },
+                ProsystemBankType::Activision => {
+                    // The bank is picked by the address written to, not the data
+                    if (ACTIVISION_BANK_SELECT..ACTIVISION_BANK_SELECT + 8).contains(&address) {
+                        self.bank = (address - ACTIVISION_BANK_SELECT) as usize;
+                        return;
+                    }
+                },
+                ProsystemBankType::Flat => {},
+            }
+
+            // Anything else the ROM answers goes to the memory error policy
+            if let Some((bank, location)) = self.get_rom_location(address) {
+                self.rom[bank].write(location, byte);
+            }
+        }
+
+        // The ROM block and the location in it that address reads
+        fn get_rom_location(&self, address: u16) -> Option<(usize, u16)> {
+            if address < 0x4000 {
+                return None;
+            }
+
+            match self.bank_type {
+                ProsystemBankType::Flat => {
+                    let base = 0x10000 - self.rom[0].get_size();
+                    if (address as usize) < base {
+                        return None;
+                    }
+                    Some((0, (address as usize - base) as u16))
+                },
+                ProsystemBankType::SuperGame => {
+                    let bank = match address {
+                        0x4000..=0x7FFF => self.get_supergame_low_bank()?,
+                        0x8000..=0xBFFF => self.get_supergame_bank(),
+                        _ => self.get_bank_count() - 1,
+                    };
+                    Some((bank, address & (BANK_SIZE as u16 - 1)))
+                },
+                ProsystemBankType::Activision => {
+                    let block = match address {
+                        0x4000..=0x5FFF => ACTIVISION_FIXED_BLOCKS[0],
+                        0x6000..=0x7FFF => ACTIVISION_FIXED_BLOCKS[1],
+                        0x8000..=0x9FFF => ACTIVISION_FIXED_BLOCKS[2],
+                        0xA000..=0xDFFF => self.bank * 2 + ((address as usize - 0xA000) / ACTIVISION_BLOCK_SIZE),
+                        _ => ACTIVISION_FIXED_BLOCKS[3],
+                    };
+                    // Two 8K blocks to a bank
+                    let location = (block % 2) * ACTIVISION_BLOCK_SIZE + (address as usize & (ACTIVISION_BLOCK_SIZE - 1));
+                    Some((block / 2, location as u16))
+                },
+            }
+        }
+
+        fn get_bank_count(&self) -> usize {
+            self.rom.len()
+        }
+
+        // 144K carts have an extra bank 0 at 0x4000, the switched banks start at 1
+        fn get_supergame_bank(&self) -> usize {
+            let bank_count = self.get_bank_count();
+            if bank_count == 9 {
+                1 + self.bank % 8
+            } else {
+                self.bank % bank_count
+            }
+        }
+
+        fn get_supergame_low_bank(&self) -> Option<usize> {
+            let bank_count = self.get_bank_count();
+            let header = self.header.as_ref()?;
+
+            if bank_count == 9 {
+                Some(0)
+            } else if header.has_bank6_at_4000() || header.has_rom_at_4000() {
+                Some(bank_count - 2)
+            } else {
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+pub mod prosystem {
+
+    use std::fs;
+    use std::sync::RwLock;
+
+    use emucpu::prelude::*;
+    use emumemory::prelude::*;
+
+    use vcs::vcs_console_type::vcs::{ConsoleType, VcsConsoleType};
+    use vcs::vcs_controller::vcs::VcsController;
+    use vcs::vcs_properties::vcs::VcsControllerType;
+    use vcs::vcs_riot::vcs::{VcsDifficulty, VcsRiot, VcsSwitches};
+
+    use crate::prosystem_a78::prosystem::ProsystemControllerType;
+    use crate::prosystem_cartridge::prosystem::{ProsystemCartridge, ProsystemCartridgeError};
+    use crate::prosystem_maria::prosystem::{ProsystemMaria, X_RESOLUTION};
+    use crate::prosystem_memory::prosystem::{ProsystemDevice, ProsystemMemory};
+    use crate::prosystem_tia::prosystem::ProsystemTia;
+
+    // MARIA clocks per CPU cycle, the TIA and RIOT slow the CPU down to 1.19MHz
+    const CPU_CLOCKS: u8 = 4;
+    const CPU_SLOW_CLOCKS: u8 = 6;
+
+    // The RIOT ignores addresses with A12 set, used on cycles that aren't for it
+    const RIOT_IDLE_ADDRESS: u16 = 0x1000;
+
+    // Where the VCS decodes the RIOT's I/O and RAM
+    const VCS_RIOT_IO: u16 = 0x0280;
+    const VCS_RIOT_RAM: u16 = 0x0080;
+
+    pub struct ProsystemConsole {
+        maria: ProsystemMaria,
+        tia: ProsystemTia,
+        riot: VcsRiot,
+        memory: ProsystemMemory,
+        console_type: VcsConsoleType,
+        controllers: [VcsController; 2],
+        // What the .a78 header says is plugged in, empty ports read as released
+        ports: [ProsystemControllerType; 2],
+        // Left and right button of each joystick
+        buttons: [[bool; 2]; 2],
+        cpu_runner: M6502Runner,
+        debugger: M6502Debugger,
+        cpu_clocks: u8,
+        cpu_halted: bool,
+        total_ticks: u32,
+        addr: AddressBus,
+        inframe: RwLock<bool>,
+    }
+
+    impl ProsystemConsole {
+
+        // The BIOS isn't run, the cartridge starts from its reset vector
+        pub fn new(rom_file: String) -> Result<ProsystemConsole, ProsystemCartridgeError> {
+
+            let rom = match fs::read(rom_file) {
+                Ok(rom) => rom,
+                Err(e) => return Err(ProsystemCartridgeError::RomRead(e.to_string())),
+            };
+            let cartridge = ProsystemCartridge::new(&rom)?;
+
+            let console_type = match cartridge.get_header() {
+                Some(header) => header.console_type,
+                None => ConsoleType::NTSC,
+            };
+            let console_type = VcsConsoleType::new(console_type);
+
+            let ports = match cartridge.get_header() {
+                Some(header) => [header.left_controller, header.right_controller],
+                None => [ProsystemControllerType::Joystick; 2],
+            };
+            for port in ports {
+                if !matches!(port, ProsystemControllerType::None | ProsystemControllerType::Joystick) {
+                    eprintln!("{:?} controllers aren't supported, a joystick is used", port);
+                }
+            }
+
+            let mut temp_instance = Self {
+                maria: ProsystemMaria::new(console_type.get_console_type()),
+                tia: ProsystemTia::new(console_type.get_frames_per_second()),
+                riot: VcsRiot::new(),
+                memory: ProsystemMemory::new(cartridge),
+                console_type,
+                controllers: [VcsController::new(VcsControllerType::Joystick),
+                    VcsController::new(VcsControllerType::Joystick)],
+                ports,
+                buttons: [[false; 2]; 2],
+                cpu_runner: M6502Runner::new(M6502Version::Atari7800),
+                debugger: M6502Debugger::new(),
+                cpu_clocks: CPU_CLOCKS,
+                cpu_halted: false,
+                total_ticks: 0,
+                addr: AddressBus { address: 0 , write: false, byte: 0 },
+                inframe: RwLock::new(false),
+            };
+
+            temp_instance.start_up();
+            Ok(temp_instance)
+        }
+
+        fn start_up(&mut self) {
+            self.riot.reset();
+            self.tia.reset();
+            self.maria.reset();
+            self.update_controllers();
+
+            self.total_ticks = 0;
+        }
+
+        pub fn get_console_type(&self) -> ConsoleType {
+            self.console_type.get_console_type()
+        }
+
+        pub fn get_x_resolution(&self) -> u32 {
+            X_RESOLUTION
+        }
+
+        pub fn get_y_resolution(&self) -> u32 {
+            self.maria.get_y_resolution()
+        }
+
+        pub fn get_frames_per_second(&self) -> u32 {
+            self.console_type.get_frames_per_second()
+        }
+
+        // Joystick directions on SWCHA, the buttons on the TIA inputs
+        fn update_controllers(&mut self) {
+            let [left_nibble, right_nibble] = [0, 1].map(|port| match self.ports[port] {
+                ProsystemControllerType::None => 0x0F,
+                _ => self.controllers[port].get_direction_nibble(),
+            });
+            self.riot.set_swcha_input((left_nibble << 4) | right_nibble);
+
+            // INPT0 - INPT3 are the Proline buttons, active high. One button
+            // games read either button as the active low INPT4 / INPT5 trigger.
+            let [left_buttons, right_buttons] = [0, 1].map(|port| match self.ports[port] {
+                ProsystemControllerType::None => [false; 2],
+                _ => self.buttons[port],
+            });
+            self.tia.set_input_levels([left_buttons[1], left_buttons[0], right_buttons[1], right_buttons[0],
+                !(left_buttons[0] || left_buttons[1]), !(right_buttons[0] || right_buttons[1])]);
+        }
+
+        // The CPU's cycle goes to the chip its address decodes to
+        fn bus_access(&mut self) {
+            let device = self.memory.decode(self.addr.address);
+
+            match device {
+                Some((ProsystemDevice::Tia, location)) => {
+                    if self.addr.write {
+                        self.tia.register_write(location, self.addr.byte);
+                        self.addr.write = false;
+                    } else {
+                        self.addr.byte = self.tia.register_read(location);
+                    }
+                },
+                Some((ProsystemDevice::Maria, location)) => {
+                    if self.addr.write {
+                        self.maria.register_write(location, self.addr.byte);
+                        self.addr.write = false;
+                    } else {
+                        self.addr.byte = self.maria.register_read(location);
+                    }
+                },
+                Some((ProsystemDevice::Ram(_) | ProsystemDevice::Cartridge, _)) => self.memory.execute_tick(&mut self.addr),
+                _ => {},
+            }
+
+            self.riot_cycle(device);
+        }
+
+        // The RIOT's I/O is at 0x280 and its RAM at 0x480, which aren't where the VCS decodes them
+        fn riot_cycle(&mut self, device: Option<(ProsystemDevice, u16)>) {
+            let address = match device {
+                Some((ProsystemDevice::RiotIo, location)) => Some(VCS_RIOT_IO | location),
+                Some((ProsystemDevice::RiotRam, location)) => Some(VCS_RIOT_RAM | location),
+                _ => None,
+            };
+
+            match address {
+                Some(address) => {
+                    let mut riot_addr = AddressBus { address, ..self.addr };
+                    self.riot.execute_tick(&mut riot_addr);
+                    self.addr.byte = riot_addr.byte;
+                    self.addr.write = riot_addr.write;
+                },
+                None => {
+                    let mut riot_addr = AddressBus { address: RIOT_IDLE_ADDRESS, write: false, ..self.addr };
+                    self.riot.execute_tick(&mut riot_addr);
+                },
+            }
+        }
+
+        fn cpu_cycle(&mut self) {
+            self.update_controllers();
+
+            // HALT takes the CPU off the bus, the cycle it stopped on already
+            // served its address so only the RIOT timer carries on
+            if self.cpu_halted {
+                let mut riot_addr = AddressBus { address: RIOT_IDLE_ADDRESS, write: false, ..self.addr };
+                self.riot.execute_tick(&mut riot_addr);
+            } else {
+                self.bus_access();
+            }
+
+            // MARIA only takes the bus between instructions
+            if self.maria.is_dma_active() {
+                if self.cpu_halted || self.cpu_runner.can_halt {
+                    self.cpu_halted = true;
+                    return;
+                }
+            } else {
+                self.cpu_halted = false;
+            }
+
+            if self.maria.take_nmi() {
+                self.cpu_runner.set_nmi();
+            }
+
+            self.cpu_runner.set_rdy(!self.maria.is_cpu_blocked());
+            self.cpu_runner.execute_tick(&mut self.addr);
+            self.debugger.check_tick(&self.cpu_runner);
+            for memory in self.memory.get_memories_mut() {
+                self.debugger.check_memory(memory);
+            }
+        }
+
+        fn is_slow_address(&self, address: u16) -> bool {
+            matches!(self.memory.decode(address),
+                Some((ProsystemDevice::Tia | ProsystemDevice::RiotIo | ProsystemDevice::RiotRam, _)))
+        }
+
+        pub fn get_cpu(&self) -> &M6502 {
+            self.cpu_runner.get_cpu()
+        }
+
+        pub fn get_debugger_mut(&mut self) -> &mut M6502Debugger {
+            &mut self.debugger
+        }
+
+        // Trap stops the debugger on a bad RAM or cartridge access
+        pub fn set_memory_policy(&mut self, policy: MemoryErrorPolicy) {
+            for memory in self.memory.get_memories_mut() {
+                memory.set_policy(policy);
+            }
+        }
+
+        // Runs one MARIA frame, or up to where the debugger stops it
+        pub fn run_frame (&mut self) -> (Option<Vec<u8>>, Option<Vec<f32>>) {
+
+            if *self.inframe.read().unwrap() {
+                return (None, None);
+            }
+            *self.inframe.write().unwrap() = true;
+
+            while !self.maria.take_frame_ready() {
+
+                if self.debugger.is_stopped() {
+                    *self.inframe.write().unwrap() = false;
+                    return (None, None);
+                }
+
+                let scan_line = self.maria.get_scan_line();
+                self.maria.execute_tick(&mut self.memory);
+                if self.maria.get_scan_line() != scan_line {
+                    self.debugger.end_scanline();
+                }
+
+                if self.total_ticks.is_multiple_of(2) {
+                    self.tia.execute_tick();
+                }
+
+                self.cpu_clocks -= 1;
+                if self.cpu_clocks == 0 {
+                    self.cpu_cycle();
+                    self.cpu_clocks = if !self.cpu_halted && self.is_slow_address(self.addr.address) {
+                        CPU_SLOW_CLOCKS
+                    } else {
+                        CPU_CLOCKS
+                    };
+                }
+
+                self.total_ticks = self.total_ticks.overflowing_add(1).0;
+            }
+
+            self.debugger.end_frame();
+            let video = self.maria.get_screen();
+            let audio = self.tia.get_audio_buffer();
+
+            *self.inframe.write().unwrap() = false;
+
+            (Some(video), Some(audio))
+        }
+
+        pub fn select_pressed(&mut self, value: bool) {
+            self.riot.select_pressed(value);
+        }
+
+        pub fn reset_pressed(&mut self, value: bool) {
+            self.riot.reset_pressed(value);
+        }
+
+        // Pause is the VCS colour switch position, held low while pressed
+        pub fn pause_pressed(&mut self, value: bool) {
+            let mut switches = self.get_switches();
+            switches.color = !value;
+            self.riot.set_switches(switches);
+        }
+
+        pub fn get_switches(&self) -> VcsSwitches {
+            self.riot.get_switches()
+        }
+
+        pub fn set_left_difficulty(&mut self, value: VcsDifficulty) {
+            let mut switches = self.get_switches();
+            switches.left_difficulty = value;
+            self.riot.set_switches(switches);
+        }
+
+        pub fn set_right_difficulty(&mut self, value: VcsDifficulty) {
+            let mut switches = self.get_switches();
+            switches.right_difficulty = value;
+            self.riot.set_switches(switches);
+        }
+
+        // Port 0 is the left controller port, 1 the right. Button 0 is the
+        // left button, the only one on a 2600 joystick.
+        pub fn controller_button(&mut self, port: usize, button: usize, value: bool) {
+            self.buttons[port][button] = value;
+        }
+
+        pub fn controller_up_down(&mut self, port: usize, value: i8) {
+            self.controllers[port].set_up_down(value);
+        }
+
+        pub fn controller_left_right(&mut self, port: usize, value: i8) {
+            self.controllers[port].set_left_right(value);
+        }
+    }
+}
@@ -0,0 +1,438 @@
+pub mod prosystem {
+
+    use vcs::vcs_console_type::vcs::{ConsoleType, VcsConsoleType};
+    use vcs::vcs_palette::vcs::VcsPalette;
+
+    use crate::prosystem_memory::prosystem::ProsystemMemory;
+
+    const REG_BACKGRND: u16 = 0x00;
+    const REG_WSYNC: u16 =    0x04;
+    const REG_MSTAT: u16 =    0x08;
+    const REG_DPPH: u16 =     0x0C;
+    const REG_DPPL: u16 =     0x10;
+    const REG_CHARBASE: u16 = 0x14;
+    const REG_CTRL: u16 =     0x1C;
+
+    const CTRL_COLOR_KILL: u8 = 0x80;
+    const CTRL_DMA: u8 =        0x60;
+    const CTRL_DMA_ON: u8 =     0x40;
+    const CTRL_CHAR_WIDTH: u8 = 0x10;
+    const CTRL_KANGAROO: u8 =   0x04;
+    const CTRL_READ_MODE: u8 =  0x03;
+
+    const MSTAT_VBLANK: u8 = 0x80;
+
+    // Display list list entry flags
+    const DLL_DLI: u8 =    0x80;
+    const DLL_HOLEY16: u8 = 0x40;
+    const DLL_HOLEY8: u8 =  0x20;
+    const DLL_OFFSET: u8 = 0x0F;
+
+    // Display list header mode byte
+    const DL_WRITE_MODE: u8 = 0x80;
+    const DL_INDIRECT: u8 =   0x20;
+
+    pub const X_RESOLUTION: u32 = 320;
+
+    // MARIA runs at 4 times the CPU's 1.79MHz, twice the TIA's colour clock
+    pub const LINE_CLOCKS: u16 = 454;
+    const NTSC_LINES: u16 = 263;
+    const PAL_LINES: u16 = 313;
+    const DISPLAY_START_LINE: u16 = 16;
+    const NTSC_DISPLAY_LINES: u16 = 243;
+    const PAL_DISPLAY_LINES: u16 = 293;
+
+    // DMA starts shortly after the line does, these are its costs in MARIA clocks
+    const DMA_START_CLOCK: u16 = 28;
+    const DMA_STARTUP_CLOCKS: u16 = 16;
+    const DMA_SHUTDOWN_CLOCKS: u16 = 24;
+    const DLL_FETCH_CLOCKS: u16 = 24;
+    const SHORT_HEADER_CLOCKS: u16 = 8;
+    const LONG_HEADER_CLOCKS: u16 = 10;
+    const DIRECT_BYTE_CLOCKS: u16 = 3;
+    const INDIRECT_CHAR_CLOCKS: u16 = 6;
+    const INDIRECT_WIDE_CHAR_CLOCKS: u16 = 9;
+
+    // The display list of one zone runs out at 512 headers at most
+    const MAX_DL_HEADERS: u16 = 512;
+
+    pub struct ProsystemMaria {
+        registers: [u8; 0x20],
+        palette: VcsPalette,
+        frame_lines: u16,
+        display_lines: u16,
+        clock: u16,
+        scan_line: u16,
+        wsync: bool,
+        dma_clocks: u16,
+        nmi_pending: bool,
+        nmi: bool,
+        dll_address: u16,
+        dl_address: u16,
+        zone_offset: u8,
+        holey16: bool,
+        holey8: bool,
+        write_mode: bool,
+        line_buffer: [u8; X_RESOLUTION as usize],
+        frame_buffer: Vec<u8>,
+        screen: Vec<u8>,
+        frame_ready: bool,
+    }
+
+    impl ProsystemMaria {
+
+        pub fn new(console_type: ConsoleType) -> Self {
+            let (frame_lines, display_lines) = match console_type {
+                ConsoleType::NTSC => (NTSC_LINES, NTSC_DISPLAY_LINES),
+                _ => (PAL_LINES, PAL_DISPLAY_LINES),
+            };
+            let buffer_size = X_RESOLUTION as usize * display_lines as usize * 3;
+
+            Self {
+                registers: [0; 0x20],
+                palette: VcsPalette::new(&VcsConsoleType::new(console_type)),
+                frame_lines,
+                display_lines,
+                clock: 0,
+                scan_line: 0,
+                wsync: false,
+                dma_clocks: 0,
+                nmi_pending: false,
+                nmi: false,
+                dll_address: 0,
+                dl_address: 0,
+                zone_offset: 0,
+                holey16: false,
+                holey8: false,
+                write_mode: false,
+                line_buffer: [0; X_RESOLUTION as usize],
+                frame_buffer: vec![0; buffer_size],
+                screen: vec![0; buffer_size],
+                frame_ready: false,
+            }
+        }
+
+        pub fn reset(&mut self) {
+            self.registers = [0; 0x20];
+            self.clock = 0;
+            self.scan_line = 0;
+            self.wsync = false;
+            self.dma_clocks = 0;
+            self.nmi_pending = false;
+            self.nmi = false;
+            self.write_mode = false;
+        }
+
+        pub fn get_scan_line(&self) -> u16 {
+            self.scan_line
+        }
+
+        pub fn get_y_resolution(&self) -> u32 {
+            self.display_lines as u32
+        }
+
+        pub fn set_black_white(&mut self, value: bool) {
+            self.palette.set_black_white(value);
+        }
+
+        // The CPU is halted while MARIA owns the bus
+        pub fn is_dma_active(&self) -> bool {
+            self.dma_clocks > 0
+        }
+
+        // WSYNC holds the CPU until the end of the line
+        pub fn is_cpu_blocked(&self) -> bool {
+            self.wsync
+        }
+
+        pub fn take_nmi(&mut self) -> bool {
+            let result = self.nmi;
+            self.nmi = false;
+            result
+        }
+
+        pub fn take_frame_ready(&mut self) -> bool {
+            let result = self.frame_ready;
+            self.frame_ready = false;
+            result
+        }
+
+        pub fn get_screen(&self) -> Vec<u8> {
+            self.screen.clone()
+        }
+
+        fn is_vblank(&self) -> bool {
+            self.scan_line < DISPLAY_START_LINE || self.scan_line >= DISPLAY_START_LINE + self.display_lines
+        }
+
+        // location is the register, 0x00 - 0x1F
+        pub fn register_write(&mut self, location: u16, byte: u8) {
+            if location == REG_WSYNC {
+                self.wsync = true;
+            } else if location != REG_MSTAT {
+                self.registers[location as usize] = byte;
+            }
+        }
+
+        pub fn register_read(&mut self, location: u16) -> u8 {
+            if location == REG_MSTAT {
+                if self.is_vblank() { MSTAT_VBLANK } else { 0 }
+            } else {
+                self.registers[location as usize]
+            }
+        }
+
+        pub fn execute_tick(&mut self, memory: &mut ProsystemMemory) {
+
+            if self.dma_clocks > 0 {
+                self.dma_clocks -= 1;
+
+                // Display list interrupts come once DMA has finished with the line
+                if self.dma_clocks == 0 && self.nmi_pending {
+                    self.nmi_pending = false;
+                    self.nmi = true;
+                }
+            }
+
+            if self.clock == DMA_START_CLOCK && !self.is_vblank() {
+                self.render_line(memory);
+            }
+
+            self.clock += 1;
+            if self.clock >= LINE_CLOCKS {
+                self.clock = 0;
+                self.wsync = false;
+                self.scan_line += 1;
+
+                if self.scan_line >= self.frame_lines {
+                    self.scan_line = 0;
+                    self.screen.copy_from_slice(&self.frame_buffer);
+                    self.frame_ready = true;
+                }
+            }
+        }
+
+        fn render_line(&mut self, memory: &mut ProsystemMemory) {
+            let dma_on = self.registers[REG_CTRL as usize] & CTRL_DMA == CTRL_DMA_ON;
+            let mut clocks = 0;
+
+            // The display list list is read from the top at the start of each frame
+            if self.scan_line == DISPLAY_START_LINE && dma_on {
+                self.dll_address = ((self.registers[REG_DPPH as usize] as u16) << 8) | self.registers[REG_DPPL as usize] as u16;
+                self.load_zone(memory);
+                clocks += DLL_FETCH_CLOCKS;
+            }
+
+            self.output_line();
+
+            if dma_on {
+                clocks += DMA_STARTUP_CLOCKS + self.store_display_list(memory) + DMA_SHUTDOWN_CLOCKS;
+
+                // The next zone is fetched on the last line of this one
+                if self.zone_offset == 0 {
+                    self.load_zone(memory);
+                    clocks += DLL_FETCH_CLOCKS;
+                } else {
+                    self.zone_offset -= 1;
+                }
+                self.dma_clocks = clocks.min(LINE_CLOCKS - DMA_START_CLOCK);
+            }
+        }
+
+        fn load_zone(&mut self, memory: &mut ProsystemMemory) {
+            let flags = memory.read(self.dll_address);
+            self.zone_offset = flags & DLL_OFFSET;
+            self.holey16 = flags & DLL_HOLEY16 > 0;
+            self.holey8 = flags & DLL_HOLEY8 > 0;
+            self.dl_address = ((memory.read(self.dll_address.wrapping_add(1)) as u16) << 8) |
+                memory.read(self.dll_address.wrapping_add(2)) as u16;
+            self.dll_address = self.dll_address.wrapping_add(3);
+
+            if flags & DLL_DLI > 0 {
+                self.nmi_pending = true;
+            }
+        }
+
+        // Draws the zone's display list into the line buffer for the next line,
+        // returns the clocks the DMA took
+        fn store_display_list(&mut self, memory: &mut ProsystemMemory) -> u16 {
+            let mut clocks = 0;
+            let mut address = self.dl_address;
+
+            for _ in 0..MAX_DL_HEADERS {
+                let mode = memory.read(address.wrapping_add(1));
+                if mode & 0x5F == 0 {
+                    break;
+                }
+
+                let low = memory.read(address);
+                let high;
+                let palette;
+                let width_byte;
+                let hpos;
+                let mut indirect = false;
+
+                if mode & 0x1F == 0 {
+                    // Extended header, also latches the write mode
+                    self.write_mode = mode & DL_WRITE_MODE > 0;
+                    indirect = mode & DL_INDIRECT > 0;
+                    high = memory.read(address.wrapping_add(2));
+                    palette = memory.read(address.wrapping_add(3)) >> 5;
+                    width_byte = memory.read(address.wrapping_add(3)) & 0x1F;
+                    hpos = memory.read(address.wrapping_add(4));
+                    address = address.wrapping_add(5);
+                    clocks += LONG_HEADER_CLOCKS;
+                } else {
+                    high = memory.read(address.wrapping_add(2));
+                    palette = mode >> 5;
+                    width_byte = mode & 0x1F;
+                    hpos = memory.read(address.wrapping_add(3));
+                    address = address.wrapping_add(4);
+                    clocks += SHORT_HEADER_CLOCKS;
+                }
+
+                // Widths are stored as two's complement, 0 is 32 bytes
+                let width = match (0x20 - width_byte as u16) & 0x1F {
+                    0 => 32,
+                    width => width,
+                };
+
+                clocks += self.store_object(memory, ((high as u16) << 8) | low as u16, width, palette, hpos, indirect);
+            }
+            clocks
+        }
+
+        fn store_object(&mut self, memory: &mut ProsystemMemory, start: u16, width: u16, palette: u8,
+            hpos: u8, indirect: bool) -> u16 {
+
+            let offset = (self.zone_offset as u16) << 8;
+            let mut position = hpos;
+            let mut clocks = 0;
+
+            for index in 0..width {
+                if indirect {
+                    let character = memory.read(start.wrapping_add(index));
+                    let char_start = ((self.registers[REG_CHARBASE as usize] as u16) << 8 | character as u16).wrapping_add(offset);
+                    let wide = self.registers[REG_CTRL as usize] & CTRL_CHAR_WIDTH > 0;
+
+                    let byte = self.read_graphics(memory, char_start);
+                    position = self.store_byte(byte, palette, position);
+                    if wide {
+                        let byte = self.read_graphics(memory, char_start.wrapping_add(1));
+                        position = self.store_byte(byte, palette, position);
+                        clocks += INDIRECT_WIDE_CHAR_CLOCKS;
+                    } else {
+                        clocks += INDIRECT_CHAR_CLOCKS;
+                    }
+                } else {
+                    let byte = self.read_graphics(memory, start.wrapping_add(index).wrapping_add(offset));
+                    position = self.store_byte(byte, palette, position);
+                    clocks += DIRECT_BYTE_CLOCKS;
+                }
+            }
+            clocks
+        }
+
+        // Holey DMA reads zeros from odd 4K or 2K blocks of cartridge space
+        fn read_graphics(&self, memory: &mut ProsystemMemory, address: u16) -> u8 {
+            if address >= 0x8000 &&
+                ((self.holey16 && address & 0x1000 > 0) || (self.holey8 && address & 0x0800 > 0)) {
+                return 0;
+            }
+            memory.read(address)
+        }
+
+        // Decodes one graphics byte for the current mode at a 160 pixel position,
+        // returning the position after it
+        fn store_byte(&mut self, byte: u8, palette: u8, position: u8) -> u8 {
+            let read_mode = self.registers[REG_CTRL as usize] & CTRL_READ_MODE;
+            let start = position as usize * 2;
+
+            match (read_mode, self.write_mode) {
+                // 160A, 4 pixels of 2 bits
+                (0, false) | (1, false) => {
+                    for pixel in 0..4 {
+                        let color = (byte >> (6 - pixel * 2)) & 0x03;
+                        self.store_pixels(start + pixel * 2, 2, palette, color);
+                    }
+                },
+                // 160B, 2 pixels of 2 bits with 2 palette bits each
+                (0, true) | (1, true) => {
+                    self.store_pixels(start, 2, (palette & 0x04) | ((byte >> 2) & 0x03), byte >> 6);
+                    self.store_pixels(start + 2, 2, (palette & 0x04) | (byte & 0x03), (byte >> 4) & 0x03);
+                },
+                // 320A, 8 pixels of 1 bit in colour 2
+                (3, false) => {
+                    for pixel in 0..8 {
+                        let color = ((byte >> (7 - pixel)) & 0x01) << 1;
+                        self.store_pixels(start + pixel, 1, palette, color);
+                    }
+                },
+                // 320C, 4 pixels in colour 2 with a palette bit pair for each half
+                (3, true) => {
+                    for pixel in 0..4 {
+                        let color = ((byte >> (7 - pixel)) & 0x01) << 1;
+                        let shift = if pixel < 2 { 2 } else { 0 };
+                        self.store_pixels(start + pixel, 1, (palette & 0x04) | ((byte >> shift) & 0x03), color);
+                    }
+                },
+                // 320B, 4 pixels of 2 bits split across the nibbles
+                (2, true) => {
+                    for pixel in 0..4 {
+                        let color = (((byte >> (7 - pixel)) & 0x01) << 1) | ((byte >> (3 - pixel)) & 0x01);
+                        self.store_pixels(start + pixel, 1, palette, color);
+                    }
+                },
+                // 320D, 8 pixels of 1 bit, the low colour bit comes from the palette
+                _ => {
+                    for pixel in 0..8 {
+                        let low = (palette >> (1 - (pixel & 0x01))) & 0x01;
+                        let color = (((byte >> (7 - pixel)) & 0x01) << 1) | low;
+                        self.store_pixels(start + pixel, 1, palette & 0x04, color);
+                    }
+                },
+            }
+
+            let bytes_width = if self.write_mode { 2 } else { 4 };
+            position.wrapping_add(bytes_width)
+        }
+
+        // Colour 0 is transparent unless kangaroo mode is on
+        fn store_pixels(&mut self, start: usize, count: usize, palette: u8, color: u8) {
+            let kangaroo = self.registers[REG_CTRL as usize] & CTRL_KANGAROO > 0;
+            for x in start..start + count {
+                if x >= X_RESOLUTION as usize {
+                    break;
+                }
+                if color != 0 {
+                    self.line_buffer[x] = (palette << 2) | color;
+                } else if kangaroo {
+                    self.line_buffer[x] = 0;
+                }
+            }
+        }
+
+        // Shows the line DMA built on the previous line, then clears it for the next
+        fn output_line(&mut self) {
+            let line = (self.scan_line - DISPLAY_START_LINE) as usize;
+            let color_kill = self.registers[REG_CTRL as usize] & CTRL_COLOR_KILL > 0;
+
+            for x in 0..X_RESOLUTION as usize {
+                // Line buffer entries are palette * 4 + colour, the same as the register layout
+                let entry = self.line_buffer[x] as usize;
+                let mut color = self.registers[if entry == 0 { REG_BACKGRND as usize } else { entry }];
+                if color_kill {
+                    color &= 0x0F;
+                }
+
+                let (r, g, b) = self.palette.get_color(color as usize);
+                let index = (line * X_RESOLUTION as usize + x) * 3;
+                self.frame_buffer[index] = r;
+                self.frame_buffer[index + 1] = g;
+                self.frame_buffer[index + 2] = b;
+            }
+
+            self.line_buffer = [0; X_RESOLUTION as usize];
+        }
+    }
+}
@@ -0,0 +1,99 @@
+pub mod prosystem {
+
+    use emumemory::prelude::*;
+    use emucpu::prelude::*;
+
+    use crate::prosystem_cartridge::prosystem::ProsystemCartridge;
+
+    // 4K of RAM at 0x1800 - 0x27FF
+    const RAM_SIZE: u16 = 0x1000;
+
+    // The chips on the 7800's bus. Ram is where in the RAM its range starts,
+    // the zero page, stack and 0x2800 - 0x3FFF are windows on the 2K at 0x2000.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum ProsystemDevice {
+        Tia,
+        Maria,
+        RiotIo,
+        RiotRam,
+        Ram(u16),
+        Cartridge,
+    }
+
+    // The 7800's memory map, shared by the CPU and MARIA's DMA
+    pub struct ProsystemMemory {
+        memory_map: MemoryMap<ProsystemDevice>,
+        ram: MemoryRam,
+        cartridge: ProsystemCartridge,
+    }
+
+    impl ProsystemMemory {
+
+        pub fn new(cartridge: ProsystemCartridge) -> ProsystemMemory {
+            let mut memory_map = MemoryMap::new();
+            // The TIA and MARIA registers repeat at 0x100
+            memory_map.add("TIA Registers", MemoryDecode::Select(0xFEE0, 0x0000), 0x1F, ProsystemDevice::Tia).unwrap();
+            memory_map.add("MARIA Registers", MemoryDecode::Select(0xFEE0, 0x0020), 0x1F, ProsystemDevice::Maria).unwrap();
+            memory_map.add("RAM Zero Page", MemoryDecode::Range(0x0040, 0x00FF), 0xFFFF, ProsystemDevice::Ram(0x0840)).unwrap();
+            memory_map.add("RAM Stack", MemoryDecode::Range(0x0140, 0x01FF), 0xFFFF, ProsystemDevice::Ram(0x0940)).unwrap();
+            memory_map.add("RIOT Registers", MemoryDecode::Range(0x0280, 0x02FF), 0x1F, ProsystemDevice::RiotIo).unwrap();
+            memory_map.add("RIOT RAM", MemoryDecode::Range(0x0480, 0x04FF), 0x7F, ProsystemDevice::RiotRam).unwrap();
+            memory_map.add("7800 RAM", MemoryDecode::Range(0x1800, 0x27FF), 0xFFFF, ProsystemDevice::Ram(0x0000)).unwrap();
+            memory_map.add("7800 RAM Mirror", MemoryDecode::Range(0x2800, 0x3FFF), 0x07FF, ProsystemDevice::Ram(0x0800)).unwrap();
+            memory_map.add("Cartridge", MemoryDecode::Range(0x4000, 0xFFFF), 0xFFFF, ProsystemDevice::Cartridge).unwrap();
+
+            Self {
+                memory_map,
+                ram: MemoryRam::new(String::from("7800 RAM"), RAM_SIZE),
+                cartridge,
+            }
+        }
+
+        pub fn get_cartridge(&self) -> &ProsystemCartridge {
+            &self.cartridge
+        }
+
+        // The memories that can fail, the RAM windows all fall inside it
+        pub fn get_memories_mut(&mut self) -> impl Iterator<Item = &mut dyn BaseMemory> {
+            std::iter::once(&mut self.ram as &mut dyn BaseMemory).chain(self.cartridge.get_memories_mut())
+        }
+
+        // The device at address and the offset into it
+        pub fn decode(&self, address: u16) -> Option<(ProsystemDevice, u16)> {
+            self.memory_map.decode(address)
+        }
+
+        // MARIA's DMA reads, nothing unmapped ever drives the bus so it reads as 0
+        pub fn read(&mut self, address: u16) -> u8 {
+            match self.memory_map.decode(address) {
+                Some((ProsystemDevice::Ram(start), location)) => self.ram.read(start + location),
+                Some((ProsystemDevice::Cartridge, _)) => self.cartridge.read(address).unwrap_or(0),
+                _ => 0,
+            }
+        }
+
+        // The CPU's accesses to the RAM and cartridge, the console hands the
+        // other chips theirs
+        pub fn execute_tick(&mut self, addr: &mut AddressBus) {
+            match self.memory_map.decode(addr.address) {
+                Some((ProsystemDevice::Ram(start), location)) => {
+                    if addr.write {
+                        self.ram.write(start + location, addr.byte);
+                        addr.write = false;
+                    } else {
+                        addr.byte = self.ram.read(start + location);
+                    }
+                },
+                Some((ProsystemDevice::Cartridge, _)) => {
+                    if addr.write {
+                        self.cartridge.write(addr.address, addr.byte);
+                        addr.write = false;
+                    } else if let Some(byte) = self.cartridge.read(addr.address) {
+                        addr.byte = byte;
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+pub mod prosystem {
+
+    use vcs::vcs_audio::vcs::VcsAudio;
+
+    const REG_AUDC0: u16 = 0x15;
+    const REG_AUDC1: u16 = 0x16;
+    const REG_AUDF0: u16 = 0x17;
+    const REG_AUDF1: u16 = 0x18;
+    const REG_AUDV0: u16 = 0x19;
+    const REG_AUDV1: u16 = 0x1A;
+
+    const REG_INPT0: u16 = 0x08;
+    const REG_INPT5: u16 = 0x0D;
+
+    // In the 7800 the TIA only makes sound and reads the controller buttons
+    pub struct ProsystemTia {
+        audio: VcsAudio,
+        // INPT0 - INPT5 levels, bit 7 of each
+        inputs: [bool; 6],
+    }
+
+    impl ProsystemTia {
+
+        pub fn new(frames_per_second: u32) -> ProsystemTia {
+            Self {
+                audio: VcsAudio::new(frames_per_second),
+                inputs: [false, false, false, false, true, true],
+            }
+        }
+
+        pub fn reset(&mut self) {
+            self.audio.reset();
+        }
+
+        pub fn set_input_levels(&mut self, inputs: [bool; 6]) {
+            self.inputs = inputs;
+        }
+
+        pub fn get_audio_buffer(&mut self) -> Vec<f32> {
+            self.audio.get_audio_buffer()
+        }
+
+        // location is the register, 0x00 - 0x1F
+        pub fn register_write(&mut self, location: u16, byte: u8) {
+            match location {
+                REG_AUDC0 | REG_AUDC1 => self.audio.set_audc((location - REG_AUDC0) as usize, byte),
+                REG_AUDF0 | REG_AUDF1 => self.audio.set_audf((location - REG_AUDF0) as usize, byte),
+                REG_AUDV0 | REG_AUDV1 => self.audio.set_audv((location - REG_AUDV0) as usize, byte),
+                _ => {},
+            }
+        }
+
+        // Reads only look at the low 4 address bits
+        pub fn register_read(&mut self, location: u16) -> u8 {
+            let location = location & 0x0F;
+            if (REG_INPT0..=REG_INPT5).contains(&location) {
+                if self.inputs[(location - REG_INPT0) as usize] { 0x80 } else { 0x00 }
+            } else {
+                0
+            }
+        }
+
+        // Called every colour clock, every other MARIA clock
+        pub fn execute_tick(&mut self) {
+            self.audio.execute_tick();
+        }
+    }
+}
@@ -0,0 +1,157 @@
+pub mod prosystem {
+
+use prosystem::prosystem_a78::prosystem::ProsystemControllerType;
+use prosystem::prosystem_cartridge::prosystem::{ProsystemBankType, ProsystemCartridge, ProsystemCartridgeError};
+use prosystem::prosystem_maria::prosystem::{ProsystemMaria, X_RESOLUTION};
+use prosystem::prosystem_memory::prosystem::ProsystemMemory;
+use vcs::vcs_console_type::vcs::{ConsoleType, VcsConsoleType};
+use vcs::vcs_palette::vcs::VcsPalette;
+
+const SUPERGAME: u16 = 0x0002;
+const SUPERGAME_RAM: u16 = 0x0004;
+const ROM_AT_4000: u16 = 0x0008;
+const ACTIVISION: u16 = 0x0100;
+
+fn a78_image(cart_type: u16, rom: &[u8]) -> Vec<u8> {
+    let mut image = vec![0u8; 128];
+    image[0] = 3;
+    image[1..10].copy_from_slice(b"ATARI7800");
+    image[17..21].copy_from_slice(b"Test");
+    image[49..53].copy_from_slice(&(rom.len() as u32).to_be_bytes());
+    image[53..55].copy_from_slice(&cart_type.to_be_bytes());
+    image[55] = 1;
+    image.extend_from_slice(rom);
+    image
+}
+
+// Each block of the ROM filled with its number
+fn numbered_rom(blocks: usize, block_size: usize) -> Vec<u8> {
+    (0..blocks).flat_map(|block| vec![block as u8; block_size]).collect()
+}
+
+#[test]
+fn test_a78_header() {
+    let mut image = a78_image(SUPERGAME | ROM_AT_4000, &numbered_rom(8, 0x4000));
+    image[56] = 3;
+    image[57] = 1;
+    // Padding after the ROM the header sizes
+    image.extend_from_slice(&[0xff; 64]);
+
+    let cartridge = ProsystemCartridge::new(&image).unwrap();
+    let header = cartridge.get_header().unwrap();
+    assert_eq!(header.title, "Test");
+    assert_eq!(header.rom_size, 0x20000);
+    assert!(header.is_supergame() && header.has_rom_at_4000() && !header.is_activision() && !header.has_pokey());
+    assert_eq!((header.left_controller, header.right_controller),
+        (ProsystemControllerType::Joystick, ProsystemControllerType::Paddle));
+    assert_eq!(header.console_type, ConsoleType::PAL);
+    assert_eq!(cartridge.get_bank_type(), ProsystemBankType::SuperGame);
+    assert_eq!(cartridge.read(0xffff), Some(7));
+
+    let flat = ProsystemCartridge::new(&[0xea; 0x4000]).unwrap();
+    assert!(flat.get_header().is_none());
+    assert_eq!(flat.get_bank_type(), ProsystemBankType::Flat);
+    assert_eq!((flat.read(0xbfff), flat.read(0xc000)), (None, Some(0xea)));
+
+    assert!(matches!(ProsystemCartridge::new(&a78_image(SUPERGAME, &[0; 0x5000])),
+        Err(ProsystemCartridgeError::UnsupportedSize(0x5000))));
+}
+
+#[test]
+fn test_vcs_image() {
+    // The BIOS reads the region nibble from 0xFFF9
+    let mut rom = vec![0u8; 0x4000];
+    rom[0x3ff9] = 0xf7;
+    assert!(!ProsystemCartridge::is_vcs_image(&rom));
+    rom[0x3ff9] = 0xf3;
+    assert!(!ProsystemCartridge::is_vcs_image(&rom));
+    rom[0x3ff9] = 0xf0;
+    assert!(ProsystemCartridge::is_vcs_image(&rom));
+    assert!(ProsystemCartridge::is_vcs_image(&[0u8; 0x800]));
+    assert!(!ProsystemCartridge::is_vcs_image(&a78_image(0, &[0u8; 0x4000])));
+}
+
+#[test]
+fn test_supergame() {
+    let mut cartridge = ProsystemCartridge::new(&a78_image(SUPERGAME, &numbered_rom(8, 0x4000))).unwrap();
+    assert_eq!((cartridge.read(0x4000), cartridge.read(0x8000), cartridge.read(0xc000)), (None, Some(0), Some(7)));
+    cartridge.write(0x8000, 3);
+    assert_eq!((cartridge.read(0xbfff), cartridge.read(0xc000)), (Some(3), Some(7)));
+
+    // 144K carts keep bank 0 at 0x4000 and switch banks 1 to 8
+    let mut cartridge = ProsystemCartridge::new(&a78_image(SUPERGAME, &numbered_rom(9, 0x4000))).unwrap();
+    assert_eq!((cartridge.read(0x4000), cartridge.read(0x8000), cartridge.read(0xc000)), (Some(0), Some(1), Some(8)));
+    cartridge.write(0x9000, 7);
+    assert_eq!(cartridge.read(0x8000), Some(8));
+
+    let mut cartridge = ProsystemCartridge::new(&a78_image(SUPERGAME | SUPERGAME_RAM, &numbered_rom(8, 0x4000))).unwrap();
+    cartridge.write(0x4123, 0x5a);
+    assert_eq!((cartridge.read(0x4123), cartridge.read(0x8000)), (Some(0x5a), Some(0)));
+}
+
+#[test]
+fn test_activision() {
+    let mut cartridge = ProsystemCartridge::new(&a78_image(ACTIVISION, &numbered_rom(16, 0x2000))).unwrap();
+    assert_eq!(cartridge.get_bank_type(), ProsystemBankType::Activision);
+    let blocks = |cartridge: &ProsystemCartridge| [0x4000, 0x6000, 0x8000, 0xa000, 0xc000, 0xe000]
+        .map(|address| cartridge.read(address).unwrap());
+    assert_eq!(blocks(&cartridge), [13, 12, 15, 0, 1, 14]);
+
+    // The address written picks the bank, not the byte
+    cartridge.write(0xff83, 0);
+    assert_eq!(blocks(&cartridge), [13, 12, 15, 6, 7, 14]);
+}
+
+#[test]
+fn test_display_list() {
+    // A flat 48K ROM at 0x4000, the display list list at 0x8000
+    let mut rom = vec![0u8; 0xc000];
+    let mut put = |address: usize, bytes: &[u8]| rom[address - 0x4000..address - 0x4000 + bytes.len()].copy_from_slice(bytes);
+
+    // A one line zone with a display list interrupt, then 16 line zones with an empty list
+    put(0x8000, &[0x80, 0x90, 0x00]);
+    for zone in 1..=16 {
+        put(0x8000 + zone * 3, &[0x0f, 0x91, 0x00]);
+    }
+    // A direct 160A byte in palette 1 at 10, then an indirect two character object in palette 2 at 40
+    put(0x9000, &[0x00, 0x3f, 0xa0, 10]);
+    put(0x9004, &[0x00, 0x60, 0x92, 0x5e, 40]);
+    put(0x9200, &[0x00, 0x01]);
+    put(0xa000, &[0b0110_1100]);
+    put(0xa100, &[0xff, 0x55]);
+
+    let mut memory = ProsystemMemory::new(ProsystemCartridge::new(&rom).unwrap());
+    let mut maria = ProsystemMaria::new(ConsoleType::NTSC);
+    maria.reset();
+
+    // BACKGRND, P1C1 - P1C3, P2C1, P2C3, CHARBASE, DPPH, DPPL and CTRL with DMA on
+    for (register, byte) in [(0x20, 0x00), (0x25, 0x86), (0x26, 0x44), (0x27, 0x1e), (0x29, 0x34), (0x2b, 0xc8),
+        (0x34, 0xa1), (0x2c, 0x80), (0x30, 0x00), (0x3c, 0x40)] {
+        maria.register_write(register & 0x1f, byte);
+    }
+
+    let mut nmi = false;
+    while !maria.take_frame_ready() {
+        maria.execute_tick(&mut memory);
+        nmi |= maria.take_nmi();
+    }
+    assert!(nmi);
+
+    let palette = VcsPalette::new(&VcsConsoleType::new(ConsoleType::NTSC));
+    let screen = maria.get_screen();
+    let pixel = |line: usize, x: usize| {
+        let index = (line * X_RESOLUTION as usize + x) * 3;
+        (screen[index], screen[index + 1], screen[index + 2])
+    };
+
+    // The zone's line shows on the line after its DMA
+    let background = palette.get_color(0x00);
+    assert_eq!(pixel(0, 20), background);
+    for (x, color) in [(19, 0x00), (20, 0x86), (21, 0x86), (22, 0x44), (24, 0x1e), (26, 0x00),
+        (80, 0xc8), (87, 0xc8), (88, 0x34), (95, 0x34), (96, 0x00)] {
+        assert_eq!(pixel(1, x), palette.get_color(color), "x {}", x);
+    }
+    assert_eq!(pixel(2, 20), background);
+}
+
+}
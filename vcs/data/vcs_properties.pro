@@ -0,0 +1,22 @@
+; VCS ROM properties, keyed by the MD5 of the ROM image.
+; Same format as Stella's properties file, each entry ends with an empty "" line.
+; This is only a starter set. Add or override entries in vcs_user.pro in the
+; working directory, a copy of Stella's stella.pro gives its whole database.
+
+"Cart.MD5" "157bddb7192754a45372be196797f284"
+"Cart.Name" "Adventure (1980) (Atari)"
+"Cart.Type" "4K"
+"Display.Format" "NTSC"
+""
+
+"Cart.MD5" "4c8832ed387bbafc055320c05205bc08"
+"Cart.Name" "Combat (1977) (Atari)"
+"Cart.Type" "2K"
+"Display.Format" "NTSC"
+""
+
+"Cart.MD5" "3e90cf23106f2e08b2781e41299de556"
+"Cart.Name" "Pitfall! (1982) (Activision)"
+"Cart.Type" "4K"
+"Display.Format" "NTSC"
+""
@@ -7,9 +7,12 @@ pub mod vcs_cartridge4k;
 pub mod vcs_cartridgef8;
 pub mod vcs_console;
 pub mod vcs_console_type;
+pub mod vcs_controller;
 pub mod vcs_parameters;
 pub mod vcs_palette;
+pub mod vcs_properties;
 pub mod vcs_riot;
+pub mod vcs_savekey;
 pub mod vcs_tia;
 pub mod vcs_bevy;
 pub mod prelude;
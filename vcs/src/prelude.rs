@@ -4,3 +4,6 @@ pub use crate::vcs_bevy::vcs::VcsBevy;
     
 pub use crate::vcs_bevy::vcs::VcsRomFile;
 
+pub use crate::vcs_cartridge::vcs::VcsCartridgeError;
+
+pub use crate::vcs_console_type::vcs::ConsoleType;
@@ -0,0 +1,820 @@
+pub mod vcs {
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use emucpu::prelude::*;
+use emumemory::prelude::*;
+
+use vcs::vcs_audio::vcs::VcsAudio;
+use vcs::vcs_cartridge::vcs::VcsBankType;
+use vcs::vcs_console::vcs::VcsConsole;
+use vcs::vcs_console_type::vcs::{ConsoleType, VcsConsoleType};
+use vcs::vcs_controller::vcs::VcsController;
+use vcs::vcs_properties::vcs::{VcsControllerType, VcsPropertiesDatabase};
+use vcs::vcs_riot::vcs::{VcsDifficulty, VcsRiot, VcsSwitches};
+use vcs::vcs_savekey::vcs::VcsSaveKey;
+use vcs::vcs_tia::vcs::{VcsTia, MAX_FRAME_LINES};
+
+const ADVENTURE_MD5: &str = "157bddb7192754a45372be196797f284";
+const COMBAT_MD5: &str = "4c8832ed387bbafc055320c05205bc08";
+
+const DEVICE_WRITE: u8 = 0xa0;
+const DEVICE_READ: u8 = 0xa1;
+
+// A scratch file per test, so they can run side by side
+fn temp_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("vcs_test_{}_{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+// Drives SDA and SCL the way a game's I2C routine does
+struct I2cMaster {
+    savekey: VcsSaveKey,
+}
+
+impl I2cMaster {
+    fn new(path: &Path) -> Self {
+        Self { savekey: VcsSaveKey::new(path.to_str().unwrap()) }
+    }
+
+    fn start(&mut self) {
+        self.savekey.set_lines(true, false);
+        self.savekey.set_lines(true, true);
+        self.savekey.set_lines(false, true);
+        self.savekey.set_lines(false, false);
+    }
+
+    fn stop(&mut self) {
+        self.savekey.set_lines(false, false);
+        self.savekey.set_lines(false, true);
+        self.savekey.set_lines(true, true);
+    }
+
+    // One clock with SDA released, returns the level the EEPROM put on it
+    fn clock_in(&mut self) -> bool {
+        self.savekey.set_lines(true, false);
+        self.savekey.set_lines(true, true);
+        let sda = self.savekey.get_sda();
+        self.savekey.set_lines(true, false);
+        sda
+    }
+
+    // Returns whether the EEPROM acknowledged the byte
+    fn send(&mut self, byte: u8) -> bool {
+        for bit in (0..8).rev() {
+            let sda = byte & (1 << bit) > 0;
+            self.savekey.set_lines(sda, false);
+            self.savekey.set_lines(sda, true);
+            self.savekey.set_lines(sda, false);
+        }
+        !self.clock_in()
+    }
+
+    fn receive(&mut self, acknowledge: bool) -> u8 {
+        let byte = (0..8).fold(0, |byte, _| (byte << 1) | self.clock_in() as u8);
+        self.savekey.set_lines(!acknowledge, false);
+        self.savekey.set_lines(!acknowledge, true);
+        self.savekey.set_lines(!acknowledge, false);
+        byte
+    }
+
+    fn write(&mut self, address: u16, bytes: &[u8]) {
+        self.start();
+        assert!(self.send(DEVICE_WRITE) && self.send((address >> 8) as u8) && self.send(address as u8));
+        for byte in bytes {
+            assert!(self.send(*byte));
+        }
+        self.stop();
+    }
+
+    // A random read, the address is set by a write that a repeated start ends
+    fn read(&mut self, address: u16, count: usize) -> Vec<u8> {
+        self.start();
+        assert!(self.send(DEVICE_WRITE) && self.send((address >> 8) as u8) && self.send(address as u8));
+        self.start();
+        assert!(self.send(DEVICE_READ));
+        let bytes = (0..count).map(|index| self.receive(index + 1 < count)).collect();
+        self.stop();
+        bytes
+    }
+}
+
+#[test]
+fn test_savekey_write_read() {
+    let path = temp_file("write_read.dat");
+    let mut master = I2cMaster::new(&path);
+
+    assert_eq!(master.read(0x0110, 2), [0xff, 0xff]);
+    master.write(0x0110, &[0x12, 0x34, 0x56]);
+    assert_eq!(master.read(0x0110, 3), [0x12, 0x34, 0x56]);
+
+    // The stop saved the EEPROM, a new SaveKey loads it
+    assert_eq!(fs::read(&path).unwrap()[0x0110..0x0113], [0x12, 0x34, 0x56]);
+    let mut master = I2cMaster::new(&path);
+    assert_eq!(master.read(0x010f, 3), [0xff, 0x12, 0x34]);
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_savekey_page_wrap() {
+    let path = temp_file("page_wrap.dat");
+    let mut master = I2cMaster::new(&path);
+
+    // Writes wrap within the 64 byte page, reads carry on into the next
+    master.write(0x013e, &[1, 2, 3, 4]);
+    assert_eq!(master.read(0x013e, 4), [1, 2, 0xff, 0xff]);
+    assert_eq!(master.read(0x0100, 2), [3, 4]);
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_savekey_other_device() {
+    let path = temp_file("other_device.dat");
+    let mut master = I2cMaster::new(&path);
+
+    // Only device 1010 000 answers, the game sees SDA stay high
+    master.start();
+    assert!(!master.send(0xa2));
+    assert!(!master.send(0x00));
+    master.stop();
+
+    master.start();
+    assert!(master.send(DEVICE_WRITE));
+    master.stop();
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_properties_load() {
+    let mut database = VcsPropertiesDatabase::with_user_file(&temp_file("none.pro"));
+    database.load_properties(r#"
+        ; A comment, then an escaped quote and text outside the quotes
+        "Cart.MD5" "0123456789ABCDEF0123456789ABCDEF"
+        "Cart.Name"  "The \"Game\""  ignored
+        "Cart.Type" "F8SC"
+        "Display.Format" "PAL60"
+        "Controller.Left" "paddles"
+        "Controller.Right" "Unknown"
+        "Display.YStart" "30"
+        "Display.Height" "tall"
+        ""
+        "Cart.Name" "No MD5"
+        ""
+        "Cart.MD5" "fedcba9876543210fedcba9876543210"
+        "Cart.Type" "NOSUCH"
+        "Cart.Name" "Last entry needs no end""#);
+
+    let properties = database.get_properties_by_md5("0123456789abcdef0123456789abcdef").unwrap();
+    assert_eq!(properties.name, "The \"Game\"");
+    assert_eq!(properties.bank_type, Some(VcsBankType::BankF8SC));
+    assert_eq!(properties.console_type, Some(ConsoleType::PAL));
+    assert_eq!((properties.left_controller, properties.right_controller),
+        (VcsControllerType::Paddles, VcsControllerType::Joystick));
+    assert_eq!((properties.display_ystart, properties.display_height), (Some(30), None));
+
+    // An unknown type is reported and left to detection
+    let properties = database.get_properties_by_md5("FEDCBA9876543210FEDCBA9876543210").unwrap();
+    assert_eq!((properties.name.as_str(), properties.bank_type), ("Last entry needs no end", None));
+    assert!(database.get_properties_by_md5(ADVENTURE_MD5).is_some());
+}
+
+#[test]
+fn test_user_properties_override() {
+    let image = [0xeau8; 0x800];
+    let image_md5 = format!("{:x}", md5::compute(image));
+    let path = temp_file("user.pro");
+    fs::write(&path, format!("
+        \"Cart.MD5\" \"{}\"
+        \"Display.Format\" \"PAL\"
+        \"\"
+        \"Cart.MD5\" \"{}\"
+        \"Cart.Name\" \"NOPs\"
+        \"Cart.Type\" \"2K\"
+        \"\"", ADVENTURE_MD5, image_md5)).unwrap();
+
+    // Built in entries are replaced whole, not merged
+    let database = VcsPropertiesDatabase::with_user_file(&path);
+    let adventure = database.get_properties_by_md5(ADVENTURE_MD5).unwrap();
+    assert_eq!((adventure.name.as_str(), adventure.bank_type, adventure.console_type),
+        ("Unknown", None, Some(ConsoleType::PAL)));
+    assert_eq!(database.get_properties_by_md5(COMBAT_MD5).unwrap().bank_type, Some(VcsBankType::Bank2K));
+
+    let properties = database.get_properties(&image);
+    assert_eq!((properties.name.as_str(), properties.bank_type), ("NOPs", Some(VcsBankType::Bank2K)));
+    let unknown = database.get_properties(&[0u8; 0x800]);
+    assert_eq!((unknown.name.as_str(), unknown.bank_type), ("Unknown", None));
+
+    let database = VcsPropertiesDatabase::with_user_file(&temp_file("missing.pro"));
+    assert_eq!(database.get_properties_by_md5(ADVENTURE_MD5).unwrap().bank_type, Some(VcsBankType::Bank4K));
+    let _ = fs::remove_file(&path);
+}
+
+const SWCHA: u16 = 0x0280;
+const SWACNT: u16 = 0x0281;
+const SWCHB: u16 = 0x0282;
+const SWBCNT: u16 = 0x0283;
+const INTIM: u16 = 0x0284;
+const INSTAT: u16 = 0x0285;
+const TIM1T: u16 = 0x0294;
+const TIM8T: u16 = 0x0295;
+// A3 on timer writes and INTIM reads enables the timer interrupt
+const TIMER_IRQ: u16 = 0x0008;
+
+// Each access is one CPU cycle
+fn riot_write(riot: &mut VcsRiot, address: u16, byte: u8) {
+    riot.execute_tick(&mut AddressBus { address, write: true, byte });
+}
+
+fn riot_read(riot: &mut VcsRiot, address: u16) -> u8 {
+    let mut addr = AddressBus { address, write: false, byte: 0 };
+    riot.execute_tick(&mut addr);
+    addr.byte
+}
+
+fn new_riot() -> VcsRiot {
+    let mut riot = VcsRiot::new();
+    riot.reset();
+    riot
+}
+
+#[test]
+fn test_riot_ports() {
+    let mut riot = new_riot();
+
+    // Output bits read back the latch, inputs the controllers or switches
+    riot.set_swcha_input(0xaf);
+    riot_write(&mut riot, SWCHA, 0x3c);
+    assert_eq!(riot_read(&mut riot, SWCHA), 0xaf);
+    riot_write(&mut riot, SWACNT, 0x0f);
+    assert_eq!(riot_read(&mut riot, SWCHA), (0x3c & 0x0f) | (0xaf & !0x0f));
+    // A controller can still pull an output low
+    riot.set_swcha_input(0xa0);
+    assert_eq!(riot_read(&mut riot, SWCHA), 0xa0);
+    assert_eq!(riot.get_swcha_output(), 0xfc);
+
+    let switches = riot.get_switches().get_swchb();
+    riot_write(&mut riot, SWCHB, 0xf0);
+    assert_eq!(riot_read(&mut riot, SWCHB), switches);
+    riot_write(&mut riot, SWBCNT, 0x34);
+    assert_eq!(riot_read(&mut riot, SWCHB), (0xf0 & 0x34) | (switches & !0x34));
+
+    // The RIOT RAM is selected by A9 low, mirrored in the stack page
+    riot_write(&mut riot, 0x0080, 0x42);
+    assert_eq!(riot_read(&mut riot, 0x0180), 0x42);
+}
+
+#[test]
+fn test_switches() {
+    // Reset and select are active low, colour and difficulty A read high
+    let mut switches = VcsSwitches::new();
+    assert_eq!(switches.get_swchb(), 0x0b);
+    switches.reset = true;
+    assert_eq!(switches.get_swchb(), 0x0a);
+    switches.select = true;
+    assert_eq!(switches.get_swchb(), 0x08);
+    switches.color = false;
+    assert_eq!(switches.get_swchb(), 0x00);
+    switches.left_difficulty = VcsDifficulty::A;
+    assert_eq!(switches.get_swchb(), 0x40);
+    switches.right_difficulty = VcsDifficulty::A;
+    assert_eq!(switches.get_swchb(), 0xc0);
+    switches.left_difficulty = VcsDifficulty::B;
+    assert_eq!(switches.get_swchb(), 0x80);
+}
+
+#[test]
+fn test_riot_pa7_edge() {
+    // A0 picks the positive edge, A1 enables the interrupt
+    for edge_control in 0..4u16 {
+        let positive = edge_control & 0x01 > 0;
+        let (before, after) = if positive { (0x7f, 0xff) } else { (0xff, 0x7f) };
+        let mut riot = new_riot();
+        riot.set_swcha_input(after);
+        riot_write(&mut riot, 0x0080, 0);
+        riot_read(&mut riot, INSTAT);
+        riot_write(&mut riot, INTIM + edge_control, 0);
+
+        // The other edge leaves the flag alone
+        riot.set_swcha_input(before);
+        riot_write(&mut riot, 0x0080, 0);
+        assert_eq!(riot_read(&mut riot, INSTAT) & 0x40, 0x00, "edge control {}", edge_control);
+
+        riot.set_swcha_input(after);
+        riot_write(&mut riot, 0x0080, 0);
+        assert!(riot.get_irq() == (edge_control & 0x02 > 0), "edge control {}", edge_control);
+        assert_eq!(riot_read(&mut riot, INSTAT) & 0x40, 0x40, "edge control {}", edge_control);
+
+        // Reading INSTAT cleared it
+        assert_eq!(riot_read(&mut riot, INSTAT) & 0x40, 0x00, "edge control {}", edge_control);
+        assert!(!riot.get_irq());
+    }
+}
+
+#[test]
+fn test_riot_timer() {
+    let mut riot = new_riot();
+
+    // Loaded with one less, then a count every 8 cycles
+    riot_write(&mut riot, TIM8T, 2);
+    let counts: Vec<u8> = (0..15).map(|_| riot_read(&mut riot, INTIM)).collect();
+    assert_eq!(counts[..7], [1; 7]);
+    assert_eq!(counts[7..], [0; 8]);
+    assert_eq!(riot_read(&mut riot, INSTAT) & 0x80, 0x80);
+    assert!(!riot.get_irq());
+    // Past zero it wrapped to 0xff and counts every cycle
+    let counts: Vec<u8> = (0..4).map(|_| riot_read(&mut riot, INTIM)).collect();
+    assert_eq!(counts, [0xfe, 0xfd, 0xfc, 0xfb]);
+
+    // Writing the timer clears the flag, A3 turns the interrupt on
+    riot_write(&mut riot, TIM1T | TIMER_IRQ, 4);
+    assert_eq!(riot_read(&mut riot, INSTAT) & 0x80, 0x00);
+    riot_write(&mut riot, 0x0080, 0);
+    assert!(!riot.get_irq());
+    riot_write(&mut riot, 0x0080, 0);
+    assert!(riot.get_irq());
+
+    // Reading INTIM doesn't clear the flag straight after passing zero
+    riot_read(&mut riot, INTIM | TIMER_IRQ);
+    assert!(riot.get_irq());
+
+    // Reading INTIM without A3 turns it off and clears the flag
+    riot_read(&mut riot, INTIM);
+    assert!(!riot.get_irq());
+    assert_eq!(riot_read(&mut riot, INSTAT) & 0x80, 0x00);
+}
+
+const VSYNC: u16 = 0x0000;
+const VBLANK: u16 = 0x0001;
+// A12 set misses the TIA
+const TIA_IDLE: u16 = 0x1000;
+const NUSIZ0: u16 = 0x0004;
+const COLUP0: u16 = 0x0006;
+const COLUPF: u16 = 0x0008;
+const COLUBK: u16 = 0x0009;
+const PF1: u16 = 0x000e;
+const RESP0: u16 = 0x0010;
+const RESM0: u16 = 0x0012;
+const GRP0: u16 = 0x001b;
+const ENAM0: u16 = 0x001d;
+const HMP0: u16 = 0x0020;
+const HMOVE: u16 = 0x002a;
+const WHITE: u8 = 0x0e;
+const INPT0: u16 = 0x0008;
+const LINE_CLOCKS: u32 = 228;
+
+fn new_tia() -> VcsTia {
+    let mut tia = VcsTia::new(&VcsConsoleType::new(ConsoleType::NTSC));
+    tia.reset();
+    tia
+}
+
+// Each access is one colour clock
+fn tia_write(tia: &mut VcsTia, address: u16, byte: u8) {
+    tia.execute_tick(&mut AddressBus { address, write: true, byte });
+}
+
+fn tia_read(tia: &mut VcsTia, address: u16) -> u8 {
+    let mut addr = AddressBus { address, write: false, byte: 0 };
+    tia.execute_tick(&mut addr);
+    addr.byte
+}
+
+fn tia_idle(tia: &mut VcsTia, clocks: u32) {
+    for _ in 0..clocks {
+        tia.execute_tick(&mut AddressBus { address: TIA_IDLE, write: false, byte: 0 });
+    }
+}
+
+// Each write on its clock of the line, then the rest of the line
+fn tia_line(tia: &mut VcsTia, writes: &[(u32, u16, u8)]) {
+    let mut clock = 0;
+    for (write_clock, address, byte) in writes {
+        tia_idle(tia, write_clock - clock);
+        tia_write(tia, *address, *byte);
+        clock = write_clock + 1;
+    }
+    tia_idle(tia, LINE_CLOCKS - clock);
+}
+
+// Ends the frame, then the pixels on line that aren't black
+fn frame_pixels(tia: &mut VcsTia, line: u32) -> Vec<u32> {
+    tia_write(tia, VSYNC, 0x02);
+    tia.get_screen(line, 1).chunks(3).enumerate()
+        .filter(|(_, rgb)| rgb.iter().any(|color| *color > 0))
+        .map(|(x, _)| x as u32)
+        .collect()
+}
+
+// 3 lines of VSYNC then vblank_lines of VBLANK, lines in all
+fn tia_frame(tia: &mut VcsTia, vblank_lines: u32, lines: u32) {
+    tia_line(tia, &[(0, VBLANK, 0x02), (1, VSYNC, 0x02)]);
+    tia_idle(tia, LINE_CLOCKS * 2);
+    tia_line(tia, &[(0, VSYNC, 0x00)]);
+    tia_idle(tia, LINE_CLOCKS * (vblank_lines - 1));
+    tia_line(tia, &[(0, VBLANK, 0x00)]);
+    tia_idle(tia, LINE_CLOCKS * (lines - 4 - vblank_lines));
+}
+
+#[test]
+fn test_tia_frame_lines() {
+    let mut tia = new_tia();
+
+    // Each VSYNC ends the frame before it
+    tia_frame(&mut tia, 37, 262);
+    assert!(tia.take_frame_ready());
+    assert!(!tia.take_frame_ready());
+    tia_frame(&mut tia, 45, 312);
+    assert!(tia.take_frame_ready());
+    assert_eq!(tia.get_frame_lines(), 262);
+    assert_eq!(tia.get_first_visible_line(), Some(37));
+    tia_frame(&mut tia, 37, 262);
+    assert_eq!(tia.get_frame_lines(), 312);
+    assert_eq!(tia.get_first_visible_line(), Some(45));
+
+    // Lines count from the end of VSYNC
+    assert_eq!(tia.get_scan_line(), 262 - 3);
+}
+
+#[test]
+fn test_tia_lost_sync() {
+    let mut tia = new_tia();
+
+    // Without VSYNC the frame is cut off at the line limit
+    tia_idle(&mut tia, LINE_CLOCKS * MAX_FRAME_LINES as u32 - 1);
+    assert!(!tia.take_frame_ready());
+    assert_eq!(tia.get_scan_line(), MAX_FRAME_LINES - 1);
+    tia_idle(&mut tia, 1);
+    assert!(tia.take_frame_ready());
+    assert_eq!(tia.get_scan_line(), 0);
+    assert_eq!(tia.get_frame_lines(), 0);
+    assert_eq!(tia.get_first_visible_line(), Some(0));
+
+    // VBLANK held on all frame leaves nothing visible
+    tia_write(&mut tia, VBLANK, 0x02);
+    tia_idle(&mut tia, LINE_CLOCKS * MAX_FRAME_LINES as u32);
+    assert!(tia.take_frame_ready());
+    assert_eq!(tia.get_first_visible_line(), None);
+}
+
+#[test]
+fn test_tia_playfield_write() {
+    let mut tia = new_tia();
+
+    // PF1 lands 2 clocks after the write, clock 93 is pixel 28 where the
+    // next 4 pixels are fetched. A clock later misses it and waits for pixel 32.
+    tia_line(&mut tia, &[(0, COLUPF, WHITE), (93, PF1, 0xff)]);
+    tia_line(&mut tia, &[(0, PF1, 0x00), (94, PF1, 0xff)]);
+    assert_eq!(frame_pixels(&mut tia, 0), (28..48).chain(96..128).collect::<Vec<u32>>());
+    assert_eq!(frame_pixels(&mut tia, 1), (32..48).chain(96..128).collect::<Vec<u32>>());
+}
+
+#[test]
+fn test_tia_player_write() {
+    let mut tia = new_tia();
+
+    // RESP0 on clock 100 puts the player at pixel 37 from the next line
+    tia_line(&mut tia, &[(0, COLUP0, WHITE), (100, RESP0, 0)]);
+    tia_line(&mut tia, &[(0, GRP0, 0xff)]);
+    // GRP0 shows a clock after the write, from pixel 40
+    tia_line(&mut tia, &[(0, GRP0, 0x00), (106, GRP0, 0xff)]);
+    assert_eq!(frame_pixels(&mut tia, 1), (37..45).collect::<Vec<u32>>());
+    assert_eq!(frame_pixels(&mut tia, 2), (40..45).collect::<Vec<u32>>());
+}
+
+#[test]
+fn test_tia_player_copies() {
+    // Copies 16 clocks apart are close, 32 medium and 64 wide.
+    // Double and quad width start a clock late.
+    let copies: [(u8, Vec<u32>); 8] = [
+        (0, vec![37]),
+        (1, vec![37, 53]),
+        (2, vec![37, 69]),
+        (3, vec![37, 53, 69]),
+        (4, vec![37, 101]),
+        (5, vec![38, 39]),
+        (6, vec![37, 69, 101]),
+        (7, vec![38, 39, 40, 41]),
+    ];
+
+    for (nusiz, pixels) in copies {
+        let mut tia = new_tia();
+        tia_line(&mut tia, &[(0, COLUP0, WHITE), (1, NUSIZ0, nusiz), (2, GRP0, 0x80), (100, RESP0, 0)]);
+        tia_line(&mut tia, &[]);
+        assert_eq!(frame_pixels(&mut tia, 1), pixels, "NUSIZ {}", nusiz);
+    }
+}
+
+#[test]
+fn test_tia_missile_size() {
+    // NUSIZ bits 4 and 5 give 1, 2, 4 or 8 pixels
+    for size in 0..4u8 {
+        let mut tia = new_tia();
+        tia_line(&mut tia, &[(0, COLUP0, WHITE), (1, NUSIZ0, size << 4), (2, ENAM0, 0x02), (100, RESM0, 0)]);
+        tia_line(&mut tia, &[]);
+        assert_eq!(frame_pixels(&mut tia, 1), (36..36 + (1 << size)).collect::<Vec<u32>>(), "size {}", size);
+    }
+}
+
+#[test]
+fn test_tia_hmove() {
+    // The high nibble is signed, positive moves left
+    let moves: [(u8, u32); 5] = [(0x70, 30), (0x10, 36), (0x00, 37), (0xf0, 38), (0x80, 45)];
+
+    for (hmp0, x) in moves {
+        let mut tia = new_tia();
+        tia_line(&mut tia, &[(0, COLUP0, WHITE), (1, GRP0, 0x80), (100, RESP0, 0)]);
+        tia_line(&mut tia, &[(0, HMP0, hmp0)]);
+        tia_line(&mut tia, &[(1, HMOVE, 0)]);
+        tia_line(&mut tia, &[]);
+        assert_eq!(frame_pixels(&mut tia, 1), [37], "HMP0 {:02x}", hmp0);
+        assert_eq!(frame_pixels(&mut tia, 2), [x], "HMP0 {:02x}", hmp0);
+        assert_eq!(frame_pixels(&mut tia, 3), [x], "HMP0 {:02x}", hmp0);
+    }
+}
+
+#[test]
+fn test_tia_hmove_comb() {
+    let mut tia = new_tia();
+
+    // HMOVE in HBLANK blanks the first 8 pixels of that line only
+    tia_line(&mut tia, &[(0, COLUBK, WHITE)]);
+    tia_line(&mut tia, &[(1, HMOVE, 0)]);
+    tia_line(&mut tia, &[]);
+    assert_eq!(frame_pixels(&mut tia, 0), (0..160).collect::<Vec<u32>>());
+    assert_eq!(frame_pixels(&mut tia, 1), (8..160).collect::<Vec<u32>>());
+    assert_eq!(frame_pixels(&mut tia, 2), (0..160).collect::<Vec<u32>>());
+}
+
+#[test]
+fn test_tia_paddle_charge() {
+    let mut tia = new_tia();
+
+    // Fully right the 1.8K resistor alone charges the capacitor in 156 clocks
+    tia.set_paddle_position(0, 1.0);
+    tia_write(&mut tia, VBLANK, 0x80);
+    tia_idle(&mut tia, 1000);
+    assert_eq!(tia_read(&mut tia, INPT0), 0x00);
+
+    // Released, INPT0 goes high once it reaches the trigger level
+    tia_write(&mut tia, VBLANK, 0x00);
+    let levels: Vec<u8> = (0..200).map(|_| tia_read(&mut tia, INPT0) & 0x80).collect();
+    assert_eq!(levels.iter().position(|level| *level > 0), Some(155));
+    assert!(levels[155..].iter().all(|level| *level > 0));
+
+    // Dumping again grounds it straight away
+    tia_write(&mut tia, VBLANK, 0x80);
+    assert_eq!(tia_read(&mut tia, INPT0), 0x00);
+
+    // A slower paddle takes longer
+    tia.set_paddle_position(0, 0.5);
+    tia_write(&mut tia, VBLANK, 0x00);
+    tia_idle(&mut tia, 1000);
+    assert_eq!(tia_read(&mut tia, INPT0), 0x00);
+}
+
+// A 4K kernel of 3 VSYNC lines, 37 VBLANK, 200 visible then the overscan
+fn frame_rom(name: &str, frame_lines: u16) -> PathBuf {
+    let source = format!("
+VSYNC = $00
+VBLANK = $01
+WSYNC = $02
+    .org $f000
+reset:
+    sei
+    cld
+    ldx #$ff
+    txs
+frame:
+    lda #2
+    sta VBLANK
+    sta VSYNC
+    sta WSYNC
+    sta WSYNC
+    sta WSYNC
+    lda #0
+    sta VSYNC
+    ldx #37
+vblank:
+    sta WSYNC
+    dex
+    bne vblank
+    stx VBLANK
+    ldx #200
+visible:
+    sta WSYNC
+    dex
+    bne visible
+    ldx #{}
+overscan:
+    sta WSYNC
+    dex
+    bne overscan
+    jmp frame
+    .org $fffc
+    .word reset
+    .word reset
+", frame_lines - 240);
+    let assembly = M6502Assembler::new(M6502Variant::Nmos).assemble(&source, 0xf000).unwrap();
+    let path = temp_file(name);
+    fs::write(&path, assembly.get_bytes()).unwrap();
+    path
+}
+
+fn new_console(path: &Path, console_type: Option<ConsoleType>) -> VcsConsole {
+    VcsConsole::new(path.to_string_lossy().into_owned(), console_type).unwrap()
+}
+
+#[test]
+fn test_detect_console_type() {
+    let ntsc = frame_rom("ntsc.bin", 262);
+    let pal = frame_rom("pal.bin", 312);
+
+    assert_eq!(new_console(&ntsc, None).get_console_type(), ConsoleType::NTSC);
+    let mut console = new_console(&pal, None);
+    assert_eq!(console.get_console_type(), ConsoleType::PAL);
+
+    // The picture starts on the first line with VBLANK off, not PAL's usual 45
+    assert_eq!(console.get_y_start(), 45);
+    for _ in 0..3 {
+        console.run_frame();
+    }
+    assert_eq!(console.get_y_start(), 37);
+
+    // The menu's choice wins over detection
+    assert_eq!(new_console(&pal, Some(ConsoleType::NTSC)).get_console_type(), ConsoleType::NTSC);
+    assert_eq!(new_console(&ntsc, Some(ConsoleType::PAL)).get_console_type(), ConsoleType::PAL);
+
+    let _ = fs::remove_file(&ntsc);
+    let _ = fs::remove_file(&pal);
+}
+
+#[test]
+fn test_keypad() {
+    let mut keypad = VcsController::new(VcsControllerType::Keyboard);
+
+    // 5 is on the second row, only seen while that row is driven low
+    keypad.set_keypad_key(4, true);
+    assert_eq!(keypad.get_input_levels(0x0f), [true; 3]);
+    assert_eq!(keypad.get_input_levels(0x0e), [true; 3]);
+    assert_eq!(keypad.get_input_levels(0x0d), [true, false, true]);
+
+    // # is on the last row and column
+    keypad.set_keypad_key(11, true);
+    assert_eq!(keypad.get_input_levels(0x07), [true, true, false]);
+    assert_eq!(keypad.get_input_levels(0x05), [true, false, false]);
+    keypad.set_keypad_key(4, false);
+    assert_eq!(keypad.get_input_levels(0x0d), [true; 3]);
+}
+
+#[test]
+fn test_driving_controller() {
+    let mut driving = VcsController::new(VcsControllerType::Driving);
+
+    // Gray code on the up and down lines, the others stay high
+    let mut clockwise = Vec::new();
+    for _ in 0..5 {
+        clockwise.push(driving.get_direction_nibble());
+        driving.rotate(1);
+    }
+    assert_eq!(clockwise, [0x0c, 0x0d, 0x0f, 0x0e, 0x0c]);
+
+    let mut counter_clockwise = Vec::new();
+    for _ in 0..5 {
+        counter_clockwise.push(driving.get_direction_nibble());
+        driving.rotate(-1);
+    }
+    assert_eq!(counter_clockwise, [0x0d, 0x0c, 0x0e, 0x0f, 0x0d]);
+
+    driving.set_trigger(true);
+    assert_eq!(driving.get_input_levels(0x0f), [true, true, false]);
+}
+
+#[test]
+fn test_console_joysticks() {
+    let source = "
+SWCHA = $0280
+    .org $f000
+reset:
+    lda SWCHA
+done:
+    jmp reset
+    .org $fffc
+    .word reset
+    .word reset
+";
+    let assembly = M6502Assembler::new(M6502Variant::Nmos).assemble(source, 0xf000).unwrap();
+    let path = temp_file("joysticks.bin");
+    fs::write(&path, assembly.get_bytes()).unwrap();
+
+    let mut console = new_console(&path, Some(ConsoleType::NTSC));
+    console.set_controller_type(0, VcsControllerType::Joystick);
+    console.set_controller_type(1, VcsControllerType::Joystick);
+    console.get_debugger_mut().add_breakpoint(assembly.get_label("done").unwrap(), None);
+
+    // The left joystick is the high nibble of SWCHA, the right one the low
+    console.controller_up_down(1, 1);
+    console.controller_left_right(1, -1);
+    console.run_frame();
+    assert_eq!(console.get_cpu().accumulator, 0xfa);
+
+    console.controller_up_down(1, 0);
+    console.controller_left_right(1, 0);
+    console.controller_up_down(0, -1);
+    console.controller_left_right(0, 1);
+    console.get_debugger_mut().resume();
+    console.run_frame();
+    assert_eq!(console.get_cpu().accumulator, 0x5f);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_console_memory_trap() {
+    let source = "
+    .org $f000
+reset:
+    lda #1
+    sta $f800
+done:
+    jmp done
+    .org $fffc
+    .word reset
+    .word reset
+";
+    let assembly = M6502Assembler::new(M6502Variant::Nmos).assemble(source, 0xf000).unwrap();
+    let path = temp_file("memory_trap.bin");
+    fs::write(&path, assembly.get_bytes()).unwrap();
+
+    // Logged by default, the console keeps running
+    let mut console = new_console(&path, Some(ConsoleType::NTSC));
+    assert!(console.run_frame().0.is_some());
+    assert!(!console.get_debugger_mut().is_stopped());
+
+    // Trapped, the write to the ROM stops it before the next instruction
+    let mut console = new_console(&path, Some(ConsoleType::NTSC));
+    console.set_memory_policy(MemoryErrorPolicy::Trap);
+    assert!(console.run_frame().0.is_none());
+    assert_eq!(console.get_debugger_mut().get_break(),
+        Some(M6502Break::Memory(MemoryError::ReadOnly(String::from("VCS Cartridge"), 0x0800))));
+    assert_eq!(console.get_cpu().program_counter, assembly.get_label("done").unwrap());
+
+    let _ = fs::remove_file(&path);
+}
+
+fn audio_ticks(audio: &mut VcsAudio, clocks: u32) {
+    for _ in 0..clocks {
+        audio.execute_tick();
+    }
+}
+
+#[test]
+fn test_audio_period() {
+    let mut audio = VcsAudio::new(50);
+
+    // Pure tone, the divider clocks every AUDF + 1 audio clocks and the output flips each time
+    audio.set_audc(0, 0x04);
+    audio.set_audf(0, 3);
+    audio.set_audv(0, 15);
+
+    // A sample at clocks 37 and 149 of each line, 441 lines give the 882 a 50Hz frame takes
+    audio_ticks(&mut audio, 441 * LINE_CLOCKS);
+    let samples = audio.get_audio_buffer();
+    let high = samples.iter().position(|sample| *sample > 0.0).unwrap();
+    assert!(high < 8);
+    let tone = |sample: usize| if (sample - high) % 8 < 4 { 0.5 } else { 0.0 };
+    for (index, sample) in samples.iter().enumerate().skip(high) {
+        assert_eq!(*sample, tone(index), "sample {}", index);
+    }
+
+    // Half as many are stretched, the extra samples halfway to the next one
+    audio_ticks(&mut audio, 220 * LINE_CLOCKS + 38);
+    let stretched = audio.get_audio_buffer();
+    let last = 882 + 440;
+    for (index, sample) in stretched.iter().enumerate() {
+        let current = 882 + index / 2;
+        let expected = if index % 2 == 0 { tone(current) } else { (tone(current) + tone((current + 1).min(last))) / 2.0 };
+        assert_eq!(*sample, expected, "sample {}", index);
+    }
+}
+
+#[test]
+fn test_audio_resample() {
+    let mut audio = VcsAudio::new(50);
+
+    // AUDC 0 holds the output high so the samples follow AUDV
+    audio.set_audc(0, 0x00);
+    audio_ticks(&mut audio, 441 * LINE_CLOCKS);
+    assert!(audio.get_audio_buffer().iter().all(|sample| *sample == 0.0));
+
+    // A change shows from the first sample after it, stretched or not
+    audio.set_audv(0, 15);
+    audio_ticks(&mut audio, 441 * LINE_CLOCKS);
+    assert!(audio.get_audio_buffer().iter().all(|sample| *sample == 0.5));
+    audio.set_audv(0, 0);
+    audio_ticks(&mut audio, 220 * LINE_CLOCKS + 38);
+    assert!(audio.get_audio_buffer().iter().all(|sample| *sample == 0.0));
+}
+
+}
@@ -1,12 +1,17 @@
 
 pub mod vcs {
 
-    use crate::vcs_tia::vcs::TiaAudio;
     use crate::vcs_audio_channel::vcs::{DATA_SAMPLE_RATE_HZ, VcsAudioChannel};
 
+    // Colour clocks in a scanline, the audio clocks repeat every line
+    const AUDIO_LINE_CLOCKS: u8 = 228;
+
     pub struct VcsAudio {
         channels: Vec<VcsAudioChannel>,
         frames_per_second: u32,
+        counter: u8,
+        samples: Vec<f32>,
+        last_sample: f32,
     }
 
     impl VcsAudio {
@@ -14,28 +19,90 @@ pub mod vcs {
         pub fn new(frames_per_second: u32) -> VcsAudio {
 
             let channels: Vec<VcsAudioChannel> = vec![
-            VcsAudioChannel::new(frames_per_second),
-            VcsAudioChannel::new(frames_per_second)];
+            VcsAudioChannel::new(),
+            VcsAudioChannel::new()];
 
             Self {
                 channels,
                 frames_per_second,
+                counter: 0,
+                samples: Vec::new(),
+                last_sample: 0.0,
             }
         }
 
+        pub fn reset(&mut self) {
+            for channel in self.channels.iter_mut() {
+                channel.reset();
+            }
+            self.counter = 0;
+            self.samples.clear();
+            self.last_sample = 0.0;
+        }
+
         pub fn samples_per_frame(&self) -> usize {
             DATA_SAMPLE_RATE_HZ / self.frames_per_second as usize
         }
 
-        pub fn execute_frame(&mut self, audio_regs: TiaAudio) {
-            self.channels[0].set_channel_settings(audio_regs.v0, audio_regs.f0, audio_regs.c0);
-            self.channels[1].set_channel_settings(audio_regs.v1, audio_regs.f1, audio_regs.c1);
+        pub fn set_audc(&mut self, channel: usize, value: u8) {
+            self.channels[channel].set_audc(value);
+        }
+
+        pub fn set_audf(&mut self, channel: usize, value: u8) {
+            self.channels[channel].set_audf(value);
+        }
+
+        pub fn set_audv(&mut self, channel: usize, value: u8) {
+            self.channels[channel].set_audv(value);
+        }
+
+        // Called every colour clock
+        pub fn execute_tick(&mut self) {
+            match self.counter {
+                9 | 81 => {
+                    self.channels[0].phase0();
+                    self.channels[1].phase0();
+                },
+                37 | 149 => {
+                    let sample0 = self.channels[0].phase1();
+                    let sample1 = self.channels[1].phase1();
+                    self.samples.push((sample0 + sample1) as f32 / 30.0);
+                },
+                _ => {}
+            }
+
+            self.counter += 1;
+            if self.counter == AUDIO_LINE_CLOCKS {
+                self.counter = 0;
+            }
         }
 
-        pub fn get_audio_buffer(&mut self, channel: usize) -> Vec<f32> {
+        // Resample the TIA samples since the last call to the host rate
+        pub fn get_audio_buffer(&mut self) -> Vec<f32> {
             let samples_per_frame = self.samples_per_frame();
-            self.channels[channel].get_buffer(samples_per_frame).clone()
+            let mut buffer: Vec<f32> = Vec::with_capacity(samples_per_frame);
+
+            if self.samples.is_empty() {
+                buffer.resize(samples_per_frame, self.last_sample);
+                return buffer;
+            }
+
+            let step = self.samples.len() as f32 / samples_per_frame as f32;
+
+            for i in 0..samples_per_frame {
+                let position = i as f32 * step;
+                let index = position as usize;
+                let fraction = position - index as f32;
+                let current = self.samples[index.min(self.samples.len() - 1)];
+                let next = self.samples[(index + 1).min(self.samples.len() - 1)];
+                buffer.push(current + (next - current) * fraction);
+            }
+
+            self.last_sample = *self.samples.last().unwrap();
+            self.samples.clear();
+
+            buffer
         }
 
     }
-}
\ No newline at end of file
+}
@@ -1,284 +1,123 @@
-
 pub mod vcs {
-    
+
     pub const DATA_SAMPLE_RATE_HZ: usize = 44100;
 
-    #[derive(PartialEq, Eq)]
-    enum ShiftRegister { 
-        Four, 
-        Five, 
-        Nine, 
-        FiveToFour, 
-        Div2, 
-        Div31, 
-        Div31Four 
+    // One TIA audio channel. The frequency divider clocks a 4 bit pulse counter
+    // and a 5 bit noise counter, with AUDC picking the feedback between them.
+    // Clocked in two phases, twice per scanline, giving about 31.4 kHz.
+    pub struct VcsAudioChannel {
+        audc: u8,
+        audf: u8,
+        audv: u8,
+        clock_enable: bool,
+        divider_counter: u8,
+        pulse_counter: u8,
+        pulse_counter_hold: bool,
+        noise_counter: u8,
+        noise_counter_bit4: bool,
+        noise_feedback: bool,
     }
 
-    pub struct VcsAudioChannel {
-        total_sample: u64,
-        volume: f32,
-        frequency: u16,
-        volume_steps: Vec<f32>,
-        audio_buffer: Vec<f32>,
-        is_shutdown: bool,
-        shift_register: ShiftRegister,
-        apply_third: bool,
-        third_count: u8,
-        div2_count: u8,
-        div31_count: u8,
-        shift_4_register: u16,
-        shift_5_register: u16,
-        shift_9_register: u16,
-        frames_per_second: u32,
+    impl Default for VcsAudioChannel {
+        fn default() -> Self {
+            VcsAudioChannel::new()
+        }
     }
 
     impl VcsAudioChannel {
 
-        pub fn new (frames_per_second: u32) -> VcsAudioChannel {
-
+        pub fn new () -> VcsAudioChannel {
             Self {
-                total_sample: 0,
-                volume: 0.0,
-                frequency: 0,
-
-                volume_steps: vec![0.0, 0.067, 0.134, 0.201, 0.268, 0.335, 0.402, 0.469, 0.535, 0.602, 0.669, 0.736, 0.803, 0.870, 0.937, 1.0],
-                audio_buffer: vec![0.0f32; DATA_SAMPLE_RATE_HZ / frames_per_second as usize],
-                is_shutdown: false,
-                shift_register: ShiftRegister::Four,
-                apply_third: false,
-                third_count: 0,
-                div2_count: 0,
-                div31_count: 0,
-                shift_4_register: 0xFFFF,
-                shift_5_register: 0xFFFF,
-                shift_9_register: 0xFFFF,
-                frames_per_second
+                audc: 0,
+                audf: 0,
+                audv: 0,
+                clock_enable: false,
+                divider_counter: 0,
+                pulse_counter: 0,
+                pulse_counter_hold: false,
+                noise_counter: 0,
+                noise_counter_bit4: false,
+                noise_feedback: false,
             }
         }
 
-        pub fn set_channel_settings(&mut self, volume_reg: u8, frequency_reg: u8, noise_reg: u8) {
-
-            self.volume = self.volume_steps[(volume_reg & 0x0F) as usize];
-            let frequency_divider: u8 = (frequency_reg & 0x1F) + 1;
-            self.frequency = 30000 / frequency_divider as u16;
-            self.apply_third = false;
-
-            match noise_reg & 0x0F {
-                0 | 11 =>
-                    self.volume = 0.0,
-                1 if self.shift_register != ShiftRegister::Four => {
-                        self.shift_register = ShiftRegister::Four
-                    },
-                2 if self.shift_register != ShiftRegister::Div31Four => {
-                        self.div31_count = 0;
-                        self.shift_register = ShiftRegister::Div31Four;
-                    },
-                3 if self.shift_register != ShiftRegister::FiveToFour => {
-                        self.shift_register = ShiftRegister::FiveToFour;
-                    },
-                4 | 5 if self.shift_register != ShiftRegister::Div2 => {
-                        self.div2_count = 0;
-                        self.shift_register = ShiftRegister::Div2;
-                    },
-                6 | 10 if self.shift_register != ShiftRegister::Div31 => {
-                        self.div31_count = 0;
-                        self.shift_register = ShiftRegister::Div31;
-                    },
-                8 if self.shift_register != ShiftRegister::Nine =>  {
-                        self.shift_register = ShiftRegister::Nine;
-                    },
-                7 | 9 if self.shift_register != ShiftRegister::Five => {
-                        self.shift_register = ShiftRegister::Five;
-                    },
-                12 | 13 if self.shift_register != ShiftRegister::Div2 => {
-                        self.div2_count = 0;
-                        self.shift_register = ShiftRegister::Div2;
-                        self.apply_third = true;
-                        self.third_count = 0;
-                    },
-                14 if self.shift_register != ShiftRegister::Div31 => {
-                        self.div31_count = 0;
-                        self.shift_register = ShiftRegister::Div31;
-                        self.apply_third = true;
-                        self.third_count = 0;                        
-                    },
-                15 if self.shift_register != ShiftRegister::Five => {
-                        self.shift_register = ShiftRegister::Five;
-                        self.apply_third = true;
-                        self.third_count = 0;
-                    },
-                _ => {}
-
-            }
+        pub fn reset(&mut self) {
+            *self = VcsAudioChannel::new();
         }
 
-        
-        fn shift_four_register(&mut self) {
-            let new_bit: u8 = (((self.shift_4_register & 0x0002) >> 1) ^ (self.shift_4_register & 0x0001)) as u8;
-            self.shift_4_register >>= 1;
-            if new_bit > 0 {
-                self.shift_4_register |= 0x0008;
-            }
-            else {
-                self.shift_4_register &= 0xFFF7;
-            }
+        pub fn set_audc(&mut self, value: u8) {
+            self.audc = value & 0x0F;
         }
-        
-        fn shift_five_register(&mut self) {
-            let new_bit: u8 = (((self.shift_5_register & 0x0004) >> 2) ^ (self.shift_5_register & 0x0001)) as u8;
-            self.shift_5_register >>= 1;
-            if new_bit > 0 {
-                self.shift_5_register |= 0x0010;
-            }
-            else {
-                self.shift_5_register &= 0xFFEF;
-            }
-        }
-        
-        fn shift_nine_register(&mut self) {
-            let new_bit: u8 = (((self.shift_9_register & 0x0010) >> 4) ^ (self.shift_9_register & 0x0001)) as u8;
-            self.shift_9_register >>= 1;
-            if new_bit > 0 {
-                self.shift_9_register |= 0x0100;
-            }
-            else {
-                self.shift_9_register &= 0xFEFF;
-            }
+
+        pub fn set_audf(&mut self, value: u8) {
+            self.audf = value & 0x1F;
         }
 
-        fn shift_registers(&mut self) {
-            if self.apply_third {
-                self.third_count += 1;
-                if self.third_count < 2 {
-                    return;
-                }
-                self.third_count = 0;
-            }
-            
-            let mut should_shift_four: bool = true;
-            if self.shift_register == ShiftRegister::FiveToFour {
-                should_shift_four = (self.shift_5_register & 0x0001) > 0;
-            }
+        pub fn set_audv(&mut self, value: u8) {
+            self.audv = value & 0x0F;
+        }
 
-            if self.shift_register == ShiftRegister::Div31Four && self.div31_count != 17 && self.div31_count != 30 {
-                    should_shift_four = false;
-            }
+        // First phase, works out the counter feedback and steps the divider
+        pub fn phase0(&mut self) {
 
-            if should_shift_four {
-                self.shift_four_register();
-            }
+            if self.clock_enable {
+                self.noise_counter_bit4 = self.noise_counter & 0x01 > 0;
 
-            self.shift_five_register();
-            self.shift_nine_register();
+                self.pulse_counter_hold = match self.audc & 0x03 {
+                    0x02 => (self.noise_counter & 0x1E) != 0x02,
+                    0x03 => !self.noise_counter_bit4,
+                    _ => false,
+                };
 
-            if self.shift_register == ShiftRegister::Div2 {
-                if self.div2_count == 0 {
-                    self.div2_count = 1;
+                self.noise_feedback = if self.audc & 0x03 == 0 {
+                    ((self.pulse_counter ^ self.noise_counter) & 0x01) > 0 ||
+                        !(self.noise_counter > 0 || self.pulse_counter != 0x0A) ||
+                        (self.audc & 0x0C) == 0
                 }
                 else {
-                    self.div2_count = 0;
-                }
+                    (((self.noise_counter & 0x04) >> 2) ^ (self.noise_counter & 0x01)) > 0 ||
+                        self.noise_counter == 0
+                };
             }
-            else if self.shift_register == ShiftRegister::Div31 || self.shift_register == ShiftRegister::Div31Four {
-                self.div31_count += 1;
 
-                if self.div31_count >= 31 {
-                    self.div31_count = 0;
-                }
-            }
-        }
-
-        fn generate_buffer_data(&mut self, sample_count: usize) -> &[f32] {
+            self.clock_enable = self.divider_counter == self.audf;
 
-            if self.frequency == 0 {
-                if sample_count > DATA_SAMPLE_RATE_HZ / self.frames_per_second as usize {
-                    panic!("Audio Sample larger than buffer size");
-                }
-                self.audio_buffer[0..sample_count].fill(0.0);
-                return self.audio_buffer.as_slice();
+            if self.divider_counter == self.audf || self.divider_counter == 0x1F {
+                self.divider_counter = 0;
             }
+            else {
+                self.divider_counter += 1;
+            }
+        }
 
-            let wavelength: u32 = (DATA_SAMPLE_RATE_HZ / self.frequency as usize) as u32;
-            let mut sample_index: u32 = 0;
-
-            while sample_index < sample_count as u32{
-
-                if self.total_sample.is_multiple_of(wavelength as u64) {
-                    self.shift_registers();
+        // Second phase, shifts the counters and returns the output volume 0 - 15
+        pub fn phase1(&mut self) -> u8 {
+
+            if self.clock_enable {
+                let pulse_feedback: bool = match self.audc >> 2 {
+                    0x00 => ((((self.pulse_counter & 0x02) >> 1) ^ (self.pulse_counter & 0x01)) > 0) &&
+                        self.pulse_counter != 0x0A && (self.audc & 0x03) > 0,
+                    0x01 => self.pulse_counter & 0x08 == 0,
+                    0x02 => !self.noise_counter_bit4,
+                    _ => !((self.pulse_counter & 0x02) > 0 || (self.pulse_counter & 0x0E) == 0),
+                };
+
+                self.noise_counter >>= 1;
+                if self.noise_feedback {
+                    self.noise_counter |= 0x10;
                 }
 
-                if self.shift_register == ShiftRegister::Four 
-                    || self.shift_register == ShiftRegister::Div31Four
-                    || self.shift_register == ShiftRegister::FiveToFour {
-
-                        if self.shift_4_register & 0x0001 == 1 {
-                            self.audio_buffer[sample_index as usize] = self.volume;
-                        }
-                        else {
-                            self.audio_buffer[sample_index as usize] = 0.0;
-                        }
-                    }
-                    else if self.shift_register == ShiftRegister::Five {
+                if !self.pulse_counter_hold {
+                    self.pulse_counter = !(self.pulse_counter >> 1) & 0x07;
 
-                        if self.shift_5_register & 0x0001 == 1 {
-                            self.audio_buffer[sample_index as usize] = self.volume;
-                        }
-                        else {
-                            self.audio_buffer[sample_index as usize] = 0.0;
-                        }
+                    if pulse_feedback {
+                        self.pulse_counter |= 0x08;
                     }
-                    else if self.shift_register == ShiftRegister::Nine {
-
-                        if self.shift_9_register & 0x0001 == 1 {
-                            self.audio_buffer[sample_index as usize] = self.volume;
-                        }
-                        else {
-                            self.audio_buffer[sample_index as usize] = 0.0;
-                        }
-                    }
-                    else if self.shift_register == ShiftRegister::Div2 {
-
-                        if self.div2_count == 1 {
-                            self.audio_buffer[sample_index as usize] = self.volume;
-                        }
-                        else {
-                            self.audio_buffer[sample_index as usize] = 0.0;
-                        }
-                    }
-                    else if self.shift_register == ShiftRegister::Div31 {
-
-                        if self.div31_count < 18 {
-                            self.audio_buffer[sample_index as usize] = self.volume;
-                        }
-                        else {
-                            self.audio_buffer[sample_index as usize] = 0.0;
-                        }
-                    }
-
-                    sample_index += 1;
-                    self.total_sample += 1;
-
-                    if sample_index > (DATA_SAMPLE_RATE_HZ / self.frames_per_second as usize) as u32 {
-                        panic!("Audio sample index larger than buffer size");
-                    }
-
-            }
-
-            self.audio_buffer.as_slice()
-        }
-
-        pub fn get_buffer(&mut self, size: usize) -> Vec<f32> {
-                
-            let mut buffer: Vec<f32> = vec![0.0; size];
-
-            if self.is_shutdown {
-                return buffer;
+                }
             }
 
-            buffer.copy_from_slice(self.generate_buffer_data(size));
-            buffer
+            (self.pulse_counter & 0x01) * self.audv
         }
-
     }
 
 }
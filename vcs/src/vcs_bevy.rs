@@ -8,10 +8,12 @@ pub mod vcs {
     use bevy::color::{palettes::css};
     use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
+    use crate::vcs_cartridge::vcs::VcsCartridgeError;
     use crate::vcs_console::vcs::VcsConsole;
-
-    const IMAGE_WIDTH: u32 = 160;
-    const IMAGE_HEIGHT: u32 = 210;
+    use crate::vcs_console_type::vcs::ConsoleType;
+    use crate::vcs_controller::vcs::KEYPAD_KEYS;
+    use crate::vcs_properties::vcs::VcsControllerType;
+    use crate::vcs_riot::vcs::{VcsDifficulty, VcsSwitches};
 
     /// Store the image handle that we will draw to, here.
     #[derive(Resource)]
@@ -20,17 +22,45 @@ pub mod vcs {
     #[derive(Resource)]
     pub struct Vcs(VcsConsole);
 
+    // Holds the console until setup, so the menu can report a bad ROM
     #[derive(Resource)]
-    pub struct VcsRomFile(String);
+    pub struct VcsRomFile(Option<VcsConsole>);
 
     impl VcsRomFile {
 
-        pub fn new (rom_file: String) -> VcsRomFile {
-            Self(rom_file)
+        // console_type of None picks the TV type from the properties or the ROM
+        pub fn new (rom_file: String, console_type: Option<ConsoleType>) -> Result<VcsRomFile, VcsCartridgeError> {
+            Ok(Self(Some(VcsConsole::new(rom_file, console_type)?)))
         }
 
     }
 
+    // Shows the console switch positions
+    #[derive(Component)]
+    pub struct VcsSwitchText;
+
+    // Keypad keys as in Stella, in the order 1 2 3 / 4 5 6 / 7 8 9 / * 0 #
+    const LEFT_KEYPAD: [KeyCode; KEYPAD_KEYS] = [
+        KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3,
+        KeyCode::KeyQ, KeyCode::KeyW, KeyCode::KeyE,
+        KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD,
+        KeyCode::KeyZ, KeyCode::KeyX, KeyCode::KeyC,
+    ];
+
+    const RIGHT_KEYPAD: [KeyCode; KEYPAD_KEYS] = [
+        KeyCode::Digit8, KeyCode::Digit9, KeyCode::Digit0,
+        KeyCode::KeyI, KeyCode::KeyO, KeyCode::KeyP,
+        KeyCode::KeyK, KeyCode::KeyL, KeyCode::Semicolon,
+        KeyCode::Comma, KeyCode::Period, KeyCode::Slash,
+    ];
+
+    fn get_switch_label(switches: &VcsSwitches) -> String {
+        format!("{}  Left: {:?}  Right: {:?}",
+            if switches.color { "Colour" } else { "B&W" },
+            switches.left_difficulty,
+            switches.right_difficulty)
+    }
+
     pub struct VcsBevy {
     }
 
@@ -39,16 +69,22 @@ pub mod vcs {
         pub fn setup(
             mut commands: Commands, 
             mut images: ResMut<Assets<Image>>,
-            rom_file:  ResMut<VcsRomFile>,
+            mut rom_file:  ResMut<VcsRomFile>,
+            mut time: ResMut<Time<Fixed>>,
             windows: Query<&mut Window>) {
 
-            let vcs_console = VcsConsole::new(rom_file.0.clone());
-            commands.insert_resource(Vcs(vcs_console));
+            let Some(vcs_console) = rom_file.0.take() else {
+                eprintln!("No VCS console to set up");
+                return;
+            };
+
+            // PAL and SECAM run at 50 frames per second
+            time.set_timestep_hz(vcs_console.get_frames_per_second() as f64);
 
             let image = Image::new_fill(
                 Extent3d {
-                    width: IMAGE_WIDTH,
-                    height: IMAGE_HEIGHT,
+                    width: vcs_console.get_x_resolution(),
+                    height: vcs_console.get_y_resolution(),
                     depth_or_array_layers: 1,
                 },
                 TextureDimension::D2,
@@ -62,6 +98,19 @@ pub mod vcs {
             sprite.custom_size = Some(Vec2::new(windows.single().unwrap().width(), windows.single().unwrap().height()));
             commands.spawn(sprite);
             commands.insert_resource(MyProcGenImage(image_handle));
+            commands.spawn((
+                VcsSwitchText,
+                Text::new(get_switch_label(&vcs_console.get_switches())),
+                TextFont { font_size: 12.0, ..default() },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: px(4),
+                    left: px(4),
+                    ..default()
+                },
+            ));
+            commands.insert_resource(Vcs(vcs_console));
         }
 
         pub fn frame(
@@ -76,13 +125,15 @@ pub mod vcs {
             match video {
                 Some(video) => {
                     let mut image = video_assets.get_mut(&video_handle.0).expect("Image not found");
+                    let image_width = image.width();
+                    let image_height = image.height();
         
-                    for y in 0..IMAGE_HEIGHT {
-                        for x in 0..IMAGE_WIDTH {
+                    for y in 0..image_height {
+                        for x in 0..image_width {
                             let color = Color::srgb_u8(
-                                video[((y * IMAGE_WIDTH + x) * 3) as usize],
-                                video[((y * IMAGE_WIDTH + x) * 3 + 1) as usize],
-                                video[((y * IMAGE_WIDTH + x) * 3 + 2) as usize],
+                                video[((y * image_width + x) * 3) as usize],
+                                video[((y * image_width + x) * 3 + 1) as usize],
+                                video[((y * image_width + x) * 3 + 2) as usize],
                             );
                             _ = image.set_color_at(x, y, color);
                         }
@@ -99,28 +150,23 @@ pub mod vcs {
                     // WAV file header, we are creating a single frame
                     // wav file for Bevy.
                     // If Bevy can stream data, replace this with that
-                    let wav_header: Vec<u8> = vec![ 
-                        0x52, 0x49, 0x46, 0x46, //b'R', b'I', b'F', b'F', 
-                        0x0b, 0x03, 0x0, 0x0, 
-                        0x57, 0x41, 0x56, 0x45, //b'W', b'A', b'V', b'E', 
-                        0x66, 0x6d, 0x74, 0x20, //b'f', b'm', b't', 0, 
-                        0x10, 0x0, 0x0, 0x0, 
-                        0x1, 0x0,
-                        0x1, 0x0, 
-                        0x44, 0xac, 0x0, 0x0, 
-                        0x44, 0xac, 0x0, 0x0, 
-                        0x1, 0x0, 
-                        0x8, 0x0, 
-                        0x64, 0x61, 0x74, 0x61, //b'd', b'a', b't', b'a', 
-                        0xdf, 0x2, 0x0, 0x0];
-                        
-                    let mut buffer: Vec<u8> = vec![0; 780];
-                    buffer[..44].copy_from_slice(&wav_header[..44]);
-                    for i in 0..735 {
-                        buffer[i+44] = ((audio[i] + 1.0) * 127.0) as u8;
+                    let data_size = audio.len() as u32;
+                    let mut buffer: Vec<u8> = Vec::with_capacity(44 + audio.len());
+                    buffer.extend_from_slice(b"RIFF");
+                    buffer.extend_from_slice(&(36 + data_size).to_le_bytes());
+                    buffer.extend_from_slice(b"WAVEfmt ");
+                    buffer.extend_from_slice(&[0x10, 0x0, 0x0, 0x0]);   // Format chunk size
+                    buffer.extend_from_slice(&[0x1, 0x0, 0x1, 0x0]);    // PCM, mono
+                    buffer.extend_from_slice(&[0x44, 0xac, 0x0, 0x0]);  // 44100 Hz
+                    buffer.extend_from_slice(&[0x44, 0xac, 0x0, 0x0]);  // Bytes per second
+                    buffer.extend_from_slice(&[0x1, 0x0, 0x8, 0x0]);    // 1 byte per sample, 8 bits
+                    buffer.extend_from_slice(b"data");
+                    buffer.extend_from_slice(&data_size.to_le_bytes());
+
+                    for sample in audio {
+                        buffer.push(((sample + 1.0) * 127.0) as u8);
                     }
-                    let buffer_array: [u8; 780] = buffer.try_into().unwrap();
-                    let audio_source = AudioSource{bytes: Arc::new(buffer_array)};
+                    let audio_source = AudioSource{bytes: Arc::from(buffer)};
                     let audio_handle = audio_assets.add(audio_source);
                     commands.spawn((AudioPlayer::new(audio_handle), PlaybackSettings::DESPAWN));
                 },
@@ -130,10 +176,89 @@ pub mod vcs {
             }
         }
 
+        // Same keys as Stella: F1 select, F2 reset, F3/F4 colour/B&W,
+        // F5/F6 left difficulty A/B, F7/F8 right difficulty A/B, and the keypads
+        pub fn keyboard_system(keys: Res<ButtonInput<KeyCode>>,
+            mut vcs_console: ResMut<Vcs>,
+            mut text_query: Query<&mut Text, With<VcsSwitchText>>) {
+
+            if keys.just_pressed(KeyCode::F1) {
+                vcs_console.0.left_controler_select(true);
+            } else if keys.just_released(KeyCode::F1) {
+                vcs_console.0.left_controler_select(false);
+            }
+
+            if keys.just_pressed(KeyCode::F2) {
+                vcs_console.0.left_controler_start(true);
+            } else if keys.just_released(KeyCode::F2) {
+                vcs_console.0.left_controler_start(false);
+            }
+
+            for (port, keypad) in [LEFT_KEYPAD, RIGHT_KEYPAD].iter().enumerate() {
+                if vcs_console.0.get_controller_type(port) != VcsControllerType::Keyboard {
+                    continue;
+                }
+                for (key, key_code) in keypad.iter().enumerate() {
+                    if keys.just_pressed(*key_code) {
+                        vcs_console.0.keypad_key(port, key, true);
+                    } else if keys.just_released(*key_code) {
+                        vcs_console.0.keypad_key(port, key, false);
+                    }
+                }
+            }
+
+            let switches = vcs_console.0.get_switches();
+
+            if keys.just_pressed(KeyCode::F3) {
+                vcs_console.0.set_color(true);
+            }
+            if keys.just_pressed(KeyCode::F4) {
+                vcs_console.0.set_color(false);
+            }
+            if keys.just_pressed(KeyCode::F5) {
+                vcs_console.0.set_left_difficulty(VcsDifficulty::A);
+            }
+            if keys.just_pressed(KeyCode::F6) {
+                vcs_console.0.set_left_difficulty(VcsDifficulty::B);
+            }
+            if keys.just_pressed(KeyCode::F7) {
+                vcs_console.0.set_right_difficulty(VcsDifficulty::A);
+            }
+            if keys.just_pressed(KeyCode::F8) {
+                vcs_console.0.set_right_difficulty(VcsDifficulty::B);
+            }
+
+            let new_switches = vcs_console.0.get_switches();
+            if new_switches.color != switches.color ||
+                new_switches.left_difficulty != switches.left_difficulty ||
+                new_switches.right_difficulty != switches.right_difficulty {
+                for mut text in &mut text_query {
+                    **text = get_switch_label(&new_switches);
+                }
+            }
+        }
+
         pub fn gamepad_system(gamepads: Query<(Entity, &Gamepad)>,
+            mouse_buttons: Res<ButtonInput<MouseButton>>,
+            windows: Query<&Window>,
             mut vcs_console: ResMut<Vcs>) {
 
-            for (_entity, gamepad) in &gamepads {
+            let paddles = vcs_console.0.get_controller_type(0) == VcsControllerType::Paddles;
+
+            // Without a gamepad the mouse turns the first paddle
+            if paddles && gamepads.is_empty() {
+                if let Ok(window) = windows.single() && let Some(cursor) = window.cursor_position() {
+                    vcs_console.0.paddle_position(0, cursor.x / window.width());
+                }
+
+                if mouse_buttons.just_pressed(MouseButton::Left) {
+                    vcs_console.0.paddle_fire(0, true);
+                } else if mouse_buttons.just_released(MouseButton::Left) {
+                    vcs_console.0.paddle_fire(0, false);
+                }
+            }
+
+            for (paddle, (_entity, gamepad)) in gamepads.iter().enumerate() {
                 if gamepad.just_pressed(GamepadButton::Select) {
                     vcs_console.0.left_controler_select(true);
                 } else if gamepad.just_released(GamepadButton::Select) {
@@ -146,34 +271,65 @@ pub mod vcs {
                     vcs_console.0.left_controler_start(false);
                 }
 
+                // Each gamepad's left stick turns one paddle
+                if paddles {
+                    if gamepad.just_pressed(GamepadButton::South) {
+                        vcs_console.0.paddle_fire(paddle, true);
+                    } else if gamepad.just_released(GamepadButton::South) {
+                        vcs_console.0.paddle_fire(paddle, false);
+                    }
+
+                    let left_stick_x = gamepad.get(GamepadAxis::LeftStickX).unwrap();
+                    vcs_console.0.paddle_position(paddle, (left_stick_x + 1.0) / 2.0);
+                    continue;
+                }
+
+                // The first two gamepads are the left and right ports
+                let port = paddle;
+                if port > 1 {
+                    continue;
+                }
+
                 if gamepad.just_pressed(GamepadButton::South) {
-                    vcs_console.0.left_controler_a(true);
+                    vcs_console.0.controller_trigger(port, true);
                 } else if gamepad.just_released(GamepadButton::South) {
-                    vcs_console.0.left_controler_a(false);
+                    vcs_console.0.controller_trigger(port, false);
                 }
 
                 let left_stick_x = gamepad.get(GamepadAxis::LeftStickX).unwrap();
+
+                if vcs_console.0.get_controller_type(port) == VcsControllerType::Driving {
+                    // One gray code step per update while the stick is held over
+                    if left_stick_x > 0.5 {
+                        vcs_console.0.driving_rotate(port, 1);
+                    }
+                    else if left_stick_x < -0.5 {
+                        vcs_console.0.driving_rotate(port, -1);
+                    }
+                    continue;
+                }
+
                 if left_stick_x > 0.01 {
-                    vcs_console.0.left_controler_left_right(1);
+                    vcs_console.0.controller_left_right(port, 1);
                 }
                 else if left_stick_x < -0.01 {
-                    vcs_console.0.left_controler_left_right(-1);
+                    vcs_console.0.controller_left_right(port, -1);
                 }
                 else {
-                    vcs_console.0.left_controler_left_right(0);
+                    vcs_console.0.controller_left_right(port, 0);
                 }
 
                 let left_stick_y = gamepad.get(GamepadAxis::LeftStickY).unwrap();
                 if left_stick_y > 0.01 {
-                    vcs_console.0.left_controler_up_down(1);
+                    vcs_console.0.controller_up_down(port, 1);
                 }
                 else if left_stick_y < -0.01 {
-                    vcs_console.0.left_controler_up_down(-1);
+                    vcs_console.0.controller_up_down(port, -1);
                 }
                 else {
-                    vcs_console.0.left_controler_up_down(0);
+                    vcs_console.0.controller_up_down(port, 0);
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
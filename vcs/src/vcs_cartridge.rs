@@ -1,10 +1,100 @@
 
 pub mod vcs {
 
+    use std::fmt;
+
     use emucpu::prelude::*;
+    use emumemory::prelude::*;
+
+    // Bank switching schemes, named as in Stella's properties file
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum VcsBankType {
+        Bank2K,
+        Bank4K,
+        Bank4KSC,
+        BankF8,
+        BankF8SC,
+        BankE0,
+        Bank3E,
+        Bank3EX,
+        Bank3F,
+        BankUA,
+        Bank0FA0,
+        BankFE,
+        Bank0840,
+        BankE7,
+        BankWD,
+        BankFC,
+        BankCV,
+        BankGL,
+        Bank03E0,
+    }
+
+    const BANK_TYPE_NAMES: [(VcsBankType, &str); 19] = [
+        (VcsBankType::Bank2K, "2K"),
+        (VcsBankType::Bank4K, "4K"),
+        (VcsBankType::Bank4KSC, "4KSC"),
+        (VcsBankType::BankF8, "F8"),
+        (VcsBankType::BankF8SC, "F8SC"),
+        (VcsBankType::BankE0, "E0"),
+        (VcsBankType::Bank3E, "3E"),
+        (VcsBankType::Bank3EX, "3EX"),
+        (VcsBankType::Bank3F, "3F"),
+        (VcsBankType::BankUA, "UA"),
+        (VcsBankType::Bank0FA0, "0FA0"),
+        (VcsBankType::BankFE, "FE"),
+        (VcsBankType::Bank0840, "0840"),
+        (VcsBankType::BankE7, "E7"),
+        (VcsBankType::BankWD, "WD"),
+        (VcsBankType::BankFC, "FC"),
+        (VcsBankType::BankCV, "CV"),
+        (VcsBankType::BankGL, "GL"),
+        (VcsBankType::Bank03E0, "03E0"),
+    ];
+
+    impl VcsBankType {
+
+        pub fn from_name(name: &str) -> Option<VcsBankType> {
+            let name = name.trim().to_uppercase();
+
+            BANK_TYPE_NAMES.iter()
+                .find(|(_, type_name)| *type_name == name)
+                .map(|(bank_type, _)| *bank_type)
+        }
+
+        pub fn get_name(&self) -> &'static str {
+            BANK_TYPE_NAMES.iter()
+                .find(|(bank_type, _)| bank_type == self)
+                .map(|(_, type_name)| *type_name)
+                .unwrap_or("?")
+        }
+    }
+
+    #[derive(Debug)]
+    pub enum VcsCartridgeError {
+        RomRead(String),
+        UnknownBankType(String),
+        UnsupportedBankType(VcsBankType),
+        UnsupportedSize(usize),
+    }
+
+    impl fmt::Display for VcsCartridgeError {
+
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                VcsCartridgeError::RomRead(e) => write!(f, "Couldn't read ROM file: {}", e),
+                VcsCartridgeError::UnknownBankType(name) => write!(f, "Unknown bank switching type '{}'", name),
+                VcsCartridgeError::UnsupportedBankType(bank_type) => write!(f, "No {} cartridge mapper", bank_type.get_name()),
+                VcsCartridgeError::UnsupportedSize(size) => write!(f, "Unsupported cartridge size of {} bytes", size),
+            }
+        }
+    }
+
+    impl std::error::Error for VcsCartridgeError {}
 
     pub struct VcsCartridge {
-        pub memory: Vec<u8>,
+        // Writes and reads past the end go to the memory error policy
+        pub memory: MemoryRom,
         pub name: String,
         pub has_super_chip: bool,
         pub memory_offset: u16,
@@ -12,9 +102,9 @@ pub mod vcs {
 
     impl VcsCartridge {
 
-        pub fn new(rom: &Vec<u8>) -> VcsCartridge {
+        pub fn new(rom: &[u8]) -> VcsCartridge {
             Self {
-                memory: rom.clone(),
+                memory: MemoryRom::new(String::from("VCS Cartridge"), rom),
                 name: String::from("VCS Cartridge"),
                 has_super_chip: false,
                 memory_offset: 0,
@@ -3,6 +3,7 @@
 pub mod vcs {
 
     use emucpu::prelude::*;
+    use emumemory::prelude::*;
 
     use crate::vcs_cartridge::vcs::{VcsCartridge, VcsCartridgeMapper};
 
@@ -19,13 +20,15 @@ pub mod vcs {
                 return;
             }
 
+            location -= 0x1000;
             if addr.write {
-                eprintln!("Cannot write to VCS 2K cartridges");
+                cart.memory.write(location, addr.byte);
+                addr.write = false;
+                return;
             }
 
-            // Read byte
-            location -= 0x1000;
-            addr.byte = cart.memory[location as usize];
+            // Read byte, ROMs under 2K go past the end
+            addr.byte = cart.memory.read(location);
         }
     }
 }
\ No newline at end of file
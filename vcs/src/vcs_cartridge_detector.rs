@@ -1,47 +1,75 @@
 
 pub mod vcs {
 
-    use crate::vcs_cartridge::vcs::VcsCartridgeMapper;
+    use crate::vcs_cartridge::vcs::{VcsBankType, VcsCartridgeError, VcsCartridgeMapper};
     use crate::vcs_cartridge2k::vcs::VcsCartridge2k;
     use crate::vcs_cartridge4k::vcs::VcsCartridge4k;
     use crate::vcs_cartridgef8::vcs::VcsCartridgeF8;
+    use crate::vcs_properties::vcs::VcsRomProperties;
 
     pub struct VcsCartridgeDetector {
     }
 
     impl VcsCartridgeDetector {
 
-        pub fn detect_cartridge(image: &[u8]) 
-            -> Box<dyn VcsCartridgeMapper> {
+        pub fn detect_cartridge(image: &[u8], properties: &VcsRomProperties)
+            -> Result<Box<dyn VcsCartridgeMapper>, VcsCartridgeError> {
+
+            // Properties database wins over the heuristics
+            let bank_type = match properties.bank_type {
+                Some(bank_type) => bank_type,
+                None => VcsCartridgeDetector::detect_bank_type(image)?,
+            };
+
+            VcsCartridgeDetector::create_mapper(bank_type)
+        }
+
+        pub fn create_mapper(bank_type: VcsBankType)
+            -> Result<Box<dyn VcsCartridgeMapper>, VcsCartridgeError> {
+
+            match bank_type {
+                VcsBankType::Bank2K => Ok(Box::new(VcsCartridge2k {})),
+                VcsBankType::Bank4K => Ok(Box::new(VcsCartridge4k {})),
+                VcsBankType::BankF8 => Ok(Box::new(VcsCartridgeF8 {})),
+                _ => Err(VcsCartridgeError::UnsupportedBankType(bank_type)),
+            }
+        }
+
+        pub fn detect_bank_type(image: &[u8])
+            -> Result<VcsBankType, VcsCartridgeError> {
 
             let size = image.len();
 
+            if size == 0 {
+                return Err(VcsCartridgeError::UnsupportedSize(size));
+            }
+
             if (size <= 2048) || (size == 4096 && image[0..2048] == image[2048..4096])
             {
                 if VcsCartridgeDetector::is_probably_cv(image) {
-                    panic!("No CV cartridge mapper");
+                    return Ok(VcsBankType::BankCV);
                 }
                 else {
                     // Bankswitch::Type::_2K
-                    return Box::new(VcsCartridge2k {});
+                    return Ok(VcsBankType::Bank2K);
                 }
             }
             else if size == 4096
             {
                 if VcsCartridgeDetector::is_probably_cv(image) {
-                    panic!("No CV cartridge mapper");
+                    return Ok(VcsBankType::BankCV);
                 }
                 else if VcsCartridgeDetector::is_probably_4ksc(image) {
-                    panic!("No 4k SC cartridge mapper");
+                    return Ok(VcsBankType::Bank4KSC);
                 }
                 else if VcsCartridgeDetector::is_probably_fc(image) {
-                    panic!("No FC cartridge mapper");
+                    return Ok(VcsBankType::BankFC);
                 }
                 else if VcsCartridgeDetector::is_probably_gl(image) {
-                    panic!("No GL cartridge mapper");
+                    return Ok(VcsBankType::BankGL);
                 }
                 else {
-                    return Box::new(VcsCartridge4k{});
+                    return Ok(VcsBankType::Bank4K);
                 }
             }
             else if size == 8192 {
@@ -53,82 +81,64 @@ pub mod vcs {
                     VcsCartridgeDetector::search_for_bytes(image, constexpr2, 1);
 
                 if VcsCartridgeDetector::is_probably_sc(image) {
-                    panic!("No F8SC cartridge mapper");
+                    return Ok(VcsBankType::BankF8SC);
                 }
                 else if image[0..4096] == image[4096..8192] {
-                    return Box::new(VcsCartridge4k{});                    
+                    return Ok(VcsBankType::Bank4K);                    
                 }
                 else if VcsCartridgeDetector::is_probably_e0(image) {
-                    panic!("No E0 cartridge mapper");
+                    return Ok(VcsBankType::BankE0);
                 }
                 else if VcsCartridgeDetector::is_probably_3ex(image) {
-                    panic!("No 3EX cartridge mapper");
+                    return Ok(VcsBankType::Bank3EX);
                 }
                 else if VcsCartridgeDetector::is_probably_3e(image) {
-                    panic!("No 3E cartridge mapper");
+                    return Ok(VcsBankType::Bank3E);
                 }
                 else if VcsCartridgeDetector::is_probably_3f(image) {
-                    panic!("No 3F cartridge mapper");
+                    return Ok(VcsBankType::Bank3F);
                 }
                 else if VcsCartridgeDetector::is_probably_ua(image) {
-                    panic!("No UA cartridge mapper");
+                    return Ok(VcsBankType::BankUA);
                 }
                 else if VcsCartridgeDetector::is_probably_0fa0(image) {
-                    panic!("No 0FA0 cartridge mapper");
+                    return Ok(VcsBankType::Bank0FA0);
                 }
                 else if VcsCartridgeDetector::is_probably_fe(image) && !f8 {
-                    panic!("No FE cartridge mapper");
+                    return Ok(VcsBankType::BankFE);
                 }
                 else if VcsCartridgeDetector::is_probably_0840(image) {
-                    panic!("No 0840 cartridge mapper");
+                    return Ok(VcsBankType::Bank0840);
                 }
                 else if VcsCartridgeDetector::is_probably_e78k(image) {
-                    panic!("No E7 cartridge mapper");
+                    return Ok(VcsBankType::BankE7);
                 }
                 else if VcsCartridgeDetector::is_probably_wd(image) {
-                    panic!("No WD cartridge mapper");
+                    return Ok(VcsBankType::BankWD);
                 }
                 else if VcsCartridgeDetector::is_probably_fc(image) {
-                    panic!("No FC cartridge mapper");
+                    return Ok(VcsBankType::BankFC);
                 }
                 else if VcsCartridgeDetector::is_probably_03e0(image) {
-                    panic!("No 03E0 cartridge mapper");
+                    return Ok(VcsBankType::Bank03E0);
                 }
                 else {
-                    return Box::new(VcsCartridgeF8{});  
+                    return Ok(VcsBankType::BankF8);  
                 }
             }
 
-            panic!("Unknown cartridge mapper");
+            Err(VcsCartridgeError::UnsupportedSize(size))
 
         }
 
         fn search_for_bytes(image: &[u8], signature: Vec<u8>, minhits: u32) -> bool {
 
-            let mut count: u32 = 0;
-
-            for i in 0..(image.len() - signature.len()) {
-
-                let j: u32 = 0;
-
-                for j in 0..signature.len() {
-
-                    if image[i + j] != signature[j] {
-                        break;
-                    }
-                }
-
-                if j == signature.len() as u32 {
-                    count += 1;
-
-                    if count == minhits {
-                        break;
-                    }
-                }
-            }
+            let count = image.windows(signature.len())
+                .filter(|window| *window == signature.as_slice())
+                .take(minhits as usize)
+                .count();
 
-            count >= minhits
-        
+            count >= minhits as usize
         }
 
         fn is_probably_sc(image: &[u8]) -> bool {
@@ -250,7 +260,7 @@ pub mod vcs {
             // an "SC" signature for one of our larger SC types at 1FFA.
             let first: u8 = image[0];
 
-            if image.iter().any(|&x| x != first) {
+            if image[0..256].iter().any(|&x| x != first) {
                 return false;
             }
 
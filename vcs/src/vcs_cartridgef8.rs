@@ -3,6 +3,7 @@
 pub mod vcs {
 
    use emucpu::prelude::*;
+    use emumemory::prelude::*;
 
     use crate::vcs_cartridge::vcs::{VcsCartridge, VcsCartridgeMapper};
 
@@ -17,15 +18,17 @@ pub mod vcs {
             let address_range = 0x1000..0x2000;
 
             if addr.write {
-                if address_range.contains(&location) {
-                    eprintln!("Cannot write to VCS F8 cartridges");
+                // Apart from the bank switch hotspots
+                if address_range.contains(&location) && !(0x1FF8..=0x1FF9).contains(&location) {
+                    cart.memory.write(location - 0x1000, addr.byte);
+                    addr.write = false;
                 }
             }
             else {
                 if address_range.contains(&location) {
                     location -= 0x1000;
                     let a_13_set = (0x2000 & location) > 0;
-                    addr.byte = cart.memory[location as usize];
+                    addr.byte = cart.memory.read(location);
                 }
             }
         }
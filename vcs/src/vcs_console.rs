@@ -5,60 +5,210 @@ pub mod vcs {
     use std::sync::RwLock;
 
     use emucpu::prelude::*;
+    use emumemory::prelude::*;
     
     use crate::vcs_parameters::vcs::VcsParameters;
-    use crate::vcs_console_type::vcs::VcsConsoleType;
-    use crate::vcs_riot::vcs::VcsRiot;
-    use crate::vcs_tia::vcs::VcsTia;
-    use crate::vcs_audio::vcs::VcsAudio;
+    use crate::vcs_console_type::vcs::{ConsoleType, VcsConsoleType};
+    use crate::vcs_riot::vcs::{VcsDifficulty, VcsRiot, VcsSwitches};
+    use crate::vcs_tia::vcs::{MAX_FRAME_LINES, VcsTia};
+    use crate::vcs_controller::vcs::VcsController;
 
-    use crate::vcs_cartridge::vcs::{VcsCartridge, VcsCartridgeMapper};
+    use crate::vcs_cartridge::vcs::{VcsCartridge, VcsCartridgeError, VcsCartridgeMapper};
     use crate::vcs_cartridge_detector::vcs::VcsCartridgeDetector;
+    use crate::vcs_properties::vcs::{VcsControllerType, VcsPropertiesDatabase, VcsRomProperties};
+
+    // Frames run when detecting the TV type, the first ones are often odd
+    const DETECT_FRAMES: u32 = 30;
+    const DETECT_SKIP_FRAMES: u32 = 10;
+
+    // NTSC frames are around 262 lines, PAL/SECAM around 312
+    const PAL_MIN_FRAME_LINES: u16 = 287;
+
+    // Frames watched for the first visible line when the properties don't give it
+    const YSTART_DETECT_FRAMES: u32 = 30;
 
     pub struct VcsConsole {
         vcs_riot: VcsRiot,
         vcs_tia: VcsTia,
-        vcs_audio: VcsAudio,
         vcs_cartridge: VcsCartridge,
         vcs_cartridge_mapper: Box<dyn VcsCartridgeMapper>,
         console_type: VcsConsoleType,
+        rom_properties: VcsRomProperties,
+        controllers: [VcsController; 2],
+        display_ystart: u32,
+        display_height: u32,
+        ystart_detect_frames: u32,
         total_ticks: u32,
         cpu_runner: M6502Runner,
+        debugger: M6502Debugger,
         addr: AddressBus,
         inframe: RwLock<bool>,
     }
 
     impl VcsConsole {
 
-        pub fn new (rom_file: String) -> VcsConsole{
+        // console_type overrides the properties database and TV type detection
+        pub fn new (rom_file: String, console_type: Option<ConsoleType>) -> Result<VcsConsole, VcsCartridgeError> {
 
             let rom = match fs::read(rom_file) {
                 Ok(rom) => rom,
-                Err(e) => panic!("Couldn't read ROM file! {}", e)
+                Err(e) => return Err(VcsCartridgeError::RomRead(e.to_string())),
             };
-            let parameters: VcsParameters = VcsParameters::new();
+            let rom_properties = VcsPropertiesDatabase::new().get_properties(&rom);
+            let mut parameters: VcsParameters = VcsParameters::from_properties(&rom_properties);
+
+            parameters.console_type = match console_type.or(rom_properties.console_type) {
+                Some(console_type) => console_type,
+                None => VcsConsole::detect_console_type(&rom, &rom_properties)?,
+            };
+
+            VcsConsole::build(&rom, rom_properties, parameters)
+        }
+
+        fn build(rom: &[u8], rom_properties: VcsRomProperties, parameters: VcsParameters) 
+            -> Result<VcsConsole, VcsCartridgeError> {
+
+            let vcs_cartridge_mapper = VcsCartridgeDetector::detect_cartridge(rom, &rom_properties)?;
             let console_type: VcsConsoleType = VcsConsoleType::new(parameters.console_type);
             let vcs_riot: VcsRiot = VcsRiot::new();
             let vcs_tia: VcsTia = VcsTia::new(&console_type);
-            let vcs_cartridge: VcsCartridge = VcsCartridge::new(&rom);
-            let frames_per_second = console_type.get_frames_per_second();
-            let vcs_audio: VcsAudio = VcsAudio::new(frames_per_second);
+            let display_ystart: u32 = parameters.display_ystart.unwrap_or(console_type.get_v_blank_lines() as u32);
+            let display_height: u32 = parameters.display_height.unwrap_or(console_type.get_y_resolution());
+            let vcs_cartridge: VcsCartridge = VcsCartridge::new(rom);
 
             let mut temp_instance = Self {
                 vcs_riot,
                 vcs_tia,
-                vcs_audio,
                 vcs_cartridge,
-                vcs_cartridge_mapper: VcsCartridgeDetector::detect_cartridge(&rom),
+                vcs_cartridge_mapper,
                 console_type,
+                rom_properties,
+                controllers: [VcsController::new(parameters.left_controller),
+                    VcsController::new(parameters.right_controller)],
+                display_ystart,
+                display_height,
+                ystart_detect_frames: if parameters.display_ystart.is_some() { 0 } else { YSTART_DETECT_FRAMES },
                 total_ticks: 0,
                 cpu_runner: M6502Runner::new(M6502Version::AtariVcs),
-                addr: AddressBus { address: 0 , write: false, byte: 0, is_accumulator: false, is_abs_y: false },
+                debugger: M6502Debugger::new(),
+                addr: AddressBus { address: 0 , write: false, byte: 0 },
                 inframe: RwLock::new(false),
             };
 
             temp_instance.start_up();
-            temp_instance
+            temp_instance.connect_controller(0);
+            temp_instance.connect_controller(1);
+            Ok(temp_instance)
+        }
+
+        fn connect_controller(&mut self, port: usize) {
+            // Paddles start centred
+            for paddle in (port * 2)..(port * 2 + 2) {
+                if self.controllers[port].get_controller_type() == VcsControllerType::Paddles {
+                    self.vcs_tia.set_paddle_position(paddle, 0.5);
+                } else {
+                    self.vcs_tia.disconnect_paddle(paddle);
+                }
+            }
+            self.update_controllers();
+        }
+
+        // Put the controller lines on SWCHA and the TIA inputs
+        fn update_controllers(&mut self) {
+            let rows = self.vcs_riot.get_swcha_output();
+            self.controllers[0].set_port_output(rows >> 4);
+            self.controllers[1].set_port_output(rows & 0x0F);
+
+            let left = &self.controllers[0];
+            let right = &self.controllers[1];
+
+            self.vcs_riot.set_swcha_input((left.get_direction_nibble() << 4) | right.get_direction_nibble());
+
+            let left_levels = left.get_input_levels(rows >> 4);
+            let right_levels = right.get_input_levels(rows & 0x0F);
+            self.vcs_tia.set_input_levels([left_levels[0], left_levels[1], right_levels[0], 
+                right_levels[1], left_levels[2], right_levels[2]]);
+        }
+
+        // Run the ROM as NTSC for a few frames and count the lines between VSYNCs.
+        // 50Hz frames are taken as PAL, SECAM has to come from the properties or menu.
+        fn detect_console_type(rom: &[u8], rom_properties: &VcsRomProperties) 
+            -> Result<ConsoleType, VcsCartridgeError> {
+
+            let mut parameters = VcsParameters::from_properties(rom_properties);
+            parameters.console_type = ConsoleType::NTSC;
+
+            // The probe mustn't save what the game writes to the EEPROM as it boots
+            for controller in [&mut parameters.left_controller, &mut parameters.right_controller] {
+                if matches!(controller, VcsControllerType::SaveKey | VcsControllerType::AtariVox) {
+                    *controller = VcsControllerType::Joystick;
+                }
+            }
+            let mut console = VcsConsole::build(rom, rom_properties.clone(), parameters)?;
+
+            let mut measured_frames = 0;
+            let mut pal_frames = 0;
+
+            for frame in 0..DETECT_FRAMES {
+                console.run_frame();
+
+                let frame_lines = console.vcs_tia.get_frame_lines();
+                if frame < DETECT_SKIP_FRAMES || frame_lines == 0 {
+                    continue;
+                }
+
+                measured_frames += 1;
+                if frame_lines >= PAL_MIN_FRAME_LINES {
+                    pal_frames += 1;
+                }
+            }
+
+            if pal_frames * 2 > measured_frames {
+                Ok(ConsoleType::PAL)
+            }
+            else {
+                Ok(ConsoleType::NTSC)
+            }
+        }
+
+        pub fn get_rom_properties(&self) -> &VcsRomProperties {
+            &self.rom_properties
+        }
+
+        pub fn get_console_type(&self) -> ConsoleType {
+            self.console_type.get_console_type()
+        }
+
+        pub fn get_x_resolution(&self) -> u32 {
+            self.console_type.get_x_resolution()
+        }
+
+        // Height of the visible window, fixed for the ROM
+        pub fn get_y_resolution(&self) -> u32 {
+            self.display_height
+        }
+
+        // First frame line shown, counted from the end of VSYNC
+        pub fn get_y_start(&self) -> u32 {
+            self.display_ystart
+        }
+
+        pub fn get_frames_per_second(&self) -> u32 {
+            self.console_type.get_frames_per_second()
+        }
+
+        // Like Stella, follow the first line with VBLANK off for the first frames
+        // then keep it, so the picture doesn't jump about later
+        fn detect_y_start(&mut self) {
+            if self.ystart_detect_frames == 0 {
+                return;
+            }
+            self.ystart_detect_frames -= 1;
+
+            if let Some(line) = self.vcs_tia.get_first_visible_line() {
+                let max_ystart = (MAX_FRAME_LINES as u32).saturating_sub(self.display_height);
+                self.display_ystart = (line as u32).min(max_ystart);
+            }
         }
 
         fn start_up(&mut self) {
@@ -68,50 +218,64 @@ pub mod vcs {
             self.total_ticks = 0;
         }
 
-        fn get_audio(&mut self) -> Vec<f32> {
-            let channel0 = self.vcs_audio.get_audio_buffer(0);
-            let channel1 = self.vcs_audio.get_audio_buffer(1);
-            let samples_per_frame = self.vcs_audio.samples_per_frame();
-            
-            let mut mix:Vec<f32> = Vec::with_capacity(samples_per_frame);
+        pub fn get_cpu(&self) -> &M6502 {
+            self.cpu_runner.get_cpu()
+        }
 
-            for i in 0..samples_per_frame {
-                mix.push((channel0[i] + channel1[i]) / 2.0);
-            }
+        pub fn get_debugger_mut(&mut self) -> &mut M6502Debugger {
+            &mut self.debugger
+        }
 
-            mix
+        // Trap stops the debugger on a write to the cartridge ROM, or a read
+        // past the end of one
+        pub fn set_memory_policy(&mut self, policy: MemoryErrorPolicy) {
+            self.vcs_cartridge.memory.set_policy(policy);
         }
 
+        // Runs until the game's VSYNC ends the frame, the TIA ends frames
+        // without one after MAX_FRAME_LINES lines. Returns nothing while the
+        // debugger has the console stopped, the frame carries on from there
+        // once it's resumed
         pub fn run_frame (&mut self) -> (Option<Vec<u8>>, Option<Vec<f32>>) {
-            let mut frame_ticks: u32 = 0;
 
             if *self.inframe.read().unwrap() {
                 return (None, None);
             }
             *self.inframe.write().unwrap() = true;
 
-            self.vcs_audio.execute_frame(self.vcs_tia.get_tia_audio());
+            while !self.vcs_tia.take_frame_ready() {
 
-            while frame_ticks < self.console_type.get_ticks_per_frame() {
+                if self.debugger.is_stopped() {
+                    *self.inframe.write().unwrap() = false;
+                    return (None, None);
+                }
                 
                 self.vcs_cartridge_mapper.execute_tick(&mut self.vcs_cartridge, &mut self.addr);
+                self.debugger.check_memory(&mut self.vcs_cartridge.memory);
+                let scan_line = self.vcs_tia.get_scan_line();
                 self.vcs_tia.execute_tick(&mut self.addr);
+                if self.vcs_tia.get_scan_line() != scan_line {
+                    self.debugger.end_scanline();
+                }
                 
                 if self.total_ticks.is_multiple_of(3) {
 
+                    self.update_controllers();
                     self.vcs_riot.execute_tick(&mut self.addr);
 
-                    if !self.vcs_tia.is_cpu_blocked() {
-                        self.cpu_runner.execute_tick(&mut self.addr);
-                    }
+                    self.cpu_runner.set_rdy(!self.vcs_tia.is_cpu_blocked());
+                    self.cpu_runner.execute_tick(&mut self.addr);
+                    self.debugger.check_tick(&self.cpu_runner);
                 }
 
                 self.total_ticks = self.total_ticks.overflowing_add(1).0;
-                frame_ticks += 1;
             }
 
-            let video = self.vcs_tia.get_screen();
-            let audio = self.get_audio();
+            self.debugger.end_frame();
+            self.detect_y_start();
+
+            let video = self.vcs_tia.get_screen(self.display_ystart, self.display_height);
+            let audio = self.vcs_tia.get_audio_buffer();
 
             *self.inframe.write().unwrap() = false;
 
@@ -126,16 +290,74 @@ pub mod vcs {
             self.vcs_riot.reset_pressed(value);
         }
 
-        pub fn left_controler_a(&mut self, value: bool) {
-            self.vcs_tia.left_controller_trigger(value);
+        pub fn get_switches(&self) -> VcsSwitches {
+            self.vcs_riot.get_switches()
+        }
+
+        pub fn set_switches(&mut self, switches: VcsSwitches) {
+            self.vcs_riot.set_switches(switches);
+            self.vcs_tia.set_black_white(!switches.color);
+        }
+
+        pub fn set_color(&mut self, value: bool) {
+            let mut switches = self.get_switches();
+            switches.color = value;
+            self.set_switches(switches);
+        }
+
+        pub fn set_left_difficulty(&mut self, value: VcsDifficulty) {
+            let mut switches = self.get_switches();
+            switches.left_difficulty = value;
+            self.set_switches(switches);
+        }
+
+        pub fn set_right_difficulty(&mut self, value: VcsDifficulty) {
+            let mut switches = self.get_switches();
+            switches.right_difficulty = value;
+            self.set_switches(switches);
+        }
+
+        // Port 0 is the left controller port, 1 the right
+        pub fn get_controller_type(&self, port: usize) -> VcsControllerType {
+            self.controllers[port].get_controller_type()
+        }
+
+        pub fn set_controller_type(&mut self, port: usize, controller_type: VcsControllerType) {
+            self.controllers[port].set_controller_type(controller_type);
+            self.connect_controller(port);
+        }
+
+        // Paddles 0 and 1 are on the left port, 2 and 3 on the right
+        pub fn paddle_position(&mut self, paddle: usize, position: f32) {
+            if paddle < 4 && self.get_controller_type(paddle / 2) == VcsControllerType::Paddles {
+                self.vcs_tia.set_paddle_position(paddle, position);
+            }
+        }
+
+        pub fn paddle_fire(&mut self, paddle: usize, value: bool) {
+            if paddle < 4 {
+                self.controllers[paddle / 2].set_paddle_fire(paddle % 2, value);
+            }
+        }
+
+        pub fn controller_trigger(&mut self, port: usize, value: bool) {
+            self.controllers[port].set_trigger(value);
+        }
+
+        pub fn controller_up_down(&mut self, port: usize, value: i8) {
+            self.controllers[port].set_up_down(value);
+        }
+
+        pub fn controller_left_right(&mut self, port: usize, value: i8) {
+            self.controllers[port].set_left_right(value);
         }
 
-        pub fn left_controler_up_down(&mut self, value: i8) {
-            self.vcs_riot.left_controller_up_down(value);
+        pub fn keypad_key(&mut self, port: usize, key: usize, value: bool) {
+            self.controllers[port].set_keypad_key(key, value);
         }
 
-        pub fn left_controler_left_right(&mut self, value: i8) {
-            self.vcs_riot.left_controller_left_right(value);
+        pub fn driving_rotate(&mut self, port: usize, delta: i8) {
+            self.controllers[port].rotate(delta);
         }
     }
 
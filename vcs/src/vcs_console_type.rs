@@ -30,11 +30,12 @@ pub mod vcs {
             160
         }
         
+        // Default visible lines, ROM properties can override it
         pub fn get_y_resolution(&self) -> u32 {
             let mut result = 228;
 
             if self.console_type == ConsoleType::NTSC {
-                result = 210; // Should be 192
+                result = 192;
             }
 
             result
@@ -50,6 +51,7 @@ pub mod vcs {
             result
         }
         
+        // Lines from the end of VSYNC to the first visible line
         pub fn get_v_blank_lines(&self) -> u8 {
             let mut result = 45;
 
@@ -71,10 +73,6 @@ pub mod vcs {
             result
         }
 
-        pub fn get_ticks_per_frame(&self) -> u32 {
-            self.ticks_per_second() as u32 / self.get_frames_per_second()
-        }
-
         pub fn audio_samples_per_frame(&self) -> usize {
             DATA_SAMPLE_RATE_HZ / self.get_frames_per_second() as usize
         }
@@ -0,0 +1,159 @@
+pub mod vcs {
+
+    use crate::vcs_properties::vcs::VcsControllerType;
+    use crate::vcs_savekey::vcs::{SAVEKEY_FILE, VcsSaveKey};
+
+    // Keypad keys, left to right and top to bottom: 1 2 3 / 4 5 6 / 7 8 9 / * 0 #
+    pub const KEYPAD_KEYS: usize = 12;
+
+    // Driving controller rotation, read on the up and down lines
+    const DRIVING_GRAY_CODE: [u8; 4] = [0x00, 0x01, 0x03, 0x02];
+
+    // SaveKey I2C lines, pin 3 (left) and pin 4 (right) of the port
+    const SAVEKEY_SDA: u8 = 0x04;
+    const SAVEKEY_SCL: u8 = 0x08;
+
+    // One controller port. SWCHA has a nibble per port, bit 0 up, 1 down,
+    // 2 left and 3 right, and each port has two pot inputs and a trigger input.
+    pub struct VcsController {
+        controller_type: VcsControllerType,
+        up_down: i8,
+        left_right: i8,
+        trigger: bool,
+        paddle_fire: [bool; 2],
+        keypad: [bool; KEYPAD_KEYS],
+        driving_position: usize,
+        savekey: Option<VcsSaveKey>,
+    }
+
+    impl VcsController {
+
+        pub fn new(controller_type: VcsControllerType) -> VcsController {
+            // The AtariVox has the same EEPROM, its speech output isn't emulated
+            let savekey = match controller_type {
+                VcsControllerType::SaveKey | VcsControllerType::AtariVox => Some(VcsSaveKey::new(SAVEKEY_FILE)),
+                _ => None,
+            };
+
+            Self {
+                controller_type,
+                up_down: 0,
+                left_right: 0,
+                trigger: false,
+                paddle_fire: [false; 2],
+                keypad: [false; KEYPAD_KEYS],
+                driving_position: 0,
+                savekey,
+            }
+        }
+
+        pub fn get_controller_type(&self) -> VcsControllerType {
+            self.controller_type
+        }
+
+        pub fn set_controller_type(&mut self, controller_type: VcsControllerType) {
+            *self = VcsController::new(controller_type);
+        }
+
+        // up + down -
+        pub fn set_up_down(&mut self, value: i8) {
+            self.up_down = value;
+        }
+
+        // left - right +
+        pub fn set_left_right(&mut self, value: i8) {
+            self.left_right = value;
+        }
+
+        pub fn set_trigger(&mut self, value: bool) {
+            self.trigger = value;
+        }
+
+        pub fn set_paddle_fire(&mut self, paddle: usize, value: bool) {
+            self.paddle_fire[paddle] = value;
+        }
+
+        pub fn set_keypad_key(&mut self, key: usize, value: bool) {
+            if key < KEYPAD_KEYS {
+                self.keypad[key] = value;
+            }
+        }
+
+        // Clockwise +
+        pub fn rotate(&mut self, delta: i8) {
+            let steps = DRIVING_GRAY_CODE.len() as isize;
+            self.driving_position = (self.driving_position as isize + delta as isize).rem_euclid(steps) as usize;
+        }
+
+        // The port's SWCHA nibble as driven by the RIOT
+        pub fn set_port_output(&mut self, nibble: u8) {
+            if let Some(savekey) = self.savekey.as_mut() {
+                savekey.set_lines(nibble & SAVEKEY_SDA > 0, nibble & SAVEKEY_SCL > 0);
+            }
+        }
+
+        // Lines are active low, released lines read 1
+        pub fn get_direction_nibble(&self) -> u8 {
+            match self.controller_type {
+                VcsControllerType::Joystick => {
+                    let mut nibble: u8 = 0x0F;
+                    if self.up_down > 0 {
+                        nibble &= !0x01;
+                    } else if self.up_down < 0 {
+                        nibble &= !0x02;
+                    }
+                    if self.left_right < 0 {
+                        nibble &= !0x04;
+                    } else if self.left_right > 0 {
+                        nibble &= !0x08;
+                    }
+                    nibble
+                }
+                VcsControllerType::Paddles => {
+                    // Paddle 0 fire on the right line, paddle 1 on the left
+                    let mut nibble: u8 = 0x0F;
+                    if self.paddle_fire[0] {
+                        nibble &= !0x08;
+                    }
+                    if self.paddle_fire[1] {
+                        nibble &= !0x04;
+                    }
+                    nibble
+                }
+                VcsControllerType::Driving => 0x0C | DRIVING_GRAY_CODE[self.driving_position],
+                // The AtariVox speech ready line (pin 2) always reads ready
+                VcsControllerType::SaveKey | VcsControllerType::AtariVox => {
+                    match &self.savekey {
+                        Some(savekey) if !savekey.get_sda() => 0x0F & !SAVEKEY_SDA,
+                        _ => 0x0F,
+                    }
+                }
+                _ => 0x0F,
+            }
+        }
+
+        // Levels of the two pot inputs and the trigger input, true is high.
+        // rows is the port's SWCHA nibble written by the CPU, keypads drive a
+        // low row onto the column inputs of any pressed key in that row.
+        pub fn get_input_levels(&self, rows: u8) -> [bool; 3] {
+            match self.controller_type {
+                VcsControllerType::Keyboard => {
+                    let mut levels = [true; 3];
+                    for (row, keys) in self.keypad.chunks(3).enumerate() {
+                        if rows & (1 << row) != 0 {
+                            continue;
+                        }
+                        for (column, pressed) in keys.iter().enumerate() {
+                            if *pressed {
+                                levels[column] = false;
+                            }
+                        }
+                    }
+                    levels
+                }
+                VcsControllerType::Joystick | VcsControllerType::Driving => [true, true, !self.trigger],
+                _ => [true; 3],
+            }
+        }
+    }
+}
@@ -62,7 +62,8 @@ pub mod vcs {
         
     pub struct VcsPalette {
         palette: [[u8; 128 * 3]; 3],
-        video_type: ConsoleType
+        video_type: ConsoleType,
+        black_white: bool,
     }
 
     impl VcsPalette {
@@ -75,6 +76,7 @@ pub mod vcs {
             temp_instance = VcsPalette { 
                 palette: [[0u8; 128 * 3]; 3],
                 video_type,
+                black_white: false,
             };
             temp_instance.setup_palettes();
 
@@ -91,9 +93,21 @@ pub mod vcs {
             }
             position >>= 1;
             position *= 3;
-            (self.palette[pallette_choice][position],
-             self.palette[pallette_choice][position + 1],
-             self.palette[pallette_choice][position + 2])
+            let (red, green, blue) = (self.palette[pallette_choice][position],
+                self.palette[pallette_choice][position + 1],
+                self.palette[pallette_choice][position + 2]);
+
+            if self.black_white {
+                // Luminance of the colour, as a black and white TV shows it
+                let grey = ((red as u32 * 299 + green as u32 * 587 + blue as u32 * 114) / 1000) as u8;
+                return (grey, grey, grey);
+            }
+
+            (red, green, blue)
+        }
+
+        pub fn set_black_white(&mut self, value: bool) {
+            self.black_white = value;
         }
 
         fn setup_palettes(&mut self) {
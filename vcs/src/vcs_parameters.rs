@@ -1,8 +1,13 @@
-
 pub mod vcs {
 
+    use crate::vcs_properties::vcs::{VcsControllerType, VcsRomProperties};
+
     pub struct VcsParameters {
         pub console_type: crate::vcs_console_type::vcs::ConsoleType,
+        pub left_controller: VcsControllerType,
+        pub right_controller: VcsControllerType,
+        pub display_ystart: Option<u32>,
+        pub display_height: Option<u32>,
     }
 
     impl VcsParameters {
@@ -10,9 +15,27 @@ pub mod vcs {
         pub fn new() -> VcsParameters {
             Self {
                 console_type: crate::vcs_console_type::vcs::ConsoleType::NTSC,
+                left_controller: VcsControllerType::Joystick,
+                right_controller: VcsControllerType::Joystick,
+                display_ystart: None,
+                display_height: None,
             }
         }
 
+        pub fn from_properties(properties: &VcsRomProperties) -> VcsParameters {
+            let mut parameters = VcsParameters::new();
+
+            if let Some(console_type) = properties.console_type {
+                parameters.console_type = console_type;
+            }
+            parameters.left_controller = properties.left_controller;
+            parameters.right_controller = properties.right_controller;
+            parameters.display_ystart = properties.display_ystart;
+            parameters.display_height = properties.display_height;
+
+            parameters
+        }
+
     }
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,227 @@
+pub mod vcs {
+
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+
+    use crate::vcs_cartridge::vcs::{VcsBankType, VcsCartridgeError};
+    use crate::vcs_console_type::vcs::ConsoleType;
+
+    // A starter set of built in properties, uses the same format as Stella's
+    // stella.pro so a Stella export can be used as the user file
+    const BUILTIN_PROPERTIES: &str = include_str!("../data/vcs_properties.pro");
+
+    // Entries in this file replace the built in ones with the same MD5
+    pub const USER_PROPERTIES_FILE: &str = "vcs_user.pro";
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum VcsControllerType {
+        Joystick,
+        Paddles,
+        Keyboard,
+        Driving,
+        SaveKey,
+        AtariVox,
+    }
+
+    impl VcsControllerType {
+
+        pub fn from_name(name: &str) -> Option<VcsControllerType> {
+            match name.trim().to_uppercase().as_str() {
+                "JOYSTICK" => Some(VcsControllerType::Joystick),
+                "PADDLES" => Some(VcsControllerType::Paddles),
+                "KEYBOARD" => Some(VcsControllerType::Keyboard),
+                "DRIVING" => Some(VcsControllerType::Driving),
+                "SAVEKEY" => Some(VcsControllerType::SaveKey),
+                "ATARIVOX" => Some(VcsControllerType::AtariVox),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct VcsRomProperties {
+        pub md5: String,
+        pub name: String,
+        pub bank_type: Option<VcsBankType>,
+        pub console_type: Option<ConsoleType>,
+        pub left_controller: VcsControllerType,
+        pub right_controller: VcsControllerType,
+        pub display_ystart: Option<u32>,
+        pub display_height: Option<u32>,
+    }
+
+    impl VcsRomProperties {
+
+        pub fn new(md5: &str) -> VcsRomProperties {
+            Self {
+                md5: md5.to_lowercase(),
+                name: String::from("Unknown"),
+                bank_type: None,
+                console_type: None,
+                left_controller: VcsControllerType::Joystick,
+                right_controller: VcsControllerType::Joystick,
+                display_ystart: None,
+                display_height: None,
+            }
+        }
+
+        fn set_property(&mut self, key: &str, value: &str) -> Result<(), VcsCartridgeError> {
+            match key {
+                "Cart.MD5" => self.md5 = value.to_lowercase(),
+                "Cart.Name" => self.name = String::from(value),
+                "Cart.Type" => {
+                    // AUTO leaves the bank switching type to the detector
+                    self.bank_type = match value.to_uppercase().as_str() {
+                        "" | "AUTO" => None,
+                        _ => match VcsBankType::from_name(value) {
+                            Some(bank_type) => Some(bank_type),
+                            None => return Err(VcsCartridgeError::UnknownBankType(String::from(value))),
+                        },
+                    };
+                }
+                "Display.Format" => {
+                    self.console_type = match value.to_uppercase().as_str() {
+                        "NTSC" | "NTSC50" => Some(ConsoleType::NTSC),
+                        "PAL" | "PAL60" => Some(ConsoleType::PAL),
+                        "SECAM" | "SECAM60" => Some(ConsoleType::SECAM),
+                        _ => None,
+                    };
+                }
+                "Controller.Left" => {
+                    if let Some(controller) = VcsControllerType::from_name(value) {
+                        self.left_controller = controller;
+                    }
+                }
+                "Controller.Right" => {
+                    if let Some(controller) = VcsControllerType::from_name(value) {
+                        self.right_controller = controller;
+                    }
+                }
+                "Display.YStart" => self.display_ystart = value.parse().ok(),
+                "Display.Height" => self.display_height = value.parse().ok(),
+                // Stella has many more properties, ignore the ones we don't use
+                _ => {}
+            }
+
+            Ok(())
+        }
+    }
+
+    pub struct VcsPropertiesDatabase {
+        properties: HashMap<String, VcsRomProperties>,
+    }
+
+    impl VcsPropertiesDatabase {
+
+        pub fn new() -> VcsPropertiesDatabase {
+            Self::with_user_file(Path::new(USER_PROPERTIES_FILE))
+        }
+
+        pub fn with_user_file(path: &Path) -> VcsPropertiesDatabase {
+            let mut temp_instance = Self {
+                properties: HashMap::new(),
+            };
+
+            temp_instance.load_properties(BUILTIN_PROPERTIES);
+
+            // The user file is optional
+            if let Ok(user_properties) = fs::read_to_string(path) {
+                temp_instance.load_properties(&user_properties);
+            }
+
+            temp_instance
+        }
+
+        pub fn load_properties(&mut self, text: &str) {
+            let mut entry: Option<VcsRomProperties> = None;
+
+            for (line_number, line) in text.lines().enumerate() {
+                let line = line.trim();
+
+                if line.is_empty() || line.starts_with(';') {
+                    continue;
+                }
+
+                let values = VcsPropertiesDatabase::split_quoted(line);
+
+                match values.as_slice() {
+                    // A single empty string ends the current entry
+                    [end] if end.is_empty() => {
+                        if let Some(properties) = entry.take() {
+                            self.insert(properties);
+                        }
+                    }
+                    [key, value] => {
+                        let properties = entry.get_or_insert_with(|| VcsRomProperties::new(""));
+                        if let Err(e) = properties.set_property(key, value) {
+                            eprintln!("Properties line {}: {}", line_number + 1, e);
+                        }
+                    }
+                    _ => {
+                        eprintln!("Properties line {}: expected \"Key\" \"Value\"", line_number + 1);
+                    }
+                }
+            }
+
+            if let Some(properties) = entry.take() {
+                self.insert(properties);
+            }
+        }
+
+        fn insert(&mut self, properties: VcsRomProperties) {
+            if properties.md5.is_empty() {
+                eprintln!("Properties entry '{}' has no Cart.MD5", properties.name);
+                return;
+            }
+
+            self.properties.insert(properties.md5.clone(), properties);
+        }
+
+        fn split_quoted(line: &str) -> Vec<String> {
+            let mut values: Vec<String> = Vec::new();
+            let mut chars = line.chars();
+
+            while let Some(c) = chars.next() {
+                if c != '"' {
+                    continue;
+                }
+
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        Some('"') | None => break,
+                        Some(c) => value.push(c),
+                    }
+                }
+                values.push(value);
+            }
+
+            values
+        }
+
+        pub fn get_properties(&self, image: &[u8]) -> VcsRomProperties {
+            let md5 = format!("{:x}", md5::compute(image));
+
+            match self.get_properties_by_md5(&md5) {
+                Some(properties) => properties.clone(),
+                None => VcsRomProperties::new(&md5),
+            }
+        }
+
+        pub fn get_properties_by_md5(&self, md5: &str) -> Option<&VcsRomProperties> {
+            self.properties.get(&md5.to_lowercase())
+        }
+    }
+
+    impl Default for VcsPropertiesDatabase {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
@@ -12,20 +12,100 @@ pub mod vcs {
     const REG_SWBCNT: u16 = 0x283 - REG_OFFSET;
 
     const REG_INTIM:  u16 = 0x284 - REG_OFFSET;
-    const REG_INSTAT: u16 = 0x285 - REG_OFFSET;
     const REG_TIMI1T: u16 = 0x294 - REG_OFFSET;
     const REG_TIM8T:  u16 = 0x295 - REG_OFFSET;
     const REG_TIM64T: u16 = 0x296 - REG_OFFSET;
     const REG_T1024T: u16 = 0x297 - REG_OFFSET;
+
+    // 6532 address lines within the I/O and timer block
+    const ADDR_TIMER: u16 =       0x04; // A2, timer and interrupts rather than ports
+    const ADDR_TIMER_WRITE: u16 = 0x10; // A4, timer rather than edge detect control
+    const ADDR_IRQ_ENABLE: u16 =  0x08; // A3, timer interrupt enable
+    const ADDR_INSTAT: u16 =      0x01; // A0, interrupt flags rather than the timer
+    const ADDR_PA7_POSITIVE: u16 = 0x01; // A0 on edge detect writes
+    const ADDR_PA7_ENABLE: u16 =  0x02; // A1 on edge detect writes
+
+    const INSTAT_TIMER: u8 = 0x80;
+    const INSTAT_PA7: u8 =   0x40;
     
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum VcsDifficulty {
+        A,
+        B,
+    }
+
+    // Console switch panel, read through SWCHB
+    #[derive(Debug, Clone, Copy)]
+    pub struct VcsSwitches {
+        pub reset: bool,
+        pub select: bool,
+        pub color: bool,
+        pub left_difficulty: VcsDifficulty,
+        pub right_difficulty: VcsDifficulty,
+    }
+
+    impl VcsSwitches {
+
+        pub fn new() -> VcsSwitches {
+            Self {
+                reset: false,
+                select: false,
+                color: true,
+                left_difficulty: VcsDifficulty::B,
+                right_difficulty: VcsDifficulty::B,
+            }
+        }
+
+        // Switches are active low, bits 2, 4 and 5 are not connected
+        pub fn get_swchb(&self) -> u8 {
+            let mut result: u8 = 0;
+            if !self.reset {
+                result |= 0x01;
+            }
+            if !self.select {
+                result |= 0x02;
+            }
+            if self.color {
+                result |= 0x08;
+            }
+            if self.left_difficulty == VcsDifficulty::A {
+                result |= 0x40;
+            }
+            if self.right_difficulty == VcsDifficulty::A {
+                result |= 0x80;
+            }
+            result
+        }
+    }
+
+    impl Default for VcsSwitches {
+        fn default() -> Self {
+            VcsSwitches::new()
+        }
+    }
+
+    // The chip selects only look at A12, A9 and A7
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    enum RiotDevice {
+        Ram,
+        Io,
+    }
+
     pub struct VcsRiot {
+        memory_map: MemoryMap<RiotDevice>,
         riot_ram: MemoryRam,
         system_ram: MemoryRam,
         step: u16,
         step_count: u16,
         overflow_tick: bool,
-        select_pressed: bool,
-        reset_pressed: bool,
+        switches: VcsSwitches,
+        swcha_input: u8,
+        timer_flag: bool,
+        timer_irq_enabled: bool,
+        pa7_flag: bool,
+        pa7_irq_enabled: bool,
+        pa7_positive_edge: bool,
+        pa7_level: bool,
         _debug: u8,
     }
 
@@ -38,113 +118,118 @@ pub mod vcs {
     impl VcsRiot {
 
         pub fn new() -> Self {
+            let mut memory_map = MemoryMap::new();
+            memory_map.add("VCS Ram", MemoryDecode::Select(0x1280, 0x0080), 0x7F, RiotDevice::Ram).unwrap();
+            // copies of PIA
+            memory_map.add("VCS RIOT Registers", MemoryDecode::Select(0x1280, 0x0280), 0x1F, RiotDevice::Io).unwrap();
+
             Self {
+                memory_map,
                 riot_ram: MemoryRam::new(String::from("VCS RIOT Registers"), 0x80),
                 system_ram: MemoryRam::new(String::from("VCS Ram"), 0x80),
                 step: 1,
                 step_count: 0,
                 overflow_tick: false,
-                select_pressed: false,
-                reset_pressed: false,
+                switches: VcsSwitches::new(),
+                swcha_input: 0xFF,
+                timer_flag: false,
+                timer_irq_enabled: false,
+                pa7_flag: false,
+                pa7_irq_enabled: false,
+                pa7_positive_edge: false,
+                pa7_level: true,
                 _debug: 0,
             }
         }
 
+        // The 6532 reset makes both ports inputs and clears the interrupts,
+        // the timer keeps running
         pub fn reset(&mut self) {
             self.step = 1;
-            self.riot_ram.write(REG_INSTAT, 0);
-            self.riot_ram.write(REG_SWCHA, 255);
-            self.riot_ram.write(REG_SWCNT, 255);
-            self.riot_ram.write(REG_SWBCNT, 255);
-            self.select_pressed = false;
-            self.reset_pressed = false;
+            self.riot_ram.write(REG_SWCHA, 0);
+            self.riot_ram.write(REG_SWCNT, 0);
+            self.riot_ram.write(REG_SWCHB, 0);
+            self.riot_ram.write(REG_SWBCNT, 0);
+            self.timer_flag = false;
+            self.timer_irq_enabled = false;
+            self.pa7_flag = false;
+            self.pa7_irq_enabled = false;
+            self.pa7_positive_edge = false;
+            self.pa7_level = self.read_swcha() & 0x80 > 0;
+            // Only the momentary switches, the others keep their position
+            self.switches.select = false;
+            self.switches.reset = false;
         }
 
         pub fn reset_pressed(&mut self, value: bool) {
-            self.reset_pressed = value;
+            self.switches.reset = value;
         }
 
         pub fn select_pressed(&mut self, value: bool)
         {
-            self.select_pressed = value;
+            self.switches.select = value;
         }
 
-        // up + down -
-        pub fn left_controller_up_down(&mut self, value : i8) {
-            let mut byte: u8 = self.riot_ram.read(REG_SWCHA);
-            byte |= 0x30;
+        pub fn get_switches(&self) -> VcsSwitches {
+            self.switches
+        }
 
-            if value < 0 {
-                byte &= 0xDF;
-            } else if value > 0 {
-                byte &= 0xEF;
-            }
+        pub fn set_switches(&mut self, switches: VcsSwitches) {
+            self.switches = switches;
+        }
 
-            self.riot_ram.write(REG_SWCHA, byte);
+        // Controller lines, the left port in the high nibble
+        pub fn set_swcha_input(&mut self, byte: u8) {
+            self.swcha_input = byte;
         }
-           
-        // left + right -
-        pub fn left_controller_left_right(&mut self, value : i8) {
-            let mut byte: u8 = self.riot_ram.read(REG_SWCHA);
-            byte |= 0xC0;
 
-            if value < 0 {
-                byte &= 0xBF;
-            } else if value > 0 {
-                byte &= 0x7F;
-            }
+        // Levels the RIOT drives onto port A, inputs float high.
+        // Keypads use it to pick a row, the SaveKey as its I2C lines.
+        pub fn get_swcha_output(&mut self) -> u8 {
+            self.riot_ram.read(REG_SWCHA) | !self.riot_ram.read(REG_SWCNT)
+        }
 
-            self.riot_ram.write(REG_SWCHA, byte);
+        // The IRQ line isn't connected on the VCS
+        pub fn get_irq(&self) -> bool {
+            (self.timer_flag && self.timer_irq_enabled) || (self.pa7_flag && self.pa7_irq_enabled)
         }
 
         pub fn execute_tick(&mut self, addr: &mut AddressBus) {
 
-            let mut location = addr.address & 0x1FFF;
-
-            if addr.write {
-                if location & 0x1280 == 0x0080 {
-                    self.system_ram.write(location & 0x7F, addr.byte);
-                    addr.write = false;
-                }
-                else if location & 0x1280 == 0x0280 {
-                    // copies of PIA
-                    location &= 0x7F;
-                    
-                    // Timer locations duplicated in each block
-                    if location == 0x06 || location == 0x07 {
-                        location -= 2;
+            match self.memory_map.decode(addr.address & 0x1FFF) {
+                Some((RiotDevice::Ram, location)) => {
+                    if addr.write {
+                        self.system_ram.write(location, addr.byte);
+                        addr.write = false;
+                    } else {
+                        addr.byte = self.system_ram.read(location);
                     }
-                    self.write(location, addr.byte);
-                    addr.write = false;
-                }
-            } else {
-                if location & 0x1280 == 0x0080 {
-                    addr.byte = self.system_ram.read(location & 0x7F)
-                }
-                else if location & 0x1280 == 0x0280 {
-                    location &= 0x1F;
-                    if location == 0x06 || location == 0x07 {
-                        location -= 2;
+                },
+                Some((RiotDevice::Io, location)) => {
+                    if addr.write {
+                        self.write(location, addr.byte);
+                        addr.write = false;
+                    } else {
+                        addr.byte = self.read(location);
                     }
-                    addr.byte = self.read(location);
-                }
+                },
+                None => {},
             }
+
+            self.check_pa7_edge();
  
             self.step_count += 1;
+            self.overflow_tick = false;
 
             if self.step_count < self.step {
                 return;
             }
 
+            // After passing zero the timer keeps counting down once a cycle
             self.step_count = 0;
-            self.overflow_tick = false;
-            let mut timer: u8 = self.riot_ram.read(REG_INTIM);
-            let (new_timer, overflow) = timer.overflowing_sub(1);
-            timer = new_timer;
+            let (timer, overflow) = self.riot_ram.read(REG_INTIM).overflowing_sub(1);
             if overflow {
-                timer = 0;
-                let status_byte: u8 = self.system_ram.read(REG_INSTAT);
-                self.riot_ram.write(REG_INSTAT, status_byte | 0xFF);
+                self.timer_flag = true;
                 self.step = 1;
                 self.overflow_tick = true;
             }            
@@ -153,63 +238,91 @@ pub mod vcs {
 
         fn read(&mut self, location: u16) -> u8 {
 
-            if location == REG_INTIM {
-                self.clear_timnnt_underflow();
-            }
-            else if location == REG_INSTAT {
-                self.clear_instat_underflow();
+            if location & ADDR_TIMER == 0 {
+                return match location & 0x03 {
+                    REG_SWCHA => self.read_swcha(),
+                    REG_SWCHB => {
+                        // Output bits read back the latch
+                        let ddr = self.riot_ram.read(REG_SWBCNT);
+                        (self.riot_ram.read(REG_SWCHB) & ddr) | (self.switches.get_swchb() & !ddr)
+                    },
+                    _ => self.riot_ram.read(location & 0x03),
+                };
             }
 
-            if location == REG_SWCHB {
-                let mut result: u8 = 11;
-                if self.select_pressed {
-                    result &= 0xFD;
-                }
-                if self.reset_pressed {
-                    result &= 0xFE;
-                }
-                return result;
+            if location & (ADDR_TIMER | ADDR_INSTAT) == REG_INTIM {
+                // A3 also sets the timer interrupt enable
+                self.timer_irq_enabled = location & ADDR_IRQ_ENABLE > 0;
+                self.clear_timnnt_underflow();
+                return self.riot_ram.read(REG_INTIM);
             }
 
-            self.riot_ram.read(location)
+            // INSTAT, reading clears the PA7 flag
+            let mut result: u8 = 0;
+            if self.timer_flag {
+                result |= INSTAT_TIMER;
+            }
+            if self.pa7_flag {
+                result |= INSTAT_PA7;
+            }
+            self.clear_instat_underflow();
+            result
         }
 
         fn write(&mut self, location: u16, byte: u8) {
 
+            if location & ADDR_TIMER == 0 {
+                self.riot_ram.write(location & 0x03, byte);
+                return;
+            }
+
+            if location & ADDR_TIMER_WRITE == 0 {
+                self.pa7_positive_edge = location & ADDR_PA7_POSITIVE > 0;
+                self.pa7_irq_enabled = location & ADDR_PA7_ENABLE > 0;
+                return;
+            }
+
             let (byte_minus_1, _overflow) = byte.overflowing_sub(1);
 
-            if matches!(location, REG_TIMI1T | REG_TIM8T | REG_TIM64T | REG_T1024T) {
-                self.clear_timnnt_underflow();
-                self.riot_ram.write(REG_INTIM, byte_minus_1);
-                self.step_count = 0;
+            self.timer_flag = false;
+            self.timer_irq_enabled = location & ADDR_IRQ_ENABLE > 0;
+            self.riot_ram.write(REG_INTIM, byte_minus_1);
+            self.step_count = 0;
 
-                match location {
-                    REG_TIMI1T => { self.step = 1; },
-                    REG_TIM8T => { self.step = 8; },
-                    REG_TIM64T => { self.step = 64; },
-                    REG_T1024T => { self.step = 1024; },
-                    _ => {}
-                }
+            match location & !ADDR_IRQ_ENABLE {
+                REG_TIMI1T => { self.step = 1; },
+                REG_TIM8T => { self.step = 8; },
+                REG_TIM64T => { self.step = 64; },
+                REG_T1024T => { self.step = 1024; },
+                _ => {}
             }
+        }
+
+        // Pins set as outputs drive the latch, a controller can still pull them low
+        fn read_swcha(&mut self) -> u8 {
+            (self.riot_ram.read(REG_SWCHA) | !self.riot_ram.read(REG_SWCNT)) & self.swcha_input
+        }
 
-            self.riot_ram.write(location, byte);
+        fn check_pa7_edge(&mut self) {
+            let level = self.read_swcha() & 0x80 > 0;
+            if level != self.pa7_level && level == self.pa7_positive_edge {
+                self.pa7_flag = true;
+            }
+            self.pa7_level = level;
         }
 
         fn clear_instat_underflow(&mut self)
         {
-            if !self.overflow_tick {
-                let status_byte: u8 = self.system_ram.read(REG_INSTAT);
-                self.riot_ram.write(REG_INSTAT, status_byte & 0xBF);
-            }
+            self.pa7_flag = false;
         }
         
+        // Not cleared if the timer passed zero on this cycle
         fn clear_timnnt_underflow(&mut self)
         {
             if !self.overflow_tick {
-                let status_byte: u8 = self.system_ram.read(REG_INSTAT);
-                self.riot_ram.write(REG_INSTAT, status_byte & 0x7F);
+                self.timer_flag = false;
             }
         }
 
     }
-}
\ No newline at end of file
+}
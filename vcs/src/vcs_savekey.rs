@@ -0,0 +1,194 @@
+pub mod vcs {
+
+    use std::fs;
+
+    // Same file as Stella, so saves can be shared
+    pub const SAVEKEY_FILE: &str = "atarivox_eeprom.dat";
+
+    // 24LC256, 32K with 64 byte write pages
+    const EEPROM_SIZE: usize = 0x8000;
+    const EEPROM_PAGE_SIZE: u16 = 64;
+
+    // Device type 1010 with the chip select pins tied low
+    const DEVICE_ADDRESS: u8 = 0xA0;
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    enum I2cState {
+        Idle,
+        DeviceAddress,
+        AddressHigh,
+        AddressLow,
+        Write,
+        Read,
+    }
+
+    // SaveKey / AtariVox I2C EEPROM, bit banged by the CPU on two SWCHA lines.
+    // Bytes are sent most significant bit first, each followed by an acknowledge
+    // clock where the receiver pulls SDA low.
+    pub struct VcsSaveKey {
+        file_name: String,
+        data: Vec<u8>,
+        dirty: bool,
+        state: I2cState,
+        address: u16,
+        byte: u8,
+        bit: u8,
+        sda_out: bool,
+        last_sda: bool,
+        last_scl: bool,
+    }
+
+    impl VcsSaveKey {
+
+        pub fn new(file_name: &str) -> VcsSaveKey {
+            // A blank EEPROM reads 0xFF
+            let mut data = vec![0xFFu8; EEPROM_SIZE];
+            if let Ok(saved) = fs::read(file_name) {
+                let length = saved.len().min(EEPROM_SIZE);
+                data[..length].copy_from_slice(&saved[..length]);
+            }
+
+            Self {
+                file_name: file_name.to_string(),
+                data,
+                dirty: false,
+                state: I2cState::Idle,
+                address: 0,
+                byte: 0,
+                bit: 0,
+                sda_out: true,
+                last_sda: true,
+                last_scl: true,
+            }
+        }
+
+        // Level the EEPROM puts on SDA, true when released
+        pub fn get_sda(&self) -> bool {
+            self.sda_out
+        }
+
+        // Levels the CPU drives onto SDA and SCL, lines set as inputs float high
+        pub fn set_lines(&mut self, sda: bool, scl: bool) {
+            // Either side can pull SDA low
+            let sda = sda && self.sda_out;
+
+            if scl && self.last_scl && sda != self.last_sda {
+                if sda {
+                    self.stop();
+                } else {
+                    // Start, or a repeated start
+                    self.state = I2cState::DeviceAddress;
+                    self.byte = 0;
+                    self.bit = 0;
+                    self.sda_out = true;
+                }
+            } else if scl && !self.last_scl {
+                self.clock_rising(sda);
+            } else if !scl && self.last_scl {
+                self.clock_falling();
+            }
+
+            self.last_sda = sda;
+            self.last_scl = scl;
+        }
+
+        fn stop(&mut self) {
+            self.state = I2cState::Idle;
+            self.sda_out = true;
+
+            // Writes are committed at the stop condition
+            if self.dirty {
+                self.dirty = false;
+                if let Err(e) = fs::write(&self.file_name, &self.data) {
+                    eprintln!("Couldn't save SaveKey EEPROM to {}: {}", self.file_name, e);
+                }
+            }
+        }
+
+        // Bits count on the rising edge, 8 is the acknowledge clock and 9 once
+        // it has been clocked
+        fn clock_rising(&mut self, sda: bool) {
+            match self.state {
+                I2cState::Idle => {},
+                I2cState::Read => {
+                    if self.bit < 8 {
+                        self.bit += 1;
+                    } else if sda {
+                        // No acknowledge from the CPU ends the read
+                        self.state = I2cState::Idle;
+                    } else {
+                        self.bit = 9;
+                    }
+                },
+                _ => {
+                    if self.bit < 8 {
+                        self.byte = (self.byte << 1) | sda as u8;
+                        self.bit += 1;
+                    } else {
+                        self.bit = 9;
+                    }
+                },
+            }
+        }
+
+        fn clock_falling(&mut self) {
+            match self.state {
+                I2cState::Idle => {
+                    self.sda_out = true;
+                },
+                I2cState::Read => {
+                    if self.bit == 9 {
+                        self.byte = self.data[self.address as usize];
+                        self.address = (self.address + 1) % EEPROM_SIZE as u16;
+                        self.bit = 0;
+                        self.sda_out = self.byte & 0x80 > 0;
+                    } else {
+                        // Release SDA for the acknowledge clock
+                        self.sda_out = self.bit == 8 || (self.byte << self.bit) & 0x80 > 0;
+                    }
+                },
+                _ => {
+                    if self.bit == 8 {
+                        let acknowledge = self.receive_byte();
+                        self.sda_out = !acknowledge;
+                    } else if self.bit == 9 {
+                        self.bit = 0;
+                        self.byte = 0;
+                        self.sda_out = true;
+                    }
+                },
+            }
+        }
+
+        // Returns true to acknowledge the byte
+        fn receive_byte(&mut self) -> bool {
+            match self.state {
+                I2cState::DeviceAddress => {
+                    if self.byte & 0xFE != DEVICE_ADDRESS {
+                        self.state = I2cState::Idle;
+                        return false;
+                    }
+                    self.state = if self.byte & 0x01 > 0 { I2cState::Read } else { I2cState::AddressHigh };
+                },
+                I2cState::AddressHigh => {
+                    self.address = ((self.byte as u16) << 8) & (EEPROM_SIZE as u16 - 1);
+                    self.state = I2cState::AddressLow;
+                },
+                I2cState::AddressLow => {
+                    self.address |= self.byte as u16;
+                    self.state = I2cState::Write;
+                },
+                I2cState::Write => {
+                    self.data[self.address as usize] = self.byte;
+                    self.dirty = true;
+
+                    // The address wraps within the page
+                    let page = self.address & !(EEPROM_PAGE_SIZE - 1);
+                    self.address = page | ((self.address + 1) & (EEPROM_PAGE_SIZE - 1));
+                },
+                _ => {},
+            }
+            true
+        }
+    }
+}
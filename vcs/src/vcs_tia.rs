@@ -5,6 +5,7 @@ pub mod vcs {
     use emucpu::prelude::*;
 
     use crate::{vcs_console_type::{vcs::VcsConsoleType}, vcs_palette::vcs::VcsPalette};
+    use crate::vcs_audio::vcs::VcsAudio;
 
     const REG_VSYNC: u16 =   0x00;
     const REG_VBLANK: u16 =  0x01;
@@ -71,105 +72,226 @@ pub mod vcs {
     const REG_INPT4: u16 =   0x3C;
     const REG_INPT5 : u16 =  0x3D;
 
+    // NUSIZ copy offsets from the main copy
     const CLOSE: u16 =       16;
-    const MEDIUM: u16 =      40;
-    const WIDE: u16 =        72;
-
-    const SPRITEOFFSET: u16 = 5;
-
-    pub struct TiaAudio {
-        pub v0: u8,
-        pub f0: u8,
-        pub c0: u8,
-        pub v1: u8,
-        pub f1: u8,
-        pub c1: u8,
-    }
+    const MEDIUM: u16 =      32;
+    const WIDE: u16 =        64;
+
+    // Objects in the order of their HMxx registers
+    const OBJECT_P0: usize = 0;
+    const OBJECT_P1: usize = 1;
+    const OBJECT_M0: usize = 2;
+    const OBJECT_M1: usize = 3;
+    const OBJECT_BL: usize = 4;
+
+    // Object counters run 0 - 159, one step per visible colour clock
+    const OBJECT_COUNTER_CLOCKS: u16 = 160;
+
+    // Clocks from a RESxx write to the object's first pixel
+    const PLAYER_START_DELAY: u16 = 5;
+    const MISSLE_START_DELAY: u16 = 4;
+
+    // Colour clocks from the HMOVE write until it starts the motion counter
+    const HMOVE_DELAY: u8 = 6;
+
+    // Colour clocks from a register write to it reaching the video output
+    const PLAYFIELD_WRITE_DELAY: u8 = 2;
+    const GRAPHICS_WRITE_DELAY: u8 = 1;
+
+    // The playfield is fetched once every 4 colour clocks
+    const PLAYFIELD_CLOCKS: u16 = 4;
+
+    // Frames without a VSYNC are ended after this many lines
+    pub const MAX_FRAME_LINES: u16 = 342;
+
+    const HBLANK_CLOCKS: u16 = 68;
+    // HMOVE during HBLANK blanks a further 8 pixels, the "comb"
+    const LATE_HBLANK_CLOCKS: u16 = 76;
+
+    // Paddle pot circuit, a 1M pot plus 1.8K resistor charging a 68nF capacitor.
+    // INPT0-3 bit 7 goes high when the capacitor reaches the trigger voltage.
+    const PADDLE_MAX_RESISTANCE: f64 = 1_000_000.0;
+    const PADDLE_MIN_RESISTANCE: f64 = 1_800.0;
+    const PADDLE_CAPACITANCE: f64 = 68e-9;
+    const PADDLE_TRIGGER_RATIO: f64 = 0.3;
+    const TIA_CLOCK_HZ: f64 = 3_579_545.0;
 
     pub struct VcsTia {
+        // The TIA is selected with A12 and A7 low
+        memory_map: MemoryMap<()>,
         registers: MemoryRam,
         cycle: u16,
         scan_line: u16,
         screen: Vec<u8>,
+        frame_buffer: Vec<u8>,
+        frame_ready: bool,
+        first_visible_line: Option<u16>,
+        last_first_visible_line: Option<u16>,
         vcs_palette: VcsPalette,
+        vcs_audio: VcsAudio,
         w_sync_set: bool,
-        res_p0_cycle: u16,
-        res_p1_cycle: u16,
-        res_m0_cycle: u16,
-        res_m1_cycle: u16,
-        res_bl_cycle: u16,
-        grp_0_delay: u8,
-        grp_1_delay: u8,
-        ball_delay: u8,
-        v_blank: u8,
+        object_counters: [u16; 5],
+        object_moving: [bool; 5],
+        hmove_delay: u8,
+        movement_clock: u8,
+        movement_in_progress: bool,
+        late_hblank: bool,
+        main_copy_skip: [u8; 5],
+        pending_writes: Vec<(u8, u16, u8)>,
+        playfield_bit: bool,
+        grp_new: [u8; 2],
+        grp_old: [u8; 2],
+        enabl_new: u8,
+        enabl_old: u8,
+        frame_line_count: u16,
+        last_frame_lines: u16,
+        paddle_trigger_ticks: [Option<u32>; 4],
+        pot_ticks: u32,
+        pots_dumped: bool,
         x_resolution: u32,
-        y_resolution: u32,
         _debug: u8
     }
 
     impl VcsTia {
 
         pub fn new(console_type: &VcsConsoleType) -> Self {
-            let v_blank: u8 = console_type.get_v_blank_lines();
             let x_resolution: u32 = console_type.get_x_resolution();
-            let y_resolution: u32 = console_type.get_y_resolution();
+            let mut memory_map = MemoryMap::new();
+            memory_map.add("TIA Registers", MemoryDecode::Select(0x1080, 0x0000), 0x3F, ()).unwrap();
+
             Self {
+                memory_map,
                 registers: MemoryRam::new(String::from("TIA Registers"), 0x7f),
                 cycle: 0,
                 scan_line: 0,
-                screen: vec![0u8; (x_resolution * y_resolution * 3) as usize],
+                screen: vec![0u8; (x_resolution * MAX_FRAME_LINES as u32 * 3) as usize],
+                frame_buffer: vec![0u8; (x_resolution * MAX_FRAME_LINES as u32 * 3) as usize],
+                frame_ready: false,
+                first_visible_line: None,
+                last_first_visible_line: None,
                 vcs_palette: VcsPalette::new(console_type),
+                vcs_audio: VcsAudio::new(console_type.get_frames_per_second()),
                 w_sync_set: false,
-                res_p0_cycle: 0,
-                res_p1_cycle: 0,
-                res_m0_cycle: 0,
-                res_m1_cycle: 0,
-                res_bl_cycle: 0,
-                grp_0_delay: 0,
-                grp_1_delay: 0,
-                ball_delay: 0,
-                v_blank,
+                object_counters: [0; 5],
+                object_moving: [false; 5],
+                hmove_delay: 0,
+                movement_clock: 0,
+                movement_in_progress: false,
+                late_hblank: false,
+                main_copy_skip: [0; 5],
+                pending_writes: Vec::new(),
+                playfield_bit: false,
+                grp_new: [0; 2],
+                grp_old: [0; 2],
+                enabl_new: 0,
+                enabl_old: 0,
+                frame_line_count: 0,
+                last_frame_lines: 0,
+                paddle_trigger_ticks: [None; 4],
+                pot_ticks: 0,
+                pots_dumped: false,
                 x_resolution,
-                y_resolution,
                 _debug: 0
             }
         }
 
-        pub fn get_screen(&self) -> Vec<u8> {
-            self.screen.clone()
+        // Lines y_start to y_start + height of the last complete frame,
+        // counted from the end of VSYNC
+        pub fn get_screen(&self, y_start: u32, height: u32) -> Vec<u8> {
+            let line_bytes = (self.x_resolution * 3) as usize;
+            let mut screen = vec![0u8; line_bytes * height as usize];
+
+            for line in 0..height as usize {
+                let frame_line = y_start as usize + line;
+                if frame_line >= MAX_FRAME_LINES as usize {
+                    break;
+                }
+                screen[line * line_bytes..(line + 1) * line_bytes]
+                    .copy_from_slice(&self.screen[frame_line * line_bytes..(frame_line + 1) * line_bytes]);
+            }
+
+            screen
+        }
+
+        // True once per frame, when VSYNC (or the line limit) ends it
+        pub fn take_frame_ready(&mut self) -> bool {
+            let ready = self.frame_ready;
+            self.frame_ready = false;
+            ready
+        }
+
+        // First line of the last frame with VBLANK off, counted from the end of VSYNC
+        pub fn get_first_visible_line(&self) -> Option<u16> {
+            self.last_first_visible_line
         }
 
         pub fn is_cpu_blocked(&self) -> bool {
             self.w_sync_set
         }
 
-        pub fn get_tia_audio(&mut self) -> TiaAudio {
-            TiaAudio { 
-                v0: self.registers.read(REG_AUDV0),
-                f0: self.registers.read(REG_AUDF0),
-                c0: self.registers.read(REG_AUDC0),
-                v1: self.registers.read(REG_AUDV1),
-                f1: self.registers.read(REG_AUDF1),
-                c1: self.registers.read(REG_AUDC1)}
+        pub fn get_scan_line(&self) -> u16 {
+            self.scan_line
         }
 
-        pub fn left_controller_trigger(&mut self, value: bool) {
-            let mut reg_input_4 = self.registers.read(REG_INPT4);
-            if value {
-                reg_input_4 &= 0x7f;
-            }
-            else {
-                reg_input_4 |= 0x80;                
+        // Scanlines between the last two VSYNCs, 0 until a full frame is seen
+        pub fn get_frame_lines(&self) -> u16 {
+            self.last_frame_lines
+        }
+
+        pub fn get_audio_buffer(&mut self) -> Vec<f32> {
+            self.vcs_audio.get_audio_buffer()
+        }
+
+        pub fn set_black_white(&mut self, value: bool) {
+            self.vcs_palette.set_black_white(value);
+        }
+
+        // Position 0.0 is fully left (counter clockwise), 1.0 fully right
+        pub fn set_paddle_position(&mut self, paddle: usize, position: f32) {
+            let position = position.clamp(0.0, 1.0) as f64;
+            let resistance = PADDLE_MIN_RESISTANCE + (1.0 - position) * PADDLE_MAX_RESISTANCE;
+            let seconds = -resistance * PADDLE_CAPACITANCE * (1.0 - PADDLE_TRIGGER_RATIO).ln();
+            self.paddle_trigger_ticks[paddle] = Some((seconds * TIA_CLOCK_HZ) as u32);
+        }
+
+        pub fn disconnect_paddle(&mut self, paddle: usize) {
+            self.paddle_trigger_ticks[paddle] = None;
+        }
+
+        // Bit 7 of INPT0-INPT5, connected paddles override INPT0-INPT3
+        pub fn set_input_levels(&mut self, levels: [bool; 6]) {
+            for (input, level) in levels.iter().enumerate() {
+                let byte: u8 = if *level { 0xFF } else { 0x7F };
+                self.registers.write(REG_INPT0 + input as u16, byte);
             }
-            self.registers.write(REG_INPT4, reg_input_4);
         }
 
         pub fn reset(&mut self) {
             self.cycle = 0;
             self.scan_line = 0;
+            self.frame_line_count = 0;
+            self.last_frame_lines = 0;
+            self.pot_ticks = 0;
+            self.pots_dumped = false;
             self.w_sync_set = false;
-            self.res_p0_cycle = 0;
-            self.res_p1_cycle = 0;
+            self.object_counters = [0; 5];
+            self.object_moving = [false; 5];
+            self.hmove_delay = 0;
+            self.movement_clock = 0;
+            self.movement_in_progress = false;
+            self.late_hblank = false;
+            self.frame_ready = false;
+            self.first_visible_line = None;
+            self.last_first_visible_line = None;
+            self.frame_buffer.fill(0);
+            self.screen.fill(0);
+            self.main_copy_skip = [0; 5];
+            self.pending_writes.clear();
+            self.playfield_bit = false;
+            self.grp_new = [0; 2];
+            self.grp_old = [0; 2];
+            self.enabl_new = 0;
+            self.enabl_old = 0;
             
             for i in 0..0x7f {
                 self.registers.write(i, 0);
@@ -188,80 +310,85 @@ pub mod vcs {
             self.registers.write(REG_AUDF1, 0);
             self.registers.write(REG_AUDV0, 0);
             self.registers.write(REG_AUDV1, 0);
+            self.vcs_audio.reset();
         }
 
         pub fn execute_tick(&mut self, addr: &mut AddressBus) {
 
+            // Writes queued on earlier clocks land first, a delay of 1 shows from the next clock
+            self.apply_pending_writes();
             self.execute_addr(addr);
+            self.vcs_audio.execute_tick();
+
+            if !self.pots_dumped {
+                self.pot_ticks = self.pot_ticks.saturating_add(1);
+            }
 
             // Move to next pixel
             self.cycle += 1;
             if self.cycle > 67 + self.x_resolution as u16 {
                 self.cycle = 0;
                 self.scan_line += 1;
+                self.frame_line_count = self.frame_line_count.saturating_add(1);
+                self.late_hblank = false;
+
+                // No VSYNC, the ROM has lost sync so end the frame here
+                if self.scan_line >= MAX_FRAME_LINES {
+                    self.end_frame();
+                    self.scan_line = 0;
+                }
             }
 
-            // If on screen, render pixel
-            if (self.scan_line > (2 + self.v_blank as u16)) && 
-                (self.scan_line <= (2 + self.v_blank as u16 + 
-                    self.y_resolution as u16)) && (self.cycle > 67) {
+            if self.hmove_delay > 0 {
+                self.hmove_delay -= 1;
+                if self.hmove_delay == 0 {
+                    self.start_movement();
+                }
+            }
+
+            self.tick_movement();
+
+            // Objects only count on visible clocks
+            if !self.is_hblank() {
+                for object in 0..self.object_counters.len() {
+                    self.clock_object(object);
+                }
+            }
+
+            if self.cycle >= HBLANK_CLOCKS {
                 self.render_pixel();
             }
 
             if self.registers.read(REG_RESMP0) & 0x02 > 0 {
-                let size: u8 = self.registers.read(REG_NUSIZ0);
                 self.registers.write(REG_ENAM0, 0);
-                self.res_m0_cycle = self.res_p0_cycle;
-                
-                if size & 0x07 == 5 { // size 2
-                    self.res_m0_cycle += 6;
-                } else if size & 0x07 == 7 { // size 4
-                    self.res_m0_cycle += 10;
-                } else { // size 1
-                    self.res_m0_cycle += 3;
-                }
+                self.lock_missle_to_player(OBJECT_M0, OBJECT_P0, REG_NUSIZ0);
             }
 
             if self.registers.read(REG_RESMP1) & 0x02 > 0 {
-                let size: u8 = self.registers.read(REG_NUSIZ1);
                 self.registers.write(REG_ENAM1, 0);
-                self.res_m1_cycle = self.res_p1_cycle;
-                
-                if size & 0x07 == 5 { // size 2
-                    self.res_m1_cycle += 6;
-                } else if size & 0x07 == 7 { // size 4
-                    self.res_m1_cycle += 10;
-                } else { // size 1
-                    self.res_m1_cycle += 3;
-                }
+                self.lock_missle_to_player(OBJECT_M1, OBJECT_P1, REG_NUSIZ1);
             }
             
             // WSYNC 
-            if self.cycle == 3 { // Not sure this should be 8, but works pretty good
+            if self.cycle == 6 { // The CPU's held read completes on the first cycle with RDY high
                 self.w_sync_set = false;
             }
         }
 
         fn execute_addr(&mut self, addr: &mut AddressBus) {
 
-            let mut location = addr.address & 0x1FFF;
+            let Some(((), location)) = self.memory_map.decode(addr.address & 0x1FFF) else {
+                return;
+            };
 
             if addr.write {
-                if location & 0x1080 == 0 {
-                    location &= 0xFF;
-                    if location >= 0x40
-                    {
-                        location %= 0x40;
-                    }
-                    self.write(location, addr.byte);
-                    addr.write = false;
+                match VcsTia::write_delay(location) {
+                    0 => self.write(location, addr.byte),
+                    delay => self.pending_writes.push((delay, location, addr.byte)),
                 }
+                addr.write = false;
             } else {
-                if location & 0x1080 == 0 {
-                    location &= 0x0F;
-                    location += 0x30;
-                    addr.byte = self.read(location);
-                }
+                addr.byte = self.read((location & 0x0F) + 0x30);
             }
         }
 
@@ -272,6 +399,12 @@ pub mod vcs {
                 return self.registers.read(0x30);
             }
 
+            if (REG_INPT0..=REG_INPT3).contains(&location)
+                && let Some(trigger_ticks) = self.paddle_trigger_ticks[(location - REG_INPT0) as usize] {
+                let charged = !self.pots_dumped && self.pot_ticks >= trigger_ticks;
+                return if charged { 0x80 } else { 0x00 };
+            }
+
             self.registers.read(location)
         }
 
@@ -284,48 +417,47 @@ pub mod vcs {
             }
 
             match location {
+                // Each GRPx write copies the other player's graphics (and GRP1
+                // the ball enable) to the old register VDELxx displays
                 REG_GRP0 => {
-                    if (self.registers.read(REG_VDELP0) & 0x01) > 0 {
-                        self.grp_0_delay = byte;
-                    } else {
-                        self.registers.write(REG_GRP0, byte);
-                    }
-                    if (self.registers.read(REG_VDELP1) & 0x01) > 0 {
-                        self.registers.write(REG_GRP1, self.grp_1_delay);
-                        //self.grp_1_delay = 0;
-                    }
+                    self.grp_new[0] = byte;
+                    self.grp_old[1] = self.grp_new[1];
+                    self.registers.write(REG_GRP0, byte);
                 },
                 REG_GRP1 => {
-                    if (self.registers.read(REG_VDELP1) & 0x01) > 0 {
-                        self.grp_1_delay = byte;
-                    } else {
-                        self.registers.write(REG_GRP1, byte);
-                    }
-                    if (self.registers.read(REG_VDELP0) & 0x01) > 0 {
-                        self.registers.write(REG_GRP0, self.grp_0_delay);
-                        //self.grp_0_delay = 0;
-                    }
-                    if (self.registers.read(REG_VDELBL) & 0x01) > 0 {
-                        self.registers.write(REG_ENABL, self.ball_delay);
-                    }
+                    self.grp_new[1] = byte;
+                    self.grp_old[0] = self.grp_new[0];
+                    self.enabl_old = self.enabl_new;
+                    self.registers.write(REG_GRP1, byte);
                 },
                 REG_ENABL => {
+                    self.enabl_new = byte;
                     self.registers.write(REG_ENABL, byte);
                 },
                 REG_VSYNC => {
+                    // Start of VSYNC ends the frame, count the lines it had
+                    if (byte & 0x02 > 0) && (self.registers.read(REG_VSYNC) & 0x02) == 0 {
+                        self.last_frame_lines = self.frame_line_count;
+                        self.frame_line_count = 0;
+                        self.end_frame();
+                    }
+
+                    // The next frame's lines count from the end of VSYNC
                     if (byte & 0x02 == 0) && (self.registers.read(REG_VSYNC) & 0x02) > 0 {
-                        self.scan_line = 2;
-                        let byte = self.registers.read(REG_VBLANK) | 0x02;
-                        self.registers.write(REG_VBLANK, byte);
+                        self.scan_line = 0;
                     }
 
                     self.registers.write(REG_VSYNC, byte);
                 },
                 REG_VBLANK => {
-                    // the scanLine_ > 30 is a hack that seems to work,  Probably a more accurate way to do it
-                    if (byte & 0x02) == 0 && (self.registers.read(REG_VBLANK) & 0x02 > 0) && self.scan_line > 30 {
-                        self.scan_line = 2 + self.v_blank as u16;
+                    // Bit 7 grounds the paddle capacitors, they charge once released
+                    if byte & 0x80 > 0 {
+                        self.pots_dumped = true;
+                        self.pot_ticks = 0;
+                    } else {
+                        self.pots_dumped = false;
                     }
+
                     self.registers.write(REG_VBLANK, byte);
                 },
                 REG_WSYNC => {
@@ -335,49 +467,47 @@ pub mod vcs {
                     self.cycle = 0;
                 },
                 REG_RESP0 => {
-                    self.res_p0_cycle = self.cycle + SPRITEOFFSET;
-                    if self.res_p0_cycle < 68 {
-                        self.res_p0_cycle = 71;
-                    }
+                    self.reset_object(OBJECT_P0, PLAYER_START_DELAY);
                 },
                 REG_RESP1 => {
-                    self.res_p1_cycle = self.cycle + SPRITEOFFSET;
-                    if self.res_p1_cycle < 68 {
-                        self.res_p1_cycle = 71;
-                    }
+                    self.reset_object(OBJECT_P1, PLAYER_START_DELAY);
                 },
                 REG_RESM0 => {
-                    self.res_m0_cycle = self.cycle + SPRITEOFFSET;
-                    if self.res_m0_cycle < 68 {
-                        self.res_m0_cycle = 71;
-                    }
+                    self.reset_object(OBJECT_M0, MISSLE_START_DELAY);
                 },
                 REG_RESMP0 => {
                     self.registers.write(REG_RESMP0, byte);
                     self.registers.write(REG_ENAM0, 0);
                 },
                 REG_RESM1 => {
-                    self.res_m1_cycle = self.cycle + SPRITEOFFSET;
-                    if self.res_m1_cycle < 68 {
-                        self.res_m1_cycle = 71;
-                    }
+                    self.reset_object(OBJECT_M1, MISSLE_START_DELAY);
                 },
                 REG_RESMP1 => {
                     self.registers.write(REG_RESMP1, byte);
                     self.registers.write(REG_ENAM1, 0);
                 },
                 REG_RESBL => {
-                    self.res_bl_cycle = self.cycle + SPRITEOFFSET;
-                    if self.res_bl_cycle < 68 {
-                        self.res_bl_cycle = 71;
-                    }
+                    self.reset_object(OBJECT_BL, MISSLE_START_DELAY);
                 },
                 REG_HMOVE => {
-                    self.apply_movement();
+                    self.hmove_delay = HMOVE_DELAY;
                 },
                 REG_HMCLR => {
                     self.clear_move_registers();
                 },
+                // Audio registers take effect on the next audio clock
+                REG_AUDC0 | REG_AUDC1 => {
+                    self.vcs_audio.set_audc((location - REG_AUDC0) as usize, byte);
+                    self.registers.write(location, byte);
+                },
+                REG_AUDF0 | REG_AUDF1 => {
+                    self.vcs_audio.set_audf((location - REG_AUDF0) as usize, byte);
+                    self.registers.write(location, byte);
+                },
+                REG_AUDV0 | REG_AUDV1 => {
+                    self.vcs_audio.set_audv((location - REG_AUDV0) as usize, byte);
+                    self.registers.write(location, byte);
+                },
                 REG_CXCLR => {
                     self.registers.write(REG_CXM0P, 0);
                     self.registers.write(REG_CXM1P, 0);
@@ -394,36 +524,129 @@ pub mod vcs {
             }
         }
 
-        fn move_object(&mut self, mov: u8, object_cycle: u16) -> u16 {
-            let mut move_value: i8 = ((mov & 0x70) >> 4) as i8;
-            if mov & 0x80 > 0 {
-                // twos compliment
-                move_value = (move_value as u8 | 0xf8) as i8;
+        // Video registers reach the output a few clocks after the write
+        fn write_delay(location: u16) -> u8 {
+            match location {
+                REG_PF0 | REG_PF1 | REG_PF2 | REG_CTRLPF => PLAYFIELD_WRITE_DELAY,
+                REG_GRP0 | REG_GRP1 | REG_ENAM0 | REG_ENAM1 | REG_ENABL |
+                    REG_REFP0 | REG_REFP1 | REG_VDELP0 | REG_VDELP1 | REG_VDELBL => GRAPHICS_WRITE_DELAY,
+                _ => 0,
+            }
+        }
+
+        fn apply_pending_writes(&mut self) {
+            let mut index = 0;
+            while index < self.pending_writes.len() {
+                let delay = &mut self.pending_writes[index].0;
+                *delay -= 1;
+                if *delay == 0 {
+                    let (_, location, byte) = self.pending_writes.remove(index);
+                    self.write(location, byte);
+                } else {
+                    index += 1;
+                }
+            }
+        }
+
+        // Step an object's counter one clock. The wrap to 0 starts the main copy,
+        // except straight after RESPx/RESMx where it isn't drawn until the next line.
+        fn clock_object(&mut self, object: usize) {
+            self.object_counters[object] = (self.object_counters[object] + 1) % OBJECT_COUNTER_CLOCKS;
+            if self.object_counters[object] == 0 && self.main_copy_skip[object] > 0 {
+                self.main_copy_skip[object] -= 1;
+            }
+        }
+
+        fn get_player_graphics(&mut self, player: usize, vdel_register: u16) -> u8 {
+            if self.registers.read(vdel_register) & 0x01 > 0 {
+                self.grp_old[player]
+            } else {
+                self.grp_new[player]
+            }
+        }
+
+        fn get_ball_enable(&mut self) -> u8 {
+            if self.registers.read(REG_VDELBL) & 0x01 > 0 {
+                self.enabl_old
+            } else {
+                self.enabl_new
             }
-            let mut new_value = object_cycle.overflowing_sub(move_value as u16).0;
+        }
 
-            if new_value > 68 + self.x_resolution as u16 {
-                new_value = 68;
+        fn end_frame(&mut self) {
+            self.screen.copy_from_slice(&self.frame_buffer);
+            self.frame_buffer.fill(0);
+            self.frame_ready = true;
+            self.last_first_visible_line = self.first_visible_line.take();
+        }
+
+        fn is_hblank(&self) -> bool {
+            self.cycle < HBLANK_CLOCKS || (self.late_hblank && self.cycle < LATE_HBLANK_CLOCKS)
+        }
+
+        // Set the counter so the object's first pixel is delay clocks away.
+        // In HBLANK the counters are stopped, giving one clock less.
+        fn reset_object(&mut self, object: usize, delay: u16) {
+            let mut counter = OBJECT_COUNTER_CLOCKS - delay;
+            if self.is_hblank() {
+                counter += 1;
             }
-            if new_value < 68 {
-                new_value = 67 + self.x_resolution as u16;
+            self.object_counters[object] = counter;
+
+            // The ball draws on the same line, players and missles skip the first pass
+            if object != OBJECT_BL {
+                self.main_copy_skip[object] = 2;
             }
-            new_value
         }
 
-        fn apply_movement(&mut self) {
-            let mut mov = self.registers.read(REG_HMP0);
-            self.res_p0_cycle = self.move_object(mov, self.res_p0_cycle);
-            mov = self.registers.read(REG_HMP1);
-            self.res_p1_cycle = self.move_object(mov, self.res_p1_cycle);
-            mov = self.registers.read(REG_HMM0);
-            self.res_m0_cycle = self.move_object(mov, self.res_m0_cycle);
-            mov = self.registers.read(REG_HMM1);
-            self.res_m1_cycle = self.move_object(mov, self.res_m1_cycle);
-            mov = self.registers.read(REG_HMBL);
-            self.res_bl_cycle = self.move_object(mov, self.res_bl_cycle);
+        // RESMPx keeps the missle centred on its player
+        fn lock_missle_to_player(&mut self, missle: usize, player: usize, size_register: u16) {
+            let offset: u16 = match self.registers.read(size_register) & 0x07 {
+                5 => 6,  // size 2
+                7 => 10, // size 4
+                _ => 3,  // size 1
+            };
+            self.object_counters[missle] = (self.object_counters[player] + OBJECT_COUNTER_CLOCKS - offset) % OBJECT_COUNTER_CLOCKS;
+        }
+
+        fn start_movement(&mut self) {
+            self.movement_clock = 0;
+            self.movement_in_progress = true;
+            self.object_moving = [true; 5];
+
+            // Only an HMOVE landing in HBLANK extends it
+            if !self.late_hblank {
+                self.late_hblank = self.cycle < HBLANK_CLOCKS;
+            }
+        }
+
+        // HMOVE gives each object up to 15 extra clocks, one every 4 colour clocks.
+        // An object stops when the motion counter matches its HMxx value, so changing
+        // HMxx while this runs can leave it moving every line (Cosmic Ark's stars).
+        fn tick_movement(&mut self) {
+            if !self.movement_in_progress || !self.cycle.is_multiple_of(4) {
+                return;
+            }
+
+            let movement_clock = if self.movement_clock > 15 { 0 } else { self.movement_clock };
+            let hblank = self.is_hblank();
+
+            for object in 0..self.object_moving.len() {
+                let move_clocks = (self.registers.read(REG_HMP0 + object as u16) >> 4) ^ 0x08;
+                if movement_clock == move_clocks {
+                    self.object_moving[object] = false;
+                }
+
+                // Outside HBLANK the extra clock merges with the normal one
+                if self.object_moving[object] && hblank {
+                    self.clock_object(object);
+                }
+            }
+
+            self.movement_in_progress = self.object_moving.iter().any(|moving| *moving);
+            self.movement_clock = self.movement_clock.saturating_add(1);
         }
-        
+
         fn clear_move_registers(&mut self) {
             self.registers.write(REG_HMP0, 0);
             self.registers.write(REG_HMP1, 0);
@@ -432,8 +655,9 @@ pub mod vcs {
             self.registers.write(REG_HMBL, 0);
         }
 
+        // position is the player's counter, 0 at its first pixel
         fn get_player_pixel(&self, graphics_player: u8, player_size: u8, reflect_player: u8, 
-            color: u8, player_cycle: u16) -> i16 {
+            color: u8, position: u16, draw_main: bool) -> i16 {
 
             if graphics_player == 0 {
                 return -1;
@@ -446,43 +670,46 @@ pub mod vcs {
                 sprite_data = VcsTia::reverse_bits(sprite_data);
             }
             
-            let mut position2_cycle: u16 = player_cycle;
-            let mut position3_cycle: u16 = player_cycle;
+            let mut position2_offset: u16 = 0;
+            let mut position3_offset: u16 = 0;
             let mut size_multiple: u8 = 1;
 
             match player_size & 0x07 {
                 0 => { size_multiple = 1; },
-                1 => { position2_cycle = player_cycle + CLOSE; },
-                2 => { position2_cycle = player_cycle + MEDIUM; },
+                1 => { position2_offset = CLOSE; },
+                2 => { position2_offset = MEDIUM; },
                 3 => {
-                    position2_cycle = player_cycle + CLOSE;
-                    position3_cycle = position2_cycle + CLOSE;
+                    position2_offset = CLOSE;
+                    position3_offset = CLOSE * 2;
                 },
-                4 => { position2_cycle = player_cycle + WIDE; },
+                4 => { position2_offset = WIDE; },
                 5 => { size_multiple = 2; },
                 6 => {
-                    position2_cycle = player_cycle + MEDIUM;
-                    position3_cycle = position2_cycle + MEDIUM;
+                    position2_offset = MEDIUM;
+                    position3_offset = MEDIUM * 2;
                 },
                 7 => { size_multiple = 4; },
                 _ => {}
             }
 
-            let value = self.cycle.overflowing_sub(player_cycle).0;
+            // Double and quad width players start a clock later
+            let position = if size_multiple > 1 { VcsTia::copy_position(position, 1) } else { position };
+
+            let value = position;
             let mut shift: u32 = (value / size_multiple as u16) as u32;
-            if shift < 8 && (((sprite_data >> shift) & 0x01) > 0) {
+            if draw_main && shift < 8 && (((sprite_data >> shift) & 0x01) > 0) {
                 result = color as i16;
                 return result;
             }
-            let value2 = self.cycle.overflowing_sub(position2_cycle).0;
+            let value2 = VcsTia::copy_position(position, position2_offset);
             shift = (value2 / size_multiple as u16) as u32;
-            if shift < 8 && (((sprite_data >> shift) & 0x01) > 0) {
+            if position2_offset > 0 && shift < 8 && (((sprite_data >> shift) & 0x01) > 0) {
                 result = color as i16;
                 return result;
             }
-            let value3 = self.cycle.overflowing_sub(position3_cycle).0;
+            let value3 = VcsTia::copy_position(position, position3_offset);
             shift = (value3 / size_multiple as u16) as u32;
-            if shift < 8 && (((sprite_data >> shift) & 0x01) > 0) {
+            if position3_offset > 0 && shift < 8 && (((sprite_data >> shift) & 0x01) > 0) {
                 result = color as i16;
                 return result;
             }                    
@@ -494,7 +721,6 @@ pub mod vcs {
             let screen_x: u16 = self.cycle - 68;
             let control_playfield: u8 = self.registers.read(REG_CTRLPF);
             let mut playfield_color: u8 = self.registers.read(REG_COLUPF);
-            let mut byte: u8;
             let mut result: i16 = -1;
 
             if (control_playfield & 0x02) > 0 {
@@ -505,6 +731,20 @@ pub mod vcs {
                 }
             }
 
+            if self.playfield_bit {
+                result = playfield_color as i16;
+            }                
+            
+            result
+        }
+
+        // Fetch the playfield bit for the next 4 pixels, writes part way
+        // through the 4 clocks show from the next fetch
+        fn latch_playfield(&mut self) {
+            let screen_x: u16 = self.cycle - 68;
+            let control_playfield: u8 = self.registers.read(REG_CTRLPF);
+            let mut byte: u8;
+
             match (screen_x, control_playfield & 0x01 > 0) {
                 (..16, _) => {
                     byte = (self.registers.read(REG_PF0) >> 4) & 0x0f;
@@ -548,42 +788,38 @@ pub mod vcs {
                 }
             }
 
-            if byte > 0 {
-                result = playfield_color as i16;
-            }                
-            
-            result
+            self.playfield_bit = byte > 0;
         }
 
         fn get_missle_pixel(&mut self, enable: u8, missle_reset: u8, missle_size: u8, 
-            missle_color: u8, missle_cycle: u16) -> i16 {
+            missle_color: u8, position: u16, draw_main: bool) -> i16 {
 
             if (enable & 0x02) == 0 || (missle_reset & 0x02) != 0 {
                 return -1;
             }
 
             let mut result: i16 = -1;            
-            let mut position_2_cycle: u16 = missle_cycle;
-            let mut position_3_cycle: u16 = missle_cycle;
+            let mut position_2_offset: u16 = 0;
+            let mut position_3_offset: u16 = 0;
             let mut size: u8 = missle_size;
 
             match size & 0x07 {
                 1 => {
-                    position_2_cycle = missle_cycle + CLOSE;
+                    position_2_offset = CLOSE;
                 },
                 2 => {
-                    position_2_cycle = missle_cycle + MEDIUM;
+                    position_2_offset = MEDIUM;
                 },
                 3 => {
-                    position_2_cycle = missle_cycle + CLOSE;
-                    position_3_cycle = missle_cycle + MEDIUM;
+                    position_2_offset = CLOSE;
+                    position_3_offset = MEDIUM;
                 },
                 4 => {
-                    position_2_cycle = missle_cycle + WIDE;
+                    position_2_offset = WIDE;
                 },
                 6 => {
-                    position_2_cycle = missle_cycle + MEDIUM;
-                    position_3_cycle = missle_cycle + WIDE;
+                    position_2_offset = MEDIUM;
+                    position_3_offset = WIDE;
                 },
                 _ => {}
             }
@@ -592,17 +828,17 @@ pub mod vcs {
                 0 => size = 1,
                 1 => size = 2,
                 2 => size = 4,
-                4 => size = 8,
+                3 => size = 8,
                 _ => ()
             }
 
-            if missle_cycle <= self.cycle && missle_cycle + size as u16 > self.cycle {
+            if draw_main && position < size as u16 {
                 result  = missle_color as i16;
             }
-            if position_2_cycle <= self.cycle && position_2_cycle + size as u16 > self.cycle {
+            if position_2_offset > 0 && VcsTia::copy_position(position, position_2_offset) < size as u16 {
                 result  = missle_color as i16;
             }
-            if position_3_cycle <= self.cycle && position_3_cycle + size as u16 > self.cycle {
+            if position_3_offset > 0 && VcsTia::copy_position(position, position_3_offset) < size as u16 {
                 result  = missle_color as i16;
             }
 
@@ -611,7 +847,7 @@ pub mod vcs {
 
         fn get_ball_pixel(&mut self) -> i16{
 
-            if self.registers.read(REG_ENABL) & 0x02 == 0 {
+            if self.get_ball_enable() & 0x02 == 0 {
                 return -1;
             }
 
@@ -626,7 +862,7 @@ pub mod vcs {
                 _ => ()
             }
 
-            if self.res_bl_cycle <= self.cycle && self.res_bl_cycle + size as u16 >= self.cycle {
+            if self.object_counters[OBJECT_BL] < size as u16 {
                 result = self.registers.read(REG_COLUPF) as i16;
             }
             
@@ -635,9 +871,25 @@ pub mod vcs {
 
         fn render_pixel(&mut self) {
             let screen_x: u16 = self.cycle - 68;
-            let screen_y: u16 = self.scan_line - (3 + self.v_blank) as u16;
+            let screen_y: u16 = self.scan_line;
             let background: u8 = self.registers.read(REG_COLUBK);
             let mut current_color: i16 = -1;
+            let current_pixel: usize = (screen_y as usize * self.x_resolution as usize + screen_x as usize) * 3;
+            let v_blank: bool = self.registers.read(REG_VBLANK) & 0x02 > 0;
+
+            if screen_x.is_multiple_of(PLAYFIELD_CLOCKS) {
+                self.latch_playfield();
+            }
+
+            // HMOVE during HBLANK extends the blank by 8 clocks, the black comb
+            if self.is_hblank() {
+                self.frame_buffer[current_pixel..current_pixel + 3].fill(0);
+                return;
+            }
+
+            if !v_blank && self.first_visible_line.is_none() {
+                self.first_visible_line = Some(screen_y);
+            }
             
             // Playfield
             let playfield_pixel: i16 = self.get_playfield_pixel();
@@ -645,37 +897,40 @@ pub mod vcs {
             
             // Get each pixel for collision detection
             // Player 0
-            let mut graphics_player = self.registers.read(REG_GRP0);
+            let mut graphics_player = self.get_player_graphics(0, REG_VDELP0);
             let mut player_size = self.registers.read(REG_NUSIZ0);
             let mut reflect_player = self.registers.read(REG_REFP0);
             let mut color = self.registers.read(REG_COLUP0);
-            let p0_pixel: i16 = self.get_player_pixel(graphics_player, player_size, reflect_player, color, self.res_p0_cycle);
+            let p0_pixel: i16 = self.get_player_pixel(graphics_player, player_size, reflect_player, color, self.object_counters[OBJECT_P0],
+                self.main_copy_skip[OBJECT_P0] == 0);
            
             // Player 1
-            graphics_player = self.registers.read(REG_GRP1);
+            graphics_player = self.get_player_graphics(1, REG_VDELP1);
             player_size = self.registers.read(REG_NUSIZ1);
             reflect_player = self.registers.read(REG_REFP1);
             color = self.registers.read(REG_COLUP1);
-            let p1_pixel: i16 = self.get_player_pixel(graphics_player, player_size, reflect_player, color, self.res_p1_cycle);
+            let p1_pixel: i16 = self.get_player_pixel(graphics_player, player_size, reflect_player, color, self.object_counters[OBJECT_P1],
+                self.main_copy_skip[OBJECT_P1] == 0);
            
             // Missle 0
             let mut enable = self.registers.read(REG_ENAM0);
-            let mut missle_reset = self.registers.read(REG_RESM0);
+            let mut missle_reset = self.registers.read(REG_RESMP0);
             let mut missle_size = self.registers.read(REG_NUSIZ0);
             let mut missle_color = self.registers.read(REG_COLUP0);
-            let m0_pixel: i16 = self.get_missle_pixel(enable, missle_reset, missle_size, missle_color, self.res_m0_cycle);
+            let m0_pixel: i16 = self.get_missle_pixel(enable, missle_reset, missle_size, missle_color, self.object_counters[OBJECT_M0],
+                self.main_copy_skip[OBJECT_M0] == 0);
            
             // Missle 1
             enable = self.registers.read(REG_ENAM1);
-            missle_reset = self.registers.read(REG_RESM1);
+            missle_reset = self.registers.read(REG_RESMP1);
             missle_size = self.registers.read(REG_NUSIZ1);
             missle_color = self.registers.read(REG_COLUP1);
-            let m1_pixel: i16 = self.get_missle_pixel(enable, missle_reset, missle_size, missle_color, self.res_m1_cycle);
+            let m1_pixel: i16 = self.get_missle_pixel(enable, missle_reset, missle_size, missle_color, self.object_counters[OBJECT_M1],
+                self.main_copy_skip[OBJECT_M1] == 0);
            
             // Ball
             let ball_pixel: i16 = self.get_ball_pixel();
-            
-            let current_pixel: usize = (screen_y * self.x_resolution as u16 + screen_x) as usize;
+
 
             // Don't display pixel if PF has priority and is set
             if pf_above {                
@@ -723,10 +978,15 @@ pub mod vcs {
                 current_color = background as i16;
             }
 
-            let (color_r, color_g, color_b) = self.vcs_palette.get_color(current_color as usize);
-            self.screen[current_pixel * 3]     = color_r;
-            self.screen[current_pixel * 3 + 1] = color_g;
-            self.screen[current_pixel * 3 + 2] = color_b;
+            // VBLANK only blanks the output, objects still collide
+            if v_blank {
+                self.frame_buffer[current_pixel..current_pixel + 3].fill(0);
+            } else {
+                let (color_r, color_g, color_b) = self.vcs_palette.get_color(current_color as usize);
+                self.frame_buffer[current_pixel]     = color_r;
+                self.frame_buffer[current_pixel + 1] = color_g;
+                self.frame_buffer[current_pixel + 2] = color_b;
+            }
 
             self.check_collisions(playfield_pixel, p0_pixel, p1_pixel, m0_pixel, m1_pixel, ball_pixel);
         }
@@ -758,6 +1018,11 @@ pub mod vcs {
             self.check_single_collision(m0_pixel, -1, m1_pixel, REG_CXPPMM);
         }
 
+        // Counter value relative to a copy starting offset clocks after the first
+        fn copy_position(position: u16, offset: u16) -> u16 {
+            (position + OBJECT_COUNTER_CLOCKS - offset) % OBJECT_COUNTER_CLOCKS
+        }
+
         fn reverse_bits(n: u8) -> u8 {
             let mut input: u8 = n;
             let mut ans: u8 = 0;